@@ -1,10 +1,15 @@
 use crate::commands::contexts::{
     CreateContextCommand, ListContextsCommand, SetDefaultContextCommand,
 };
-use crate::commands::{ListenCommand, LoginCommand};
+use crate::commands::tunnels::{AddTunnelCommand, ListTunnelsCommand, RemoveTunnelCommand};
+#[cfg(unix)]
+use crate::commands::{ControlCommand, DaemonCommand};
+use crate::commands::{ListenCommand, LoginCommand, ReplayCommand, SessionOptions, UsageCommand};
+#[cfg(unix)]
+use crate::daemon::ControlRequest;
 use crate::{
-    config::{self, directory_resolver},
-    AppCommandType, ClientArgs, ContextCommands,
+    config::{self, directory_resolver, Config, TunnelDefinition},
+    AppCommandType, ClientArgs, ContextCommands, TunnelCommands,
 };
 use std::future::Future;
 use std::sync::Arc;
@@ -41,41 +46,111 @@ impl App {
                     }
                 }
             }
+            AppCommandType::Usage(args) => {
+                let mut command = UsageCommand::new(args, &Arc::new(config.clone()));
+                if let Err(err) = command.handle().await {
+                    println!("unexpected error when trying to fetch usage: {}", err);
+                }
+            }
             AppCommandType::Listen(args) => {
-                // TODO: abstract this into a better way.
-                // used to notify running threads that stop signal was received.
-                let (notify_shutdown, _) = broadcast::channel::<()>(1);
-
-                // used to wait for all threads to finish before closing the program..
+                let tunnels = [args.tunnel_definition()];
+                run_tunnels(
+                    &tunnels,
+                    args.app_context(),
+                    args.session_options(),
+                    &config,
+                    shutdown_signal,
+                )
+                .await;
+            }
+            AppCommandType::Up(args) => {
+                let selected = config.lock_tunnel_manager()?.select(args.names());
+                match selected {
+                    Ok(tunnels) => {
+                        let app_context = tunnels[0].context().map(String::from);
+                        run_tunnels(
+                            &tunnels,
+                            app_context,
+                            args.session_options(),
+                            &config,
+                            shutdown_signal,
+                        )
+                        .await;
+                    }
+                    Err(err) => println!("{}", err),
+                }
+            }
+            #[cfg(unix)]
+            AppCommandType::Daemon(args) => {
                 let (shutdown_complete_tx, mut shutdown_complete_rx) = mpsc::channel::<()>(1);
-
-                let mut command = ListenCommand::new(
-                    Arc::new(args.clone()),
-                    Arc::new(config.clone()),
-                    shutdown_complete_tx,
-                    notify_shutdown,
-                );
+                let mut command =
+                    DaemonCommand::new(args, &directory_resolver, shutdown_complete_tx);
 
                 tokio::select! {
                     res = command.handle() => {
-                        debug!("ListenCommand has been finished with {:?}", res);
-                        match res {
-                            Ok(_) => {},
-                            Err(err) => {
-                                println!("{}", err);
-                            }
+                        if let Err(err) = res {
+                            println!("{}", err);
                         }
                     },
                     _ = shutdown_signal => {
-                        debug!("app received stop signal..");
+                        debug!("daemon received stop signal..");
                     },
                 };
 
                 drop(command);
-
-                // waits for all internal threads/object that contains shutdown_complete_tx
-                // to be dropped.
                 let _ = shutdown_complete_rx.recv().await;
+
+                // tunnels can be added while the daemon runs, saving its copy of
+                // the config file would discard them.
+                return Ok(());
+            }
+            #[cfg(unix)]
+            AppCommandType::Status => {
+                let request = ControlRequest::Status;
+                if let Err(err) = ControlCommand::new(request, &directory_resolver)
+                    .handle()
+                    .await
+                {
+                    println!("{}", err);
+                }
+            }
+            #[cfg(unix)]
+            AppCommandType::Start(args) => {
+                let request = args.control_request();
+                if let Err(err) = ControlCommand::new(request, &directory_resolver)
+                    .handle()
+                    .await
+                {
+                    println!("{}", err);
+                }
+            }
+            #[cfg(unix)]
+            AppCommandType::Stop(args) => {
+                let request = args.control_request();
+                if let Err(err) = ControlCommand::new(request, &directory_resolver)
+                    .handle()
+                    .await
+                {
+                    println!("{}", err);
+                }
+            }
+            AppCommandType::Replay(args) => {
+                if let Err(err) = ReplayCommand::new(args, &config).handle().await {
+                    println!("failed to replay request: {}", err);
+                }
+            }
+            AppCommandType::Tunnel(args) => {
+                let result = match args {
+                    TunnelCommands::Add(args) => AddTunnelCommand::new(args, &config).handle(),
+                    TunnelCommands::List => ListTunnelsCommand::new(&config).handle(),
+                    TunnelCommands::Remove(args) => {
+                        RemoveTunnelCommand::new(args, &config).handle()
+                    }
+                };
+
+                if let Err(err) = result {
+                    println!("Failed when running command: {}", err);
+                }
             }
             AppCommandType::Context(args) => {
                 let result = match args {
@@ -104,3 +179,52 @@ impl App {
         Ok(())
     }
 }
+
+/// opens `tunnels` on a single session until it ends or the stop signal is received.
+async fn run_tunnels(
+    tunnels: &[TunnelDefinition],
+    app_context: Option<String>,
+    options: SessionOptions,
+    config: &Config,
+    shutdown_signal: impl Future,
+) {
+    // TODO: abstract this into a better way.
+    // used to notify running threads that stop signal was received.
+    let (notify_shutdown, mut quit_requested) = broadcast::channel::<()>(1);
+
+    // used to wait for all threads to finish before closing the program..
+    let (shutdown_complete_tx, mut shutdown_complete_rx) = mpsc::channel::<()>(1);
+
+    let mut command = ListenCommand::new(
+        tunnels,
+        app_context,
+        options,
+        Arc::new(config.clone()),
+        shutdown_complete_tx,
+        notify_shutdown,
+    );
+
+    tokio::select! {
+        res = command.handle() => {
+            debug!("ListenCommand has been finished with {:?}", res);
+            match res {
+                Ok(_) => {},
+                Err(err) => {
+                    println!("{}", err);
+                }
+            }
+        },
+        _ = shutdown_signal => {
+            debug!("app received stop signal..");
+        },
+        _ = quit_requested.recv() => {
+            debug!("dashboard asked to quit..");
+        },
+    };
+
+    drop(command);
+
+    // waits for all internal threads/object that contains shutdown_complete_tx
+    // to be dropped.
+    let _ = shutdown_complete_rx.recv().await;
+}
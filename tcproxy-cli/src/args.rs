@@ -1,12 +1,19 @@
 use std::{
-    net::{Ipv4Addr, SocketAddrV4},
+    path::{Path, PathBuf},
     str::FromStr,
 };
 
 use clap::Parser;
+use ipnet::IpNet;
+use tcproxy_core::tcp::parse_network;
 use tcproxy_core::Result;
 
+use crate::commands::SessionOptions;
+use crate::config::{TunnelDefinition, TunnelProtocol};
+#[cfg(unix)]
+use crate::daemon::ControlRequest;
 use crate::server_addr::ServerAddr;
+use crate::{BalanceStrategy, ProxyProtocol, ReplayRequest};
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
@@ -24,9 +31,38 @@ pub enum AppCommandType {
 
     Login(LoginArgs),
 
+    /// Shows how much traffic the account moved this month
+    Usage(UsageArgs),
+
+    /// Starts the tunnels defined in the config file on a single session
+    Up(UpArgs),
+
+    /// Runs the tunnels defined in the config file in the background
+    #[cfg(unix)]
+    Daemon(DaemonArgs),
+
+    /// Shows the tunnels run by the daemon
+    #[cfg(unix)]
+    Status,
+
+    /// Starts a tunnel on the running daemon
+    #[cfg(unix)]
+    Start(StartTunnelArgs),
+
+    /// Stops a tunnel of the running daemon, or the daemon itself when no tunnel is given
+    #[cfg(unix)]
+    Stop(StopTunnelArgs),
+
+    /// Sends a captured http request again to the local service
+    Replay(ReplayArgs),
+
     /// Context configuration.
     #[clap(subcommand)]
     Context(ContextCommands),
+
+    /// Tunnel definitions, started with `up`
+    #[clap(subcommand)]
+    Tunnel(TunnelCommands),
 }
 
 #[derive(Parser, Debug)]
@@ -37,6 +73,146 @@ pub enum ContextCommands {
     SetDefault(SetDefaultContextArgs),
 }
 
+#[derive(Parser, Debug)]
+pub enum TunnelCommands {
+    Add(AddTunnelArgs),
+    List,
+    Remove(RemoveTunnelArgs),
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct AddTunnelArgs {
+    name: String,
+
+    /// Where remote connections are forwarded to, a port on 127.0.0.1 or host:port.
+    /// Connections are balanced when several are given
+    #[clap(value_parser = parse_target, required = true)]
+    targets: Vec<ServerAddr>,
+
+    /// How the target of a connection is picked when several are given
+    #[clap(long, value_enum, default_value_t = BalanceStrategy::RoundRobin)]
+    balance: BalanceStrategy,
+
+    /// App context the tunnel is opened on, the default one when not set
+    #[clap(long, short)]
+    app_context: Option<String>,
+
+    /// Ask the server for this port instead of a random one
+    #[clap(long)]
+    remote_port: Option<u16>,
+
+    #[clap(long, value_enum, default_value_t = TunnelProtocol::Tcp)]
+    protocol: TunnelProtocol,
+
+    /// Only accept remote connections from this network (can be repeated)
+    #[clap(long = "allow", value_parser = parse_network)]
+    allow_list: Vec<IpNet>,
+
+    /// Reject remote connections from this network (can be repeated)
+    #[clap(long = "deny", value_parser = parse_network)]
+    deny_list: Vec<IpNet>,
+
+    /// Bandwidth limit in bytes per second, the server limit is used if it is lower
+    #[clap(long, value_parser = parse_rate_limit)]
+    rate_limit: Option<u64>,
+
+    /// Write a PROXY protocol header with the remote peer address to the target
+    #[clap(long, value_enum)]
+    proxy_protocol: Option<ProxyProtocol>,
+
+    /// Append every http request and response to this HAR file, implies --protocol http
+    #[clap(long)]
+    har: Option<PathBuf>,
+
+    /// Record every tunneled byte to this pcap file, to be opened with Wireshark
+    #[clap(long)]
+    capture: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct ReplayArgs {
+    /// Id of the captured request, shown at the end of its log line
+    id: String,
+
+    /// Capture file to read, the capture files of the defined tunnels are searched when not set
+    #[clap(long)]
+    har: Option<PathBuf>,
+
+    /// Send the request here instead of the target it was captured on
+    #[clap(long, value_parser = parse_target)]
+    target: Option<ServerAddr>,
+
+    /// Replace the method
+    #[clap(long, short = 'X')]
+    method: Option<String>,
+
+    /// Replace the path and query
+    #[clap(long)]
+    path: Option<String>,
+
+    /// Set a header, replacing the captured ones with the same name (can be repeated)
+    #[clap(long = "header", short = 'H', value_parser = parse_header)]
+    headers: Vec<(String, String)>,
+
+    /// Replace the body, @file reads it from a file
+    #[clap(long, short)]
+    body: Option<String>,
+
+    /// Edit the request in $EDITOR before sending it
+    #[clap(long, value_parser, default_value = "false")]
+    edit: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct RemoveTunnelArgs {
+    name: String,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct UpArgs {
+    /// Tunnels to start, every defined tunnel when empty
+    names: Vec<String>,
+
+    #[clap(short, long, value_parser, default_value = "false")]
+    verbose: bool,
+
+    #[clap(long, default_value = "5", value_parser = parse_ping_interval)]
+    ping_interval: u8,
+
+    /// Reconnect once the server comes back after restarting
+    #[clap(long, value_parser, default_value = "false")]
+    reconnect: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+#[cfg(unix)]
+pub struct DaemonArgs {
+    /// Tunnels to start, every defined tunnel when empty
+    names: Vec<String>,
+
+    /// Runs the daemon attached to the terminal
+    #[clap(long, value_parser, default_value = "false")]
+    foreground: bool,
+
+    #[clap(short, long, value_parser, default_value = "false")]
+    verbose: bool,
+
+    #[clap(long, default_value = "5", value_parser = parse_ping_interval)]
+    ping_interval: u8,
+}
+
+#[derive(Parser, Debug, Clone)]
+#[cfg(unix)]
+pub struct StartTunnelArgs {
+    name: String,
+}
+
+#[derive(Parser, Debug, Clone)]
+#[cfg(unix)]
+pub struct StopTunnelArgs {
+    name: Option<String>,
+}
+
 #[derive(Parser, Debug)]
 pub struct DeleteContextArgs {
     name: String,
@@ -54,8 +230,29 @@ pub struct CreateContextArgs {
     #[clap(value_parser = parse_server_addr)]
     host: ServerAddr,
 
-    #[clap(value_parser, long, default_value = "true")]
+    /// Connect to the server without TLS
+    #[clap(long, value_parser, default_value = "false", conflicts_with_all = ["ca_bundle", "pin_fingerprint", "insecure", "client_cert"])]
     disable_tls: bool,
+
+    /// Trust the CA certificates in this PEM file when verifying the server
+    #[clap(long)]
+    ca_bundle: Option<PathBuf>,
+
+    /// Only accept the server certificate with this sha256 fingerprint
+    #[clap(long, value_parser = parse_fingerprint)]
+    pin_fingerprint: Option<String>,
+
+    /// Skip verifying the server certificate
+    #[clap(long, value_parser, default_value = "false", conflicts_with_all = ["ca_bundle", "pin_fingerprint"])]
+    insecure: bool,
+
+    /// Authenticate with this PEM client certificate instead of logging in
+    #[clap(long, requires = "client_key")]
+    client_cert: Option<PathBuf>,
+
+    /// PKCS#8 PEM private key of the client certificate
+    #[clap(long, requires = "client_cert")]
+    client_key: Option<PathBuf>,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -64,12 +261,22 @@ pub struct LoginArgs {
     app_context: Option<String>,
 }
 
+#[derive(Parser, Debug, Clone)]
+pub struct UsageArgs {
+    #[clap(long, short)]
+    app_context: Option<String>,
+}
+
 #[derive(Parser, Debug, Clone)]
 pub struct ListenArgs {
-    port: u16,
+    /// Where remote connections are forwarded to, a port on 127.0.0.1 or host:port.
+    /// Connections are balanced when several are given
+    #[clap(value_parser = parse_target, required = true)]
+    targets: Vec<ServerAddr>,
 
-    #[clap(short, long, value_parser = parse_ip, default_value = "127.0.0.1")]
-    ip: Ipv4Addr,
+    /// How the target of a connection is picked when several are given
+    #[clap(long, value_enum, default_value_t = BalanceStrategy::RoundRobin)]
+    balance: BalanceStrategy,
 
     #[clap(short, long, value_parser, default_value = "false")]
     verbose: bool,
@@ -79,6 +286,38 @@ pub struct ListenArgs {
 
     #[clap(long, short)]
     app_context: Option<String>,
+
+    /// Only accept remote connections from this network (can be repeated)
+    #[clap(long = "allow", value_parser = parse_network)]
+    allow_list: Vec<IpNet>,
+
+    /// Reject remote connections from this network (can be repeated)
+    #[clap(long = "deny", value_parser = parse_network)]
+    deny_list: Vec<IpNet>,
+
+    /// Bandwidth limit in bytes per second, the server limit is used if it is lower
+    #[clap(long, value_parser = parse_rate_limit)]
+    rate_limit: Option<u64>,
+
+    /// Reconnect once the server comes back after restarting
+    #[clap(long, value_parser, default_value = "false")]
+    reconnect: bool,
+
+    /// Log every http request with its response status, latency and sizes
+    #[clap(long, value_enum, default_value_t = TunnelProtocol::Tcp)]
+    protocol: TunnelProtocol,
+
+    /// Write a PROXY protocol header with the remote peer address to the target
+    #[clap(long, value_enum)]
+    proxy_protocol: Option<ProxyProtocol>,
+
+    /// Append every http request and response to this HAR file, implies --protocol http
+    #[clap(long)]
+    har: Option<PathBuf>,
+
+    /// Record every tunneled byte to this pcap file, to be opened with Wireshark
+    #[clap(long)]
+    capture: Option<PathBuf>,
 }
 
 impl LoginArgs {
@@ -91,6 +330,12 @@ impl LoginArgs {
     }
 }
 
+impl UsageArgs {
+    pub fn app_context(&self) -> Option<&String> {
+        self.app_context.as_ref()
+    }
+}
+
 impl ClientArgs {
     pub fn get_type(&self) -> &AppCommandType {
         &self.command_type
@@ -115,6 +360,11 @@ impl CreateContextArgs {
             name: String::from(name),
             host: host.clone(),
             disable_tls: false,
+            ca_bundle: None,
+            pin_fingerprint: None,
+            insecure: false,
+            client_cert: None,
+            client_key: None,
         }
     }
 
@@ -129,6 +379,26 @@ impl CreateContextArgs {
     pub fn disable_tls(&self) -> bool {
         self.disable_tls
     }
+
+    pub fn ca_bundle(&self) -> Option<&Path> {
+        self.ca_bundle.as_deref()
+    }
+
+    pub fn pin_fingerprint(&self) -> Option<&str> {
+        self.pin_fingerprint.as_deref()
+    }
+
+    pub fn insecure(&self) -> bool {
+        self.insecure
+    }
+
+    pub fn client_cert(&self) -> Option<&Path> {
+        self.client_cert.as_deref()
+    }
+
+    pub fn client_key(&self) -> Option<&Path> {
+        self.client_key.as_deref()
+    }
 }
 
 impl ListenArgs {
@@ -136,8 +406,12 @@ impl ListenArgs {
         self.verbose
     }
 
-    pub fn parse_socket_addr(&self) -> SocketAddrV4 {
-        SocketAddrV4::new(self.ip, self.port)
+    pub fn target(&self) -> &ServerAddr {
+        &self.targets[0]
+    }
+
+    pub fn targets(&self) -> &[ServerAddr] {
+        &self.targets
     }
 
     pub fn ping_interval(&self) -> u8 {
@@ -147,6 +421,147 @@ impl ListenArgs {
     pub fn app_context(&self) -> Option<String> {
         self.app_context.clone()
     }
+
+    pub fn allow_list(&self) -> &[IpNet] {
+        &self.allow_list
+    }
+
+    pub fn deny_list(&self) -> &[IpNet] {
+        &self.deny_list
+    }
+
+    pub fn rate_limit(&self) -> Option<u64> {
+        self.rate_limit
+    }
+
+    pub fn reconnect(&self) -> bool {
+        self.reconnect
+    }
+
+    pub fn session_options(&self) -> SessionOptions {
+        SessionOptions::new(self.verbose, self.ping_interval, self.reconnect)
+    }
+
+    /// the tunnel described by the arguments, named after its target.
+    pub fn tunnel_definition(&self) -> TunnelDefinition {
+        TunnelDefinition::new(&self.targets[0].to_string(), &self.targets[0])
+            .with_balancing(&self.targets[1..], self.balance)
+            .with_access_lists(&self.allow_list, &self.deny_list)
+            .with_rate_limit(self.rate_limit)
+            .with_protocol(self.protocol)
+            .with_proxy_protocol(self.proxy_protocol)
+            .with_har(self.har.clone())
+            .with_capture(self.capture.clone())
+    }
+}
+
+impl AddTunnelArgs {
+    pub fn tunnel_definition(&self) -> TunnelDefinition {
+        TunnelDefinition::new(&self.name, &self.targets[0])
+            .with_balancing(&self.targets[1..], self.balance)
+            .with_context(self.app_context.as_deref())
+            .with_remote_port(self.remote_port)
+            .with_protocol(self.protocol)
+            .with_access_lists(&self.allow_list, &self.deny_list)
+            .with_rate_limit(self.rate_limit)
+            .with_proxy_protocol(self.proxy_protocol)
+            .with_har(self.har.clone())
+            .with_capture(self.capture.clone())
+    }
+}
+
+impl ReplayArgs {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn har(&self) -> Option<&Path> {
+        self.har.as_deref()
+    }
+
+    pub fn target(&self) -> Option<&ServerAddr> {
+        self.target.as_ref()
+    }
+
+    pub fn edit(&self) -> bool {
+        self.edit
+    }
+
+    /// applies the method, path, headers and body given on the command line.
+    pub fn apply(&self, mut request: ReplayRequest) -> Result<ReplayRequest> {
+        if let Some(method) = &self.method {
+            request = request.with_method(method);
+        }
+
+        if let Some(path) = &self.path {
+            request = request.with_path(path);
+        }
+
+        for (name, value) in &self.headers {
+            request = request.with_header(name, value);
+        }
+
+        match self.body.as_deref().map(|body| body.strip_prefix('@')) {
+            Some(Some(path)) => Ok(request.with_body(&std::fs::read(path)?)),
+            Some(None) => {
+                Ok(request.with_body(self.body.as_deref().unwrap_or_default().as_bytes()))
+            }
+            None => Ok(request),
+        }
+    }
+}
+
+impl RemoveTunnelArgs {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl UpArgs {
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn session_options(&self) -> SessionOptions {
+        SessionOptions::new(self.verbose, self.ping_interval, self.reconnect)
+    }
+}
+
+#[cfg(unix)]
+impl DaemonArgs {
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn foreground(&self) -> bool {
+        self.foreground
+    }
+
+    pub fn verbose(&self) -> bool {
+        self.verbose
+    }
+
+    /// sessions of the daemon reconnect on their own and never draw on the terminal.
+    pub fn session_options(&self) -> SessionOptions {
+        SessionOptions::new(false, self.ping_interval, true).without_console()
+    }
+}
+
+#[cfg(unix)]
+impl StartTunnelArgs {
+    pub fn control_request(&self) -> ControlRequest {
+        ControlRequest::Start(self.name.clone())
+    }
+}
+
+#[cfg(unix)]
+impl StopTunnelArgs {
+    pub fn control_request(&self) -> ControlRequest {
+        match &self.name {
+            Some(name) => ControlRequest::Stop(name.clone()),
+            None => ControlRequest::Shutdown,
+        }
+    }
 }
 
 fn parse_server_addr(given_str: &str) -> Result<ServerAddr> {
@@ -154,11 +569,21 @@ fn parse_server_addr(given_str: &str) -> Result<ServerAddr> {
     Ok(result)
 }
 
+fn parse_fingerprint(s: &str) -> Result<String> {
+    tcproxy_core::tls::parse_fingerprint(s)
+}
+
+fn parse_header(s: &str) -> Result<(String, String)> {
+    match s.split_once(':') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_owned(), value.trim().to_owned()))
+        }
+        _ => Err(format!("invalid header {}, expected Name: value", s).into()),
+    }
+}
+
 fn parse_ping_interval(s: &str) -> Result<u8> {
-    let parsed_value = match s.parse::<u8>() {
-        Ok(value) => value,
-        Err(err) => return Err(err.into()),
-    };
+    let parsed_value = s.parse::<u8>()?;
 
     if 2 > parsed_value {
         return Err("minimum ping interval is 2s".into());
@@ -167,10 +592,241 @@ fn parse_ping_interval(s: &str) -> Result<u8> {
     Ok(parsed_value)
 }
 
-/// validates if given ip target is a valid ip.
-fn parse_ip(s: &str) -> Result<Ipv4Addr> {
-    match Ipv4Addr::from_str(s) {
-        Ok(ip) => Ok(ip),
-        Err(_) => Err("Invalid IP Address.".into()),
+/// 0 would be sent as "no limit" to the server, so the lowest limit is 1 byte per second.
+fn parse_rate_limit(s: &str) -> Result<u64> {
+    let parsed_value = s.parse::<u64>()?;
+
+    if 1 > parsed_value {
+        return Err("minimum rate limit is 1 byte per second".into());
+    }
+
+    Ok(parsed_value)
+}
+
+/// validates the forwarding target, a bare port is a port on 127.0.0.1.
+fn parse_target(s: &str) -> Result<ServerAddr> {
+    match s.parse::<u16>() {
+        Ok(port) => Ok(ServerAddr::new("127.0.0.1", &port)?),
+        Err(_) => parse_server_addr(s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::{
+        AppCommandType, ClientArgs, ContextCommands, CreateContextArgs, ListenArgs, TunnelCommands,
+    };
+    use crate::config::TunnelProtocol;
+    #[cfg(unix)]
+    use crate::daemon::ControlRequest;
+    use crate::{BalanceStrategy, ReplayRequest};
+
+    fn parse_create(args: &[&str]) -> clap::error::Result<CreateContextArgs> {
+        let args = [&["tcproxy-cli", "context", "create"], args].concat();
+        match ClientArgs::try_parse_from(args)?.command_type {
+            AppCommandType::Context(ContextCommands::Create(args)) => Ok(args),
+            _ => unreachable!(),
+        }
+    }
+
+    fn parse_listen(args: &[&str]) -> clap::error::Result<ListenArgs> {
+        let args = [&["tcproxy-cli", "listen"], args].concat();
+        match ClientArgs::try_parse_from(args)?.command_type {
+            AppCommandType::Listen(args) => Ok(args),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn should_reject_zero_rate_limit() {
+        // Act
+        let zero = parse_listen(&["8080", "--rate-limit", "0"]);
+        let lowest = parse_listen(&["8080", "--rate-limit", "1"]).unwrap();
+
+        // Assert
+        assert!(zero.is_err());
+        assert_eq!(lowest.rate_limit(), Some(1));
+    }
+
+    #[test]
+    fn should_forward_bare_port_to_localhost() {
+        // Act
+        let args = parse_listen(&["8080"]).unwrap();
+
+        // Assert
+        assert_eq!(args.target().to_string(), "127.0.0.1:8080");
+    }
+
+    #[test]
+    fn should_accept_host_and_port_target() {
+        // Act
+        let dns = parse_listen(&["db.docker.internal:5432"]).unwrap();
+        let ipv6 = parse_listen(&["[::1]:8080"]).unwrap();
+
+        // Assert
+        assert_eq!(dns.target().host(), "db.docker.internal");
+        assert_eq!(*dns.target().port(), 5432);
+        assert_eq!(ipv6.target().to_string(), "[::1]:8080");
+    }
+
+    #[test]
+    fn should_balance_across_several_targets() {
+        // Act
+        let args = parse_listen(&["3000", "3001", "--balance", "least-connections"]).unwrap();
+        let definition = args.tunnel_definition();
+
+        // Assert
+        assert_eq!(args.targets().len(), 2);
+        assert_eq!(definition.name(), "127.0.0.1:3000");
+        assert_eq!(
+            definition.targets().unwrap()[1].to_string(),
+            "127.0.0.1:3001"
+        );
+        assert_eq!(definition.balance(), BalanceStrategy::LeastConnections);
+        assert!(parse_listen(&[]).is_err());
+    }
+
+    #[test]
+    fn should_apply_replay_edits() {
+        // Arrange
+        let args = [
+            "tcproxy-cli",
+            "replay",
+            "1f2e3d4c",
+            "-X",
+            "put",
+            "-H",
+            "X-Retry: 1",
+            "--body",
+            "{}",
+        ];
+        let request = ReplayRequest::from_raw(b"POST /hooks HTTP/1.1\r\nHost: a\r\n\r\n").unwrap();
+
+        // Act
+        let result = match ClientArgs::try_parse_from(args).unwrap().command_type {
+            AppCommandType::Replay(args) => args.apply(request).unwrap(),
+            _ => unreachable!(),
+        };
+
+        // Assert
+        assert_eq!(
+            result.to_bytes(),
+            b"PUT /hooks HTTP/1.1\r\nHost: a\r\nX-Retry: 1\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}"
+        );
+        assert!(
+            ClientArgs::try_parse_from(["tcproxy-cli", "replay", "a", "-H", "broken"]).is_err()
+        );
+    }
+
+    #[test]
+    fn should_parse_tunnel_definition() {
+        // Arrange
+        let args = [
+            "tcproxy-cli",
+            "tunnel",
+            "add",
+            "api",
+            "3000",
+            "--remote-port",
+            "15080",
+            "--protocol",
+            "http",
+            "--app-context",
+            "prod",
+        ];
+
+        // Act
+        let definition = match ClientArgs::try_parse_from(args).unwrap().command_type {
+            AppCommandType::Tunnel(TunnelCommands::Add(args)) => args.tunnel_definition(),
+            _ => unreachable!(),
+        };
+
+        // Assert
+        assert_eq!(definition.name(), "api");
+        assert_eq!(definition.target().unwrap().to_string(), "127.0.0.1:3000");
+        assert_eq!(definition.remote_port(), Some(15080));
+        assert_eq!(definition.protocol(), TunnelProtocol::Http);
+        assert_eq!(definition.context(), Some("prod"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn should_stop_daemon_when_no_tunnel_is_given() {
+        // Act
+        let stop_tunnel = ClientArgs::try_parse_from(["tcproxy-cli", "stop", "api"]).unwrap();
+        let stop_daemon = ClientArgs::try_parse_from(["tcproxy-cli", "stop"]).unwrap();
+
+        // Assert
+        match (stop_tunnel.command_type, stop_daemon.command_type) {
+            (AppCommandType::Stop(tunnel), AppCommandType::Stop(daemon)) => {
+                assert_eq!(
+                    tunnel.control_request(),
+                    ControlRequest::Stop(String::from("api"))
+                );
+                assert_eq!(daemon.control_request(), ControlRequest::Shutdown);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn should_enable_tls_by_default() {
+        // Act
+        let args = parse_create(&["default", "proxy.server.io:8080"]).unwrap();
+
+        // Assert
+        assert!(!args.disable_tls());
+        assert!(!args.insecure());
+    }
+
+    #[test]
+    fn should_parse_tls_options() {
+        // Arrange
+        let fingerprint = "AB:".repeat(31) + "AB";
+
+        // Act
+        let args = parse_create(&[
+            "default",
+            "proxy.server.io:8080",
+            "--ca-bundle",
+            "./ca.pem",
+            "--pin-fingerprint",
+            &fingerprint,
+        ])
+        .unwrap();
+
+        // Assert
+        assert_eq!(args.ca_bundle().unwrap().to_str(), Some("./ca.pem"));
+        assert_eq!(args.pin_fingerprint(), Some("ab".repeat(32).as_str()));
+    }
+
+    #[test]
+    fn should_require_client_key_with_certificate() {
+        // Act
+        let result = parse_create(&[
+            "default",
+            "proxy.server.io:8080",
+            "--client-cert",
+            "./client.pem",
+        ]);
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn should_reject_insecure_without_tls() {
+        // Act
+        let result = parse_create(&[
+            "default",
+            "proxy.server.io:8080",
+            "--disable-tls",
+            "--insecure",
+        ]);
+
+        // Assert
+        assert!(result.is_err());
     }
 }
@@ -1,38 +1,232 @@
 use bytes::BytesMut;
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
-use std::net::{Ipv4Addr, SocketAddr};
-use std::str::FromStr;
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Instant;
+use tcproxy_core::framing::{ServerShutdown, TunnelStats};
 use tokio::sync::mpsc::Sender;
 use tokio_util::sync::CancellationToken;
 use tracing::debug;
 
+use crate::config::{TunnelDefinition, TunnelProtocol};
+use crate::dashboard::LogBuffer;
+use crate::inspector::HarWriter;
+use crate::pcap::PcapWriter;
+use crate::server_addr::ServerAddr;
+use crate::{ProxyProtocol, TargetPool};
+
+/// tunnel opened by the server for the current session.
+#[derive(Debug, Clone)]
+pub struct ActiveTunnel {
+    name: String,
+    targets: Arc<TargetPool>,
+    protocol: TunnelProtocol,
+    proxy_protocol: Option<ProxyProtocol>,
+    listener_port: u16,
+    public_addr: Option<SocketAddr>,
+    har: Option<Arc<HarWriter>>,
+    capture: Option<Arc<PcapWriter>>,
+}
+
+impl ActiveTunnel {
+    pub fn new(
+        definition: &TunnelDefinition,
+        targets: &[ServerAddr],
+        listener_port: &u16,
+        public_addr: Option<SocketAddr>,
+    ) -> Self {
+        Self {
+            name: definition.name().to_owned(),
+            targets: Arc::new(TargetPool::new(targets, definition.balance())),
+            protocol: definition.protocol(),
+            proxy_protocol: definition.proxy_protocol(),
+            listener_port: *listener_port,
+            public_addr,
+            har: None,
+            capture: None,
+        }
+    }
+
+    /// capture file shared by every connection of the tunnel.
+    pub fn with_har(mut self, har: Option<Arc<HarWriter>>) -> Self {
+        self.har = har;
+        self
+    }
+
+    /// pcap file shared by every connection of the tunnel.
+    pub fn with_capture(mut self, capture: Option<Arc<PcapWriter>>) -> Self {
+        self.capture = capture;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// first target of the tunnel, the only one unless connections are balanced.
+    pub fn target(&self) -> &ServerAddr {
+        self.targets.get(0)
+    }
+
+    pub fn targets(&self) -> &Arc<TargetPool> {
+        &self.targets
+    }
+
+    pub fn protocol(&self) -> TunnelProtocol {
+        self.protocol
+    }
+
+    pub fn proxy_protocol(&self) -> Option<ProxyProtocol> {
+        self.proxy_protocol
+    }
+
+    pub fn listener_port(&self) -> &u16 {
+        &self.listener_port
+    }
+
+    pub fn public_addr(&self) -> Option<SocketAddr> {
+        self.public_addr
+    }
+
+    pub fn har(&self) -> Option<Arc<HarWriter>> {
+        self.har.clone()
+    }
+
+    pub fn capture(&self) -> Option<Arc<PcapWriter>> {
+        self.capture.clone()
+    }
+
+    /// http requests are logged on http tunnels and on tunnels with a capture file.
+    pub fn inspects(&self) -> bool {
+        self.protocol == TunnelProtocol::Http || self.har.is_some()
+    }
+}
+
+/// remote connection forwarded to a local target.
+#[derive(Debug, Clone)]
+pub struct ConnectionInfo {
+    id: u32,
+    tunnel: String,
+    remote_addr: Option<SocketAddr>,
+    listener_addr: Option<SocketAddr>,
+    opened_at: Instant,
+    bytes_in: u64,
+    bytes_out: u64,
+}
+
+impl ConnectionInfo {
+    pub fn new(id: &u32, tunnel: &str) -> Self {
+        Self {
+            id: *id,
+            tunnel: String::from(tunnel),
+            remote_addr: None,
+            listener_addr: None,
+            opened_at: Instant::now(),
+            bytes_in: 0,
+            bytes_out: 0,
+        }
+    }
+
+    /// peer that connected to the tunnel and the address it connected to,
+    /// as sent by the server.
+    pub fn with_addresses(
+        mut self,
+        remote_addr: Option<SocketAddr>,
+        listener_addr: Option<SocketAddr>,
+    ) -> Self {
+        self.remote_addr = remote_addr;
+        self.listener_addr = listener_addr;
+        self
+    }
+
+    pub fn id(&self) -> &u32 {
+        &self.id
+    }
+
+    pub fn tunnel(&self) -> &str {
+        &self.tunnel
+    }
+
+    pub fn remote_addr(&self) -> Option<SocketAddr> {
+        self.remote_addr
+    }
+
+    pub fn listener_addr(&self) -> Option<SocketAddr> {
+        self.listener_addr
+    }
+
+    pub fn opened_at(&self) -> Instant {
+        self.opened_at
+    }
+
+    /// bytes received from the remote peer.
+    pub fn bytes_in(&self) -> &u64 {
+        &self.bytes_in
+    }
+
+    /// bytes sent back to the remote peer.
+    pub fn bytes_out(&self) -> &u64 {
+        &self.bytes_out
+    }
+}
+
+struct ConnectionEntry {
+    sender: Sender<BytesMut>,
+    cancellation_token: CancellationToken,
+    info: ConnectionInfo,
+}
+
+/// how many ping samples are kept for the rtt graph.
+const PING_HISTORY_LEN: usize = 120;
+
 pub struct ClientState {
     console_sender: Sender<i32>,
-    remote_ip: Mutex<String>,
+    tunnels: Mutex<Vec<ActiveTunnel>>,
     last_sent_ping: Mutex<u32>,
     last_ping: Mutex<u32>,
-    connections: Mutex<HashMap<u32, (Sender<BytesMut>, CancellationToken)>>,
+    ping_history: Mutex<VecDeque<u64>>,
+    tunnel_stats: Mutex<TunnelStats>,
+    server_shutdown: Mutex<Option<ServerShutdown>>,
+    connections: Mutex<HashMap<u32, ConnectionEntry>>,
+    log: LogBuffer,
 }
 
 pub struct ConsoleStatus {
-    pub remote_ip: SocketAddr,
+    pub tunnels: Vec<ActiveTunnel>,
     pub ping: f64,
+    pub ping_history: Vec<u64>,
     pub connections: i32,
+    pub tunnel_stats: TunnelStats,
+    pub server_shutdown: Option<ServerShutdown>,
 }
 
 impl ClientState {
     pub fn new(console_sender: &Sender<i32>) -> Self {
         Self {
-            remote_ip: Mutex::new(String::from("")),
+            tunnels: Mutex::new(Vec::new()),
             connections: Mutex::new(HashMap::new()),
             last_sent_ping: Mutex::new(0),
             last_ping: Mutex::new(0),
+            ping_history: Mutex::new(VecDeque::with_capacity(PING_HISTORY_LEN)),
+            tunnel_stats: Mutex::new(TunnelStats::default()),
+            server_shutdown: Mutex::new(None),
             console_sender: console_sender.clone(),
+            log: LogBuffer::default(),
         }
     }
 
+    /// writes session events to `log` instead of a buffer of its own.
+    pub fn with_log(mut self, log: &LogBuffer) -> Self {
+        self.log = log.clone();
+        self
+    }
+
+    pub fn log(&self) -> &LogBuffer {
+        &self.log
+    }
+
     pub fn update_last_sent_ping(&self, time: &DateTime<Utc>) {
         let mut last_sent_ping = self.last_sent_ping.lock().unwrap();
         *last_sent_ping = time.timestamp_subsec_millis();
@@ -45,50 +239,105 @@ impl ClientState {
         *last_ping = time.timestamp_subsec_millis() - *mutex_lock;
         drop(mutex_lock);
 
+        let mut ping_history = self.ping_history.lock().unwrap();
+        if ping_history.len() == PING_HISTORY_LEN {
+            ping_history.pop_front();
+        }
+        ping_history.push_back(*last_ping as u64);
+        drop(ping_history);
+        drop(last_ping);
+
         self.notify_console_update();
     }
 
-    pub fn update_remote_ip(&self, ip: &str) {
-        let mut mutex = self.remote_ip.lock().unwrap();
-        *mutex = String::from(ip);
+    pub fn add_tunnel(&self, tunnel: ActiveTunnel) {
+        let mut mutex = self.tunnels.lock().unwrap();
+        mutex.push(tunnel);
+        drop(mutex);
+
+        self.notify_console_update();
+    }
+
+    /// tunnel listening on the given server port, servers that don't send the port
+    /// only have a single tunnel per session.
+    pub fn get_tunnel(&self, listener_port: &u16) -> Option<ActiveTunnel> {
+        let tunnels = self.tunnels.lock().unwrap();
+        let tunnel = tunnels
+            .iter()
+            .find(|tunnel| tunnel.listener_port() == listener_port);
+
+        match (tunnel, tunnels.len()) {
+            (Some(tunnel), _) => Some(tunnel.clone()),
+            (None, 1) if *listener_port == 0 => tunnels.first().cloned(),
+            _ => None,
+        }
+    }
+
+    pub fn tunnels(&self) -> Vec<ActiveTunnel> {
+        self.tunnels.lock().unwrap().clone()
+    }
+
+    pub fn update_tunnel_stats(&self, stats: TunnelStats) {
+        let mut mutex = self.tunnel_stats.lock().unwrap();
+        *mutex = stats;
+        drop(mutex);
 
         self.notify_console_update();
     }
 
+    /// records that the server announced it is going down.
+    pub fn set_server_shutdown(&self, notice: ServerShutdown) {
+        self.log.push(&format!(
+            "server is restarting ({}), the tunnel closes in {}s",
+            notice.reason(),
+            notice.deadline()
+        ));
+
+        let mut mutex = self.server_shutdown.lock().unwrap();
+        *mutex = Some(notice);
+        drop(mutex);
+
+        self.notify_console_update();
+    }
+
+    pub fn server_shutdown(&self) -> Option<ServerShutdown> {
+        self.server_shutdown.lock().unwrap().clone()
+    }
+
     pub fn get_console_status(&self) -> ConsoleStatus {
-        let remote_ip = self.remote_ip.lock().unwrap();
+        let tunnels = self.tunnels.lock().unwrap().clone();
         let ping = *self.last_ping.lock().unwrap() as f64;
         let connections = self.connections.lock().unwrap();
 
-        let remote_ip_str = remote_ip.clone();
         let connections_len = connections.len();
 
-        let remote_ip = match remote_ip_str.as_str() {
-            "" => SocketAddr::new(
-                std::net::IpAddr::V4(Ipv4Addr::from_str("0.0.0.0").unwrap()),
-                8080u16,
-            ),
-            value => SocketAddr::new(
-                std::net::IpAddr::V4(Ipv4Addr::from_str("0.0.0.0").unwrap()),
-                value.parse::<u16>().unwrap(),
-            ),
-        };
-
         ConsoleStatus {
             ping,
-            remote_ip,
+            ping_history: self.ping_history.lock().unwrap().iter().copied().collect(),
+            tunnels,
             connections: connections_len as i32,
+            tunnel_stats: self.tunnel_stats.lock().unwrap().clone(),
+            server_shutdown: self.server_shutdown(),
         }
     }
 
     pub fn insert_connection(
         &self,
-        connection_id: &u32,
+        info: ConnectionInfo,
         sender: Sender<BytesMut>,
         cancellation_token: CancellationToken,
     ) {
+        self.log.push(&format_connection_opened(&info));
+
         let mut lock = self.connections.lock().unwrap();
-        lock.insert(*connection_id, (sender, cancellation_token));
+        lock.insert(
+            *info.id(),
+            ConnectionEntry {
+                sender,
+                cancellation_token,
+                info,
+            },
+        );
         drop(lock);
 
         self.notify_console_update();
@@ -97,7 +346,7 @@ impl ClientState {
     pub fn get_connection(&self, id: &u32) -> Option<(Sender<BytesMut>, CancellationToken)> {
         let lock = self.connections.lock().unwrap();
         match lock.get(id) {
-            Some((sender, token)) => Some((sender.clone(), token.clone())),
+            Some(entry) => Some((entry.sender.clone(), entry.cancellation_token.clone())),
             None => {
                 debug!("connection {} not found", id);
                 None
@@ -108,14 +357,47 @@ impl ClientState {
     pub fn remove_connection(&self, id: &u32) -> Option<(Sender<BytesMut>, CancellationToken)> {
         debug!("removing connection {}", id);
         let mut lock = self.connections.lock().unwrap();
-        if !lock.contains_key(id) {
-            return None;
+        let entry = lock.remove(id)?;
+        drop(lock);
+
+        self.log.push(&format!("connection {} closed", id));
+        self.notify_console_update();
+        Some((entry.sender, entry.cancellation_token))
+    }
+
+    /// closes the local side of a connection, the server is told once it stops.
+    pub fn close_connection(&self, id: &u32) -> bool {
+        let lock = self.connections.lock().unwrap();
+        match lock.get(id) {
+            Some(entry) => {
+                entry.cancellation_token.cancel();
+                true
+            }
+            None => false,
         }
+    }
 
-        let result = lock.remove(id).unwrap();
+    /// open connections, oldest first.
+    pub fn connections(&self) -> Vec<ConnectionInfo> {
+        let lock = self.connections.lock().unwrap();
+        let mut connections: Vec<ConnectionInfo> =
+            lock.values().map(|entry| entry.info.clone()).collect();
+        connections.sort_by_key(|info| info.id);
 
-        self.notify_console_update();
-        Some(result)
+        connections
+    }
+
+    /// counters are read by the dashboard on its own ticks, so they don't notify it.
+    pub fn record_bytes_in(&self, id: &u32, bytes: usize) {
+        if let Some(entry) = self.connections.lock().unwrap().get_mut(id) {
+            entry.info.bytes_in += bytes as u64;
+        }
+    }
+
+    pub fn record_bytes_out(&self, id: &u32, bytes: usize) {
+        if let Some(entry) = self.connections.lock().unwrap().get_mut(id) {
+            entry.info.bytes_out += bytes as u64;
+        }
     }
 
     fn notify_console_update(&self) {
@@ -126,3 +408,74 @@ impl ClientState {
         });
     }
 }
+
+/// `connection 7 from 198.51.100.20:40000 opened on api (203.0.113.5:15080)`
+fn format_connection_opened(info: &ConnectionInfo) -> String {
+    let mut line = format!("connection {}", info.id());
+    if let Some(remote_addr) = info.remote_addr() {
+        line.push_str(&format!(" from {}", remote_addr));
+    }
+
+    line.push_str(&format!(" opened on {}", info.tunnel()));
+    if let Some(listener_addr) = info.listener_addr() {
+        line.push_str(&format!(" ({})", listener_addr));
+    }
+
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::str::FromStr;
+    use tokio::sync::mpsc;
+    use tokio_util::sync::CancellationToken;
+
+    use super::{ClientState, ConnectionInfo};
+
+    #[tokio::test]
+    async fn should_log_peer_and_listener_address() {
+        // Arrange
+        let (console_sender, _) = mpsc::channel::<i32>(10);
+        let (sender, _) = mpsc::channel(1);
+        let state = ClientState::new(&console_sender);
+        let info = ConnectionInfo::new(&7, "api").with_addresses(
+            Some(SocketAddr::from_str("198.51.100.20:40000").unwrap()),
+            Some(SocketAddr::from_str("203.0.113.5:15080").unwrap()),
+        );
+
+        // Act
+        state.insert_connection(info, sender, CancellationToken::new());
+
+        // Assert
+        let lines = state.log().lines();
+        assert!(lines[0]
+            .ends_with("connection 7 from 198.51.100.20:40000 opened on api (203.0.113.5:15080)"));
+        assert_eq!(
+            state.connections()[0].remote_addr(),
+            Some(SocketAddr::from_str("198.51.100.20:40000").unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn should_close_connection_and_count_bytes() {
+        // Arrange
+        let (console_sender, _) = mpsc::channel::<i32>(10);
+        let (sender, _) = mpsc::channel(1);
+        let token = CancellationToken::new();
+        let state = ClientState::new(&console_sender);
+        state.insert_connection(ConnectionInfo::new(&7, "api"), sender, token.clone());
+
+        // Act
+        state.record_bytes_in(&7, 100);
+        state.record_bytes_out(&7, 40);
+        let closed = state.close_connection(&7);
+
+        // Assert
+        assert!(closed);
+        assert!(token.is_cancelled());
+        assert_eq!(state.connections()[0].bytes_in(), &100);
+        assert_eq!(state.connections()[0].bytes_out(), &40);
+        assert!(!state.close_connection(&8));
+    }
+}
@@ -22,7 +22,20 @@ impl Command for CreateContextCommand {
     fn handle(&mut self) -> Self::Output {
         let context_addr = self.args.host();
         let use_ssl = !self.args.disable_tls();
-        let context = AppContext::from_addr(self.args.name(), context_addr, use_ssl);
+        let mut context = AppContext::from_addr(self.args.name(), context_addr, use_ssl)
+            .with_insecure(self.args.insecure());
+
+        if let Some(path) = self.args.ca_bundle() {
+            context = context.with_ca_bundle(path);
+        }
+
+        if let Some(fingerprint) = self.args.pin_fingerprint() {
+            context = context.with_pinned_fingerprint(fingerprint);
+        }
+
+        if let (Some(certificate), Some(key)) = (self.args.client_cert(), self.args.client_key()) {
+            context = context.with_client_certificate(certificate, key);
+        }
 
         push_context(&self.config, &context)?;
 
@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use tcproxy_core::{AsyncCommand, Result};
+
+use crate::config::directory_resolver::DirectoryResolver;
+use crate::daemon::{send_request, ControlRequest, ControlResponse};
+
+/// Sends a request to the running daemon and prints its answer.
+pub struct ControlCommand {
+    request: ControlRequest,
+    directory_resolver: DirectoryResolver,
+}
+
+impl ControlCommand {
+    pub fn new(request: ControlRequest, directory_resolver: &DirectoryResolver) -> Self {
+        Self {
+            request,
+            directory_resolver: directory_resolver.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl AsyncCommand for ControlCommand {
+    type Output = Result<()>;
+
+    async fn handle(&mut self) -> Self::Output {
+        let socket = self.directory_resolver.get_daemon_socket();
+        match send_request(&socket, &self.request).await? {
+            ControlResponse::Ok(body) => {
+                println!("{}", body);
+                Ok(())
+            }
+            ControlResponse::Err(message) => Err(message.into()),
+        }
+    }
+}
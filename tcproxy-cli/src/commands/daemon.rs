@@ -0,0 +1,135 @@
+use async_trait::async_trait;
+use std::fs::OpenOptions;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+use tcproxy_core::{AsyncCommand, Result};
+use tokio::sync::{broadcast, mpsc::Sender};
+
+use crate::config::{self, directory_resolver::DirectoryResolver};
+use crate::daemon::{ControlListener, TunnelSupervisor};
+use crate::{DaemonArgs, Shutdown};
+
+/// how long `tcproxy-cli daemon` waits for the background process to open its socket.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Runs tunnels in the background, controlled with `tcproxy-cli status|start|stop`.
+pub struct DaemonCommand {
+    args: DaemonArgs,
+    directory_resolver: DirectoryResolver,
+    _shutdown_complete_tx: Sender<()>,
+}
+
+impl DaemonCommand {
+    pub fn new(
+        args: &DaemonArgs,
+        directory_resolver: &DirectoryResolver,
+        shutdown_complete_tx: Sender<()>,
+    ) -> Self {
+        Self {
+            args: args.clone(),
+            directory_resolver: directory_resolver.clone(),
+            _shutdown_complete_tx: shutdown_complete_tx,
+        }
+    }
+
+    /// starts `tcproxy-cli daemon --foreground` detached from the terminal and waits for
+    /// its socket, so startup errors are reported right away.
+    async fn spawn_background(&self) -> Result<()> {
+        let socket = self.directory_resolver.get_daemon_socket();
+        if std::os::unix::net::UnixStream::connect(&socket).is_ok() {
+            return Err("tcproxy-cli daemon is already running".into());
+        }
+
+        let log_path = self.directory_resolver.get_daemon_log();
+        let log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)?;
+
+        let mut command = Command::new(std::env::current_exe()?);
+        command.args(["daemon", "--foreground"]);
+        if self.args.verbose() {
+            command.arg("--verbose");
+        }
+
+        let mut child = command
+            .args(self.args.names())
+            .stdin(Stdio::null())
+            .stdout(log.try_clone()?)
+            .stderr(log)
+            .process_group(0)
+            .spawn()?;
+
+        let started_at = tokio::time::Instant::now();
+        while started_at.elapsed() < STARTUP_TIMEOUT {
+            if let Some(status) = child.try_wait()? {
+                return Err(format!("daemon exited with {}, see {:?}", status, log_path).into());
+            }
+
+            if std::os::unix::net::UnixStream::connect(&socket).is_ok() {
+                println!(
+                    "daemon started (pid {}), logs are written to {:?}",
+                    child.id(),
+                    log_path
+                );
+                return Ok(());
+            }
+
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        Err(format!("daemon didn't start in time, see {:?}", log_path).into())
+    }
+}
+
+#[async_trait]
+impl AsyncCommand for DaemonCommand {
+    type Output = Result<()>;
+
+    async fn handle(&mut self) -> Self::Output {
+        if !self.args.foreground() {
+            return self.spawn_background().await;
+        }
+
+        if self.args.verbose() {
+            tracing_subscriber::fmt::init();
+        }
+
+        let (notify_shutdown, _) = broadcast::channel::<()>(1);
+        let supervisor = Arc::new(TunnelSupervisor::new(
+            &self.directory_resolver,
+            self.args.session_options(),
+            &self._shutdown_complete_tx,
+        ));
+
+        let socket = self.directory_resolver.get_daemon_socket();
+        let listener = ControlListener::bind(&socket, &supervisor, &notify_shutdown).await?;
+        println!("daemon listening on {:?}", socket);
+
+        let names = match self.args.names().is_empty() {
+            true => {
+                let config = config::load(&self.directory_resolver)?;
+                let tunnel_manager = config.lock_tunnel_manager()?;
+                tunnel_manager
+                    .tunnels()
+                    .iter()
+                    .map(|tunnel| String::from(tunnel.name()))
+                    .collect()
+            }
+            false => self.args.names().to_vec(),
+        };
+
+        for name in names {
+            match supervisor.start(&name) {
+                Ok(_) => println!("started tunnel {}", name),
+                Err(err) => println!("failed to start tunnel {}: {}", name, err),
+            }
+        }
+
+        listener
+            .run(Shutdown::new(notify_shutdown.subscribe()))
+            .await
+    }
+}
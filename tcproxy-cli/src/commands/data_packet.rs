@@ -44,6 +44,8 @@ impl AsyncCommand for DataPacketCommand {
         debug!("received new packet from {}", self.connection_id);
         match self.state.get_connection(&self.connection_id) {
             Some((sender, _)) => {
+                self.state
+                    .record_bytes_in(&self.connection_id, self.buffer.len());
                 let sender_clone = sender.clone();
                 let buffer = BytesMut::from(&self.buffer[..]);
                 let _ = sender_clone.send(buffer).await;
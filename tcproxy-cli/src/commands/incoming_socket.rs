@@ -1,32 +1,38 @@
 use async_trait::async_trait;
 use bytes::BytesMut;
+use std::net::SocketAddr;
 use std::sync::Arc;
-use tcproxy_core::framing::SocketDisconnected;
+use tcproxy_core::framing::{SocketConnected, SocketDisconnected};
 use tcproxy_core::{AsyncCommand, Result, TcpFrame};
 use tokio::sync::mpsc::{self, Sender};
 use tokio_util::sync::CancellationToken;
 use tracing::debug;
 
-use crate::{client_state::ClientState, ListenArgs, LocalConnection};
+use crate::{
+    client_state::ClientState, ConnectionInfo, HttpInspector, LocalConnection, PacketCapture,
+};
 
 /// issued when a remote socket connects to server.
 pub struct IncomingSocketCommand {
     connection_id: u32,
+    listener_port: u16,
+    peer_addr: Option<SocketAddr>,
+    listener_addr: Option<SocketAddr>,
     client_sender: Sender<TcpFrame>,
     state: Arc<ClientState>,
-    args: Arc<ListenArgs>,
 }
 
 impl IncomingSocketCommand {
     pub fn new(
-        id: &u32,
+        frame: &SocketConnected,
         sender: &Sender<TcpFrame>,
         state: &Arc<ClientState>,
-        args: &Arc<ListenArgs>,
     ) -> Self {
         Self {
-            connection_id: *id,
-            args: args.clone(),
+            connection_id: *frame.connection_id(),
+            listener_port: *frame.listener_port(),
+            peer_addr: *frame.peer_addr(),
+            listener_addr: *frame.listener_addr(),
             state: state.clone(),
             client_sender: sender.clone(),
         }
@@ -39,18 +45,55 @@ impl AsyncCommand for IncomingSocketCommand {
 
     async fn handle(&mut self) -> Self::Output {
         debug!("new connection received!");
+        let tunnel = match self.state.get_tunnel(&self.listener_port) {
+            Some(tunnel) => tunnel,
+            None => {
+                debug!("no tunnel listening on port {}", self.listener_port);
+                let _ = self
+                    .client_sender
+                    .send(TcpFrame::SocketDisconnected(SocketDisconnected::new(
+                        &self.connection_id,
+                    )))
+                    .await;
+                return Ok(());
+            }
+        };
+
         let (connection_sender, reader) = mpsc::channel::<BytesMut>(1000);
         let token = CancellationToken::new();
         let cancellation_token = token.child_token();
 
-        self.state
-            .insert_connection(&self.connection_id, connection_sender, token);
+        let info = ConnectionInfo::new(&self.connection_id, tunnel.name())
+            .with_addresses(self.peer_addr, self.listener_addr);
+        self.state.insert_connection(info, connection_sender, token);
 
-        let target_ip = self.args.parse_socket_addr();
         let connection_id = self.connection_id;
         let sender = self.client_sender.clone();
-        let mut local_connection =
-            LocalConnection::new(self.connection_id, &self.client_sender, target_ip);
+        let state = self.state.clone();
+        let mut local_connection = LocalConnection::new(
+            self.connection_id,
+            &self.client_sender,
+            tunnel.targets(),
+            &self.state,
+        )
+        .with_proxy_header(
+            tunnel
+                .proxy_protocol()
+                .map(|version| version.header(self.peer_addr, self.listener_addr)),
+        )
+        .with_observer(
+            tunnel
+                .inspects()
+                .then(|| HttpInspector::new(&self.connection_id, &tunnel, self.state.log())),
+        )
+        .with_observer(tunnel.capture().map(|writer| {
+            PacketCapture::new(
+                &writer,
+                &self.connection_id,
+                self.peer_addr,
+                self.listener_addr,
+            )
+        }));
 
         tokio::spawn(async move {
             let _ = local_connection
@@ -58,6 +101,7 @@ impl AsyncCommand for IncomingSocketCommand {
                 .await;
 
             debug!("Local connection socket finished.");
+            state.remove_connection(&connection_id);
             let _ = sender
                 .send(TcpFrame::SocketDisconnected(SocketDisconnected::new(
                     &connection_id,
@@ -1,42 +1,107 @@
 use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use tcproxy_core::auth::token_handler::AuthToken;
-use tokio::sync::broadcast;
 use tokio::sync::mpsc::{self, Sender};
+use tokio::sync::{broadcast, watch};
 
 use tracing::{debug, error, info};
 
-use tcproxy_core::framing::Reason;
-use tcproxy_core::framing::{Authenticate, ClientConnected, GrantType, TokenAuthenticationArgs};
+use tcproxy_core::framing::{Authenticate, GrantType, TokenAuthenticationArgs};
+use tcproxy_core::framing::{Reason, ServerShutdown};
 use tcproxy_core::{transport::TcpFrameTransport, AsyncCommand, Result, TcpFrame};
 
-use crate::config::{AppContext, Config};
+use crate::config::{AppContext, Config, TunnelDefinition};
 use crate::server_addr::ServerAddr;
 use crate::{
-    ClientState, ConsoleUpdater, ListenArgs, PingSender, Shutdown, TcpFrameReader, TcpFrameWriter,
+    ActiveTunnel, ClientState, Dashboard, HarWriter, HealthChecker, LogBuffer, PcapWriter,
+    PingSender, Shutdown, TcpFrameReader, TcpFrameWriter,
 };
 
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// settings of the session carrying the tunnels, shared by `listen` and `up`.
+#[derive(Debug, Clone, Copy)]
+pub struct SessionOptions {
+    verbose: bool,
+    ping_interval: u8,
+    reconnect: bool,
+    console: bool,
+}
+
+impl SessionOptions {
+    pub fn new(verbose: bool, ping_interval: u8, reconnect: bool) -> Self {
+        Self {
+            verbose,
+            ping_interval,
+            reconnect,
+            console: true,
+        }
+    }
+
+    /// doesn't draw the tunnel status on the terminal, used when running in the background.
+    pub fn without_console(mut self) -> Self {
+        self.console = false;
+        self
+    }
+
+    pub fn verbose(&self) -> bool {
+        self.verbose
+    }
+
+    pub fn ping_interval(&self) -> u8 {
+        self.ping_interval
+    }
+
+    pub fn reconnect(&self) -> bool {
+        self.reconnect
+    }
+
+    pub fn console(&self) -> bool {
+        self.console
+    }
+}
+
+/// opens every tunnel on a single session with the server.
 pub struct ListenCommand {
-    args: Arc<ListenArgs>,
+    tunnels: Vec<TunnelDefinition>,
+    app_context: Option<String>,
+    options: SessionOptions,
     config: Arc<Config>,
+    state_sender: Option<watch::Sender<Option<Arc<ClientState>>>>,
+    log: LogBuffer,
     _shutdown_complete_tx: Sender<()>,
-    _notify_shutdown: broadcast::Sender<()>,
+    notify_shutdown: broadcast::Sender<()>,
 }
 
 impl ListenCommand {
     pub fn new(
-        args: Arc<ListenArgs>,
+        tunnels: &[TunnelDefinition],
+        app_context: Option<String>,
+        options: SessionOptions,
         config: Arc<Config>,
         shutdown_complete_tx: Sender<()>,
         notify_shutdown: broadcast::Sender<()>,
     ) -> Self {
         Self {
+            tunnels: tunnels.to_vec(),
+            app_context,
+            options,
             config,
-            args: Arc::clone(&args),
-            _notify_shutdown: notify_shutdown,
+            state_sender: None,
+            log: LogBuffer::default(),
+            notify_shutdown,
             _shutdown_complete_tx: shutdown_complete_tx,
         }
     }
+
+    /// publishes the state of the current session, `None` while reconnecting.
+    pub fn with_state_sender(mut self, sender: watch::Sender<Option<Arc<ClientState>>>) -> Self {
+        self.state_sender = Some(sender);
+        self
+    }
 }
 
 #[async_trait]
@@ -44,32 +109,70 @@ impl AsyncCommand for ListenCommand {
     type Output = Result<()>;
 
     async fn handle(&mut self) -> Result<()> {
-        if self.args.is_debug() {
-            tracing_subscriber::fmt::init();
+        if self.options.verbose() {
+            tracing_subscriber::fmt()
+                .with_writer(self.log.clone())
+                .with_ansi(false)
+                .init();
         }
 
-        let app_context = get_context(&self.args, &self.config)?;
+        let app_context = get_context(self.app_context.clone(), &self.config)?;
         let mut transport = get_transport(&app_context).await?;
 
+        loop {
+            let notice = match self.run_session(&app_context, transport).await? {
+                Some(notice) => notice,
+                None => return Ok(()),
+            };
+
+            if !self.options.reconnect() {
+                println!("Server is restarting ({}), tunnel closed.", notice.reason());
+                return Ok(());
+            }
+
+            println!(
+                "Server is restarting ({}), reconnecting...",
+                notice.reason()
+            );
+            transport = reconnect(&app_context).await;
+        }
+    }
+}
+
+impl ListenCommand {
+    /// runs a single tunnel session until the server goes away.
+    /// returns the shutdown notice when the server announced it was going down.
+    async fn run_session(
+        &self,
+        app_context: &AppContext,
+        mut transport: TcpFrameTransport,
+    ) -> Result<Option<ServerShutdown>> {
         let (console_sender, console_receiver) = mpsc::channel::<i32>(10);
         let (sender, receiver) = mpsc::channel::<TcpFrame>(10000);
-        let state = Arc::new(ClientState::new(&console_sender));
+        let state = Arc::new(ClientState::new(&console_sender).with_log(&self.log));
+
+        authenticate_session(&self.config, app_context, &mut transport).await?;
+        for tunnel in do_handshake(&self.tunnels, &mut transport).await? {
+            state.add_tunnel(tunnel);
+        }
+        self.publish_state(Some(state.clone()));
 
-        let token = get_token(&self.config)?;
-        do_handshake(&mut transport).await?;
-        authenticate(&self.config, &token, &mut transport).await?;
+        // stops the tasks of this session only, so it can be started again after reconnecting.
+        let (notify_session_shutdown, _) = broadcast::channel::<()>(1);
 
         let (reader, writer) = transport.split();
         let ping_task = PingSender::new(
             &sender,
             &state,
-            self.args.ping_interval(),
+            self.options.ping_interval(),
             &self._shutdown_complete_tx,
         );
-        let console_task = ConsoleUpdater::new(
+        let health_task = HealthChecker::new(&state, &self._shutdown_complete_tx);
+        let dashboard_task = Dashboard::new(
             console_receiver,
             &state,
-            &self.args,
+            self.options.console(),
+            &self.notify_shutdown,
             &self._shutdown_complete_tx,
         );
 
@@ -77,25 +180,45 @@ impl AsyncCommand for ListenCommand {
         let forward_task = TcpFrameReader::new(
             &sender,
             &state,
-            &self.args,
             reader,
             &self._shutdown_complete_tx,
+            &notify_session_shutdown,
         );
 
         info!("Connected to server, spawning required tasks...");
 
         let _ = tokio::join!(
-            console_task.spawn(Shutdown::new(self._notify_shutdown.subscribe())),
-            receive_task.spawn(Shutdown::new(self._notify_shutdown.subscribe())),
-            forward_task.spawn(Shutdown::new(self._notify_shutdown.subscribe())),
-            ping_task.spawn(Shutdown::new(self._notify_shutdown.subscribe()))
+            dashboard_task.spawn(Shutdown::new(notify_session_shutdown.subscribe())),
+            receive_task.spawn(Shutdown::new(notify_session_shutdown.subscribe())),
+            forward_task.spawn(Shutdown::new(notify_session_shutdown.subscribe())),
+            ping_task.spawn(Shutdown::new(notify_session_shutdown.subscribe())),
+            health_task.spawn(Shutdown::new(notify_session_shutdown.subscribe()))
         );
 
-        Ok(())
+        self.publish_state(None);
+        Ok(state.server_shutdown())
+    }
+
+    fn publish_state(&self, state: Option<Arc<ClientState>>) {
+        if let Some(sender) = &self.state_sender {
+            let _ = sender.send(state);
+        }
+    }
+}
+
+/// keeps trying to connect until the server is back.
+async fn reconnect(app_context: &AppContext) -> TcpFrameTransport {
+    loop {
+        tokio::time::sleep(RECONNECT_INTERVAL).await;
+
+        match get_transport(app_context).await {
+            Ok(transport) => return transport,
+            Err(err) => debug!("server is not back yet: {}", err),
+        }
     }
 }
 
-fn get_token(config: &Arc<Config>) -> Result<String> {
+pub(crate) fn get_token(config: &Arc<Config>) -> Result<String> {
     let auth_manager = match config.lock_auth_manager() {
         Ok(lock) => lock,
         Err(err) => {
@@ -115,12 +238,14 @@ fn get_token(config: &Arc<Config>) -> Result<String> {
 async fn get_transport(app_context: &AppContext) -> Result<TcpFrameTransport> {
     info!("Trying to connect...");
 
-    let addr = ServerAddr::new(app_context.host(), app_context.port())?.to_socket_addr()?;
+    let addrs = ServerAddr::new(app_context.host(), app_context.port())?
+        .resolve()
+        .await?;
 
-    TcpFrameTransport::connect(addr, app_context.tls()).await
+    TcpFrameTransport::connect(&addrs, app_context.client_tls().as_ref()).await
 }
 
-fn get_context(args: &Arc<ListenArgs>, config: &Arc<Config>) -> Result<AppContext> {
+fn get_context(app_context: Option<String>, config: &Arc<Config>) -> Result<AppContext> {
     let contexts = match config.lock_context_manager() {
         Ok(lock) => lock,
         Err(err) => {
@@ -129,7 +254,7 @@ fn get_context(args: &Arc<ListenArgs>, config: &Arc<Config>) -> Result<AppContex
         }
     };
     let fallback = contexts.default_context_str().to_string();
-    let context_name = args.app_context().unwrap_or(fallback);
+    let context_name = app_context.unwrap_or(fallback);
 
     match contexts.get_context(&context_name) {
         Some(ctx) => Ok(ctx),
@@ -137,7 +262,23 @@ fn get_context(args: &Arc<ListenArgs>, config: &Arc<Config>) -> Result<AppContex
     }
 }
 
-async fn authenticate(
+/// logs in with the stored token, unless the context authenticates with a client certificate,
+/// in which case the server already authenticated the session during the tls handshake.
+pub(crate) async fn authenticate_session(
+    config: &Arc<Config>,
+    app_context: &AppContext,
+    client: &mut TcpFrameTransport,
+) -> Result<()> {
+    if app_context.has_client_certificate() {
+        debug!("authenticating with client certificate");
+        return Ok(());
+    }
+
+    let token = get_token(config)?;
+    authenticate(config, &token, client).await
+}
+
+pub(crate) async fn authenticate(
     config: &Arc<Config>,
     token: &str,
     client: &mut TcpFrameTransport,
@@ -171,15 +312,70 @@ async fn authenticate(
     }
 }
 
-async fn do_handshake(client: &mut TcpFrameTransport) -> Result<()> {
+/// opens the file at `path` once, tunnels writing to the same file share it.
+fn open_shared<T>(
+    files: &mut HashMap<PathBuf, Arc<T>>,
+    path: Option<&Path>,
+    open: impl Fn(&Path) -> Result<T>,
+) -> Result<Option<Arc<T>>> {
+    let path = match path {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+
+    if let Some(file) = files.get(path) {
+        return Ok(Some(file.clone()));
+    }
+
+    let file = Arc::new(open(path)?);
+    files.insert(path.to_owned(), file.clone());
+    Ok(Some(file))
+}
+
+/// asks the server to open every tunnel, using the server address as public ip of
+/// tunnels listening on every interface.
+async fn do_handshake(
+    tunnels: &[TunnelDefinition],
+    client: &mut TcpFrameTransport,
+) -> Result<Vec<ActiveTunnel>> {
     info!("Connected to server, trying handshake...");
 
-    let frame = TcpFrame::ClientConnected(ClientConnected);
-    match client.send_frame(&frame).await? {
-        TcpFrame::ClientConnectedAck(_) => Ok(()),
-        actual => {
-            debug!("received invalid frame when doing handshake. received {} instead of ClientConnectedAck", actual);
-            Err("failed to do handshake with server.".into())
+    let server_ip = client.peer_addr().map(|addr| addr.ip());
+    let mut active_tunnels = Vec::with_capacity(tunnels.len());
+    let mut har_files = HashMap::new();
+    let mut pcap_files = HashMap::new();
+    for tunnel in tunnels {
+        let targets = tunnel.targets()?;
+        let har = open_shared(&mut har_files, tunnel.har(), HarWriter::open)?;
+        let capture = open_shared(&mut pcap_files, tunnel.capture(), PcapWriter::open)?;
+
+        let frame = TcpFrame::ClientConnected(tunnel.client_connected()?);
+        match client.send_frame(&frame).await? {
+            TcpFrame::ClientConnectedAck(ack) => {
+                let public_addr = server_ip.map(|ip| ack.public_addr(&ip));
+                active_tunnels.push(
+                    ActiveTunnel::new(tunnel, &targets, ack.listening_port(), public_addr)
+                        .with_har(har)
+                        .with_capture(capture),
+                );
+            }
+            TcpFrame::Error(err) if *err.reason() == Reason::QuotaExceeded => {
+                return Err("Monthly usage quota exceeded, check it with tcproxy-cli usage".into())
+            }
+            TcpFrame::Error(err) if *err.reason() == Reason::PortUnavailable => {
+                return Err(format!(
+                    "port {} requested by tunnel {} is not available",
+                    tunnel.remote_port().unwrap_or_default(),
+                    tunnel.name()
+                )
+                .into())
+            }
+            actual => {
+                debug!("received invalid frame when doing handshake. received {} instead of ClientConnectedAck", actual);
+                return Err("failed to do handshake with server.".into());
+            }
         }
     }
+
+    Ok(active_tunnels)
 }
@@ -38,8 +38,11 @@ impl AsyncCommand for LoginCommand {
 
         // creates transport
         let app_context = get_context(&self.args, &self.config).await?;
-        let addr = ServerAddr::new(app_context.host(), app_context.port())?.to_socket_addr()?;
-        let mut transport = TcpFrameTransport::connect(addr, app_context.tls()).await?;
+        let addrs = ServerAddr::new(app_context.host(), app_context.port())?
+            .resolve()
+            .await?;
+        let mut transport =
+            TcpFrameTransport::connect(&addrs, app_context.client_tls().as_ref()).await?;
 
         match transport.send_frame(&authenticate_frame).await? {
             TcpFrame::AuthenticateAck(data) => {
@@ -77,11 +80,11 @@ async fn get_context(args: &LoginArgs, config: &Config) -> Result<AppContext> {
 fn get_password_auth_args(args: &LoginArgs) -> Result<PasswordAuthArgs> {
     let username = get_username(args)?;
     let password = rpassword::prompt_password("Your password: ")?;
-    return Ok(PasswordAuthArgs::new(
+    Ok(PasswordAuthArgs::new(
         strip_newline(&username),
         &password,
         None,
-    ));
+    ))
 }
 
 fn get_username(args: &LoginArgs) -> Result<String> {
@@ -1,12 +1,25 @@
 pub mod contexts;
+#[cfg(unix)]
+mod control;
+#[cfg(unix)]
+mod daemon;
 mod data_packet;
 mod incoming_socket;
 mod listen;
 mod login;
 mod remote_disconnected;
+mod replay;
+pub mod tunnels;
+mod usage;
 
+#[cfg(unix)]
+pub use control::*;
+#[cfg(unix)]
+pub use daemon::*;
 pub use data_packet::*;
 pub use incoming_socket::*;
 pub use listen::*;
 pub use login::*;
 pub use remote_disconnected::*;
+pub use replay::*;
+pub use usage::*;
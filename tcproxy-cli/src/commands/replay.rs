@@ -0,0 +1,192 @@
+use async_trait::async_trait;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tcproxy_core::tcp::connect_happy_eyeballs;
+use tcproxy_core::{AsyncCommand, Result};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::dashboard::format_bytes;
+use crate::inspector::{
+    Direction, Har, HarEntry, HttpMessage, MessageParser, ReplayRequest, StartLine,
+};
+use crate::server_addr::ServerAddr;
+use crate::ReplayArgs;
+
+/// how long the local service has to answer a replayed request.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// sends a captured request again to the local service and prints its response.
+pub struct ReplayCommand {
+    args: ReplayArgs,
+    config: Config,
+}
+
+impl ReplayCommand {
+    pub fn new(args: &ReplayArgs, config: &Config) -> Self {
+        Self {
+            args: args.clone(),
+            config: config.clone(),
+        }
+    }
+
+    /// capture files to search, the given one or the ones of every defined tunnel.
+    fn capture_files(&self) -> Result<Vec<PathBuf>> {
+        if let Some(path) = self.args.har() {
+            return Ok(vec![path.to_owned()]);
+        }
+
+        let tunnel_manager = self.config.lock_tunnel_manager()?;
+        Ok(tunnel_manager
+            .tunnels()
+            .iter()
+            .filter_map(|tunnel| tunnel.har())
+            .filter(|path| path.exists())
+            .map(Path::to_owned)
+            .collect())
+    }
+
+    fn find_entry(&self) -> Result<HarEntry> {
+        let files = self.capture_files()?;
+        for path in &files {
+            if let Some(entry) = Har::load(path)?.entry(self.args.id()) {
+                return Ok(entry.clone());
+            }
+        }
+
+        match files.is_empty() {
+            true => Err("no capture files found, pass one with --har".into()),
+            false => Err(format!("capture {} not found", self.args.id()).into()),
+        }
+    }
+}
+
+#[async_trait]
+impl AsyncCommand for ReplayCommand {
+    type Output = Result<()>;
+
+    async fn handle(&mut self) -> Self::Output {
+        let entry = self.find_entry()?;
+        let mut request = self.args.apply(ReplayRequest::from_entry(&entry)?)?;
+        if self.args.edit() {
+            request = edit_request(&request, self.args.id())?;
+        }
+
+        let target = match self.args.target() {
+            Some(target) => target.clone(),
+            None if entry.target.is_empty() => {
+                return Err("the capture has no target, pass one with --target".into())
+            }
+            None => ServerAddr::from_str(&entry.target)?,
+        };
+
+        println!(
+            "replaying {} {} {} to {}",
+            self.args.id(),
+            request.method(),
+            request.path(),
+            target
+        );
+
+        let started_at = Instant::now();
+        let response = match tokio::time::timeout(RESPONSE_TIMEOUT, send(&request, &target)).await {
+            Ok(response) => response?,
+            Err(_) => return Err(format!("{} didn't answer in time", target).into()),
+        };
+
+        println!(
+            "{} in {}ms, {} (captured {} {})",
+            format_status(&response),
+            started_at.elapsed().as_millis(),
+            format_bytes(&(response.body_size() as u64)),
+            entry.response.status,
+            entry.response.status_text
+        );
+        for (name, value) in response.headers() {
+            println!("{}: {}", name, value);
+        }
+
+        println!();
+        println!("{}", String::from_utf8_lossy(response.body()));
+
+        Ok(())
+    }
+}
+
+/// writes the request to a file and opens it on `$VISUAL`/`$EDITOR`, reading it back
+/// once the editor exits.
+fn edit_request(request: &ReplayRequest, id: &str) -> Result<ReplayRequest> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| String::from("vi"));
+
+    // a new file with a random name, so other users of the temp dir can't point it
+    // somewhere else or change the request before it is read back.
+    let path = std::env::temp_dir().join(format!(
+        "tcproxy-replay-{}-{}.http",
+        id,
+        Uuid::new_v4().simple()
+    ));
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(&path)?.write_all(&request.to_raw())?;
+
+    let status = std::process::Command::new(&editor).arg(&path).status();
+    let edited = std::fs::read(&path);
+    let _ = std::fs::remove_file(&path);
+
+    match status {
+        Ok(status) if status.success() => ReplayRequest::from_raw(&edited?),
+        Ok(status) => Err(format!("{} exited with {}, request not sent", editor, status).into()),
+        Err(err) => Err(format!("failed to run {}: {}", editor, err).into()),
+    }
+}
+
+async fn send(request: &ReplayRequest, target: &ServerAddr) -> Result<HttpMessage> {
+    let mut stream = connect_happy_eyeballs(&target.resolve().await?).await?;
+    stream.write_all(&request.to_bytes()).await?;
+
+    let mut parser = MessageParser::new(Direction::Response, true);
+    parser.expect_response(request.method());
+
+    let mut buffer = vec![0; 8 * 1024];
+    loop {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            return match parser.finish() {
+                Some(response) => Ok(response),
+                None => Err(format!("{} closed the connection without answering", target).into()),
+            };
+        }
+
+        let responses = parser.push(&buffer[..read])?;
+        let response = responses.into_iter().find(|response| {
+            !matches!(
+                response.start_line(),
+                StartLine::Response {
+                    status: 100..=199,
+                    ..
+                }
+            )
+        });
+
+        if let Some(response) = response {
+            return Ok(response);
+        }
+    }
+}
+
+fn format_status(response: &HttpMessage) -> String {
+    match response.start_line() {
+        StartLine::Response { status, reason } => {
+            format!("{} {} {}", response.http_version(), status, reason)
+        }
+        StartLine::Request { .. } => String::new(),
+    }
+}
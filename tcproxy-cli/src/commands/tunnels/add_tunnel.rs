@@ -0,0 +1,41 @@
+use tcproxy_core::{Command, Result};
+
+use crate::config::Config;
+use crate::AddTunnelArgs;
+
+pub struct AddTunnelCommand {
+    args: AddTunnelArgs,
+    config: Config,
+}
+
+impl AddTunnelCommand {
+    pub fn new(args: &AddTunnelArgs, config: &Config) -> Self {
+        Self {
+            args: args.clone(),
+            config: config.clone(),
+        }
+    }
+}
+
+impl Command for AddTunnelCommand {
+    type Output = Result<()>;
+
+    fn handle(&mut self) -> Self::Output {
+        let tunnel = self.args.tunnel_definition();
+        if let Some(context) = tunnel.context() {
+            if self
+                .config
+                .lock_context_manager()?
+                .get_context(context)
+                .is_none()
+            {
+                return Err(format!("context {} doesn't exist", context).into());
+            }
+        }
+
+        self.config.lock_tunnel_manager()?.push_tunnel(&tunnel)?;
+
+        println!("added tunnel {}", tunnel.name());
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use tcproxy_core::{Command, Result};
+
+use crate::config::Config;
+use crate::server_addr::join_targets;
+
+pub struct ListTunnelsCommand {
+    config: Config,
+}
+
+impl ListTunnelsCommand {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+}
+
+impl Command for ListTunnelsCommand {
+    type Output = Result<()>;
+
+    fn handle(&mut self) -> Self::Output {
+        let tunnel_manager = self.config.lock_tunnel_manager()?;
+        let lines: Vec<[String; 5]> = tunnel_manager
+            .tunnels()
+            .iter()
+            .map(|tunnel| {
+                [
+                    tunnel.name().to_owned(),
+                    tunnel
+                        .targets()
+                        .map(|targets| join_targets(&targets))
+                        .unwrap_or_else(|_| String::from("-")),
+                    tunnel
+                        .remote_port()
+                        .map(|port| port.to_string())
+                        .unwrap_or_else(|| String::from("random")),
+                    tunnel.protocol().to_string(),
+                    tunnel.context().unwrap_or("(default)").to_owned(),
+                ]
+            })
+            .collect();
+
+        let width = lines
+            .iter()
+            .flat_map(|line| line.iter().map(|column| column.len()))
+            .fold("Remote Port".len(), usize::max);
+
+        println!(
+            "{0: <width$}  {1: <width$} {2: <width$} {3: <width$} {4: <width$}",
+            "Name",
+            "Target",
+            "Remote Port",
+            "Protocol",
+            "Context",
+            width = width
+        );
+        for [name, target, remote_port, protocol, context] in lines {
+            println!(
+                "{0: <width$}  {1: <width$} {2: <width$} {3: <width$} {4: <width$}",
+                name,
+                target,
+                remote_port,
+                protocol,
+                context,
+                width = width
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,7 @@
+mod add_tunnel;
+mod list_tunnels;
+mod remove_tunnel;
+
+pub use add_tunnel::AddTunnelCommand;
+pub use list_tunnels::ListTunnelsCommand;
+pub use remove_tunnel::RemoveTunnelCommand;
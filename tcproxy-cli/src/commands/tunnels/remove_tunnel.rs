@@ -0,0 +1,31 @@
+use tcproxy_core::{Command, Result};
+
+use crate::config::Config;
+use crate::RemoveTunnelArgs;
+
+pub struct RemoveTunnelCommand {
+    args: RemoveTunnelArgs,
+    config: Config,
+}
+
+impl RemoveTunnelCommand {
+    pub fn new(args: &RemoveTunnelArgs, config: &Config) -> Self {
+        Self {
+            args: args.clone(),
+            config: config.clone(),
+        }
+    }
+}
+
+impl Command for RemoveTunnelCommand {
+    type Output = Result<()>;
+
+    fn handle(&mut self) -> Self::Output {
+        self.config
+            .lock_tunnel_manager()?
+            .remove_tunnel(self.args.name())?;
+
+        println!("removed tunnel {}", self.args.name());
+        Ok(())
+    }
+}
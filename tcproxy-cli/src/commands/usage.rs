@@ -0,0 +1,117 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use tracing::debug;
+
+use tcproxy_core::framing::{Reason, UsageReport, UsageRequest};
+use tcproxy_core::transport::TcpFrameTransport;
+use tcproxy_core::{AsyncCommand, Result, TcpFrame};
+
+use crate::commands::authenticate_session;
+use crate::config::{AppContext, Config};
+use crate::dashboard::format_bytes;
+use crate::server_addr::ServerAddr;
+use crate::UsageArgs;
+
+/// Asks the server for the traffic usage of the current month.
+pub struct UsageCommand {
+    args: UsageArgs,
+    config: Arc<Config>,
+}
+
+impl UsageCommand {
+    pub fn new(args: &UsageArgs, config: &Arc<Config>) -> Self {
+        Self {
+            args: args.clone(),
+            config: config.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl AsyncCommand for UsageCommand {
+    type Output = Result<()>;
+
+    async fn handle(&mut self) -> Self::Output {
+        let app_context = get_context(&self.args, &self.config)?;
+        let addrs = ServerAddr::new(app_context.host(), app_context.port())?
+            .resolve()
+            .await?;
+        let mut transport =
+            TcpFrameTransport::connect(&addrs, app_context.client_tls().as_ref()).await?;
+
+        authenticate_session(&self.config, &app_context, &mut transport).await?;
+
+        match transport.send_frame(&UsageRequest::new().into()).await? {
+            TcpFrame::UsageReport(report) => {
+                println!("{}", format_report(&report));
+                Ok(())
+            }
+            TcpFrame::Error(err) if *err.reason() == Reason::AuthenticationFailed => {
+                Err("Authentication failed. Try logging again with tcproxy-cli login".into())
+            }
+            actual => {
+                debug!(
+                    "received invalid frame. received {} instead of UsageReport",
+                    actual
+                );
+                Err("Error while trying to communicate with server.".into())
+            }
+        }
+    }
+}
+
+fn get_context(args: &UsageArgs, config: &Config) -> Result<AppContext> {
+    let contexts = config.lock_context_manager()?;
+    let default = &contexts.default_context_str().to_string();
+    let context_name = args.app_context().unwrap_or(default);
+
+    match contexts.get_context(context_name) {
+        Some(ctx) => Ok(ctx),
+        None => Err(format!("context {} was not found.", context_name).into()),
+    }
+}
+
+fn format_report(report: &UsageReport) -> String {
+    let total = report.bytes_in() + report.bytes_out();
+    let quota = match report.quota() {
+        Some(quota) => format!(
+            " / {} ({:.1}%)",
+            format_bytes(quota),
+            total as f64 * 100.0 / *quota as f64
+        ),
+        None => String::default(),
+    };
+
+    format!(
+        "Usage for {}:\n  in:    {}\n  out:   {}\n  total: {}{}",
+        report.period(),
+        format_bytes(report.bytes_in()),
+        format_bytes(report.bytes_out()),
+        format_bytes(&total),
+        quota
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_report;
+    use tcproxy_core::framing::UsageReport;
+
+    #[test]
+    pub fn should_format_report_with_quota() {
+        // Arrange
+        let report = UsageReport::new(
+            "2026-10",
+            &(512 * 1024),
+            &(512 * 1024),
+            &Some(4 * 1024 * 1024),
+        );
+
+        // Act
+        let result = format_report(&report);
+
+        // Assert
+        assert!(result.starts_with("Usage for 2026-10:"));
+        assert!(result.ends_with("total: 1.0 MiB / 4.0 MiB (25.0%)"));
+    }
+}
@@ -5,7 +5,7 @@ use std::io::Write;
 use std::path::Path;
 
 use super::directory_resolver::DirectoryResolver;
-use crate::config::{AppConfigError, AppContext};
+use crate::config::{AppConfigError, AppContext, TunnelDefinition};
 
 type Result<T> = std::result::Result<T, AppConfigError>;
 
@@ -14,6 +14,9 @@ pub struct AppConfig {
     default_context: String,
     user_token: Option<String>,
     contexts: Vec<AppContext>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tunnels: Vec<TunnelDefinition>,
 }
 
 impl AppConfig {
@@ -29,13 +32,23 @@ impl AppConfig {
                 None => String::default(),
             },
             user_token,
+            tunnels: Vec::new(),
         }
     }
 
+    pub fn with_tunnels(mut self, tunnels: &[TunnelDefinition]) -> Self {
+        self.tunnels = Vec::from(tunnels);
+        self
+    }
+
     pub fn contexts(&self) -> &[AppContext] {
         &self.contexts
     }
 
+    pub fn tunnels(&self) -> &[TunnelDefinition] {
+        &self.tunnels
+    }
+
     pub fn user_token(&self) -> &Option<String> {
         &self.user_token
     }
@@ -60,6 +73,7 @@ pub fn save_to_file(config: &AppConfig, path: &Path) -> Result<()> {
         .write(true)
         .append(false)
         .create(true)
+        .truncate(true)
         .open(path)?;
 
     let self_contents = serde_yaml::to_string(&config)?;
@@ -76,6 +90,7 @@ fn create_default(path: &Path) -> Result<()> {
         .write(true)
         .create(true)
         .append(false)
+        .truncate(true)
         .open(path)?;
 
     file.write_all(config_str.as_bytes())?;
@@ -85,11 +100,11 @@ fn create_default(path: &Path) -> Result<()> {
 }
 
 fn read_from_file(path: &Path) -> Result<AppConfig> {
-    let file = OpenOptions::new().read(true).open(path)?;
-    if file.metadata().is_err() {
+    if !exists(path) {
         create_default(path)?;
     }
 
+    let file = OpenOptions::new().read(true).open(path)?;
     let contents = serde_yaml::from_reader::<File, AppConfig>(file)?;
     Ok(contents)
 }
@@ -102,8 +117,11 @@ fn exists(path: &Path) -> bool {
 mod tests {
     use crate::config::app_config::AppConfig;
     use crate::config::app_config::{read_from_file, save_to_file};
+    use crate::config::TunnelDefinition;
+    use crate::server_addr::ServerAddr;
     use std::fs;
     use std::path::Path;
+    use std::str::FromStr;
     use uuid::Uuid;
 
     #[test]
@@ -124,6 +142,25 @@ mod tests {
         remove_file(file_path);
     }
 
+    #[test]
+    fn should_keep_tunnel_definitions() {
+        // Arrange
+        let file_path = format!("./{}.yaml", Uuid::new_v4());
+        let file_path = Path::new(&file_path);
+        let target = ServerAddr::from_str("127.0.0.1:3000").unwrap();
+        let tunnel = TunnelDefinition::new("api", &target).with_remote_port(Some(15080));
+        let config = AppConfig::default().with_tunnels(&[tunnel]);
+
+        // Act
+        save_to_file(&config, file_path).unwrap();
+        let created_config = read_from_file(file_path).unwrap();
+
+        // Assert
+        assert_eq!(created_config.tunnels(), config.tunnels());
+
+        remove_file(file_path);
+    }
+
     #[test]
     fn should_return_err_if_path_doesnt_exist() {
         let path = format!("~/{}.test", Uuid::new_v4());
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use tcproxy_core::tls::ClientTls;
 
 use crate::server_addr::ServerAddr;
 
@@ -9,6 +11,21 @@ pub struct AppContext {
     target_host: String,
     target_port: u16,
     tls: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ca_bundle: Option<PathBuf>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pinned_fingerprint: Option<String>,
+
+    #[serde(default)]
+    insecure: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    client_certificate: Option<PathBuf>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    client_key: Option<PathBuf>,
 }
 
 impl AppContext {
@@ -18,9 +35,39 @@ impl AppContext {
             target_host: host.to_owned(),
             target_port: port.to_owned(),
             tls: use_ssl,
+            ca_bundle: None,
+            pinned_fingerprint: None,
+            insecure: false,
+            client_certificate: None,
+            client_key: None,
         }
     }
 
+    /// authenticates with the PEM client certificate and pkcs8 key instead of a login.
+    pub fn with_client_certificate(mut self, certificate_path: &Path, key_path: &Path) -> Self {
+        self.client_certificate = Some(certificate_path.to_path_buf());
+        self.client_key = Some(key_path.to_path_buf());
+        self
+    }
+
+    /// trusts the PEM certificates in `path` when verifying the server.
+    pub fn with_ca_bundle(mut self, path: &Path) -> Self {
+        self.ca_bundle = Some(path.to_path_buf());
+        self
+    }
+
+    /// only accepts the server certificate with the given sha256 fingerprint.
+    pub fn with_pinned_fingerprint(mut self, fingerprint: &str) -> Self {
+        self.pinned_fingerprint = Some(String::from(fingerprint));
+        self
+    }
+
+    /// skips verifying the server certificate.
+    pub fn with_insecure(mut self, insecure: bool) -> Self {
+        self.insecure = insecure;
+        self
+    }
+
     pub fn from_addr(name: &str, addr: &ServerAddr, use_ssl: bool) -> Self {
         Self::new(name, addr.host(), addr.port(), use_ssl)
     }
@@ -40,6 +87,32 @@ impl AppContext {
     pub fn tls(&self) -> bool {
         self.tls
     }
+
+    pub fn has_client_certificate(&self) -> bool {
+        self.tls && self.client_certificate.is_some() && self.client_key.is_some()
+    }
+
+    /// tls settings used to connect, verifying the certificate against the context host.
+    pub fn client_tls(&self) -> Option<ClientTls> {
+        if !self.tls {
+            return None;
+        }
+
+        let mut client_tls = ClientTls::new(&self.target_host).with_insecure(self.insecure);
+        if let Some(path) = &self.ca_bundle {
+            client_tls = client_tls.with_ca_bundle(path);
+        }
+
+        if let Some(fingerprint) = &self.pinned_fingerprint {
+            client_tls = client_tls.with_pinned_fingerprint(fingerprint);
+        }
+
+        if let (Some(certificate), Some(key)) = (&self.client_certificate, &self.client_key) {
+            client_tls = client_tls.with_client_identity(certificate, key);
+        }
+
+        Some(client_tls)
+    }
 }
 
 impl From<ServerAddr> for AppContext {
@@ -57,3 +130,57 @@ impl Display for AppContext {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use tcproxy_core::tls::ClientTls;
+
+    use super::AppContext;
+
+    #[test]
+    fn should_verify_tls_against_context_host() {
+        // Arrange
+        let context = AppContext::new("default", "proxy.server.io", &8080, true)
+            .with_ca_bundle(Path::new("./ca.pem"));
+
+        // Act
+        let client_tls = context.client_tls();
+
+        // Assert
+        let expected = ClientTls::new("proxy.server.io").with_ca_bundle(Path::new("./ca.pem"));
+        assert_eq!(client_tls, Some(expected));
+    }
+
+    #[test]
+    fn should_use_client_certificate_when_set() {
+        // Arrange
+        let context = AppContext::new("default", "proxy.server.io", &8080, true)
+            .with_client_certificate(Path::new("./client.pem"), Path::new("./client.key"));
+
+        // Act
+        let client_tls = context.client_tls();
+
+        // Assert
+        let expected = ClientTls::new("proxy.server.io")
+            .with_client_identity(Path::new("./client.pem"), Path::new("./client.key"));
+        assert!(context.has_client_certificate());
+        assert_eq!(client_tls, Some(expected));
+    }
+
+    #[test]
+    fn should_read_contexts_without_tls_options() {
+        // Arrange
+        let yaml = "name: default\ntarget_host: proxy.server.io\ntarget_port: 8080\ntls: false\n";
+
+        // Act
+        let context: AppContext = serde_yaml::from_str(yaml).unwrap();
+
+        // Assert
+        assert_eq!(
+            context,
+            AppContext::new("default", "proxy.server.io", &8080, false)
+        );
+        assert!(context.client_tls().is_none());
+    }
+}
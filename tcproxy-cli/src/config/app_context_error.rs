@@ -5,7 +5,7 @@ use tcproxy_core::Error;
 #[derive(Debug)]
 pub enum AppContextError {
     DoesntExist(String),
-    AlreadyExists(AppContext),
+    AlreadyExists(Box<AppContext>),
     ConfigError(Error),
     ValidationError(String),
     Other(Error),
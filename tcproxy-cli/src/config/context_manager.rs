@@ -67,8 +67,8 @@ impl ContextManager {
     pub fn get_context(&self, name: &str) -> Option<AppContext> {
         self.contexts
             .iter()
-            .cloned()
             .find(|item| item.name() == name)
+            .cloned()
     }
 
     pub fn push_context(
@@ -76,7 +76,7 @@ impl ContextManager {
         context: &AppContext,
     ) -> std::result::Result<(), AppContextError> {
         if self.ctx_exists(context) {
-            return Err(AppContextError::AlreadyExists(context.clone()));
+            return Err(AppContextError::AlreadyExists(Box::new(context.clone())));
         }
 
         self.contexts.push(context.clone());
@@ -14,6 +14,8 @@ const ORGANIZATION_NAME: &str = "m4urici0gm";
 const APPLICATION_NAME: &str = "tcproxy";
 const QUALIFIER: &str = "";
 const FILE_NAME: &str = "config.yaml";
+const DAEMON_SOCKET_NAME: &str = "daemon.sock";
+const DAEMON_LOG_NAME: &str = "daemon.log";
 
 impl DirectoryResolver {
     pub fn new(path: &Path, name: &str) -> Self {
@@ -29,6 +31,15 @@ impl DirectoryResolver {
 
         base_path
     }
+
+    /// unix socket the daemon answers `status`, `start` and `stop` on.
+    pub fn get_daemon_socket(&self) -> PathBuf {
+        self.path.join(DAEMON_SOCKET_NAME)
+    }
+
+    pub fn get_daemon_log(&self) -> PathBuf {
+        self.path.join(DAEMON_LOG_NAME)
+    }
 }
 
 pub fn load() -> Result<DirectoryResolver> {
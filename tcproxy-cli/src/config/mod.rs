@@ -4,15 +4,21 @@ mod app_context;
 mod app_context_error;
 pub mod context_manager;
 pub mod directory_resolver;
+mod tunnel_definition;
+pub mod tunnel_manager;
 
 pub use app_config_error::AppConfigError;
 pub use app_context::*;
 pub use app_context_error::AppContextError;
+pub use tunnel_definition::*;
 
 use std::sync::{Arc, Mutex, MutexGuard};
 use tracing::info;
 
-use self::{context_manager::ContextManager, directory_resolver::DirectoryResolver};
+use self::{
+    context_manager::ContextManager, directory_resolver::DirectoryResolver,
+    tunnel_manager::TunnelManager,
+};
 use crate::config::app_config::AppConfig;
 use tcproxy_core::{auth::token_handler::AuthToken, Result};
 
@@ -24,6 +30,7 @@ pub struct AuthManager {
 #[derive(Debug, Clone)]
 pub struct Config {
     contexts: Arc<Mutex<ContextManager>>,
+    tunnels: Arc<Mutex<TunnelManager>>,
     auth: Arc<Mutex<AuthManager>>,
 }
 
@@ -47,14 +54,24 @@ impl Config {
     pub fn new(contexts: &ContextManager, auth: &AuthManager) -> Self {
         Self {
             contexts: Arc::new(Mutex::new(contexts.clone())),
+            tunnels: Arc::new(Mutex::new(TunnelManager::default())),
             auth: Arc::new(Mutex::new(auth.clone())),
         }
     }
 
+    pub fn with_tunnels(mut self, tunnels: &TunnelManager) -> Self {
+        self.tunnels = Arc::new(Mutex::new(tunnels.clone()));
+        self
+    }
+
     pub fn lock_context_manager(&self) -> Result<MutexGuard<'_, ContextManager>> {
         Ok(self.contexts.lock().unwrap()) // TODO: fix me
     }
 
+    pub fn lock_tunnel_manager(&self) -> Result<MutexGuard<'_, TunnelManager>> {
+        Ok(self.tunnels.lock().unwrap()) // TODO: fix me
+    }
+
     pub fn lock_auth_manager(&self) -> Result<MutexGuard<'_, AuthManager>> {
         Ok(self.auth.lock().unwrap()) // TODO: fix me
     }
@@ -64,13 +81,15 @@ pub fn save_to_disk(config: &Config, directory_resolver: &DirectoryResolver) ->
     let path = directory_resolver.get_config_file();
     info!("trying to load config file from {:?}", path);
     let context_manager = config.lock_context_manager()?;
+    let tunnel_manager = config.lock_tunnel_manager()?;
     let auth_manager = config.lock_auth_manager()?;
 
     let app_config = AppConfig::new(
         context_manager.contexts_arr(),
         context_manager.default_context(),
         auth_manager.current_token().clone(),
-    );
+    )
+    .with_tunnels(tunnel_manager.tunnels());
 
     app_config::save_to_file(&app_config, &path)?;
     Ok(())
@@ -83,7 +102,8 @@ pub fn load(directory_resolver: &DirectoryResolver) -> Result<Config> {
     let context_manager =
         ContextManager::new(config_file.default_context(), config_file.contexts());
 
+    let tunnel_manager = TunnelManager::new(config_file.tunnels());
     let auth = AuthManager::new(config_file.user_token().clone());
-    let config = Config::new(&context_manager, &auth);
+    let config = Config::new(&context_manager, &auth).with_tunnels(&tunnel_manager);
     Ok(config)
 }
@@ -0,0 +1,280 @@
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tcproxy_core::framing::ClientConnected;
+
+use crate::server_addr::{ServerAddr, ServerAddrError};
+use crate::{BalanceStrategy, ProxyProtocol};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum TunnelProtocol {
+    #[default]
+    Tcp,
+    Http,
+}
+
+/// named tunnel stored in the config file, started with `tcproxy-cli up`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TunnelDefinition {
+    name: String,
+    target: String,
+
+    /// more targets, connections are balanced across them and `target`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    targets: Vec<String>,
+
+    #[serde(default)]
+    balance: BalanceStrategy,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    context: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    remote_port: Option<u16>,
+
+    #[serde(default)]
+    protocol: TunnelProtocol,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    allow: Vec<IpNet>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    deny: Vec<IpNet>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rate_limit: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    proxy_protocol: Option<ProxyProtocol>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    har: Option<PathBuf>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    capture: Option<PathBuf>,
+}
+
+impl TunnelDefinition {
+    pub fn new(name: &str, target: &ServerAddr) -> Self {
+        Self {
+            name: String::from(name),
+            target: target.to_string(),
+            targets: Vec::new(),
+            balance: BalanceStrategy::default(),
+            context: None,
+            remote_port: None,
+            protocol: TunnelProtocol::default(),
+            allow: Vec::new(),
+            deny: Vec::new(),
+            rate_limit: None,
+            proxy_protocol: None,
+            har: None,
+            capture: None,
+        }
+    }
+
+    /// app context the tunnel is opened on, the default one when not set.
+    pub fn with_context(mut self, context: Option<&str>) -> Self {
+        self.context = context.map(String::from);
+        self
+    }
+
+    /// asks the server for this port instead of a random one.
+    pub fn with_remote_port(mut self, port: Option<u16>) -> Self {
+        self.remote_port = port;
+        self
+    }
+
+    /// balances connections across `target` and `targets`, skipping the ones that
+    /// don't accept connections.
+    pub fn with_balancing(mut self, targets: &[ServerAddr], balance: BalanceStrategy) -> Self {
+        self.targets = targets.iter().map(ToString::to_string).collect();
+        self.balance = balance;
+        self
+    }
+
+    pub fn with_protocol(mut self, protocol: TunnelProtocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    pub fn with_access_lists(mut self, allow: &[IpNet], deny: &[IpNet]) -> Self {
+        self.allow = allow.to_vec();
+        self.deny = deny.to_vec();
+        self
+    }
+
+    pub fn with_rate_limit(mut self, bytes_per_second: Option<u64>) -> Self {
+        self.rate_limit = bytes_per_second;
+        self
+    }
+
+    /// writes a PROXY protocol header with the remote peer address to the target.
+    pub fn with_proxy_protocol(mut self, proxy_protocol: Option<ProxyProtocol>) -> Self {
+        self.proxy_protocol = proxy_protocol;
+        self
+    }
+
+    /// http exchanges are appended to this HAR file, implies http inspection.
+    pub fn with_har(mut self, har: Option<PathBuf>) -> Self {
+        self.har = har;
+        self
+    }
+
+    /// every byte of the tunnel is recorded to this pcap file.
+    pub fn with_capture(mut self, capture: Option<PathBuf>) -> Self {
+        self.capture = capture;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn target(&self) -> Result<ServerAddr, ServerAddrError> {
+        ServerAddr::from_str(&self.target)
+    }
+
+    /// every target of the tunnel, the first one is `target`.
+    pub fn targets(&self) -> Result<Vec<ServerAddr>, ServerAddrError> {
+        std::iter::once(&self.target)
+            .chain(&self.targets)
+            .map(|target| ServerAddr::from_str(target))
+            .collect()
+    }
+
+    pub fn balance(&self) -> BalanceStrategy {
+        self.balance
+    }
+
+    pub fn context(&self) -> Option<&str> {
+        self.context.as_deref()
+    }
+
+    pub fn remote_port(&self) -> Option<u16> {
+        self.remote_port
+    }
+
+    pub fn protocol(&self) -> TunnelProtocol {
+        self.protocol
+    }
+
+    pub fn proxy_protocol(&self) -> Option<ProxyProtocol> {
+        self.proxy_protocol
+    }
+
+    pub fn har(&self) -> Option<&Path> {
+        self.har.as_deref()
+    }
+
+    pub fn capture(&self) -> Option<&Path> {
+        self.capture.as_deref()
+    }
+
+    /// frame asking the server to open this tunnel, fails when the access lists are too long.
+    pub fn client_connected(&self) -> tcproxy_core::Result<ClientConnected> {
+        Ok(ClientConnected::with_access_lists(&self.allow, &self.deny)?
+            .with_rate_limit(self.rate_limit)
+            .with_requested_port(self.remote_port))
+    }
+}
+
+impl Display for TunnelProtocol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TunnelProtocol::Tcp => write!(f, "tcp"),
+            TunnelProtocol::Http => write!(f, "http"),
+        }
+    }
+}
+
+impl Display for TunnelDefinition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[name = {}, target = {}]", self.name, self.target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ipnet::IpNet;
+    use std::str::FromStr;
+
+    use super::{TunnelDefinition, TunnelProtocol};
+    use crate::server_addr::ServerAddr;
+    use crate::{BalanceStrategy, ProxyProtocol};
+
+    #[test]
+    fn should_read_definition_with_defaults() {
+        // Arrange
+        let yaml = "name: api\ntarget: 127.0.0.1:3000\n";
+
+        // Act
+        let definition: TunnelDefinition = serde_yaml::from_str(yaml).unwrap();
+
+        // Assert
+        assert_eq!(definition.name(), "api");
+        assert_eq!(definition.target().unwrap().to_string(), "127.0.0.1:3000");
+        assert_eq!(definition.protocol(), TunnelProtocol::Tcp);
+        assert_eq!(definition.context(), None);
+        assert_eq!(definition.remote_port(), None);
+        assert_eq!(definition.proxy_protocol(), None);
+        assert_eq!(definition.targets().unwrap().len(), 1);
+        assert_eq!(definition.balance(), BalanceStrategy::RoundRobin);
+    }
+
+    #[test]
+    fn should_read_proxy_protocol_version() {
+        // Arrange
+        let yaml = "name: web\ntarget: 127.0.0.1:8080\nproxy_protocol: v2\n";
+
+        // Act
+        let definition: TunnelDefinition = serde_yaml::from_str(yaml).unwrap();
+
+        // Assert
+        assert_eq!(definition.proxy_protocol(), Some(ProxyProtocol::V2));
+    }
+
+    #[test]
+    fn should_read_balanced_targets() {
+        // Arrange
+        let yaml = "name: api\ntarget: 127.0.0.1:3000\ntargets: [127.0.0.1:3001, 'app.local:3002']\nbalance: least-connections\n";
+
+        // Act
+        let definition: TunnelDefinition = serde_yaml::from_str(yaml).unwrap();
+
+        // Assert
+        let targets: Vec<String> = definition
+            .targets()
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            targets,
+            vec!["127.0.0.1:3000", "127.0.0.1:3001", "app.local:3002"]
+        );
+        assert_eq!(definition.balance(), BalanceStrategy::LeastConnections);
+    }
+
+    #[test]
+    fn should_request_tunnel_options_from_server() {
+        // Arrange
+        let target = ServerAddr::from_str("db.docker.internal:5432").unwrap();
+        let allow = vec![IpNet::from_str("10.0.0.0/8").unwrap()];
+        let definition = TunnelDefinition::new("db", &target)
+            .with_remote_port(Some(15432))
+            .with_access_lists(&allow, &[])
+            .with_rate_limit(Some(1024));
+
+        // Act
+        let frame = definition.client_connected().unwrap();
+
+        // Assert
+        assert_eq!(frame.requested_port(), &Some(15432));
+        assert_eq!(frame.allow_list(), &allow[..]);
+        assert_eq!(frame.rate_limit(), &Some(1024));
+    }
+}
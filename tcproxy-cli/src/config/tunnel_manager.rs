@@ -0,0 +1,143 @@
+use tcproxy_core::Result;
+
+use super::TunnelDefinition;
+
+#[derive(Debug, Clone, Default)]
+pub struct TunnelManager {
+    tunnels: Vec<TunnelDefinition>,
+}
+
+impl TunnelManager {
+    pub fn new(tunnels: &[TunnelDefinition]) -> Self {
+        Self {
+            tunnels: Vec::from(tunnels),
+        }
+    }
+
+    pub fn tunnels(&self) -> &[TunnelDefinition] {
+        &self.tunnels
+    }
+
+    pub fn get_tunnel(&self, name: &str) -> Option<TunnelDefinition> {
+        self.tunnels
+            .iter()
+            .find(|tunnel| tunnel.name() == name)
+            .cloned()
+    }
+
+    pub fn push_tunnel(&mut self, tunnel: &TunnelDefinition) -> Result<()> {
+        if self.get_tunnel(tunnel.name()).is_some() {
+            return Err(format!("tunnel {} already exists", tunnel.name()).into());
+        }
+
+        self.tunnels.push(tunnel.clone());
+        Ok(())
+    }
+
+    pub fn remove_tunnel(&mut self, name: &str) -> Result<()> {
+        match self.tunnels.iter().position(|tunnel| tunnel.name() == name) {
+            Some(idx) => {
+                self.tunnels.remove(idx);
+                Ok(())
+            }
+            None => Err(format!("tunnel {} doesn't exist", name).into()),
+        }
+    }
+
+    /// tunnels started together by `up`, every tunnel when no name is given.
+    /// they share a single session, so they must use the same app context.
+    pub fn select(&self, names: &[String]) -> Result<Vec<TunnelDefinition>> {
+        let tunnels = match names.is_empty() {
+            true => self.tunnels.clone(),
+            false => names
+                .iter()
+                .map(|name| {
+                    self.get_tunnel(name)
+                        .ok_or_else(|| format!("tunnel {} doesn't exist", name))
+                })
+                .collect::<std::result::Result<Vec<_>, _>>()?,
+        };
+
+        let first = match tunnels.first() {
+            Some(tunnel) => tunnel,
+            None => return Err("no tunnels defined, add one with tcproxy-cli tunnel add".into()),
+        };
+
+        if tunnels
+            .iter()
+            .any(|tunnel| tunnel.context() != first.context())
+        {
+            return Err("tunnels use different app contexts, start them separately".into());
+        }
+
+        Ok(tunnels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::TunnelManager;
+    use crate::config::TunnelDefinition;
+    use crate::server_addr::ServerAddr;
+
+    fn tunnel(name: &str, context: Option<&str>) -> TunnelDefinition {
+        let target = ServerAddr::from_str("127.0.0.1:3000").unwrap();
+        TunnelDefinition::new(name, &target).with_context(context)
+    }
+
+    #[test]
+    fn should_reject_duplicated_tunnel() {
+        // Arrange
+        let mut manager = TunnelManager::new(&[tunnel("api", None)]);
+
+        // Act
+        let result = manager.push_tunnel(&tunnel("api", None));
+
+        // Assert
+        assert!(result.is_err());
+        assert_eq!(manager.tunnels().len(), 1);
+    }
+
+    #[test]
+    fn should_remove_tunnel() {
+        // Arrange
+        let mut manager = TunnelManager::new(&[tunnel("api", None), tunnel("db", None)]);
+
+        // Act
+        let result = manager.remove_tunnel("api");
+
+        // Assert
+        assert!(result.is_ok());
+        assert!(manager.get_tunnel("api").is_none());
+        assert!(manager.remove_tunnel("api").is_err());
+    }
+
+    #[test]
+    fn should_select_every_tunnel_when_no_name_is_given() {
+        // Arrange
+        let manager = TunnelManager::new(&[tunnel("api", None), tunnel("db", None)]);
+
+        // Act
+        let all = manager.select(&[]).unwrap();
+        let some = manager.select(&[String::from("db")]).unwrap();
+
+        // Assert
+        assert_eq!(all.len(), 2);
+        assert_eq!(some.len(), 1);
+        assert_eq!(some[0].name(), "db");
+    }
+
+    #[test]
+    fn should_not_select_tunnels_of_different_contexts() {
+        // Arrange
+        let manager = TunnelManager::new(&[tunnel("api", None), tunnel("db", Some("prod"))]);
+
+        // Act
+        let result = manager.select(&[]);
+
+        // Assert
+        assert!(result.is_err());
+    }
+}
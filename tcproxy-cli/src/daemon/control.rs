@@ -0,0 +1,155 @@
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::str::FromStr;
+use tcproxy_core::Result;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+
+/// request sent by the cli to the daemon, a single line per connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlRequest {
+    Status,
+    Start(String),
+    Stop(String),
+    Shutdown,
+}
+
+/// answer of the daemon, `ok` or `error` followed by the text printed by the cli.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlResponse {
+    Ok(String),
+    Err(String),
+}
+
+impl FromStr for ControlRequest {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let mut parts = value.split_whitespace();
+        let request = match (parts.next(), parts.next()) {
+            (Some("status"), None) => ControlRequest::Status,
+            (Some("shutdown"), None) => ControlRequest::Shutdown,
+            (Some("start"), Some(name)) => ControlRequest::Start(String::from(name)),
+            (Some("stop"), Some(name)) => ControlRequest::Stop(String::from(name)),
+            _ => return Err(format!("invalid request: {}", value.trim())),
+        };
+
+        match parts.next() {
+            Some(_) => Err(format!("invalid request: {}", value.trim())),
+            None => Ok(request),
+        }
+    }
+}
+
+impl Display for ControlRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ControlRequest::Status => write!(f, "status"),
+            ControlRequest::Shutdown => write!(f, "shutdown"),
+            ControlRequest::Start(name) => write!(f, "start {}", name),
+            ControlRequest::Stop(name) => write!(f, "stop {}", name),
+        }
+    }
+}
+
+impl ControlResponse {
+    pub fn encode(&self) -> String {
+        match self {
+            ControlResponse::Ok(body) => format!("ok\n{}", body),
+            ControlResponse::Err(message) => format!("error\n{}", message),
+        }
+    }
+
+    pub fn decode(value: &str) -> Self {
+        match value.split_once('\n') {
+            Some(("ok", body)) => ControlResponse::Ok(String::from(body)),
+            Some(("error", message)) => ControlResponse::Err(String::from(message)),
+            _ => ControlResponse::Err(format!("invalid response from daemon: {}", value)),
+        }
+    }
+}
+
+impl From<Result<String>> for ControlResponse {
+    fn from(value: Result<String>) -> Self {
+        match value {
+            Ok(body) => ControlResponse::Ok(body),
+            Err(err) => ControlResponse::Err(err.to_string()),
+        }
+    }
+}
+
+/// sends `request` to the daemon listening on `path` and waits for its answer.
+pub async fn send_request(path: &Path, request: &ControlRequest) -> Result<ControlResponse> {
+    let mut stream = match UnixStream::connect(path).await {
+        Ok(stream) => stream,
+        Err(_) => {
+            return Err(
+                "tcproxy-cli daemon is not running, start it with tcproxy-cli daemon".into(),
+            )
+        }
+    };
+
+    stream
+        .write_all(format!("{}\n", request).as_bytes())
+        .await?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+
+    Ok(ControlResponse::decode(&response))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::{ControlRequest, ControlResponse};
+
+    #[test]
+    fn should_parse_requests() {
+        // Arrange
+        let requests = [
+            ControlRequest::Status,
+            ControlRequest::Shutdown,
+            ControlRequest::Start(String::from("api")),
+            ControlRequest::Stop(String::from("db")),
+        ];
+
+        // Act
+        let parsed: Vec<ControlRequest> = requests
+            .iter()
+            .map(|request| ControlRequest::from_str(&format!("{}\n", request)).unwrap())
+            .collect();
+
+        // Assert
+        assert_eq!(parsed, requests);
+    }
+
+    #[test]
+    fn should_reject_invalid_requests() {
+        // Act
+        let missing_name = ControlRequest::from_str("start");
+        let extra_argument = ControlRequest::from_str("stop api db");
+        let unknown = ControlRequest::from_str("restart api");
+
+        // Assert
+        assert!(missing_name.is_err());
+        assert!(extra_argument.is_err());
+        assert!(unknown.is_err());
+    }
+
+    #[test]
+    fn should_encode_and_decode_responses() {
+        // Arrange
+        let ok = ControlResponse::Ok(String::from("Name  Status\napi   running"));
+        let err = ControlResponse::Err(String::from("tunnel db doesn't exist"));
+
+        // Act
+        let decoded_ok = ControlResponse::decode(&ok.encode());
+        let decoded_err = ControlResponse::decode(&err.encode());
+
+        // Assert
+        assert_eq!(decoded_ok, ok);
+        assert_eq!(decoded_err, err);
+    }
+}
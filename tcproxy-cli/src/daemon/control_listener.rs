@@ -0,0 +1,166 @@
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use tcproxy_core::Result;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::broadcast;
+use tracing::{debug, error};
+
+use super::{ControlRequest, ControlResponse, TunnelSupervisor};
+use crate::Shutdown;
+
+/// answers the requests of `tcproxy-cli status|start|stop` on the daemon socket.
+pub struct ControlListener {
+    listener: UnixListener,
+    path: PathBuf,
+    supervisor: Arc<TunnelSupervisor>,
+    notify_shutdown: broadcast::Sender<()>,
+}
+
+impl ControlListener {
+    /// binds the socket at `path`, replacing it when it was left behind by a daemon that
+    /// didn't exit cleanly.
+    pub async fn bind(
+        path: &Path,
+        supervisor: &Arc<TunnelSupervisor>,
+        notify_shutdown: &broadcast::Sender<()>,
+    ) -> Result<Self> {
+        if path.exists() {
+            if UnixStream::connect(path).await.is_ok() {
+                return Err("tcproxy-cli daemon is already running".into());
+            }
+
+            debug!("removing stale daemon socket {:?}", path);
+            std::fs::remove_file(path)?;
+        }
+
+        Ok(Self {
+            listener: UnixListener::bind(path)?,
+            path: path.to_owned(),
+            supervisor: supervisor.clone(),
+            notify_shutdown: notify_shutdown.clone(),
+        })
+    }
+
+    pub async fn run(&self, mut shutdown: Shutdown) -> Result<()> {
+        loop {
+            tokio::select! {
+                res = self.listener.accept() => {
+                    let (stream, _) = res?;
+                    let supervisor = self.supervisor.clone();
+                    let notify_shutdown = self.notify_shutdown.clone();
+                    tokio::spawn(async move {
+                        if let Err(err) = handle_request(stream, &supervisor, &notify_shutdown).await {
+                            error!("failed to answer control request: {}", err);
+                        }
+                    });
+                }
+                _ = shutdown.recv() => {
+                    debug!("control listener received stop signal");
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+impl Drop for ControlListener {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+async fn handle_request(
+    stream: UnixStream,
+    supervisor: &TunnelSupervisor,
+    notify_shutdown: &broadcast::Sender<()>,
+) -> Result<()> {
+    let mut stream = BufReader::new(stream);
+    let mut line = String::new();
+    stream.read_line(&mut line).await?;
+
+    let response = match ControlRequest::from_str(&line) {
+        Ok(ControlRequest::Status) => ControlResponse::Ok(supervisor.status()),
+        Ok(ControlRequest::Start(name)) => supervisor
+            .start(&name)
+            .map(|_| format!("started tunnel {}", name))
+            .into(),
+        Ok(ControlRequest::Stop(name)) => supervisor
+            .stop(&name)
+            .map(|_| format!("stopped tunnel {}", name))
+            .into(),
+        Ok(ControlRequest::Shutdown) => {
+            let _ = notify_shutdown.send(());
+            ControlResponse::Ok(String::from("daemon is shutting down"))
+        }
+        Err(err) => ControlResponse::Err(err),
+    };
+
+    let mut stream = stream.into_inner();
+    stream.write_all(response.encode().as_bytes()).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use tokio::sync::{broadcast, mpsc};
+    use uuid::Uuid;
+
+    use super::ControlListener;
+    use crate::commands::SessionOptions;
+    use crate::config::directory_resolver::DirectoryResolver;
+    use crate::daemon::{send_request, ControlRequest, ControlResponse, TunnelSupervisor};
+    use crate::Shutdown;
+
+    #[tokio::test]
+    async fn should_answer_requests_until_shutdown() {
+        // Arrange
+        let path = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&path).unwrap();
+
+        let resolver = DirectoryResolver::new(&path, "config.yaml");
+        let socket = resolver.get_daemon_socket();
+        let (shutdown_complete_tx, _) = mpsc::channel::<()>(1);
+        let (notify_shutdown, _) = broadcast::channel::<()>(1);
+        let options = SessionOptions::new(false, 5, true).without_console();
+        let supervisor = Arc::new(TunnelSupervisor::new(
+            &resolver,
+            options,
+            &shutdown_complete_tx,
+        ));
+
+        let listener = ControlListener::bind(&socket, &supervisor, &notify_shutdown)
+            .await
+            .unwrap();
+        let shutdown = Shutdown::new(notify_shutdown.subscribe());
+        let task = tokio::spawn(async move { listener.run(shutdown).await });
+
+        // Act
+        let status = send_request(&socket, &ControlRequest::Status)
+            .await
+            .unwrap();
+        let start = send_request(&socket, &ControlRequest::Start(String::from("api")))
+            .await
+            .unwrap();
+        let stop = send_request(&socket, &ControlRequest::Shutdown)
+            .await
+            .unwrap();
+        let result = task.await.unwrap();
+
+        // Assert
+        assert!(matches!(status, ControlResponse::Ok(_)));
+        assert_eq!(
+            start,
+            ControlResponse::Err(String::from("tunnel api doesn't exist"))
+        );
+        assert!(matches!(stop, ControlResponse::Ok(_)));
+        assert!(result.is_ok());
+        assert!(!socket.exists());
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
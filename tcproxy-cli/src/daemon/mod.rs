@@ -0,0 +1,7 @@
+mod control;
+mod control_listener;
+mod supervisor;
+
+pub use control::*;
+pub use control_listener::*;
+pub use supervisor::*;
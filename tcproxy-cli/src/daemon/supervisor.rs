@@ -0,0 +1,249 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use tcproxy_core::{AsyncCommand, Result};
+use tokio::sync::{broadcast, mpsc, watch};
+use tracing::info;
+
+use crate::commands::{ListenCommand, SessionOptions};
+use crate::config::{self, directory_resolver::DirectoryResolver, TunnelDefinition};
+use crate::server_addr::join_targets;
+use crate::{ClientState, Shutdown};
+
+/// tunnels run by the daemon, each one on its own session so they can be
+/// stopped and started independently.
+pub struct TunnelSupervisor {
+    directory_resolver: DirectoryResolver,
+    options: SessionOptions,
+    tunnels: Mutex<BTreeMap<String, SupervisedTunnel>>,
+    shutdown_complete_tx: mpsc::Sender<()>,
+}
+
+struct SupervisedTunnel {
+    definition: TunnelDefinition,
+    notify_shutdown: broadcast::Sender<()>,
+    state: watch::Receiver<Option<Arc<ClientState>>>,
+
+    /// why the session ended, `None` while it is running.
+    outcome: Arc<Mutex<Option<String>>>,
+}
+
+impl TunnelSupervisor {
+    pub fn new(
+        directory_resolver: &DirectoryResolver,
+        options: SessionOptions,
+        shutdown_complete_tx: &mpsc::Sender<()>,
+    ) -> Self {
+        Self {
+            directory_resolver: directory_resolver.clone(),
+            options,
+            tunnels: Mutex::new(BTreeMap::new()),
+            shutdown_complete_tx: shutdown_complete_tx.clone(),
+        }
+    }
+
+    /// starts the tunnel defined as `name`, reading the config file again so
+    /// tunnels added after the daemon started can be used.
+    pub fn start(&self, name: &str) -> Result<()> {
+        let config = config::load(&self.directory_resolver)?;
+        let definition = match config.lock_tunnel_manager()?.get_tunnel(name) {
+            Some(definition) => definition,
+            None => return Err(format!("tunnel {} doesn't exist", name).into()),
+        };
+
+        let mut tunnels = self.tunnels.lock().unwrap();
+        if tunnels.get(name).map(|tunnel| tunnel.is_running()) == Some(true) {
+            return Err(format!("tunnel {} is already running", name).into());
+        }
+
+        let (notify_shutdown, _) = broadcast::channel::<()>(1);
+        let (state_sender, state) = watch::channel(None);
+        let outcome = Arc::new(Mutex::new(None));
+
+        let mut command = ListenCommand::new(
+            std::slice::from_ref(&definition),
+            definition.context().map(String::from),
+            self.options,
+            Arc::new(config),
+            self.shutdown_complete_tx.clone(),
+            notify_shutdown.clone(),
+        )
+        .with_state_sender(state_sender);
+
+        let mut shutdown = Shutdown::new(notify_shutdown.subscribe());
+        let session_outcome = outcome.clone();
+        let tunnel_name = String::from(name);
+        tokio::spawn(async move {
+            let result = tokio::select! {
+                res = command.handle() => match res {
+                    Ok(_) => String::from("disconnected"),
+                    Err(err) => format!("failed: {}", err),
+                },
+                _ = shutdown.recv() => String::from("stopped"),
+            };
+
+            info!("tunnel {} {}", tunnel_name, result);
+            session_outcome.lock().unwrap().get_or_insert(result);
+        });
+
+        tunnels.insert(
+            String::from(name),
+            SupervisedTunnel {
+                definition,
+                notify_shutdown,
+                state,
+                outcome,
+            },
+        );
+
+        Ok(())
+    }
+
+    pub fn stop(&self, name: &str) -> Result<()> {
+        let tunnels = self.tunnels.lock().unwrap();
+        match tunnels.get(name) {
+            Some(tunnel) if tunnel.is_running() => {
+                tunnel.stop();
+                Ok(())
+            }
+            _ => Err(format!("tunnel {} is not running", name).into()),
+        }
+    }
+
+    pub fn stop_all(&self) {
+        for tunnel in self.tunnels.lock().unwrap().values() {
+            tunnel.stop();
+        }
+    }
+
+    /// table with every tunnel started by the daemon.
+    pub fn status(&self) -> String {
+        let tunnels = self.tunnels.lock().unwrap();
+        if tunnels.is_empty() {
+            return String::from("no tunnels running, start one with tcproxy-cli start <name>");
+        }
+
+        let header =
+            ["Name", "Status", "Public Address", "Target", "Connections"].map(String::from);
+        let lines: Vec<[String; 5]> = std::iter::once(header)
+            .chain(tunnels.values().map(|tunnel| tunnel.report()))
+            .collect();
+
+        let mut widths = [0; 5];
+        for line in &lines {
+            for (width, column) in widths.iter_mut().zip(line) {
+                *width = column.len().max(*width);
+            }
+        }
+
+        let rows: Vec<String> = lines
+            .iter()
+            .map(|line| {
+                let mut row = String::new();
+                for (width, column) in widths.iter().zip(line) {
+                    let _ = write!(row, "{: <width$}  ", column, width = width);
+                }
+                row.trim_end().to_owned()
+            })
+            .collect();
+
+        rows.join("\n")
+    }
+}
+
+impl SupervisedTunnel {
+    fn is_running(&self) -> bool {
+        self.outcome.lock().unwrap().is_none()
+    }
+
+    fn stop(&self) {
+        let _ = self.notify_shutdown.send(());
+    }
+
+    fn report(&self) -> [String; 5] {
+        let name = String::from(self.definition.name());
+        let target = self
+            .definition
+            .targets()
+            .map(|targets| join_targets(&targets))
+            .unwrap_or_else(|_| String::from("-"));
+
+        if let Some(outcome) = self.outcome.lock().unwrap().clone() {
+            return [name, outcome, String::from("-"), target, String::from("-")];
+        }
+
+        match self.state.borrow().as_ref() {
+            Some(state) => {
+                let status = state.get_console_status();
+                let public_addr = status
+                    .tunnels
+                    .first()
+                    .and_then(|tunnel| tunnel.public_addr())
+                    .map(|addr| addr.to_string())
+                    .unwrap_or_else(|| String::from("-"));
+
+                [
+                    name,
+                    String::from("running"),
+                    public_addr,
+                    target,
+                    status.connections.to_string(),
+                ]
+            }
+            None => [
+                name,
+                String::from("connecting"),
+                String::from("-"),
+                target,
+                String::from("-"),
+            ],
+        }
+    }
+}
+
+impl Drop for TunnelSupervisor {
+    fn drop(&mut self) {
+        self.stop_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    use super::TunnelSupervisor;
+    use crate::commands::SessionOptions;
+    use crate::config::directory_resolver::DirectoryResolver;
+
+    fn create_supervisor() -> (TunnelSupervisor, PathBuf) {
+        let path = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&path).unwrap();
+
+        let (shutdown_complete_tx, _) = mpsc::channel::<()>(1);
+        let resolver = DirectoryResolver::new(&path, "config.yaml");
+        let options = SessionOptions::new(false, 5, true).without_console();
+
+        (
+            TunnelSupervisor::new(&resolver, options, &shutdown_complete_tx),
+            path,
+        )
+    }
+
+    #[tokio::test]
+    async fn should_not_start_undefined_tunnel() {
+        // Arrange
+        let (supervisor, path) = create_supervisor();
+
+        // Act
+        let result = supervisor.start("api");
+
+        // Assert
+        assert!(result.is_err());
+        assert!(supervisor.stop("api").is_err());
+        assert!(supervisor.status().starts_with("no tunnels running"));
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -0,0 +1,85 @@
+use emoji_printer::print_emojis;
+use std::time::Duration;
+use tcproxy_core::framing::ServerShutdown;
+
+pub(crate) fn format_server_shutdown(notice: &ServerShutdown) -> String {
+    print_emojis(&format!(
+        ":warning: Server is restarting ({}), the tunnel closes in {}s",
+        notice.reason(),
+        notice.deadline()
+    ))
+}
+
+/// formats how long a connection has been open, e.g. `42s`, `3m12s` or `1h02m`.
+pub(crate) fn format_duration(duration: &Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds {
+        0..=59 => format!("{}s", seconds),
+        60..=3599 => format!("{}m{:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h{:02}m", seconds / 3600, (seconds % 3600) / 60),
+    }
+}
+
+/// formats a byte count using binary units (KiB, MiB..).
+pub(crate) fn format_bytes(bytes: &u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut value = *bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{} {}", bytes, UNITS[0]),
+        _ => format!("{:.1} {}", value, UNITS[unit]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use tcproxy_core::framing::ServerShutdown;
+
+    use super::{format_bytes, format_duration, format_server_shutdown};
+
+    #[test]
+    pub fn should_format_bytes() {
+        // Act
+        let bytes = format_bytes(&512);
+        let kibibytes = format_bytes(&(1024 * 3 / 2));
+        let mebibytes = format_bytes(&(5 * 1024 * 1024));
+
+        // Assert
+        assert_eq!(bytes, "512 B");
+        assert_eq!(kibibytes, "1.5 KiB");
+        assert_eq!(mebibytes, "5.0 MiB");
+    }
+
+    #[test]
+    pub fn should_format_duration() {
+        // Act
+        let seconds = format_duration(&Duration::from_secs(42));
+        let minutes = format_duration(&Duration::from_secs(3 * 60 + 5));
+        let hours = format_duration(&Duration::from_secs(3600 + 2 * 60 + 30));
+
+        // Assert
+        assert_eq!(seconds, "42s");
+        assert_eq!(minutes, "3m05s");
+        assert_eq!(hours, "1h02m");
+    }
+
+    #[test]
+    pub fn should_format_server_shutdown() {
+        // Arrange
+        let notice = ServerShutdown::new("server is shutting down", &30);
+
+        // Act
+        let result = format_server_shutdown(&notice);
+
+        // Assert
+        assert!(result
+            .ends_with("Server is restarting (server is shutting down), the tunnel closes in 30s"));
+    }
+}
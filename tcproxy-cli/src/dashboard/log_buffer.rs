@@ -0,0 +1,110 @@
+use chrono::Local;
+use std::collections::VecDeque;
+use std::io;
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// how many lines the log pane keeps.
+const LOG_BUFFER_LEN: usize = 500;
+
+/// last events of the session, shown on the dashboard log pane.
+#[derive(Debug, Clone, Default)]
+pub struct LogBuffer {
+    lines: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl LogBuffer {
+    /// appends `line` prefixed with the current time.
+    pub fn push(&self, line: &str) {
+        self.push_raw(&format!("{} {}", Local::now().format("%H:%M:%S"), line));
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.lines.lock().unwrap().iter().cloned().collect()
+    }
+
+    fn push_raw(&self, line: &str) {
+        let mut lines = self.lines.lock().unwrap();
+        if lines.len() == LOG_BUFFER_LEN {
+            lines.pop_front();
+        }
+
+        lines.push_back(String::from(line));
+    }
+}
+
+/// collects a single tracing event, written to the buffer once tracing drops it.
+pub struct LogWriter {
+    buffer: LogBuffer,
+    content: Vec<u8>,
+}
+
+impl io::Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.content.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        let content = String::from_utf8_lossy(&self.content);
+        for line in content.lines().filter(|line| !line.is_empty()) {
+            self.buffer.push_raw(line);
+        }
+    }
+}
+
+/// sends tracing output to the log pane when running verbose.
+impl<'a> MakeWriter<'a> for LogBuffer {
+    type Writer = LogWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LogWriter {
+            buffer: self.clone(),
+            content: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    use super::{LogBuffer, LOG_BUFFER_LEN};
+
+    #[test]
+    fn should_keep_only_last_lines() {
+        // Arrange
+        let buffer = LogBuffer::default();
+
+        // Act
+        for i in 0..LOG_BUFFER_LEN + 10 {
+            buffer.push(&format!("line {}", i));
+        }
+
+        // Assert
+        let lines = buffer.lines();
+        assert_eq!(lines.len(), LOG_BUFFER_LEN);
+        assert!(lines[0].ends_with("line 10"));
+    }
+
+    #[test]
+    fn should_write_tracing_events_line_by_line() {
+        // Arrange
+        let buffer = LogBuffer::default();
+
+        // Act
+        let mut writer = buffer.make_writer();
+        writer.write_all(b"first event\nsecond line\n").unwrap();
+        drop(writer);
+
+        // Assert
+        assert_eq!(buffer.lines(), vec!["first event", "second line"]);
+    }
+}
@@ -0,0 +1,172 @@
+mod format;
+mod log_buffer;
+mod view;
+
+pub(crate) use format::*;
+pub use log_buffer::*;
+pub use view::*;
+
+use crossterm::event::{Event, EventStream, KeyEventKind};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use futures::StreamExt;
+use ratatui::backend::CrosstermBackend;
+use ratatui::Terminal;
+use std::io::{self, Stdout};
+use std::sync::Arc;
+use std::time::Duration;
+use tcproxy_core::Result;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::task::JoinHandle;
+use tracing::{debug, error};
+
+use crate::{ClientState, Shutdown};
+
+/// how often durations and throughput are refreshed when nothing else changes.
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// terminal ui of a session: tunnels, open connections, rtt and logs.
+pub struct Dashboard {
+    receiver: Receiver<i32>,
+    state: Arc<ClientState>,
+    enabled: bool,
+    notify_shutdown: broadcast::Sender<()>,
+    _shutdown_complete_tx: Sender<()>,
+}
+
+impl Dashboard {
+    /// a disabled dashboard only consumes the state notifications, used when running
+    /// in the background.
+    pub fn new(
+        receiver: Receiver<i32>,
+        state: &Arc<ClientState>,
+        enabled: bool,
+        notify_shutdown: &broadcast::Sender<()>,
+        shutdown_complete_signal: &Sender<()>,
+    ) -> Self {
+        Self {
+            receiver,
+            enabled,
+            state: state.clone(),
+            notify_shutdown: notify_shutdown.clone(),
+            _shutdown_complete_tx: shutdown_complete_signal.clone(),
+        }
+    }
+
+    pub fn spawn(mut self, mut shutdown: Shutdown) -> JoinHandle<Result<()>> {
+        tokio::spawn(async move {
+            if self.enabled {
+                if let Err(err) = self.run(&mut shutdown).await {
+                    error!("dashboard stopped: {}", err);
+                }
+            }
+
+            self.drain(shutdown).await;
+            Ok(())
+        })
+    }
+
+    async fn run(&mut self, shutdown: &mut Shutdown) -> Result<()> {
+        let mut terminal = TerminalGuard::enter()?;
+        let mut events = EventStream::new();
+        let mut ticker = tokio::time::interval(TICK_INTERVAL);
+        let mut view = DashboardView::default();
+
+        loop {
+            terminal.draw(|frame| view.draw(frame, &self.state))?;
+
+            tokio::select! {
+                res = self.receiver.recv() => {
+                    if res.is_none() {
+                        return Ok(());
+                    }
+                }
+                _ = ticker.tick() => view.sample(&self.state.connections()),
+                event = events.next() => match event {
+                    Some(Ok(Event::Key(key))) if key.kind == KeyEventKind::Press => {
+                        match view.handle_key(key, &self.state.connections()) {
+                            Action::Quit => {
+                                let _ = self.notify_shutdown.send(());
+                                return Ok(());
+                            }
+                            Action::Close(id) => {
+                                if self.state.close_connection(&id) {
+                                    self.state.log().push(&format!("closing connection {}", id));
+                                }
+                            }
+                            Action::None => {}
+                        }
+                    }
+                    Some(Ok(_)) => {}
+                    Some(Err(err)) => return Err(err.into()),
+                    None => return Ok(()),
+                },
+                _ = shutdown.recv() => {
+                    debug!("received stop signal.");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// keeps consuming notifications so the session never waits on the dashboard.
+    async fn drain(&mut self, mut shutdown: Shutdown) {
+        if shutdown.is_shutdown() {
+            return;
+        }
+
+        loop {
+            tokio::select! {
+                res = self.receiver.recv() => {
+                    if res.is_none() {
+                        return;
+                    }
+                }
+                _ = shutdown.recv() => return,
+            }
+        }
+    }
+}
+
+/// puts the terminal in raw mode on the alternate screen, restoring it when dropped.
+struct TerminalGuard {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl TerminalGuard {
+    fn enter() -> Result<Self> {
+        enable_raw_mode()?;
+        if let Err(err) = execute!(io::stdout(), EnterAlternateScreen) {
+            let _ = disable_raw_mode();
+            return Err(err.into());
+        }
+
+        let terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+        Ok(Self { terminal })
+    }
+}
+
+impl std::ops::Deref for TerminalGuard {
+    type Target = Terminal<CrosstermBackend<Stdout>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.terminal
+    }
+}
+
+impl std::ops::DerefMut for TerminalGuard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.terminal
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(self.terminal.backend_mut(), LeaveAlternateScreen);
+        let _ = self.terminal.show_cursor();
+    }
+}
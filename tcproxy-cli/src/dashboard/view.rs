@@ -0,0 +1,350 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Sparkline, Table, TableState};
+use ratatui::Frame;
+use std::collections::HashMap;
+use std::time::Instant;
+
+use super::{format_bytes, format_duration, format_server_shutdown};
+use crate::{ClientState, ConnectionInfo, ConsoleStatus, TargetPool};
+
+/// lines scrolled by page up and page down on the log pane.
+const LOG_PAGE: usize = 5;
+
+/// what the dashboard has to do after a key press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    None,
+    Quit,
+    Close(u32),
+}
+
+/// dashboard state that isn't part of the session: selected connection,
+/// log scroll and throughput of every connection since the last tick.
+#[derive(Debug, Default)]
+pub struct DashboardView {
+    table: TableState,
+    log_scroll: usize,
+    samples: HashMap<u32, (u64, u64, Instant)>,
+    rates: HashMap<u32, (u64, u64)>,
+}
+
+impl DashboardView {
+    /// updates the throughput of every connection from its byte counters.
+    pub fn sample(&mut self, connections: &[ConnectionInfo]) {
+        let now = Instant::now();
+        let mut samples = HashMap::with_capacity(connections.len());
+        let mut rates = HashMap::with_capacity(connections.len());
+
+        for connection in connections {
+            let (bytes_in, bytes_out) = (*connection.bytes_in(), *connection.bytes_out());
+            if let Some((last_in, last_out, at)) = self.samples.get(connection.id()) {
+                let elapsed = now.duration_since(*at).as_secs_f64().max(f64::EPSILON);
+                rates.insert(
+                    *connection.id(),
+                    (
+                        ((bytes_in - last_in) as f64 / elapsed) as u64,
+                        ((bytes_out - last_out) as f64 / elapsed) as u64,
+                    ),
+                );
+            }
+
+            samples.insert(*connection.id(), (bytes_in, bytes_out, now));
+        }
+
+        self.samples = samples;
+        self.rates = rates;
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, connections: &[ConnectionInfo]) -> Action {
+        let selected = self.table.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Action::Quit
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.table.select(Some(selected.saturating_sub(1))),
+            KeyCode::Down | KeyCode::Char('j') => self.table.select(Some(selected + 1)),
+            KeyCode::Char('x') | KeyCode::Delete => {
+                if let Some(connection) = connections.get(selected) {
+                    return Action::Close(*connection.id());
+                }
+            }
+            KeyCode::PageUp => self.log_scroll += LOG_PAGE,
+            KeyCode::PageDown => self.log_scroll = self.log_scroll.saturating_sub(LOG_PAGE),
+            KeyCode::End => self.log_scroll = 0,
+            _ => {}
+        }
+
+        Action::None
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, state: &ClientState) {
+        let status = state.get_console_status();
+        let connections = state.connections();
+        let logs = state.log().lines();
+
+        let selected = match connections.len() {
+            0 => None,
+            len => Some(self.table.selected().unwrap_or(0).min(len - 1)),
+        };
+        self.table.select(selected);
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(status.tunnels.len() as u16 + 6),
+                Constraint::Min(6),
+                Constraint::Length(10),
+                Constraint::Length(1),
+            ])
+            .split(frame.size());
+
+        let middle = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+            .split(layout[1]);
+
+        draw_tunnels(frame, layout[0], &status);
+        self.draw_connections(frame, middle[0], &connections);
+        draw_rtt(frame, middle[1], &status);
+        self.draw_logs(frame, layout[2], &logs);
+        frame.render_widget(
+            Paragraph::new(
+                "q quit  ↑/↓ select connection  x close connection  PgUp/PgDn scroll logs",
+            )
+            .style(Style::default().fg(Color::DarkGray)),
+            layout[3],
+        );
+    }
+
+    fn draw_connections(&mut self, frame: &mut Frame, area: Rect, connections: &[ConnectionInfo]) {
+        let rows = connections.iter().map(|connection| {
+            let (rate_in, rate_out) = self.rates.get(connection.id()).copied().unwrap_or_default();
+
+            Row::new(vec![
+                connection.id().to_string(),
+                String::from(connection.tunnel()),
+                connection
+                    .remote_addr()
+                    .map(|addr| addr.to_string())
+                    .unwrap_or_else(|| String::from("-")),
+                format_duration(&connection.opened_at().elapsed()),
+                format_bytes(connection.bytes_in()),
+                format_bytes(connection.bytes_out()),
+                format!(
+                    "{}/s | {}/s",
+                    format_bytes(&rate_in),
+                    format_bytes(&rate_out)
+                ),
+            ])
+        });
+
+        let widths = [
+            Constraint::Length(6),
+            Constraint::Length(12),
+            Constraint::Min(22),
+            Constraint::Length(8),
+            Constraint::Length(11),
+            Constraint::Length(11),
+            Constraint::Min(23),
+        ];
+
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(vec![
+                    "Id",
+                    "Tunnel",
+                    "Remote Address",
+                    "Open",
+                    "In",
+                    "Out",
+                    "Throughput (in | out)",
+                ])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+            )
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Connections ({}) ", connections.len())),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        frame.render_stateful_widget(table, area, &mut self.table);
+    }
+
+    fn draw_logs(&mut self, frame: &mut Frame, area: Rect, logs: &[String]) {
+        let visible = area.height.saturating_sub(2) as usize;
+        self.log_scroll = self.log_scroll.min(logs.len().saturating_sub(visible));
+
+        let end = logs.len() - self.log_scroll;
+        let start = end.saturating_sub(visible);
+        let lines: Vec<Line> = logs[start..end]
+            .iter()
+            .map(|line| Line::from(line.as_str()))
+            .collect();
+
+        let title = match self.log_scroll {
+            0 => String::from(" Logs "),
+            scroll => format!(" Logs (-{}) ", scroll),
+        };
+
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)),
+            area,
+        );
+    }
+}
+
+/// `127.0.0.1:3000, 127.0.0.1:3001 (down)`
+fn format_targets(targets: &TargetPool) -> String {
+    (0..targets.len())
+        .map(|index| match targets.is_healthy(index) {
+            true => targets.get(index).to_string(),
+            false => format!("{} (down)", targets.get(index)),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn draw_tunnels(frame: &mut Frame, area: Rect, status: &ConsoleStatus) {
+    let stats = &status.tunnel_stats;
+    let limit = match stats.rate_limit() {
+        Some(limit) => format!("{}/s", format_bytes(limit)),
+        None => String::from("none"),
+    };
+
+    let mut lines: Vec<Line> = status
+        .tunnels
+        .iter()
+        .map(|tunnel| {
+            let public_addr = match tunnel.public_addr() {
+                Some(addr) => addr.to_string(),
+                None => format!("port {}", tunnel.listener_port()),
+            };
+
+            Line::from(format!(
+                "{} ({}) {} -> {}",
+                tunnel.name(),
+                tunnel.protocol(),
+                public_addr,
+                format_targets(tunnel.targets())
+            ))
+        })
+        .collect();
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(format!(
+        "Ping: {:.2}ms  Connections: {}  Throughput: in {}/s, out {}/s (limit: {})",
+        status.ping,
+        status.connections,
+        format_bytes(stats.bytes_in_per_second()),
+        format_bytes(stats.bytes_out_per_second()),
+        limit
+    )));
+
+    if let Some(notice) = &status.server_shutdown {
+        lines.push(Line::styled(
+            format_server_shutdown(notice),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" Tunnels ")),
+        area,
+    );
+}
+
+fn draw_rtt(frame: &mut Frame, area: Rect, status: &ConsoleStatus) {
+    let width = area.width.saturating_sub(2) as usize;
+    let history = &status.ping_history;
+    let data = &history[history.len().saturating_sub(width)..];
+    let max = data.iter().max().copied().unwrap_or_default();
+
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" RTT (max {}ms) ", max)),
+        )
+        .data(data)
+        .style(Style::default().fg(Color::Cyan));
+
+    frame.render_widget(sparkline, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+    use tokio_util::sync::CancellationToken;
+
+    use super::{Action, DashboardView};
+    use crate::{ClientState, ConnectionInfo};
+
+    fn create_state() -> Arc<ClientState> {
+        let (console_sender, _) = mpsc::channel::<i32>(10);
+        let state = Arc::new(ClientState::new(&console_sender));
+        for id in [7, 9] {
+            let (sender, _) = mpsc::channel(1);
+            state.insert_connection(
+                ConnectionInfo::new(&id, "api"),
+                sender,
+                CancellationToken::new(),
+            );
+        }
+
+        state
+    }
+
+    #[tokio::test]
+    async fn should_draw_every_connection() {
+        // Arrange
+        let state = create_state();
+        state.record_bytes_in(&9, 2048);
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        let mut view = DashboardView::default();
+
+        // Act
+        terminal.draw(|frame| view.draw(frame, &state)).unwrap();
+
+        // Assert
+        let content: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(content.contains("Connections (2)"));
+        assert!(content.contains("2.0 KiB"));
+        assert!(content.contains("connection 9 opened on api"));
+    }
+
+    #[tokio::test]
+    async fn should_close_selected_connection() {
+        // Arrange
+        let state = create_state();
+        let connections = state.connections();
+        let mut view = DashboardView::default();
+
+        // Act
+        view.handle_key(
+            KeyEvent::new(KeyCode::Down, KeyModifiers::NONE),
+            &connections,
+        );
+        let action = view.handle_key(
+            KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE),
+            &connections,
+        );
+
+        // Assert
+        assert_eq!(action, Action::Close(9));
+    }
+}
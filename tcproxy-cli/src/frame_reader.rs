@@ -1,38 +1,40 @@
 use chrono::Utc;
 use std::sync::Arc;
+use tokio::sync::broadcast;
 use tokio::{sync::mpsc::Sender, task::JoinHandle};
 
-use tracing::debug;
+use tracing::{debug, info};
 
+use tcproxy_core::framing::Reason;
 use tcproxy_core::transport::TransportReader;
 use tcproxy_core::AsyncCommand;
 use tcproxy_core::{Result, TcpFrame};
 
 use crate::commands::{DataPacketCommand, IncomingSocketCommand, RemoteDisconnectedCommand};
-use crate::{ClientState, ListenArgs, Shutdown};
+use crate::{ClientState, Shutdown};
 
 pub struct TcpFrameReader {
     sender: Sender<TcpFrame>,
     reader: TransportReader,
     state: Arc<ClientState>,
-    args: Arc<ListenArgs>,
     _shutdown_complete_tx: Sender<()>,
+    notify_shutdown: broadcast::Sender<()>,
 }
 
 impl TcpFrameReader {
     pub fn new(
         sender: &Sender<TcpFrame>,
         state: &Arc<ClientState>,
-        args: &Arc<ListenArgs>,
         reader: TransportReader,
         shutdown_complete_tx: &Sender<()>,
+        notify_shutdown: &broadcast::Sender<()>,
     ) -> Self {
         Self {
-            args: args.clone(),
             sender: sender.clone(),
             state: state.clone(),
             reader,
             _shutdown_complete_tx: shutdown_complete_tx.clone(),
+            notify_shutdown: notify_shutdown.clone(),
         }
     }
 
@@ -40,7 +42,13 @@ impl TcpFrameReader {
         tokio::spawn(async move {
             while !shutdown.is_shutdown() {
                 let maybe_frame = tokio::select! {
-                    res = self.reader.next() => res?,
+                    res = self.reader.next() => match res {
+                        Ok(frame) => frame,
+                        Err(err) => {
+                            let _ = self.notify_shutdown.send(());
+                            return Err(err);
+                        }
+                    },
                     _ = shutdown.recv() => {
                         debug!("received stop signal from cancellation token");
                         return Ok(())
@@ -62,12 +70,9 @@ impl TcpFrameReader {
                         data.buffer(),
                         &self.state,
                     )),
-                    TcpFrame::SocketConnected(data) => Box::new(IncomingSocketCommand::new(
-                        data.connection_id(),
-                        &self.sender,
-                        &self.state,
-                        &self.args,
-                    )),
+                    TcpFrame::SocketConnected(data) => {
+                        Box::new(IncomingSocketCommand::new(&data, &self.sender, &self.state))
+                    }
                     TcpFrame::SocketDisconnected(data) => {
                         debug!("remote socket disconnected");
                         Box::new(RemoteDisconnectedCommand::new(
@@ -75,6 +80,27 @@ impl TcpFrameReader {
                             &self.state,
                         ))
                     }
+                    TcpFrame::TunnelStats(data) => {
+                        self.state.update_tunnel_stats(data);
+
+                        continue;
+                    }
+                    TcpFrame::ServerShutdown(notice) => {
+                        info!(
+                            "server is restarting: {}, closing in {}s",
+                            notice.reason(),
+                            notice.deadline()
+                        );
+                        self.state.set_server_shutdown(notice);
+
+                        continue;
+                    }
+                    TcpFrame::Error(err) if *err.reason() == Reason::QuotaExceeded => {
+                        println!("Tunnel closed by server: monthly usage quota exceeded.");
+                        let _ = self.notify_shutdown.send(());
+
+                        return Ok(());
+                    }
                     TcpFrame::Pong(_) => {
                         let time = Utc::now();
                         self.state.update_last_ping(time);
@@ -90,7 +116,9 @@ impl TcpFrameReader {
                 command.handle().await?;
             }
 
+            // server went away, stops every other task of this session.
             debug!("tcpframe_reader::start finished");
+            let _ = self.notify_shutdown.send(());
             Ok(())
         })
     }
@@ -0,0 +1,365 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tcproxy_core::Result;
+
+use super::{HttpMessage, StartLine};
+
+/// capture file in the HAR 1.2 format, extra fields are prefixed with `_` as the
+/// format asks for custom fields.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Har {
+    pub log: HarLog,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HarLog {
+    pub version: String,
+    pub creator: HarCreator,
+    pub entries: Vec<HarEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HarCreator {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarEntry {
+    #[serde(rename = "_id")]
+    pub id: String,
+    #[serde(rename = "_tunnel")]
+    pub tunnel: String,
+    /// local service the request was forwarded to.
+    #[serde(rename = "_target", default)]
+    pub target: String,
+    pub started_date_time: DateTime<Utc>,
+    pub time: f64,
+    pub request: HarRequest,
+    pub response: HarResponse,
+    pub timings: HarTimings,
+    pub connection: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarRequest {
+    pub method: String,
+    pub url: String,
+    pub http_version: String,
+    pub headers: Vec<HarHeader>,
+    pub query_string: Vec<HarHeader>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_data: Option<HarContent>,
+    pub headers_size: i64,
+    pub body_size: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarResponse {
+    pub status: u16,
+    pub status_text: String,
+    pub http_version: String,
+    pub headers: Vec<HarHeader>,
+    pub content: HarContent,
+    #[serde(rename = "redirectURL")]
+    pub redirect_url: String,
+    pub headers_size: i64,
+    pub body_size: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HarHeader {
+    pub name: String,
+    pub value: String,
+}
+
+/// body of a message, binary ones are base64 encoded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarContent {
+    pub size: i64,
+    pub mime_type: String,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HarTimings {
+    pub send: f64,
+    pub wait: f64,
+    pub receive: f64,
+}
+
+impl Default for HarLog {
+    fn default() -> Self {
+        Self {
+            version: String::from("1.2"),
+            creator: HarCreator {
+                name: String::from("tcproxy-cli"),
+                version: String::from(env!("CARGO_PKG_VERSION")),
+            },
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl Har {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read(path)?;
+        match serde_json::from_slice(&content) {
+            Ok(har) => Ok(har),
+            Err(err) => {
+                Err(format!("{} is not a valid capture file: {}", path.display(), err).into())
+            }
+        }
+    }
+
+    pub fn entry(&self, id: &str) -> Option<&HarEntry> {
+        self.log.entries.iter().find(|entry| entry.id == id)
+    }
+}
+
+impl HarEntry {
+    /// entry for a request and the response that answered it.
+    pub fn new(
+        id: &str,
+        tunnel: &str,
+        connection_id: &u32,
+        target: &str,
+        request: &HttpMessage,
+        response: &HttpMessage,
+    ) -> Self {
+        let started_date_time = Utc::now()
+            - chrono::Duration::from_std(request.started_at().elapsed())
+                .unwrap_or(chrono::Duration::zero());
+        let wait = millis(
+            response
+                .started_at()
+                .saturating_duration_since(request.started_at()),
+        );
+        let time = wait + millis(response.started_at().elapsed());
+
+        let (method, path) = match request.start_line() {
+            StartLine::Request { method, path } => (method.clone(), path.clone()),
+            StartLine::Response { .. } => (String::new(), String::new()),
+        };
+
+        let (status, status_text) = match response.start_line() {
+            StartLine::Response { status, reason } => (*status, reason.clone()),
+            StartLine::Request { .. } => (0, String::new()),
+        };
+
+        let host = request.header("host").unwrap_or(target);
+        let post_data = match request.body_size() {
+            0 => None,
+            _ => Some(HarContent::new(request)),
+        };
+
+        Self {
+            id: String::from(id),
+            tunnel: String::from(tunnel),
+            target: String::from(target),
+            started_date_time,
+            time,
+            request: HarRequest {
+                method,
+                url: format!("http://{}{}", host, path),
+                http_version: request.http_version(),
+                headers: headers(request),
+                query_string: query_string(&path),
+                post_data,
+                headers_size: request.head_size() as i64,
+                body_size: request.body_size() as i64,
+            },
+            response: HarResponse {
+                status,
+                status_text,
+                http_version: response.http_version(),
+                headers: headers(response),
+                content: HarContent::new(response),
+                redirect_url: response.header("location").unwrap_or_default().to_owned(),
+                headers_size: response.head_size() as i64,
+                body_size: response.body_size() as i64,
+            },
+            timings: HarTimings {
+                send: 0.0,
+                wait,
+                receive: time - wait,
+            },
+            connection: connection_id.to_string(),
+        }
+    }
+}
+
+impl HarContent {
+    fn new(message: &HttpMessage) -> Self {
+        let (text, encoding) = match std::str::from_utf8(message.body()) {
+            Ok(text) => (String::from(text), None),
+            Err(_) => (
+                STANDARD.encode(message.body()),
+                Some(String::from("base64")),
+            ),
+        };
+
+        Self {
+            size: message.body_size() as i64,
+            mime_type: message
+                .header("content-type")
+                .unwrap_or_default()
+                .to_owned(),
+            text,
+            encoding,
+        }
+    }
+
+    /// decoded bytes of the body.
+    pub fn bytes(&self) -> Result<Vec<u8>> {
+        match self.encoding.as_deref() {
+            Some("base64") => Ok(STANDARD.decode(&self.text)?),
+            _ => Ok(self.text.clone().into_bytes()),
+        }
+    }
+}
+
+fn headers(message: &HttpMessage) -> Vec<HarHeader> {
+    message
+        .headers()
+        .iter()
+        .map(|(name, value)| HarHeader {
+            name: name.clone(),
+            value: value.clone(),
+        })
+        .collect()
+}
+
+fn query_string(path: &str) -> Vec<HarHeader> {
+    let query = match path.split_once('?') {
+        Some((_, query)) => query,
+        None => return Vec::new(),
+    };
+
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            HarHeader {
+                name: String::from(name),
+                value: String::from(value),
+            }
+        })
+        .collect()
+}
+
+fn millis(duration: std::time::Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// appends captured requests to a HAR file, the whole file is written again for every
+/// entry so it is always valid json.
+#[derive(Debug)]
+pub struct HarWriter {
+    path: PathBuf,
+    har: Mutex<Har>,
+}
+
+impl HarWriter {
+    /// opens the capture file, keeping the entries already in it.
+    pub fn open(path: &Path) -> Result<Self> {
+        let har = match path.exists() {
+            true => Har::load(path)?,
+            false => Har::default(),
+        };
+
+        Ok(Self {
+            path: path.to_owned(),
+            har: Mutex::new(har),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, entry: HarEntry) -> Result<()> {
+        let mut har = self.har.lock().unwrap();
+        har.log.entries.push(entry);
+
+        let content = serde_json::to_vec_pretty(&*har)?;
+        std::fs::write(&self.path, content)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::{Har, HarEntry, HarWriter};
+    use crate::inspector::{Direction, MessageParser};
+
+    #[test]
+    fn should_append_entries_to_capture_file() {
+        // Arrange
+        let path = std::env::temp_dir().join(format!("{}.har", Uuid::new_v4()));
+        let mut requests = MessageParser::new(Direction::Request, true);
+        let mut responses = MessageParser::new(Direction::Response, true);
+        responses.expect_response("POST");
+        let request = requests
+            .push(b"POST /hooks?event=push HTTP/1.1\r\nContent-Length: 2\r\n\r\n{}")
+            .unwrap()
+            .remove(0);
+        let response = responses
+            .push(b"HTTP/1.1 204 No Content\r\n\r\n")
+            .unwrap()
+            .remove(0);
+
+        // Act
+        let writer = HarWriter::open(&path).unwrap();
+        writer
+            .append(HarEntry::new(
+                "a1",
+                "api",
+                &7,
+                "127.0.0.1:3000",
+                &request,
+                &response,
+            ))
+            .unwrap();
+        drop(writer);
+        HarWriter::open(&path)
+            .unwrap()
+            .append(HarEntry::new(
+                "b2",
+                "api",
+                &8,
+                "127.0.0.1:3000",
+                &request,
+                &response,
+            ))
+            .unwrap();
+
+        // Assert
+        let har = Har::load(&path).unwrap();
+        let entry = har.entry("a1").unwrap();
+        assert_eq!(har.log.entries.len(), 2);
+        assert_eq!(entry.request.url, "http://127.0.0.1:3000/hooks?event=push");
+        assert_eq!(entry.request.query_string[0].value, "push");
+        assert_eq!(
+            entry.request.post_data.as_ref().unwrap().bytes().unwrap(),
+            b"{}"
+        );
+        assert_eq!(entry.response.status, 204);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
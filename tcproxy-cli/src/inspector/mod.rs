@@ -0,0 +1,261 @@
+mod har;
+mod parser;
+mod replay;
+
+pub use har::*;
+pub use parser::*;
+pub use replay::*;
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::dashboard::{format_bytes, LogBuffer};
+use crate::server_addr::ServerAddr;
+use crate::{ActiveTunnel, ConnectionObserver};
+
+/// follows the http exchanges of a connection on an http tunnel, logging every request
+/// with its response and appending them to the tunnel capture file when it has one.
+#[derive(Debug)]
+pub struct HttpInspector {
+    connection_id: u32,
+    tunnel: String,
+    target: String,
+    requests: MessageParser,
+    responses: MessageParser,
+    pending: VecDeque<HttpMessage>,
+    log: LogBuffer,
+    har: Option<Arc<HarWriter>>,
+}
+
+impl HttpInspector {
+    pub fn new(connection_id: &u32, tunnel: &ActiveTunnel, log: &LogBuffer) -> Self {
+        let capture_bodies = tunnel.har().is_some();
+        Self {
+            connection_id: *connection_id,
+            tunnel: String::from(tunnel.name()),
+            target: String::new(),
+            requests: MessageParser::new(Direction::Request, capture_bodies),
+            responses: MessageParser::new(Direction::Response, capture_bodies),
+            pending: VecDeque::new(),
+            log: log.clone(),
+            har: tunnel.har(),
+        }
+    }
+
+    fn complete(&mut self, response: HttpMessage) {
+        // interim responses (100 Continue) don't answer the request.
+        if let StartLine::Response {
+            status: 100..=199, ..
+        } = response.start_line()
+        {
+            if !self.responses.is_passthrough() {
+                return;
+            }
+        }
+
+        let request = match self.pending.pop_front() {
+            Some(request) => request,
+            None => return self.stop("response without request"),
+        };
+
+        let capture_id = self.har.as_ref().and_then(|har| {
+            let id = Uuid::new_v4().simple().to_string()[..8].to_owned();
+            let entry = HarEntry::new(
+                &id,
+                &self.tunnel,
+                &self.connection_id,
+                &self.target,
+                &request,
+                &response,
+            );
+
+            match har.append(entry) {
+                Ok(_) => Some(id),
+                Err(err) => {
+                    self.log.push(&format!(
+                        "failed to write capture to {}: {}",
+                        har.path().display(),
+                        err
+                    ));
+                    None
+                }
+            }
+        });
+
+        self.log.push(&format_exchange(
+            &self.tunnel,
+            &request,
+            &response,
+            capture_id,
+        ));
+    }
+
+    /// the stream isn't http anymore, the rest of the connection isn't inspected.
+    fn stop(&mut self, reason: &str) {
+        debug!(
+            "connection {} is no longer inspected: {}",
+            self.connection_id, reason
+        );
+
+        self.requests.passthrough();
+        self.responses.passthrough();
+        self.pending.clear();
+    }
+}
+
+impl ConnectionObserver for HttpInspector {
+    fn connected(&mut self, target: &ServerAddr) {
+        self.target = target.to_string();
+    }
+
+    fn request_data(&mut self, data: &[u8]) {
+        match self.requests.push(data) {
+            Ok(requests) => {
+                for request in requests {
+                    if let StartLine::Request { method, .. } = request.start_line() {
+                        self.responses.expect_response(method);
+                    }
+                    self.pending.push_back(request);
+                }
+            }
+            Err(err) => self.stop(&err.to_string()),
+        }
+    }
+
+    fn response_data(&mut self, data: &[u8]) {
+        match self.responses.push(data) {
+            Ok(responses) => {
+                for response in responses {
+                    self.complete(response);
+                }
+            }
+            Err(err) => self.stop(&err.to_string()),
+        }
+
+        if self.responses.is_passthrough() {
+            self.requests.passthrough();
+        }
+    }
+
+    /// completes a response that lasted until the end of the stream.
+    fn finish(&mut self) {
+        if let Some(response) = self.responses.finish() {
+            self.complete(response);
+        }
+    }
+}
+
+/// `api POST /hooks -> 200 OK in 12ms (req 1.2 KiB, resp 340 B) [capture 1f2e3d4c]`
+fn format_exchange(
+    tunnel: &str,
+    request: &HttpMessage,
+    response: &HttpMessage,
+    capture_id: Option<String>,
+) -> String {
+    let request_line = match request.start_line() {
+        StartLine::Request { method, path } => format!("{} {}", method, path),
+        StartLine::Response { .. } => String::new(),
+    };
+
+    let status_line = match response.start_line() {
+        StartLine::Response { status, reason } => format!("{} {}", status, reason),
+        StartLine::Request { .. } => String::new(),
+    };
+
+    let mut line = format!(
+        "{} {} -> {} in {}ms (req {}, resp {})",
+        tunnel,
+        request_line,
+        status_line.trim_end(),
+        request.started_at().elapsed().as_millis(),
+        format_bytes(&(request.body_size() as u64)),
+        format_bytes(&(response.body_size() as u64)),
+    );
+
+    if let Some(id) = capture_id {
+        line.push_str(&format!(" [capture {}]", id));
+    }
+
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    use super::{Har, HarWriter, HttpInspector};
+    use crate::config::TunnelDefinition;
+    use crate::server_addr::ServerAddr;
+    use crate::ConnectionObserver;
+    use crate::{ActiveTunnel, LogBuffer};
+
+    fn create_tunnel(har: Option<Arc<HarWriter>>) -> ActiveTunnel {
+        let targets = [
+            ServerAddr::new("127.0.0.1", &3000).unwrap(),
+            ServerAddr::new("127.0.0.1", &3001).unwrap(),
+        ];
+        let definition = TunnelDefinition::new("api", &targets[0]);
+        ActiveTunnel::new(&definition, &targets, &15080, None).with_har(har)
+    }
+
+    #[test]
+    fn should_log_every_exchange() {
+        // Arrange
+        let log = LogBuffer::default();
+        let mut inspector = HttpInspector::new(&7, &create_tunnel(None), &log);
+
+        // Act
+        inspector.request_data(b"GET /health HTTP/1.1\r\n\r\nPOST /hooks HTTP/1.1\r\n");
+        inspector.request_data(b"Content-Length: 3\r\n\r\nabc");
+        inspector.response_data(
+            b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok",
+        );
+        inspector.response_data(b"HTTP/1.1 500 Internal Server Error\r\n\r\nfailed");
+        inspector.finish();
+
+        // Assert
+        let lines = log.lines();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("api GET /health -> 200 OK in"));
+        assert!(lines[0].ends_with("(req 0 B, resp 2 B)"));
+        assert!(lines[1].contains("api POST /hooks -> 500 Internal Server Error in"));
+        assert!(lines[1].ends_with("(req 3 B, resp 6 B)"));
+    }
+
+    #[test]
+    fn should_capture_exchanges() {
+        // Arrange
+        let path = std::env::temp_dir().join(format!("{}.har", Uuid::new_v4()));
+        let har = Arc::new(HarWriter::open(&path).unwrap());
+        let log = LogBuffer::default();
+        let mut inspector = HttpInspector::new(&7, &create_tunnel(Some(har)), &log);
+
+        // Act
+        inspector.connected(&ServerAddr::new("127.0.0.1", &3001).unwrap());
+        inspector.request_data(b"PUT /items/1 HTTP/1.1\r\nHost: example.com\r\nContent-Length: 4\r\n\r\n\xff\xfe\x00\x01");
+        inspector.response_data(b"HTTP/1.1 201 Created\r\nContent-Length: 0\r\n\r\n");
+
+        // Assert
+        let har = Har::load(&path).unwrap();
+        let entry = &har.log.entries[0];
+        assert!(log.lines()[0].ends_with(&format!("[capture {}]", entry.id)));
+        assert_eq!(entry.target, "127.0.0.1:3001");
+        assert_eq!(entry.request.url, "http://example.com/items/1");
+        assert_eq!(
+            entry
+                .request
+                .post_data
+                .as_ref()
+                .unwrap()
+                .encoding
+                .as_deref(),
+            Some("base64")
+        );
+        assert_eq!(entry.response.status, 201);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
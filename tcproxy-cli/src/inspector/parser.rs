@@ -0,0 +1,462 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+use tcproxy_core::Result;
+
+/// headers parsed from a single http message.
+const MAX_HEADERS: usize = 64;
+
+/// bodies bigger than this are counted but only kept up to the limit.
+pub const MAX_BODY_CAPTURE: usize = 1024 * 1024;
+
+/// request line or status line of a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StartLine {
+    Request { method: String, path: String },
+    Response { status: u16, reason: String },
+}
+
+/// http message read from one direction of a connection.
+#[derive(Debug, Clone)]
+pub struct HttpMessage {
+    start_line: StartLine,
+    version: u8,
+    headers: Vec<(String, String)>,
+    head_size: usize,
+    body: Vec<u8>,
+    body_size: usize,
+    started_at: Instant,
+}
+
+impl HttpMessage {
+    pub fn start_line(&self) -> &StartLine {
+        &self.start_line
+    }
+
+    /// `HTTP/1.x` of the message.
+    pub fn http_version(&self) -> String {
+        format!("HTTP/1.{}", self.version)
+    }
+
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// value of the first header named `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn head_size(&self) -> usize {
+        self.head_size
+    }
+
+    /// decoded body, empty when bodies aren't captured and cut at `MAX_BODY_CAPTURE`.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// size of the decoded body, even when it wasn't captured.
+    pub fn body_size(&self) -> usize {
+        self.body_size
+    }
+
+    /// when the first byte of the message was seen.
+    pub fn started_at(&self) -> Instant {
+        self.started_at
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Request,
+    Response,
+}
+
+#[derive(Debug)]
+enum BodyState {
+    Length(usize),
+    ChunkSize,
+    ChunkData(usize),
+    ChunkEnd,
+    Trailers,
+    UntilClose,
+}
+
+/// incremental http/1.x parser for one direction of a connection, bytes are fed as they
+/// flow through the tunnel and complete messages are returned.
+#[derive(Debug)]
+pub struct MessageParser {
+    direction: Direction,
+    capture_bodies: bool,
+    buffer: Vec<u8>,
+    current: Option<(HttpMessage, BodyState)>,
+    started_at: Option<Instant>,
+
+    /// for responses, whether the request they answer can't have a body (HEAD).
+    bodyless: VecDeque<bool>,
+
+    /// set once the stream stops being http (upgrades, CONNECT or garbage).
+    passthrough: bool,
+}
+
+impl MessageParser {
+    pub fn new(direction: Direction, capture_bodies: bool) -> Self {
+        Self {
+            direction,
+            capture_bodies,
+            buffer: Vec::new(),
+            current: None,
+            started_at: None,
+            bodyless: VecDeque::new(),
+            passthrough: false,
+        }
+    }
+
+    /// tells a response parser the method of the next request, responses to HEAD
+    /// requests have no body whatever their headers say.
+    pub fn expect_response(&mut self, method: &str) {
+        self.bodyless.push_back(method.eq_ignore_ascii_case("HEAD"));
+    }
+
+    /// stops parsing, every byte after this point is ignored.
+    pub fn passthrough(&mut self) {
+        self.passthrough = true;
+        self.buffer.clear();
+        self.current = None;
+    }
+
+    pub fn is_passthrough(&self) -> bool {
+        self.passthrough
+    }
+
+    pub fn push(&mut self, data: &[u8]) -> Result<Vec<HttpMessage>> {
+        let mut messages = Vec::new();
+        if self.passthrough {
+            return Ok(messages);
+        }
+
+        if self.current.is_none() && self.buffer.is_empty() && !data.is_empty() {
+            self.started_at = Some(Instant::now());
+        }
+
+        self.buffer.extend_from_slice(data);
+        loop {
+            let progressed = match self.current.take() {
+                None => self.read_head()?,
+                Some((message, state)) => self.read_body(message, state, &mut messages)?,
+            };
+
+            if !progressed {
+                return Ok(messages);
+            }
+        }
+    }
+
+    /// completes a message whose body lasts until the connection is closed.
+    pub fn finish(&mut self) -> Option<HttpMessage> {
+        match self.current.take() {
+            Some((message, BodyState::UntilClose)) => Some(message),
+            _ => None,
+        }
+    }
+
+    fn read_head(&mut self) -> Result<bool> {
+        if self.buffer.is_empty() {
+            return Ok(false);
+        }
+
+        let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+        let (status, start_line, version) = match self.direction {
+            Direction::Request => {
+                let mut request = httparse::Request::new(&mut headers);
+                let status = request.parse(&self.buffer)?;
+                let start_line = StartLine::Request {
+                    method: request.method.unwrap_or_default().to_owned(),
+                    path: request.path.unwrap_or_default().to_owned(),
+                };
+
+                (status, start_line, request.version.unwrap_or_default())
+            }
+            Direction::Response => {
+                let mut response = httparse::Response::new(&mut headers);
+                let status = response.parse(&self.buffer)?;
+                let start_line = StartLine::Response {
+                    status: response.code.unwrap_or_default(),
+                    reason: response.reason.unwrap_or_default().to_owned(),
+                };
+
+                (status, start_line, response.version.unwrap_or_default())
+            }
+        };
+
+        let head_size = match status {
+            httparse::Status::Complete(size) => size,
+            httparse::Status::Partial => return Ok(false),
+        };
+
+        let headers: Vec<(String, String)> = headers
+            .iter()
+            .take_while(|header| !header.name.is_empty())
+            .map(|header| {
+                (
+                    header.name.to_owned(),
+                    String::from_utf8_lossy(header.value).into_owned(),
+                )
+            })
+            .collect();
+
+        self.buffer.drain(..head_size);
+        let message = HttpMessage {
+            start_line,
+            version,
+            headers,
+            head_size,
+            body: Vec::new(),
+            body_size: 0,
+            started_at: self.started_at.take().unwrap_or_else(Instant::now),
+        };
+
+        let state = self.body_state(&message)?;
+        self.current = Some((message, state));
+        Ok(true)
+    }
+
+    fn body_state(&mut self, message: &HttpMessage) -> Result<BodyState> {
+        let bodyless = match &message.start_line {
+            StartLine::Request { method, .. } => {
+                if method.eq_ignore_ascii_case("CONNECT") {
+                    self.passthrough = true;
+                }
+                false
+            }
+            StartLine::Response { status, .. } => {
+                if *status == 101 {
+                    self.passthrough = true;
+                }
+
+                // interim responses don't answer the request yet.
+                let head_request = match status {
+                    100..=199 => false,
+                    _ => self.bodyless.pop_front().unwrap_or(false),
+                };
+                head_request || matches!(status, 100..=199 | 204 | 304)
+            }
+        };
+
+        if bodyless {
+            return Ok(BodyState::Length(0));
+        }
+
+        let chunked = message
+            .header("transfer-encoding")
+            .map(|value| value.to_ascii_lowercase().contains("chunked"))
+            .unwrap_or(false);
+        if chunked {
+            return Ok(BodyState::ChunkSize);
+        }
+
+        match message.header("content-length") {
+            Some(length) => match length.trim().parse::<usize>() {
+                Ok(length) => Ok(BodyState::Length(length)),
+                Err(_) => Err(format!("invalid content-length {}", length).into()),
+            },
+            None if self.direction == Direction::Response => Ok(BodyState::UntilClose),
+            None => Ok(BodyState::Length(0)),
+        }
+    }
+
+    /// reads as much of the body as is buffered, returns whether parsing can continue.
+    fn read_body(
+        &mut self,
+        mut message: HttpMessage,
+        state: BodyState,
+        messages: &mut Vec<HttpMessage>,
+    ) -> Result<bool> {
+        let state = match state {
+            BodyState::Length(remaining) => {
+                let read = remaining.min(self.buffer.len());
+                self.take_body(&mut message, read);
+                match remaining - read {
+                    0 => None,
+                    remaining => Some(BodyState::Length(remaining)),
+                }
+            }
+            BodyState::UntilClose => {
+                self.take_body(&mut message, self.buffer.len());
+                Some(BodyState::UntilClose)
+            }
+            BodyState::ChunkSize => match httparse::parse_chunk_size(&self.buffer) {
+                Ok(httparse::Status::Complete((used, 0))) => {
+                    self.buffer.drain(..used);
+                    Some(BodyState::Trailers)
+                }
+                Ok(httparse::Status::Complete((used, size))) => {
+                    self.buffer.drain(..used);
+                    Some(BodyState::ChunkData(size as usize))
+                }
+                Ok(httparse::Status::Partial) => {
+                    self.current = Some((message, BodyState::ChunkSize));
+                    return Ok(false);
+                }
+                Err(_) => return Err("invalid chunk size".into()),
+            },
+            BodyState::ChunkData(remaining) => {
+                let read = remaining.min(self.buffer.len());
+                self.take_body(&mut message, read);
+                match remaining - read {
+                    0 => Some(BodyState::ChunkEnd),
+                    remaining => Some(BodyState::ChunkData(remaining)),
+                }
+            }
+            BodyState::ChunkEnd => match self.buffer.len() {
+                0 | 1 => {
+                    self.current = Some((message, BodyState::ChunkEnd));
+                    return Ok(false);
+                }
+                _ => {
+                    self.buffer.drain(..2);
+                    Some(BodyState::ChunkSize)
+                }
+            },
+            BodyState::Trailers => match self.buffer.windows(2).position(|w| w == b"\r\n") {
+                Some(0) => {
+                    self.buffer.drain(..2);
+                    None
+                }
+                Some(end) => {
+                    self.buffer.drain(..end + 2);
+                    Some(BodyState::Trailers)
+                }
+                None => {
+                    self.current = Some((message, BodyState::Trailers));
+                    return Ok(false);
+                }
+            },
+        };
+
+        let waiting = self.buffer.is_empty();
+        match state {
+            Some(state) if waiting => {
+                self.current = Some((message, state));
+                Ok(false)
+            }
+            Some(state) => {
+                self.current = Some((message, state));
+                Ok(true)
+            }
+            None => {
+                messages.push(message);
+                if self.passthrough {
+                    self.buffer.clear();
+                    return Ok(false);
+                }
+
+                if !waiting {
+                    self.started_at = Some(Instant::now());
+                }
+                Ok(!waiting)
+            }
+        }
+    }
+
+    fn take_body(&mut self, message: &mut HttpMessage, len: usize) {
+        message.body_size += len;
+        if self.capture_bodies {
+            let room = MAX_BODY_CAPTURE.saturating_sub(message.body.len());
+            message
+                .body
+                .extend_from_slice(&self.buffer[..len.min(room)]);
+        }
+
+        self.buffer.drain(..len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Direction, MessageParser, StartLine};
+
+    #[test]
+    fn should_read_requests_split_across_packets() {
+        // Arrange
+        let mut parser = MessageParser::new(Direction::Request, true);
+
+        // Act
+        let first = parser
+            .push(b"POST /hooks HTTP/1.1\r\nHost: example.com\r\nContent-")
+            .unwrap();
+        let second = parser
+            .push(b"Length: 5\r\n\r\nhelloGET / HTTP/1.1\r\n\r\n")
+            .unwrap();
+
+        // Assert
+        assert!(first.is_empty());
+        assert_eq!(second.len(), 2);
+        assert_eq!(
+            second[0].start_line(),
+            &StartLine::Request {
+                method: String::from("POST"),
+                path: String::from("/hooks")
+            }
+        );
+        assert_eq!(second[0].header("host"), Some("example.com"));
+        assert_eq!(second[0].body(), b"hello");
+        assert_eq!(second[1].body_size(), 0);
+    }
+
+    #[test]
+    fn should_decode_chunked_responses() {
+        // Arrange
+        let mut parser = MessageParser::new(Direction::Response, true);
+        parser.expect_response("GET");
+
+        // Act
+        let messages = parser
+            .push(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nWiki\r\n5\r\npedia\r\n0\r\n\r\n")
+            .unwrap();
+
+        // Assert
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].body(), b"Wikipedia");
+    }
+
+    #[test]
+    fn should_read_response_body_until_close() {
+        // Arrange
+        let mut parser = MessageParser::new(Direction::Response, false);
+        parser.expect_response("HEAD");
+        parser.expect_response("GET");
+
+        // Act
+        let head = parser
+            .push(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\n")
+            .unwrap();
+        let body = parser.push(b"HTTP/1.0 200 OK\r\n\r\nbody").unwrap();
+        let finished = parser.finish().unwrap();
+
+        // Assert
+        assert_eq!(head.len(), 1);
+        assert!(body.is_empty());
+        assert_eq!(finished.body_size(), 4);
+        assert!(finished.body().is_empty());
+    }
+
+    #[test]
+    fn should_stop_parsing_after_upgrade() {
+        // Arrange
+        let mut parser = MessageParser::new(Direction::Response, false);
+        parser.expect_response("GET");
+
+        // Act
+        let messages = parser
+            .push(b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\r\n\x81\x05hello")
+            .unwrap();
+
+        // Assert
+        assert_eq!(messages.len(), 1);
+        assert!(parser.is_passthrough());
+    }
+}
@@ -0,0 +1,217 @@
+use tcproxy_core::Result;
+
+use super::HarEntry;
+
+/// headers that describe how the original message was framed, they are set again
+/// from the body that is actually sent.
+const FRAMING_HEADERS: [&str; 3] = ["content-length", "transfer-encoding", "connection"];
+
+/// http request rebuilt from a capture, to be edited and sent again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayRequest {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl ReplayRequest {
+    pub fn from_entry(entry: &HarEntry) -> Result<Self> {
+        let body = match &entry.request.post_data {
+            Some(content) => content.bytes()?,
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            method: entry.request.method.clone(),
+            path: path_of(&entry.request.url),
+            headers: entry
+                .request
+                .headers
+                .iter()
+                .map(|header| (header.name.clone(), header.value.clone()))
+                .collect(),
+            body,
+        })
+    }
+
+    /// reads a request written as text, the body is everything after the head and
+    /// its length is computed again.
+    pub fn from_raw(raw: &[u8]) -> Result<Self> {
+        let mut headers = [httparse::EMPTY_HEADER; 64];
+        let mut request = httparse::Request::new(&mut headers);
+        let head_size = match request.parse(raw)? {
+            httparse::Status::Complete(size) => size,
+            httparse::Status::Partial => return Err("request head is incomplete".into()),
+        };
+
+        Ok(Self {
+            method: request.method.unwrap_or_default().to_owned(),
+            path: request.path.unwrap_or_default().to_owned(),
+            headers: request
+                .headers
+                .iter()
+                .map(|header| {
+                    (
+                        header.name.to_owned(),
+                        String::from_utf8_lossy(header.value).into_owned(),
+                    )
+                })
+                .collect(),
+            body: raw[head_size..].to_vec(),
+        })
+    }
+
+    pub fn with_method(mut self, method: &str) -> Self {
+        self.method = method.to_uppercase();
+        self
+    }
+
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.path = String::from(path);
+        self
+    }
+
+    /// replaces every header named `name`, ignoring case.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers
+            .retain(|(key, _)| !key.eq_ignore_ascii_case(name));
+        self.headers.push((String::from(name), String::from(value)));
+        self
+    }
+
+    pub fn with_body(mut self, body: &[u8]) -> Self {
+        self.body = body.to_vec();
+        self
+    }
+
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// the request as text, without the framing headers, used for editing.
+    pub fn to_raw(&self) -> Vec<u8> {
+        let mut raw = self.head();
+        raw.extend_from_slice(b"\r\n");
+        raw.extend_from_slice(&self.body);
+        raw
+    }
+
+    /// the request as sent to the target, with the length of the body and asking the
+    /// target to close the connection after answering.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.head();
+        if !self.body.is_empty() || !matches!(self.method.as_str(), "GET" | "HEAD" | "DELETE") {
+            bytes.extend_from_slice(format!("Content-Length: {}\r\n", self.body.len()).as_bytes());
+        }
+
+        bytes.extend_from_slice(b"Connection: close\r\n\r\n");
+        bytes.extend_from_slice(&self.body);
+        bytes
+    }
+
+    /// request line and headers, without the framing ones and the blank line.
+    fn head(&self) -> Vec<u8> {
+        let mut head = format!("{} {} HTTP/1.1\r\n", self.method, self.path).into_bytes();
+        for (name, value) in self.headers.iter().filter(|(name, _)| !is_framing(name)) {
+            head.extend_from_slice(format!("{}: {}\r\n", name, value).as_bytes());
+        }
+
+        head
+    }
+}
+
+fn is_framing(name: &str) -> bool {
+    FRAMING_HEADERS
+        .iter()
+        .any(|header| header.eq_ignore_ascii_case(name))
+}
+
+/// path and query of a captured url, `http://example.com/hooks?a=1` -> `/hooks?a=1`.
+fn path_of(url: &str) -> String {
+    let without_scheme = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    match without_scheme.find('/') {
+        Some(start) => String::from(&without_scheme[start..]),
+        None => String::from("/"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReplayRequest;
+    use crate::inspector::{Direction, HarEntry, MessageParser};
+
+    fn create_entry() -> HarEntry {
+        let mut requests = MessageParser::new(Direction::Request, true);
+        let mut responses = MessageParser::new(Direction::Response, true);
+        responses.expect_response("POST");
+        let request = requests
+            .push(b"POST /hooks?event=push HTTP/1.1\r\nHost: example.com\r\nTransfer-Encoding: chunked\r\n\r\n2\r\n{}\r\n0\r\n\r\n")
+            .unwrap()
+            .remove(0);
+        let response = responses
+            .push(b"HTTP/1.1 204 No Content\r\n\r\n")
+            .unwrap()
+            .remove(0);
+
+        HarEntry::new("a1", "api", &7, "127.0.0.1:3000", &request, &response)
+    }
+
+    #[test]
+    fn should_rebuild_captured_request() {
+        // Arrange
+        let entry = create_entry();
+
+        // Act
+        let request = ReplayRequest::from_entry(&entry).unwrap();
+
+        // Assert
+        assert_eq!(
+            request.to_bytes(),
+            b"POST /hooks?event=push HTTP/1.1\r\nHost: example.com\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}"
+        );
+    }
+
+    #[test]
+    fn should_apply_edits() {
+        // Arrange
+        let request = ReplayRequest::from_entry(&create_entry()).unwrap();
+
+        // Act
+        let result = request
+            .with_method("put")
+            .with_path("/hooks/retry")
+            .with_header("host", "localhost")
+            .with_body(b"{\"retry\":true}");
+
+        // Assert
+        assert_eq!(
+            String::from_utf8(result.to_bytes()).unwrap(),
+            "PUT /hooks/retry HTTP/1.1\r\nhost: localhost\r\nContent-Length: 14\r\nConnection: close\r\n\r\n{\"retry\":true}"
+        );
+    }
+
+    #[test]
+    fn should_read_edited_text() {
+        // Arrange
+        let request = ReplayRequest::from_entry(&create_entry()).unwrap();
+        let mut raw = request.to_raw();
+        raw.extend_from_slice(b" ");
+
+        // Act
+        let result = ReplayRequest::from_raw(&raw).unwrap();
+
+        // Assert
+        assert_eq!(result.method(), "POST");
+        assert_eq!(result.path(), "/hooks?event=push");
+        assert_eq!(result.body(), b"{} ");
+    }
+}
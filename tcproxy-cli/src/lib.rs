@@ -1,23 +1,33 @@
 mod app;
 mod args;
 mod client_state;
-mod console_updater;
+mod dashboard;
 mod frame_reader;
 mod frame_writer;
+mod inspector;
 mod local_connection;
+mod pcap;
 mod ping_sender;
+mod proxy_protocol;
 mod server_addr;
 mod shutdown;
+mod target_pool;
 
 pub mod commands;
 pub mod config;
+#[cfg(unix)]
+pub mod daemon;
 
 pub use app::*;
 pub use args::*;
 pub use client_state::*;
-pub use console_updater::*;
+pub use dashboard::*;
 pub use frame_reader::*;
 pub use frame_writer::*;
+pub use inspector::*;
 pub use local_connection::*;
+pub use pcap::*;
 pub use ping_sender::*;
+pub use proxy_protocol::*;
 pub use shutdown::*;
+pub use target_pool::*;
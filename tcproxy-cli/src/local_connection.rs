@@ -1,6 +1,7 @@
 use bytes::BytesMut;
-use std::net::SocketAddrV4;
+use std::sync::{Arc, Mutex};
 use tcproxy_core::framing::{DataPacket, Error, Reason};
+use tcproxy_core::tcp::connect_happy_eyeballs;
 use tcproxy_core::Result;
 use tcproxy_core::TcpFrame;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
@@ -11,44 +12,111 @@ use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 use tracing::debug;
 
+use crate::server_addr::ServerAddr;
+use crate::{ClientState, TargetLease, TargetPool};
+
+/// sees the bytes of a connection as they are forwarded between the remote peer
+/// and the target.
+pub trait ConnectionObserver: Send {
+    /// the connection to `target`, picked from the tunnel targets, was established
+    /// before any data is forwarded.
+    fn connected(&mut self, _target: &ServerAddr) {}
+
+    /// bytes sent by the remote peer to the target.
+    fn request_data(&mut self, data: &[u8]);
+
+    /// bytes sent back by the target.
+    fn response_data(&mut self, data: &[u8]);
+
+    /// the connection closed.
+    fn finish(&mut self);
+}
+
+type Observers = Arc<Mutex<Vec<Box<dyn ConnectionObserver>>>>;
+
 pub struct LocalConnection {
     connection_id: u32,
-    target_ip: SocketAddrV4,
+    targets: Arc<TargetPool>,
+    lease: Option<TargetLease>,
     sender: Sender<TcpFrame>,
+    state: Arc<ClientState>,
+    proxy_header: Option<Vec<u8>>,
+    observers: Observers,
 }
 
 impl LocalConnection {
-    pub fn new(connection_id: u32, sender: &Sender<TcpFrame>, target_ip: SocketAddrV4) -> Self {
+    pub fn new(
+        connection_id: u32,
+        sender: &Sender<TcpFrame>,
+        targets: &Arc<TargetPool>,
+        state: &Arc<ClientState>,
+    ) -> Self {
         Self {
-            target_ip,
+            targets: targets.clone(),
+            lease: None,
             connection_id,
             sender: sender.clone(),
+            state: state.clone(),
+            proxy_header: None,
+            observers: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
-    async fn connect(&self) -> Result<TcpStream> {
-        match TcpStream::connect(self.target_ip).await {
-            Ok(stream) => Ok(stream),
-            Err(err) => {
-                debug!(
-                    "Error when connecting to {}: {}. Aborting connection..",
-                    "192.168.0.221:22", err
-                );
+    /// PROXY protocol header written to the target before any data.
+    pub fn with_proxy_header(mut self, header: Option<Vec<u8>>) -> Self {
+        self.proxy_header = header;
+        self
+    }
 
-                let error_data = self.connection_id.to_be_bytes();
-                let error_frame =
-                    TcpFrame::Error(Error::new(&Reason::ClientUnableToConnect, &error_data));
+    pub fn with_observer(self, observer: Option<impl ConnectionObserver + 'static>) -> Self {
+        if let Some(observer) = observer {
+            self.observers.lock().unwrap().push(Box::new(observer));
+        }
 
-                let _ = self.sender.send(error_frame).await;
+        self
+    }
 
-                Err(err.into())
+    /// tries the targets in the order picked by the pool, skipping the ones that don't
+    /// accept the connection. targets are resolved again for every connection, so dns
+    /// changes are picked up.
+    async fn connect(&mut self) -> Result<TcpStream> {
+        for index in self.targets.candidates() {
+            let target = self.targets.get(index);
+            let result = match target.resolve().await {
+                Ok(addrs) => connect_happy_eyeballs(&addrs).await,
+                Err(err) => Err(err.into()),
+            };
+
+            match result {
+                Ok(stream) => {
+                    self.targets.mark(index, true, self.state.log());
+                    self.lease = Some(self.targets.acquire(index));
+                    for observer in self.observers.lock().unwrap().iter_mut() {
+                        observer.connected(target);
+                    }
+
+                    return Ok(stream);
+                }
+                Err(err) => {
+                    debug!("Error when connecting to {}: {}", target, err);
+                    self.targets.mark(index, false, self.state.log());
+                }
             }
         }
+
+        debug!("No target accepted the connection. Aborting connection..");
+        let error_data = self.connection_id.to_be_bytes();
+        let error_frame = TcpFrame::Error(Error::new(&Reason::ClientUnableToConnect, &error_data));
+        let _ = self.sender.send(error_frame).await;
+
+        Err("unable to connect to any target".into())
     }
 
     fn read_from_socket(
         mut reader: OwnedReadHalf,
         sender: Sender<TcpFrame>,
+        state: Arc<ClientState>,
+        observers: Observers,
         connection_id: u32,
     ) -> JoinHandle<Result<()>> {
         tokio::spawn(async move {
@@ -60,6 +128,11 @@ impl LocalConnection {
                     return Ok(());
                 }
 
+                state.record_bytes_out(&connection_id, bytes_read);
+                for observer in observers.lock().unwrap().iter_mut() {
+                    observer.response_data(&buffer[..bytes_read]);
+                }
+
                 let tcp_frame = TcpFrame::DataPacket(DataPacket::new(
                     &connection_id,
                     &buffer.split_to(bytes_read),
@@ -73,6 +146,7 @@ impl LocalConnection {
     fn write_to_socket(
         mut writer: OwnedWriteHalf,
         mut reader: Receiver<BytesMut>,
+        observers: Observers,
     ) -> JoinHandle<Result<()>> {
         tokio::spawn(async move {
             loop {
@@ -82,6 +156,10 @@ impl LocalConnection {
                 }
 
                 let mut msg = result.unwrap();
+                for observer in observers.lock().unwrap().iter_mut() {
+                    observer.request_data(&msg);
+                }
+
                 let bytes_written = writer.write_buf(&mut msg).await?;
                 writer.flush().await?;
 
@@ -98,15 +176,21 @@ impl LocalConnection {
         reader: Receiver<BytesMut>,
         cancellation_token: CancellationToken,
     ) -> Result<()> {
-        let connection = self.connect().await?;
+        let mut connection = self.connect().await?;
+        if let Some(header) = &self.proxy_header {
+            connection.write_all(header).await?;
+        }
+
         let (stream_reader, stream_writer) = connection.into_split();
         let task1 = LocalConnection::read_from_socket(
             stream_reader,
             self.sender.clone(),
+            self.state.clone(),
+            self.observers.clone(),
             self.connection_id,
         );
 
-        let task2 = LocalConnection::write_to_socket(stream_writer, reader);
+        let task2 = LocalConnection::write_to_socket(stream_writer, reader, self.observers.clone());
 
         tokio::select! {
             _ = task2 => {},
@@ -114,6 +198,10 @@ impl LocalConnection {
             _ = cancellation_token.cancelled() => {}
         };
 
+        for observer in self.observers.lock().unwrap().iter_mut() {
+            observer.finish();
+        }
+
         if cancellation_token.is_cancelled() {
             return Ok(());
         }
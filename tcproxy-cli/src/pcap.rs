@@ -0,0 +1,380 @@
+use bytes::BufMut;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tcproxy_core::Result;
+use tracing::debug;
+
+use crate::proxy_protocol::{same_family, to_ipv6};
+use crate::server_addr::ServerAddr;
+use crate::ConnectionObserver;
+
+const PCAP_MAGIC: u32 = 0xa1b2c3d4;
+const SNAPLEN: u32 = 65535;
+
+/// packets start with an ipv4 or ipv6 header, no link layer.
+const LINKTYPE_RAW: u32 = 101;
+
+/// payload carried by a single synthesized segment.
+const MAX_SEGMENT: usize = 16 * 1024;
+
+const TCP_FIN: u8 = 0x01;
+const TCP_SYN: u8 = 0x02;
+const TCP_PSH: u8 = 0x08;
+const TCP_ACK: u8 = 0x10;
+
+/// pcap file shared by every connection of the tunnels capturing to it.
+#[derive(Debug)]
+pub struct PcapWriter {
+    path: PathBuf,
+    file: Mutex<BufWriter<File>>,
+}
+
+impl PcapWriter {
+    /// opens the capture file, packets are appended when it was written before.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let empty = file.metadata()?.len() == 0;
+        let mut file = BufWriter::new(file);
+
+        if empty {
+            let mut header = Vec::with_capacity(24);
+            header.put_u32_le(PCAP_MAGIC);
+            header.put_u16_le(2);
+            header.put_u16_le(4);
+            header.put_i32_le(0);
+            header.put_u32_le(0);
+            header.put_u32_le(SNAPLEN);
+            header.put_u32_le(LINKTYPE_RAW);
+            file.write_all(&header)?;
+            file.flush()?;
+        }
+
+        Ok(Self {
+            path: path.to_owned(),
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// appends a packet with the current time, flushed right away so the file can be
+    /// opened while the tunnel runs.
+    pub fn write_packet(&self, packet: &[u8]) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+        let mut record = Vec::with_capacity(16 + packet.len());
+        record.put_u32_le(now.as_secs() as u32);
+        record.put_u32_le(now.subsec_micros());
+        record.put_u32_le(packet.len() as u32);
+        record.put_u32_le(packet.len() as u32);
+        record.put_slice(packet);
+
+        let mut file = self.file.lock().unwrap();
+        file.write_all(&record)?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+/// records a tunneled connection as a tcp stream between the remote peer and the
+/// target, with synthesized handshake, sequence numbers and teardown.
+pub struct PacketCapture {
+    writer: Arc<PcapWriter>,
+    client: SocketAddr,
+    server_ip: IpAddr,
+    endpoints: Option<(SocketAddr, SocketAddr)>,
+    client_seq: u32,
+    server_seq: u32,
+    ip_id: u16,
+    finished: bool,
+}
+
+impl PacketCapture {
+    /// `peer_addr` is the remote peer, unknown peers get an address from the connection id
+    /// so every connection is a stream of its own. The server side uses the public address
+    /// with the port of the target the connection is made to, so dissectors pick its protocol.
+    /// nothing is recorded until the target is connected.
+    pub fn new(
+        writer: &Arc<PcapWriter>,
+        connection_id: &u32,
+        peer_addr: Option<SocketAddr>,
+        listener_addr: Option<SocketAddr>,
+    ) -> Self {
+        let client = peer_addr.unwrap_or_else(|| {
+            SocketAddr::new(
+                IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)),
+                (1024 + connection_id % 64512) as u16,
+            )
+        });
+        let server_ip = listener_addr
+            .map(|addr| addr.ip())
+            .filter(|ip| !ip.is_unspecified())
+            .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+
+        Self {
+            writer: writer.clone(),
+            client,
+            server_ip,
+            endpoints: None,
+            client_seq: connection_id.wrapping_mul(7919),
+            server_seq: connection_id.wrapping_mul(104729),
+            ip_id: 0,
+            finished: false,
+        }
+    }
+
+    /// writes a segment, `from_client` tells its direction. syn and fin take a sequence number.
+    fn send(&mut self, from_client: bool, flags: u8, payload: &[u8]) {
+        let Some((client, server)) = self.endpoints else {
+            return;
+        };
+
+        let (source, destination, seq, ack) = match from_client {
+            true => (client, server, self.client_seq, self.server_seq),
+            false => (server, client, self.server_seq, self.client_seq),
+        };
+
+        // the first syn doesn't acknowledge anything yet.
+        let ack = match flags {
+            TCP_SYN => 0,
+            _ => ack,
+        };
+
+        self.ip_id = self.ip_id.wrapping_add(1);
+        let packet = tcp_packet(&source, &destination, seq, ack, flags, self.ip_id, payload);
+        if let Err(err) = self.writer.write_packet(&packet) {
+            debug!(
+                "failed to write packet to {}: {}",
+                self.writer.path().display(),
+                err
+            );
+        }
+
+        let consumed = payload.len() as u32 + u32::from(flags & (TCP_SYN | TCP_FIN) != 0);
+        match from_client {
+            true => self.client_seq = self.client_seq.wrapping_add(consumed),
+            false => self.server_seq = self.server_seq.wrapping_add(consumed),
+        }
+    }
+
+    fn send_data(&mut self, from_client: bool, data: &[u8]) {
+        for segment in data.chunks(MAX_SEGMENT) {
+            self.send(from_client, TCP_PSH | TCP_ACK, segment);
+        }
+    }
+}
+
+impl ConnectionObserver for PacketCapture {
+    fn connected(&mut self, target: &ServerAddr) {
+        let server = SocketAddr::new(self.server_ip, *target.port());
+        self.endpoints = Some(same_family((self.client, server)));
+
+        self.send(true, TCP_SYN, &[]);
+        self.send(false, TCP_SYN | TCP_ACK, &[]);
+        self.send(true, TCP_ACK, &[]);
+    }
+
+    fn request_data(&mut self, data: &[u8]) {
+        self.send_data(true, data);
+    }
+
+    fn response_data(&mut self, data: &[u8]) {
+        self.send_data(false, data);
+    }
+
+    fn finish(&mut self) {
+        if self.finished || self.endpoints.is_none() {
+            return;
+        }
+
+        self.finished = true;
+        self.send(true, TCP_FIN | TCP_ACK, &[]);
+        self.send(false, TCP_FIN | TCP_ACK, &[]);
+        self.send(true, TCP_ACK, &[]);
+    }
+}
+
+/// ip packet carrying a tcp segment, both addresses must share a family.
+fn tcp_packet(
+    source: &SocketAddr,
+    destination: &SocketAddr,
+    seq: u32,
+    ack: u32,
+    flags: u8,
+    ip_id: u16,
+    payload: &[u8],
+) -> Vec<u8> {
+    let mut segment = Vec::with_capacity(20 + payload.len());
+    segment.put_u16(source.port());
+    segment.put_u16(destination.port());
+    segment.put_u32(seq);
+    segment.put_u32(ack);
+    segment.put_u8(5 << 4);
+    segment.put_u8(flags);
+    segment.put_u16(65535);
+    segment.put_u16(0);
+    segment.put_u16(0);
+    segment.put_slice(payload);
+
+    let mut packet = Vec::with_capacity(40 + segment.len());
+    let mut pseudo_header = Vec::with_capacity(40);
+    match (source.ip(), destination.ip()) {
+        (IpAddr::V4(source_ip), IpAddr::V4(destination_ip)) => {
+            packet.put_u8(0x45);
+            packet.put_u8(0);
+            packet.put_u16(20 + segment.len() as u16);
+            packet.put_u16(ip_id);
+            packet.put_u16(0x4000);
+            packet.put_u8(64);
+            packet.put_u8(6);
+            packet.put_u16(0);
+            packet.put_slice(&source_ip.octets());
+            packet.put_slice(&destination_ip.octets());
+            let checksum = checksum(&[&packet]);
+            packet[10..12].copy_from_slice(&checksum.to_be_bytes());
+
+            pseudo_header.put_slice(&source_ip.octets());
+            pseudo_header.put_slice(&destination_ip.octets());
+            pseudo_header.put_u8(0);
+            pseudo_header.put_u8(6);
+            pseudo_header.put_u16(segment.len() as u16);
+        }
+        (source_ip, destination_ip) => {
+            let (source_ip, destination_ip) = (to_ipv6(source_ip), to_ipv6(destination_ip));
+
+            packet.put_u32(0x6000_0000);
+            packet.put_u16(segment.len() as u16);
+            packet.put_u8(6);
+            packet.put_u8(64);
+            packet.put_slice(&source_ip.octets());
+            packet.put_slice(&destination_ip.octets());
+
+            pseudo_header.put_slice(&source_ip.octets());
+            pseudo_header.put_slice(&destination_ip.octets());
+            pseudo_header.put_u32(segment.len() as u32);
+            pseudo_header.put_slice(&[0, 0, 0, 6]);
+        }
+    }
+
+    let checksum = checksum(&[&pseudo_header, &segment]);
+    segment[16..18].copy_from_slice(&checksum.to_be_bytes());
+    packet.put_slice(&segment);
+    packet
+}
+
+/// internet checksum over the concatenation of `parts`, each one of even length but the last.
+fn checksum(parts: &[&[u8]]) -> u16 {
+    let mut sum: u32 = 0;
+    for part in parts {
+        for word in part.chunks(2) {
+            let value = match word {
+                [high, low] => u16::from_be_bytes([*high, *low]),
+                [high] => u16::from_be_bytes([*high, 0]),
+                _ => 0,
+            };
+            sum += u32::from(value);
+        }
+    }
+
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+
+    !(sum as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::str::FromStr;
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    use super::{checksum, tcp_packet, PacketCapture, PcapWriter, TCP_ACK, TCP_PSH};
+    use crate::server_addr::ServerAddr;
+    use crate::ConnectionObserver;
+
+    #[test]
+    fn should_build_valid_ipv4_segment() {
+        // Arrange
+        let source = SocketAddr::from_str("198.51.100.20:40000").unwrap();
+        let destination = SocketAddr::from_str("10.0.0.5:5432").unwrap();
+
+        // Act
+        let packet = tcp_packet(&source, &destination, 1, 2, TCP_PSH | TCP_ACK, 1, b"ping");
+
+        // Assert
+        assert_eq!(packet.len(), 44);
+        assert_eq!(&packet[12..16], &[198, 51, 100, 20]);
+        assert_eq!(checksum(&[&packet[..20]]), 0);
+        assert_eq!(&packet[40..], b"ping");
+
+        let mut pseudo_header = packet[12..20].to_vec();
+        pseudo_header.extend_from_slice(&[0, 6, 0, 24]);
+        assert_eq!(checksum(&[&pseudo_header, &packet[20..]]), 0);
+    }
+
+    #[test]
+    fn should_record_connection_as_tcp_stream() {
+        // Arrange
+        let path = std::env::temp_dir().join(format!("{}.pcap", Uuid::new_v4()));
+        let writer = Arc::new(PcapWriter::open(&path).unwrap());
+        let peer = SocketAddr::from_str("[2001:db8::7]:40000").unwrap();
+
+        // Act
+        let mut capture = PacketCapture::new(&writer, &7, Some(peer), None);
+        capture.connected(&ServerAddr::from_str("localhost:5432").unwrap());
+        capture.request_data(b"hello");
+        capture.response_data(b"world!");
+        capture.finish();
+        capture.finish();
+        drop(capture);
+        PcapWriter::open(&path).unwrap();
+
+        // Assert
+        let content = std::fs::read(&path).unwrap();
+        let mut offset = 24;
+        let mut packets = Vec::new();
+        while offset < content.len() {
+            let len = u32::from_le_bytes(content[offset + 8..offset + 12].try_into().unwrap());
+            packets.push(&content[offset + 16..offset + 16 + len as usize]);
+            offset += 16 + len as usize;
+        }
+
+        assert_eq!(&content[..4], &[0xd4, 0xc3, 0xb2, 0xa1]);
+        assert_eq!(packets.len(), 8);
+        assert!(packets.iter().all(|packet| packet[0] >> 4 == 6));
+        assert_eq!(&packets[0][42..44], &5432u16.to_be_bytes());
+        assert_eq!(&packets[3][60..], b"hello");
+        assert_eq!(&packets[4][60..], b"world!");
+
+        // the response acknowledges the syn and the 5 bytes of the request.
+        let client_seq = u32::from_be_bytes(packets[0][44..48].try_into().unwrap());
+        let response_ack = u32::from_be_bytes(packets[4][48..52].try_into().unwrap());
+        assert_eq!(response_ack, client_seq.wrapping_add(6));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn should_not_record_before_target_is_connected() {
+        // Arrange
+        let path = std::env::temp_dir().join(format!("{}.pcap", Uuid::new_v4()));
+        let writer = Arc::new(PcapWriter::open(&path).unwrap());
+
+        // Act
+        let mut capture = PacketCapture::new(&writer, &7, None, None);
+        capture.finish();
+        drop(capture);
+
+        // Assert
+        assert_eq!(std::fs::read(&path).unwrap().len(), 24);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,178 @@
+use bytes::BufMut;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::net::{IpAddr, SocketAddr};
+
+/// signature that starts every PROXY protocol v2 header.
+const V2_SIGNATURE: [u8; 12] = [
+    0x0D, 0x0A, 0x0D, 0x0A, 0x00, 0x0D, 0x0A, 0x51, 0x55, 0x49, 0x54, 0x0A,
+];
+const V2_PROXY_COMMAND: u8 = 0x21;
+const V2_LOCAL_COMMAND: u8 = 0x20;
+const V2_TCP_OVER_IPV4: u8 = 0x11;
+const V2_TCP_OVER_IPV6: u8 = 0x21;
+const V2_UNSPECIFIED: u8 = 0x00;
+
+/// PROXY protocol header written to the local service before the first byte of a
+/// connection, so it sees the address of the remote peer instead of 127.0.0.1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyProtocol {
+    V1,
+    V2,
+}
+
+impl ProxyProtocol {
+    /// header for a connection from `source` to `destination`, the addresses are sent
+    /// by the server and a header without them is written when they are unknown.
+    pub fn header(&self, source: Option<SocketAddr>, destination: Option<SocketAddr>) -> Vec<u8> {
+        let addresses = source.zip(destination).map(same_family);
+        match self {
+            ProxyProtocol::V1 => v1_header(addresses),
+            ProxyProtocol::V2 => v2_header(addresses),
+        }
+    }
+}
+
+fn v1_header(addresses: Option<(SocketAddr, SocketAddr)>) -> Vec<u8> {
+    let header = match addresses {
+        Some((source, destination)) => format!(
+            "PROXY {} {} {} {} {}\r\n",
+            match source {
+                SocketAddr::V4(_) => "TCP4",
+                SocketAddr::V6(_) => "TCP6",
+            },
+            source.ip(),
+            destination.ip(),
+            source.port(),
+            destination.port()
+        ),
+        None => String::from("PROXY UNKNOWN\r\n"),
+    };
+
+    header.into_bytes()
+}
+
+fn v2_header(addresses: Option<(SocketAddr, SocketAddr)>) -> Vec<u8> {
+    let mut header = Vec::from(V2_SIGNATURE);
+    let (source, destination) = match addresses {
+        Some(addresses) => addresses,
+        None => {
+            header.put_u8(V2_LOCAL_COMMAND);
+            header.put_u8(V2_UNSPECIFIED);
+            header.put_u16(0);
+            return header;
+        }
+    };
+
+    header.put_u8(V2_PROXY_COMMAND);
+    match (source.ip(), destination.ip()) {
+        (IpAddr::V4(source_ip), IpAddr::V4(destination_ip)) => {
+            header.put_u8(V2_TCP_OVER_IPV4);
+            header.put_u16(12);
+            header.put_slice(&source_ip.octets());
+            header.put_slice(&destination_ip.octets());
+        }
+        (source_ip, destination_ip) => {
+            header.put_u8(V2_TCP_OVER_IPV6);
+            header.put_u16(36);
+            header.put_slice(&to_ipv6(source_ip).octets());
+            header.put_slice(&to_ipv6(destination_ip).octets());
+        }
+    }
+
+    header.put_u16(source.port());
+    header.put_u16(destination.port());
+    header
+}
+
+/// both addresses of a header must share a family, ipv4 ones are mapped to ipv6 otherwise.
+pub(crate) fn same_family(
+    (source, destination): (SocketAddr, SocketAddr),
+) -> (SocketAddr, SocketAddr) {
+    match source.is_ipv4() == destination.is_ipv4() {
+        true => (source, destination),
+        false => (
+            SocketAddr::new(IpAddr::V6(to_ipv6(source.ip())), source.port()),
+            SocketAddr::new(IpAddr::V6(to_ipv6(destination.ip())), destination.port()),
+        ),
+    }
+}
+
+pub(crate) fn to_ipv6(ip: IpAddr) -> std::net::Ipv6Addr {
+    match ip {
+        IpAddr::V4(ip) => ip.to_ipv6_mapped(),
+        IpAddr::V6(ip) => ip,
+    }
+}
+
+impl Display for ProxyProtocol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProxyProtocol::V1 => write!(f, "v1"),
+            ProxyProtocol::V2 => write!(f, "v2"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::str::FromStr;
+
+    use super::{ProxyProtocol, V2_SIGNATURE};
+
+    fn addr(value: &str) -> Option<SocketAddr> {
+        Some(SocketAddr::from_str(value).unwrap())
+    }
+
+    #[test]
+    fn should_write_v1_header() {
+        // Act
+        let ipv4 = ProxyProtocol::V1.header(addr("198.51.100.20:40000"), addr("10.0.0.5:15080"));
+        let ipv6 = ProxyProtocol::V1.header(addr("[2001:db8::7]:40000"), addr("[::1]:15080"));
+        let unknown = ProxyProtocol::V1.header(None, addr("10.0.0.5:15080"));
+
+        // Assert
+        assert_eq!(ipv4, b"PROXY TCP4 198.51.100.20 10.0.0.5 40000 15080\r\n");
+        assert_eq!(ipv6, b"PROXY TCP6 2001:db8::7 ::1 40000 15080\r\n");
+        assert_eq!(unknown, b"PROXY UNKNOWN\r\n");
+    }
+
+    #[test]
+    fn should_map_mixed_families_to_ipv6() {
+        // Act
+        let result = ProxyProtocol::V1.header(addr("198.51.100.20:40000"), addr("[::1]:15080"));
+
+        // Assert
+        assert_eq!(
+            result,
+            b"PROXY TCP6 ::ffff:198.51.100.20 ::1 40000 15080\r\n"
+        );
+    }
+
+    #[test]
+    fn should_write_v2_header() {
+        // Act
+        let result = ProxyProtocol::V2.header(addr("198.51.100.20:40000"), addr("10.0.0.5:15080"));
+
+        // Assert
+        let mut expected = Vec::from(V2_SIGNATURE);
+        expected.extend_from_slice(&[0x21, 0x11, 0x00, 0x0C]);
+        expected.extend_from_slice(&[198, 51, 100, 20, 10, 0, 0, 5]);
+        expected.extend_from_slice(&40000u16.to_be_bytes());
+        expected.extend_from_slice(&15080u16.to_be_bytes());
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn should_write_v2_local_header_without_addresses() {
+        // Act
+        let result = ProxyProtocol::V2.header(None, None);
+
+        // Assert
+        let mut expected = Vec::from(V2_SIGNATURE);
+        expected.extend_from_slice(&[0x20, 0x00, 0x00, 0x00]);
+        assert_eq!(result, expected);
+    }
+}
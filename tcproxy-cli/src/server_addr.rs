@@ -1,21 +1,13 @@
-use fancy_regex::Regex;
-use lazy_static::lazy_static;
 use std::error::Error;
 use std::net::{AddrParseError, IpAddr, SocketAddr};
 use std::{fmt::Display, num::ParseIntError, str::FromStr};
 
-
-
-lazy_static! {
-    static ref IP_REGEX: Regex =
-        Regex::new(r"^((25[0-5]|(2[0-4]|1\d|[1-9]|)\d)\.?\b){4}$").unwrap();
-}
-
 #[derive(Debug)]
 pub enum ServerAddrError {
     InvalidString,
     InvalidPort,
     InvalidHost,
+    Unresolved(String),
     Other(tcproxy_core::Error),
 }
 
@@ -33,7 +25,7 @@ pub struct ServerAddr {
 }
 
 // Used to represent a ServerAddr
-// It will be useful for allowing app contexts to store domain names, instead of only IPs
+// App contexts and forwarding targets can store domain names, which are resolved on every connect.
 impl ServerAddr {
     pub fn new(host: &str, port: &u16) -> Result<Self, ServerAddrError> {
         if host == String::default() {
@@ -48,16 +40,12 @@ impl ServerAddr {
     }
 
     fn parse_type(host: &str) -> Result<ServerAddrType, ServerAddrError> {
-        match Self::is_ip(host)? {
+        match IpAddr::from_str(host).is_ok() {
             true => Ok(ServerAddrType::IpAddr),
             _ => Ok(ServerAddrType::Dns),
         }
     }
 
-    fn is_ip(host: &str) -> Result<bool, ServerAddrError> {
-        Ok(IP_REGEX.is_match(host)?)
-    }
-
     pub fn host(&self) -> &str {
         &self.host
     }
@@ -76,23 +64,66 @@ impl ServerAddr {
 
         Ok(addr)
     }
+
+    /// every address the host points to, looking up domain names (A and AAAA records).
+    pub async fn resolve(&self) -> Result<Vec<SocketAddr>, ServerAddrError> {
+        if self.addr_type == ServerAddrType::IpAddr {
+            return Ok(vec![self.to_socket_addr()?]);
+        }
+
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((self.host.as_str(), self.port))
+            .await
+            .map_err(|_| ServerAddrError::Unresolved(self.host.clone()))?
+            .collect();
+
+        match addrs.is_empty() {
+            true => Err(ServerAddrError::Unresolved(self.host.clone())),
+            false => Ok(addrs),
+        }
+    }
+}
+
+/// `127.0.0.1:3000, 127.0.0.1:3001`
+pub fn join_targets(targets: &[ServerAddr]) -> String {
+    targets
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 impl FromStr for ServerAddr {
     type Err = ServerAddrError;
 
+    /// parses `host:port`, ipv6 addresses are written between brackets (`[::1]:8080`).
     fn from_str(given_str: &str) -> Result<Self, Self::Err> {
-        let groups: Vec<&str> = given_str.split(':').collect();
-        if 2 != groups.len() {
-            return Err(ServerAddrError::InvalidString);
-        }
+        let (host, port) = match given_str.strip_prefix('[') {
+            Some(rest) => match rest.split_once("]:") {
+                Some((host, port)) if host.parse::<std::net::Ipv6Addr>().is_ok() => (host, port),
+                Some(_) => return Err(ServerAddrError::InvalidHost),
+                None => return Err(ServerAddrError::InvalidString),
+            },
+            None => match given_str.split_once(':') {
+                Some((host, port)) if !port.contains(':') => (host, port),
+                _ => return Err(ServerAddrError::InvalidString),
+            },
+        };
 
-        let port = match groups[1].parse::<u16>() {
+        let port = match port.parse::<u16>() {
             Ok(port) => port,
             Err(_) => return Err(ServerAddrError::InvalidPort),
         };
 
-        ServerAddr::new(groups[0], &port)
+        ServerAddr::new(host, &port)
+    }
+}
+
+impl Display for ServerAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.host.contains(':') {
+            true => write!(f, "[{}]:{}", self.host, self.port),
+            false => write!(f, "{}:{}", self.host, self.port),
+        }
     }
 }
 
@@ -110,18 +141,15 @@ impl From<ParseIntError> for ServerAddrError {
     }
 }
 
-impl From<fancy_regex::Error> for ServerAddrError {
-    fn from(err: fancy_regex::Error) -> Self {
-        Self::Other(err.into())
-    }
-}
-
 impl Display for ServerAddrError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let msg = match self {
             ServerAddrError::InvalidPort => "invalid port.".to_string(),
-            ServerAddrError::InvalidString => "invalid host string should be IP:PORT".to_string(),
+            ServerAddrError::InvalidString => {
+                "invalid host string should be HOST:PORT or [IPV6]:PORT".to_string()
+            }
             ServerAddrError::InvalidHost => "invalid host".to_string(),
+            ServerAddrError::Unresolved(host) => format!("could not resolve host {}", host),
             ServerAddrError::Other(err) => format!("unexpected error: {}", err),
         };
 
@@ -153,6 +181,33 @@ mod tests {
         assert_eq!(server_addr.addr_type, ServerAddrType::Dns);
     }
 
+    #[test]
+    pub fn should_parse_ipv6_between_brackets() {
+        // Act
+        let server_addr = ServerAddr::from_str("[::1]:8080").unwrap();
+
+        // Assert
+        assert_eq!("::1", server_addr.host());
+        assert_eq!(8080u16, *server_addr.port());
+        assert_eq!(server_addr.addr_type, ServerAddrType::IpAddr);
+        assert_eq!(
+            server_addr.to_socket_addr().unwrap().to_string(),
+            "[::1]:8080"
+        );
+    }
+
+    #[test]
+    pub fn should_return_err_when_ipv6_has_no_brackets() {
+        // Act
+        let result = ServerAddr::from_str("::1:8080");
+
+        // Assert
+        assert!(is_type!(
+            result.unwrap_err(),
+            ServerAddrError::InvalidString
+        ));
+    }
+
     #[test]
     pub fn should_return_err_when_host_is_invalid() {
         // Arrange
@@ -208,6 +263,19 @@ mod tests {
         assert!(is_type!(result.unwrap_err(), ServerAddrError::InvalidPort));
     }
 
+    #[tokio::test]
+    async fn should_resolve_domain_name() {
+        // Arrange
+        let server_addr = ServerAddr::new("localhost", &8080).unwrap();
+
+        // Act
+        let addrs = server_addr.resolve().await.unwrap();
+
+        // Assert
+        assert!(addrs.iter().all(|addr| addr.ip().is_loopback()));
+        assert!(addrs.iter().all(|addr| addr.port() == 8080));
+    }
+
     #[test]
     pub fn should_be_able_to_parse_from_str() {
         // Arrange
@@ -0,0 +1,284 @@
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tcproxy_core::tcp::connect_happy_eyeballs;
+use tcproxy_core::Result;
+use tokio::sync::mpsc::Sender;
+use tokio::task::JoinHandle;
+use tokio::time;
+use tracing::debug;
+
+use crate::server_addr::ServerAddr;
+use crate::{ClientState, LogBuffer, Shutdown};
+
+/// time between two health checks of the targets of a tunnel.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// how long a target has to accept the health check connection.
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// how the target of a new connection is picked when a tunnel has several.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum BalanceStrategy {
+    #[default]
+    RoundRobin,
+    LeastConnections,
+}
+
+#[derive(Debug)]
+struct Target {
+    addr: ServerAddr,
+    healthy: AtomicBool,
+    connections: AtomicUsize,
+}
+
+/// local targets of a tunnel, with their health and open connections.
+#[derive(Debug)]
+pub struct TargetPool {
+    targets: Vec<Target>,
+    strategy: BalanceStrategy,
+    next: AtomicUsize,
+}
+
+impl TargetPool {
+    pub fn new(targets: &[ServerAddr], strategy: BalanceStrategy) -> Self {
+        Self {
+            targets: targets
+                .iter()
+                .map(|addr| Target {
+                    addr: addr.clone(),
+                    healthy: AtomicBool::new(true),
+                    connections: AtomicUsize::new(0),
+                })
+                .collect(),
+            strategy,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.targets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> &ServerAddr {
+        &self.targets[index].addr
+    }
+
+    pub fn is_healthy(&self, index: usize) -> bool {
+        self.targets[index].healthy.load(Ordering::Relaxed)
+    }
+
+    /// connections currently open to the target.
+    pub fn connections(&self, index: usize) -> usize {
+        self.targets[index].connections.load(Ordering::Relaxed)
+    }
+
+    /// order in which targets are tried for a new connection. healthy targets come first,
+    /// picked by the strategy, and the unhealthy ones last in case they are back.
+    pub fn candidates(&self) -> Vec<usize> {
+        let (mut healthy, unhealthy): (Vec<usize>, Vec<usize>) =
+            (0..self.targets.len()).partition(|index| self.is_healthy(*index));
+
+        if !healthy.is_empty() {
+            let start = self.next.fetch_add(1, Ordering::Relaxed) % healthy.len();
+            healthy.rotate_left(start);
+        }
+
+        // stable, so targets with the same connections still take turns.
+        if self.strategy == BalanceStrategy::LeastConnections {
+            healthy.sort_by_key(|index| self.connections(*index));
+        }
+
+        healthy.extend(unhealthy);
+        healthy
+    }
+
+    /// counts a connection to the target until the lease is dropped.
+    pub fn acquire(self: &Arc<Self>, index: usize) -> TargetLease {
+        self.targets[index]
+            .connections
+            .fetch_add(1, Ordering::Relaxed);
+
+        TargetLease {
+            pool: self.clone(),
+            index,
+        }
+    }
+
+    /// records whether the target accepted a connection, logging when that changes
+    /// on tunnels with more than one target.
+    pub fn mark(&self, index: usize, healthy: bool, log: &LogBuffer) {
+        let target = &self.targets[index];
+        if target.healthy.swap(healthy, Ordering::Relaxed) == healthy || self.len() < 2 {
+            return;
+        }
+
+        match healthy {
+            true => log.push(&format!("target {} is back up", target.addr)),
+            false => log.push(&format!(
+                "target {} is down, skipped until it answers again",
+                target.addr
+            )),
+        }
+    }
+
+    /// tries to open a tcp connection to every target at once.
+    pub async fn check_health(&self, log: &LogBuffer) {
+        let checks = self.targets.iter().map(|target| async move {
+            let result = time::timeout(HEALTH_CHECK_TIMEOUT, async {
+                connect_happy_eyeballs(&target.addr.resolve().await?).await
+            })
+            .await;
+
+            match result {
+                Ok(Ok(_)) => true,
+                Ok(Err(err)) => {
+                    debug!("health check of {} failed: {}", target.addr, err);
+                    false
+                }
+                Err(_) => {
+                    debug!("health check of {} timed out", target.addr);
+                    false
+                }
+            }
+        });
+
+        for (index, healthy) in join_all(checks).await.into_iter().enumerate() {
+            self.mark(index, healthy, log);
+        }
+    }
+}
+
+/// connection open to a target of the pool.
+#[derive(Debug)]
+pub struct TargetLease {
+    pool: Arc<TargetPool>,
+    index: usize,
+}
+
+impl Drop for TargetLease {
+    fn drop(&mut self) {
+        self.pool.targets[self.index]
+            .connections
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// checks the targets of every tunnel of the session that has more than one.
+pub struct HealthChecker {
+    state: Arc<ClientState>,
+    _shutdown_signal: Sender<()>,
+}
+
+impl HealthChecker {
+    pub fn new(state: &Arc<ClientState>, shutdown_signal: &Sender<()>) -> Self {
+        Self {
+            state: state.clone(),
+            _shutdown_signal: shutdown_signal.clone(),
+        }
+    }
+
+    pub fn spawn(self, mut shutdown: Shutdown) -> JoinHandle<Result<()>> {
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = self.start() => {},
+                _ = shutdown.recv() => {
+                    debug!("received stop signal..");
+                }
+            };
+            Ok(())
+        })
+    }
+
+    async fn start(&self) {
+        loop {
+            time::sleep(HEALTH_CHECK_INTERVAL).await;
+
+            let pools: Vec<Arc<TargetPool>> = self
+                .state
+                .tunnels()
+                .iter()
+                .map(|tunnel| tunnel.targets().clone())
+                .filter(|pool| pool.len() > 1)
+                .collect();
+
+            for pool in pools {
+                pool.check_health(self.state.log()).await;
+            }
+        }
+    }
+}
+
+impl Display for BalanceStrategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BalanceStrategy::RoundRobin => write!(f, "round-robin"),
+            BalanceStrategy::LeastConnections => write!(f, "least-connections"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    use super::{BalanceStrategy, TargetPool};
+    use crate::server_addr::ServerAddr;
+    use crate::LogBuffer;
+
+    fn create_pool(strategy: BalanceStrategy) -> Arc<TargetPool> {
+        let targets: Vec<ServerAddr> = ["127.0.0.1:3000", "127.0.0.1:3001", "127.0.0.1:3002"]
+            .iter()
+            .map(|target| ServerAddr::from_str(target).unwrap())
+            .collect();
+
+        Arc::new(TargetPool::new(&targets, strategy))
+    }
+
+    #[test]
+    fn should_take_turns_skipping_unhealthy_targets() {
+        // Arrange
+        let pool = create_pool(BalanceStrategy::RoundRobin);
+        let log = LogBuffer::default();
+        pool.mark(1, false, &log);
+
+        // Act
+        let first = pool.candidates();
+        let second = pool.candidates();
+
+        // Assert
+        assert_eq!(first, vec![0, 2, 1]);
+        assert_eq!(second, vec![2, 0, 1]);
+        assert_eq!(log.lines().len(), 1);
+        assert!(log.lines()[0]
+            .ends_with("target 127.0.0.1:3001 is down, skipped until it answers again"));
+    }
+
+    #[test]
+    fn should_prefer_target_with_least_connections() {
+        // Arrange
+        let pool = create_pool(BalanceStrategy::LeastConnections);
+        let _first = pool.acquire(0);
+        let second = pool.acquire(1);
+        let _third = pool.acquire(1);
+
+        // Act
+        let busy = pool.candidates();
+        drop(second);
+        let after_close = pool.candidates();
+
+        // Assert
+        assert_eq!(busy, vec![2, 0, 1]);
+        assert_eq!(pool.connections(1), 1);
+        assert_eq!(after_close[0], 2);
+    }
+}
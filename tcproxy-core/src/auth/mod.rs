@@ -8,6 +8,8 @@ pub struct User {
     name: String,
     email: String,
     password_hash: String,
+    rate_limit: Option<u64>,
+    monthly_quota: Option<u64>,
 }
 
 impl User {
@@ -26,6 +28,16 @@ impl User {
     pub fn password(&self) -> &str {
         &self.password_hash
     }
+
+    /// bandwidth limit (bytes per second) configured for this account.
+    pub fn rate_limit(&self) -> &Option<u64> {
+        &self.rate_limit
+    }
+
+    /// monthly traffic quota (bytes in + out) configured for this account.
+    pub fn monthly_quota(&self) -> &Option<u64> {
+        &self.monthly_quota
+    }
 }
 
 impl User {
@@ -35,6 +47,18 @@ impl User {
             name: String::from(name),
             email: String::from(email),
             password_hash: String::from(password),
+            rate_limit: None,
+            monthly_quota: None,
         }
     }
+
+    pub fn with_rate_limit(mut self, rate_limit: Option<u64>) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    pub fn with_monthly_quota(mut self, monthly_quota: Option<u64>) -> Self {
+        self.monthly_quota = monthly_quota;
+        self
+    }
 }
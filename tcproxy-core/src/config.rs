@@ -1,5 +1,7 @@
-use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tracing::info;
@@ -8,40 +10,65 @@ use crate::Result;
 
 pub trait Config<T> {
     fn apply_env(&mut self, env: &HashMap<String, String>) -> Result<()>;
-    fn apply_args(&mut self, args: &T);
+    fn apply_args(&mut self, args: &T) -> Result<()>;
     fn validate(&self) -> Result<()>;
+
+    /// where each value currently set came from.
+    fn origins_mut(&mut self) -> &mut ConfigOrigins;
 }
 
-pub trait ConfigLoader<'a, Args, T = Self>
+pub trait ConfigLoader<Args, T = Self>
 where
-    T: 'a + Config<Args>,
-    T: Deserialize<'a>,
+    T: Config<Args>,
+    T: DeserializeOwned,
     T: Serialize,
     T: Default,
 {
     /// function that return available environment names.
     fn named_environment_variables() -> HashSet<String>;
 
-    /// reads config file from disk.
-    fn read_from_file(path: &Path) -> Result<T>;
-
     /// gets where config should be read from.
-    fn get_config_path(environment_variables: &HashMap<String, String>) -> PathBuf;
+    fn get_config_path(environment_variables: &HashMap<String, String>, args: &Args) -> PathBuf;
+
+    /// reads config file from disk, the format is picked by the file extension.
+    fn read_from_file(path: &Path) -> Result<T> {
+        let file_contents = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read config file {}: {}", path.display(), err))?;
+
+        let mut config: T = ConfigFormat::from_path(path)
+            .parse(&file_contents)
+            .map_err(|err| format!("failed to parse config file {}: {}", path.display(), err))?;
+
+        config.origins_mut().set_file(path);
+        Ok(config)
+    }
 
-    /// loads config from environment variables
+    /// loads config from environment variables, creating the file with defaults if missing.
     fn load(env_vars: &[(String, String)], args: &Args) -> Result<T> {
         let parsed_env_vars = Self::parse_environment_variables(env_vars);
-        let config_path = Self::get_config_path(&parsed_env_vars);
+        let config_path = Self::get_config_path(&parsed_env_vars, args);
 
         if !Self::file_exists(&config_path) {
-            info!("Config file doesnt exist. Creating default...");
+            info!(
+                "Config file {} doesnt exist. Creating default...",
+                config_path.display()
+            );
             Self::create_default(&config_path)?;
         }
 
+        Self::load_existing(env_vars, args)
+    }
+
+    /// loads config from an existing file, environment variables and arguments.
+    /// each one overrides the values set by the previous: file < env < args.
+    fn load_existing(env_vars: &[(String, String)], args: &Args) -> Result<T> {
+        let parsed_env_vars = Self::parse_environment_variables(env_vars);
+        let config_path = Self::get_config_path(&parsed_env_vars, args);
+
         let mut config = Self::read_from_file(&config_path)?;
 
-        config.apply_args(args);
         config.apply_env(&parsed_env_vars)?;
+        config.apply_args(args)?;
         config.validate()?;
 
         Ok(config)
@@ -66,12 +93,225 @@ where
         fs::metadata(file_path).is_ok()
     }
 
-    /// creates default implementation of T (Config)
+    /// creates default implementation of T (Config). the defaults must pass `validate`,
+    /// secrets included, or the first start fails on the file it just wrote.
     fn create_default(file_path: &Path) -> Result<()> {
         let config = T::default();
-        let config_str = serde_json::to_string(&config)?;
+        let config_str = ConfigFormat::from_path(file_path).serialize(&config)?;
 
         fs::write(file_path, config_str)?;
         Ok(())
     }
 }
+
+/// Supported config file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// picks the format by the file extension, defaulting to json.
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("toml") => ConfigFormat::Toml,
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            _ => ConfigFormat::Json,
+        }
+    }
+
+    pub fn parse<T: DeserializeOwned>(&self, contents: &str) -> Result<T> {
+        let value = match self {
+            ConfigFormat::Json => serde_json::from_str(contents)?,
+            ConfigFormat::Toml => toml::from_str(contents)?,
+            ConfigFormat::Yaml => serde_yaml::from_str(contents)?,
+        };
+
+        Ok(value)
+    }
+
+    pub fn serialize<T: Serialize>(&self, value: &T) -> Result<String> {
+        let contents = match self {
+            ConfigFormat::Json => serde_json::to_string(value)?,
+            ConfigFormat::Toml => toml::to_string(value)?,
+            ConfigFormat::Yaml => serde_yaml::to_string(value)?,
+        };
+
+        Ok(contents)
+    }
+}
+
+/// Where a config value was set.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ConfigOrigin {
+    #[default]
+    Default,
+    File(PathBuf),
+    Env(String),
+    Arg(String),
+}
+
+impl Display for ConfigOrigin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigOrigin::Default => write!(f, "default value"),
+            ConfigOrigin::File(path) => write!(f, "file {}", path.display()),
+            ConfigOrigin::Env(name) => write!(f, "env {}", name),
+            ConfigOrigin::Arg(name) => write!(f, "arg {}", name),
+        }
+    }
+}
+
+/// Keeps track of where each config key was set.
+/// keys without an override come from the file, if one was read.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOrigins {
+    file: Option<PathBuf>,
+    overrides: HashMap<String, ConfigOrigin>,
+}
+
+impl ConfigOrigins {
+    pub fn set_file(&mut self, path: &Path) {
+        self.file = Some(path.to_path_buf());
+    }
+
+    pub fn set(&mut self, key: &str, origin: ConfigOrigin) {
+        self.overrides.insert(String::from(key), origin);
+    }
+
+    pub fn get(&self, key: &str) -> ConfigOrigin {
+        if let Some(origin) = self.overrides.get(key) {
+            return origin.clone();
+        }
+
+        match &self.file {
+            Some(path) => ConfigOrigin::File(path.clone()),
+            None => ConfigOrigin::Default,
+        }
+    }
+}
+
+/// A config key with an invalid value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    key: String,
+    origin: ConfigOrigin,
+    message: String,
+}
+
+impl ConfigError {
+    pub fn new(key: &str, origin: &ConfigOrigin, message: &str) -> Self {
+        Self {
+            key: String::from(key),
+            origin: origin.clone(),
+            message: String::from(message),
+        }
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn origin(&self) -> &ConfigOrigin {
+        &self.origin
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}` (from {}): {}", self.key, self.origin, self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Every error found when validating a config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidConfig {
+    errors: Vec<ConfigError>,
+}
+
+impl InvalidConfig {
+    pub fn new(errors: Vec<ConfigError>) -> Self {
+        Self { errors }
+    }
+
+    pub fn errors(&self) -> &[ConfigError] {
+        &self.errors
+    }
+}
+
+impl Display for InvalidConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let errors: Vec<String> = self.errors.iter().map(|err| err.to_string()).collect();
+        write!(f, "invalid config: {}", errors.join("; "))
+    }
+}
+
+impl std::error::Error for InvalidConfig {}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{ConfigError, ConfigFormat, ConfigOrigin, ConfigOrigins};
+
+    #[test]
+    pub fn should_pick_format_by_extension() {
+        // Arrange
+        let paths = ["config.json", "config.TOML", "config.yml", "config"];
+
+        // Act
+        let formats: Vec<ConfigFormat> = paths
+            .iter()
+            .map(|path| ConfigFormat::from_path(Path::new(path)))
+            .collect();
+
+        // Assert
+        assert_eq!(
+            formats,
+            vec![
+                ConfigFormat::Json,
+                ConfigFormat::Toml,
+                ConfigFormat::Yaml,
+                ConfigFormat::Json
+            ]
+        );
+    }
+
+    #[test]
+    pub fn should_name_key_and_origin_in_error() {
+        // Arrange
+        let mut origins = ConfigOrigins::default();
+        origins.set_file(Path::new("config.toml"));
+        origins.set(
+            "listen_port",
+            ConfigOrigin::Env("TCPROXY_LISTEN_PORT".into()),
+        );
+
+        // Act
+        let from_env = ConfigError::new("listen_port", &origins.get("listen_port"), "invalid");
+        let from_file = ConfigError::new("port_min", &origins.get("port_min"), "invalid");
+
+        // Assert
+        assert_eq!(
+            from_env.to_string(),
+            "`listen_port` (from env TCPROXY_LISTEN_PORT): invalid"
+        );
+        assert_eq!(
+            from_file.to_string(),
+            "`port_min` (from file config.toml): invalid"
+        );
+    }
+}
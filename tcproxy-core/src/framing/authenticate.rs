@@ -166,4 +166,4 @@ impl From<TokenAuthenticationArgs> for GrantType {
 }
 
 #[cfg(test)]
-pub mod tests {}
+mod tests {}
@@ -1,17 +1,98 @@
 use bytes::BufMut;
+use ipnet::IpNet;
 use std::io::Cursor;
+use std::str::FromStr;
 
 use crate::framing::frame_types::CLIENT_CONNECTED;
 use crate::framing::utils::assert_connection_type;
-use crate::io::get_u16;
-use crate::{Frame, FrameDecodeError};
-
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub struct ClientConnected;
+use crate::io::{get_u16, get_u32_string, get_u64};
+use crate::{Frame, FrameDecodeError, PutU32String};
+
+/// networks an access list can hold, its length is encoded as an u16.
+pub const MAX_ACCESS_LIST_LEN: usize = u16::MAX as usize;
+
+/// Sent by the client for opening a new tunnel.
+/// `allow_list` and `deny_list` restricts which remote peers can connect to it,
+/// `rate_limit` (bytes per second) can only lower the limit enforced by the server,
+/// `requested_port` asks for a specific port instead of a random one.
+/// both are encoded as 0 when unset, so a 0 rate limit or port reads back as unset.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ClientConnected {
+    allow_list: Vec<IpNet>,
+    deny_list: Vec<IpNet>,
+    rate_limit: Option<u64>,
+    requested_port: Option<u16>,
+}
 
 impl ClientConnected {
     pub fn new() -> Self {
-        Self {}
+        Self::default()
+    }
+
+    /// fails when a list holds more than `MAX_ACCESS_LIST_LEN` networks.
+    pub fn with_access_lists(allow_list: &[IpNet], deny_list: &[IpNet]) -> crate::Result<Self> {
+        if allow_list.len() > MAX_ACCESS_LIST_LEN || deny_list.len() > MAX_ACCESS_LIST_LEN {
+            return Err(format!(
+                "access lists can hold at most {} networks",
+                MAX_ACCESS_LIST_LEN
+            )
+            .into());
+        }
+
+        Ok(Self {
+            allow_list: allow_list.to_vec(),
+            deny_list: deny_list.to_vec(),
+            rate_limit: None,
+            requested_port: None,
+        })
+    }
+
+    pub fn with_rate_limit(mut self, bytes_per_second: Option<u64>) -> Self {
+        self.rate_limit = bytes_per_second;
+        self
+    }
+
+    pub fn with_requested_port(mut self, port: Option<u16>) -> Self {
+        self.requested_port = port;
+        self
+    }
+
+    pub fn allow_list(&self) -> &[IpNet] {
+        &self.allow_list
+    }
+
+    pub fn deny_list(&self) -> &[IpNet] {
+        &self.deny_list
+    }
+
+    pub fn rate_limit(&self) -> &Option<u64> {
+        &self.rate_limit
+    }
+
+    pub fn requested_port(&self) -> &Option<u16> {
+        &self.requested_port
+    }
+}
+
+fn decode_networks(buffer: &mut Cursor<&[u8]>) -> Result<Vec<IpNet>, FrameDecodeError> {
+    let total = get_u16(buffer)?;
+    let mut networks = Vec::with_capacity(total as usize);
+    for _ in 0..total {
+        let raw_network = get_u32_string(buffer)?;
+        match IpNet::from_str(&raw_network) {
+            Ok(network) => networks.push(network),
+            Err(_) => return Err(format!("invalid network: {}", raw_network).into()),
+        };
+    }
+
+    Ok(networks)
+}
+
+/// lists are at most `MAX_ACCESS_LIST_LEN` long, checked when the frame is built.
+fn encode_networks(buffer: &mut Vec<u8>, networks: &[IpNet]) {
+    buffer.put_u16(networks.len() as u16);
+    for network in networks {
+        buffer.put_u32_sized_str(&network.to_string());
     }
 }
 
@@ -21,12 +102,33 @@ impl Frame for ClientConnected {
         Self: Sized,
     {
         assert_connection_type(&get_u16(buffer)?, &CLIENT_CONNECTED)?;
-        Ok(Self)
+
+        let allow_list = decode_networks(buffer)?;
+        let deny_list = decode_networks(buffer)?;
+        let rate_limit = match get_u64(buffer)? {
+            0 => None,
+            value => Some(value),
+        };
+        let requested_port = match get_u16(buffer)? {
+            0 => None,
+            value => Some(value),
+        };
+
+        Ok(Self {
+            allow_list,
+            deny_list,
+            rate_limit,
+            requested_port,
+        })
     }
 
     fn encode(&self) -> Vec<u8> {
         let mut buffer = Vec::new();
         buffer.put_u16(CLIENT_CONNECTED);
+        encode_networks(&mut buffer, &self.allow_list);
+        encode_networks(&mut buffer, &self.deny_list);
+        buffer.put_u64(self.rate_limit.unwrap_or(0));
+        buffer.put_u16(self.requested_port.unwrap_or(0));
 
         buffer
     }
@@ -35,17 +137,24 @@ impl Frame for ClientConnected {
 #[cfg(test)]
 mod tests {
     use bytes::BufMut;
+    use ipnet::IpNet;
     use std::io::Cursor;
+    use std::str::FromStr;
 
     use crate::framing::frame_types::CLIENT_CONNECTED;
-    use crate::framing::ClientConnected;
+    use crate::framing::{ClientConnected, MAX_ACCESS_LIST_LEN};
     use crate::tcp_frame::Frame;
+    use crate::{is_type, FrameDecodeError};
 
     #[test]
     pub fn should_parse_client_connected() {
         // Arrange
         let mut bufferf = Vec::new();
         bufferf.put_u16(CLIENT_CONNECTED);
+        bufferf.put_u16(0);
+        bufferf.put_u16(0);
+        bufferf.put_u64(0);
+        bufferf.put_u16(0);
 
         let mut cursor = Cursor::new(&bufferf[..]);
 
@@ -53,7 +162,7 @@ mod tests {
         let frame = ClientConnected::decode(&mut cursor).unwrap();
 
         // Assert
-        assert_eq!(ClientConnected, frame);
+        assert_eq!(ClientConnected::new(), frame);
     }
 
     #[test]
@@ -61,6 +170,10 @@ mod tests {
         // Arrange
         let mut expected_encoded = Vec::new();
         expected_encoded.put_u16(CLIENT_CONNECTED);
+        expected_encoded.put_u16(0);
+        expected_encoded.put_u16(0);
+        expected_encoded.put_u64(0);
+        expected_encoded.put_u16(0);
 
         let frame = ClientConnected::new();
 
@@ -70,4 +183,58 @@ mod tests {
         // Assert
         assert_eq!(&expected_encoded[..], &result[..]);
     }
+
+    #[test]
+    pub fn should_encode_and_decode_access_lists() {
+        // Arrange
+        let allow_list = vec![
+            IpNet::from_str("10.0.0.0/8").unwrap(),
+            IpNet::from_str("fd00::/8").unwrap(),
+        ];
+        let deny_list = vec![IpNet::from_str("10.1.2.3/32").unwrap()];
+        let frame = ClientConnected::with_access_lists(&allow_list, &deny_list)
+            .unwrap()
+            .with_rate_limit(Some(1024))
+            .with_requested_port(Some(15080));
+
+        // Act
+        let encoded = frame.encode();
+        let mut cursor = Cursor::new(&encoded[..]);
+        let decoded = ClientConnected::decode(&mut cursor).unwrap();
+
+        // Assert
+        assert_eq!(decoded.allow_list(), &allow_list[..]);
+        assert_eq!(decoded.deny_list(), &deny_list[..]);
+        assert_eq!(decoded.rate_limit(), &Some(1024));
+        assert_eq!(decoded.requested_port(), &Some(15080));
+    }
+
+    #[test]
+    pub fn should_reject_access_list_longer_than_its_length_prefix() {
+        // Arrange
+        let network = IpNet::from_str("10.0.0.1/32").unwrap();
+        let allow_list = vec![network; MAX_ACCESS_LIST_LEN + 1];
+
+        // Act
+        let result = ClientConnected::with_access_lists(&allow_list, &[]);
+
+        // Assert
+        assert!(result.is_err());
+        assert!(ClientConnected::with_access_lists(&[], &allow_list[1..]).is_ok());
+    }
+
+    #[test]
+    pub fn should_return_incomplete_when_lists_are_missing() {
+        // Arrange
+        let mut buffer = Vec::new();
+        buffer.put_u16(CLIENT_CONNECTED);
+
+        let mut cursor = Cursor::new(&buffer[..]);
+
+        // Act
+        let result = ClientConnected::decode(&mut cursor);
+
+        // Assert
+        assert!(is_type!(result.unwrap_err(), FrameDecodeError::Incomplete));
+    }
 }
@@ -1,21 +1,48 @@
 use crate::framing::frame_types::CLIENT_CONNECTED_ACK;
 use crate::framing::utils::assert_connection_type;
-use crate::io::get_u16;
-use crate::{Frame, FrameDecodeError, TcpFrame};
+use crate::io::{get_ip_addr, get_u16};
+use crate::{Frame, FrameDecodeError, PutIpAddr, TcpFrame};
 use bytes::BufMut;
 use std::io::Cursor;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct ClientConnectedAck {
     listening_port: u16,
+    listening_ip: IpAddr,
 }
 
 impl ClientConnectedAck {
     pub fn new(port: &u16) -> Self {
         Self {
             listening_port: *port,
+            listening_ip: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
         }
     }
+
+    /// address the tunnel listens on, unspecified when it listens on every interface.
+    pub fn with_listening_ip(mut self, ip: &IpAddr) -> Self {
+        self.listening_ip = *ip;
+        self
+    }
+
+    pub fn listening_port(&self) -> &u16 {
+        &self.listening_port
+    }
+
+    pub fn listening_ip(&self) -> &IpAddr {
+        &self.listening_ip
+    }
+
+    /// public address of the tunnel, using `server_ip` when it listens on every interface.
+    pub fn public_addr(&self, server_ip: &IpAddr) -> SocketAddr {
+        let ip = match self.listening_ip.is_unspecified() {
+            true => *server_ip,
+            false => self.listening_ip,
+        };
+
+        SocketAddr::new(ip, self.listening_port)
+    }
 }
 
 impl From<ClientConnectedAck> for TcpFrame {
@@ -31,15 +58,72 @@ impl Frame for ClientConnectedAck {
     {
         assert_connection_type(&get_u16(buffer)?, &CLIENT_CONNECTED_ACK)?;
         let listening_port = get_u16(buffer)?;
+        let listening_ip = get_ip_addr(buffer)?;
 
-        Ok(Self::new(&listening_port))
+        Ok(Self::new(&listening_port).with_listening_ip(&listening_ip))
     }
 
     fn encode(&self) -> Vec<u8> {
         let mut vec = Vec::new();
         vec.put_u16(CLIENT_CONNECTED_ACK);
         vec.put_u16(self.listening_port);
+        vec.put_ip_addr(&self.listening_ip);
 
         vec
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::net::{IpAddr, SocketAddr};
+    use std::str::FromStr;
+
+    use crate::framing::ClientConnectedAck;
+    use crate::{is_type, Frame, FrameDecodeError, TcpFrame};
+
+    #[test]
+    pub fn should_encode_and_decode_ipv6_listening_ip() {
+        // Arrange
+        let ip = IpAddr::from_str("2001:db8::1").unwrap();
+        let frame = ClientConnectedAck::new(&15000).with_listening_ip(&ip);
+
+        // Act
+        let encoded = frame.encode();
+        let mut cursor = Cursor::new(&encoded[..]);
+        let result = TcpFrame::parse(&mut cursor).unwrap();
+
+        // Assert
+        match result {
+            TcpFrame::ClientConnectedAck(decoded) => assert_eq!(decoded, frame),
+            actual => panic!("expected ClientConnectedAck, got {}", actual),
+        }
+    }
+
+    #[test]
+    pub fn should_use_server_ip_when_listening_on_every_interface() {
+        // Arrange
+        let frame =
+            ClientConnectedAck::new(&15000).with_listening_ip(&IpAddr::from_str("::").unwrap());
+        let server_ip = IpAddr::from_str("2001:db8::1").unwrap();
+
+        // Act
+        let result = frame.public_addr(&server_ip);
+
+        // Assert
+        assert_eq!(result, SocketAddr::from_str("[2001:db8::1]:15000").unwrap());
+    }
+
+    #[test]
+    pub fn should_return_incomplete() {
+        // Arrange
+        let encoded = ClientConnectedAck::new(&15000).encode();
+        let mut cursor = Cursor::new(&encoded[..encoded.len() - 1]);
+
+        // Act
+        let result = ClientConnectedAck::decode(&mut cursor);
+
+        // Assert
+        assert!(is_type!(result.unwrap_err(), FrameDecodeError::Incomplete));
+    }
+}
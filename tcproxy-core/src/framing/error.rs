@@ -11,7 +11,7 @@ use crate::{Frame, FrameDecodeError};
 
 use super::error_types::{
     ALREADY_AUTHENTICATED, AUTHENTICATION_FAILED, CLIENT_UNABLE_TO_CONNECT, FAILED_TO_CREATE_PROXY,
-    PORT_LIMIT_REACHED, UNEXPECTED_ERROR,
+    PORT_LIMIT_REACHED, PORT_UNAVAILABLE, QUOTA_EXCEEDED, UNEXPECTED_ERROR,
 };
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -22,6 +22,8 @@ pub enum Reason {
     AuthenticationFailed,
     AlreadyAuthenticated,
     UnexpectedError,
+    QuotaExceeded,
+    PortUnavailable,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -50,6 +52,8 @@ impl Error {
             Reason::AuthenticationFailed => AUTHENTICATION_FAILED,
             Reason::UnexpectedError => UNEXPECTED_ERROR,
             Reason::AlreadyAuthenticated => ALREADY_AUTHENTICATED,
+            Reason::QuotaExceeded => QUOTA_EXCEEDED,
+            Reason::PortUnavailable => PORT_UNAVAILABLE,
         }
     }
 
@@ -61,6 +65,8 @@ impl Error {
             AUTHENTICATION_FAILED => Ok(Reason::AuthenticationFailed),
             UNEXPECTED_ERROR => Ok(Reason::UnexpectedError),
             ALREADY_AUTHENTICATED => Ok(Reason::AlreadyAuthenticated),
+            QUOTA_EXCEEDED => Ok(Reason::QuotaExceeded),
+            PORT_UNAVAILABLE => Ok(Reason::PortUnavailable),
             actual => Err(FrameDecodeError::Other(
                 format!("invalid reason: {}", actual).into(),
             )),
@@ -110,8 +116,10 @@ impl fmt::Display for Reason {
             Reason::FailedToCreateProxy => "Failed to create proxy".to_string(),
             Reason::PortLimitReached => "port limit reached".to_string(),
             Reason::ClientUnableToConnect => "target host unable to connect".to_string(),
+            Reason::QuotaExceeded => "usage quota exceeded".to_string(),
+            Reason::PortUnavailable => "requested port is not available".to_string(),
         };
 
-        write!(f, "{}", format!("reason: {}", msg))
+        write!(f, "reason: {}", msg)
     }
 }
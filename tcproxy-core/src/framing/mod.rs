@@ -6,8 +6,12 @@ mod data_packet;
 mod error;
 mod ping;
 mod pong;
+mod server_shutdown;
 mod socket_connected;
 mod socket_disconnected;
+mod tunnel_stats;
+mod usage_report;
+mod usage_request;
 
 pub use authenticate::*;
 pub use authenticate_ack::*;
@@ -17,8 +21,12 @@ pub use data_packet::*;
 pub use error::*;
 pub use ping::*;
 pub use pong::*;
+pub use server_shutdown::*;
 pub use socket_connected::*;
 pub use socket_disconnected::*;
+pub use tunnel_stats::*;
+pub use usage_report::*;
+pub use usage_request::*;
 
 pub mod frame_types {
     pub const PING: u16 = 0x15;
@@ -32,6 +40,10 @@ pub mod frame_types {
     pub const AUTHENTICATE: u16 = 0x23;
     pub const AUTHENTICATE_ACK: u16 = 0x24;
     pub const LOGIN: u16 = 0x25;
+    pub const TUNNEL_STATS: u16 = 0x26;
+    pub const USAGE_REQUEST: u16 = 0x27;
+    pub const USAGE_REPORT: u16 = 0x28;
+    pub const SERVER_SHUTDOWN: u16 = 0x29;
 }
 
 pub mod error_types {
@@ -41,6 +53,8 @@ pub mod error_types {
     pub const AUTHENTICATION_FAILED: u16 = 0x96;
     pub const UNEXPECTED_ERROR: u16 = 0x95;
     pub const ALREADY_AUTHENTICATED: u16 = 0x94;
+    pub const QUOTA_EXCEEDED: u16 = 0x93;
+    pub const PORT_UNAVAILABLE: u16 = 0x92;
 }
 
 pub mod authentication_grant_types {
@@ -23,6 +23,12 @@ impl Ping {
     }
 }
 
+impl Default for Ping {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Frame for Ping {
     fn decode(buffer: &mut Cursor<&[u8]>) -> Result<Ping, FrameDecodeError>
     where
@@ -24,6 +24,12 @@ impl Pong {
     }
 }
 
+impl Default for Pong {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Frame for Pong {
     fn decode(buffer: &mut Cursor<&[u8]>) -> Result<Self, FrameDecodeError>
     where
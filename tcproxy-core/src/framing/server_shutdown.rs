@@ -0,0 +1,99 @@
+use crate::framing::frame_types::SERVER_SHUTDOWN;
+use crate::framing::utils::assert_connection_type;
+use crate::io::{get_u16, get_u32, get_u32_string};
+use crate::{Frame, FrameDecodeError, PutU32String, TcpFrame};
+use bytes::BufMut;
+use std::io::Cursor;
+
+/// Sent by the server before it goes down.
+/// Existing remote connections keep working for `deadline` seconds, new ones are refused.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ServerShutdown {
+    reason: String,
+    deadline: u32,
+}
+
+impl ServerShutdown {
+    pub fn new(reason: &str, deadline: &u32) -> Self {
+        Self {
+            reason: String::from(reason),
+            deadline: *deadline,
+        }
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    /// seconds until the server closes the connection.
+    pub fn deadline(&self) -> &u32 {
+        &self.deadline
+    }
+}
+
+impl From<ServerShutdown> for TcpFrame {
+    fn from(value: ServerShutdown) -> Self {
+        TcpFrame::ServerShutdown(value)
+    }
+}
+
+impl Frame for ServerShutdown {
+    fn decode(buffer: &mut Cursor<&[u8]>) -> Result<Self, FrameDecodeError>
+    where
+        Self: Sized,
+    {
+        assert_connection_type(&get_u16(buffer)?, &SERVER_SHUTDOWN)?;
+
+        let reason = get_u32_string(buffer)?;
+        let deadline = get_u32(buffer)?;
+
+        Ok(Self { reason, deadline })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        buffer.put_u16(SERVER_SHUTDOWN);
+        buffer.put_u32_sized_str(&self.reason);
+        buffer.put_u32(self.deadline);
+
+        buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::framing::ServerShutdown;
+    use crate::{is_type, Frame, FrameDecodeError, TcpFrame};
+
+    #[test]
+    pub fn should_encode_and_decode_server_shutdown() {
+        // Arrange
+        let frame = ServerShutdown::new("server is restarting", &30);
+
+        // Act
+        let encoded = frame.encode();
+        let mut cursor = Cursor::new(&encoded[..]);
+        let result = TcpFrame::parse(&mut cursor).unwrap();
+
+        // Assert
+        match result {
+            TcpFrame::ServerShutdown(decoded) => assert_eq!(decoded, frame),
+            actual => panic!("expected ServerShutdown, got {}", actual),
+        }
+    }
+
+    #[test]
+    pub fn should_return_incomplete_when_deadline_is_missing() {
+        // Arrange
+        let encoded = ServerShutdown::new("server is restarting", &30).encode();
+        let mut cursor = Cursor::new(&encoded[..encoded.len() - 4]);
+
+        // Act
+        let result = ServerShutdown::decode(&mut cursor);
+
+        // Assert
+        assert!(is_type!(result.unwrap_err(), FrameDecodeError::Incomplete));
+    }
+}
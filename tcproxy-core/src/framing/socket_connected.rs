@@ -1,25 +1,63 @@
 use crate::framing::frame_types::SOCKET_CONNECTED;
 use crate::framing::utils::assert_connection_type;
-use crate::io::{get_u16, get_u32};
-use crate::{Frame, FrameDecodeError};
+use crate::io::{get_socket_addr, get_u16, get_u32};
+use crate::{Frame, FrameDecodeError, PutSocketAddr};
 use bytes::BufMut;
 use std::io::Cursor;
+use std::net::SocketAddr;
 
+/// Sent by the server when a remote peer connects to one of the session tunnels.
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct SocketConnected {
     connection_id: u32,
+    listener_port: u16,
+    peer_addr: Option<SocketAddr>,
+    listener_addr: Option<SocketAddr>,
 }
 
 impl SocketConnected {
     pub fn new(connection_id: &u32) -> Self {
         Self {
             connection_id: *connection_id,
+            listener_port: 0,
+            peer_addr: None,
+            listener_addr: None,
         }
     }
 
+    /// port of the tunnel the peer connected to.
+    pub fn with_listener_port(mut self, port: &u16) -> Self {
+        self.listener_port = *port;
+        self
+    }
+
+    /// address of the remote peer that connected to the tunnel.
+    pub fn with_peer_addr(mut self, addr: &SocketAddr) -> Self {
+        self.peer_addr = Some(*addr);
+        self
+    }
+
+    /// public address the peer connected to.
+    pub fn with_listener_addr(mut self, addr: &SocketAddr) -> Self {
+        self.listener_addr = Some(*addr);
+        self
+    }
+
     pub fn connection_id(&self) -> &u32 {
         &self.connection_id
     }
+
+    pub fn listener_port(&self) -> &u16 {
+        &self.listener_port
+    }
+
+    pub fn peer_addr(&self) -> &Option<SocketAddr> {
+        &self.peer_addr
+    }
+
+    pub fn listener_addr(&self) -> &Option<SocketAddr> {
+        &self.listener_addr
+    }
 }
 
 impl Frame for SocketConnected {
@@ -30,7 +68,15 @@ impl Frame for SocketConnected {
         assert_connection_type(&get_u16(buffer)?, &SOCKET_CONNECTED)?;
 
         let connection_id = get_u32(buffer)?;
-        Ok(Self { connection_id })
+        let listener_port = get_u16(buffer)?;
+        let peer_addr = get_socket_addr(buffer)?;
+        let listener_addr = get_socket_addr(buffer)?;
+        Ok(Self {
+            connection_id,
+            listener_port,
+            peer_addr,
+            listener_addr,
+        })
     }
 
     fn encode(&self) -> Vec<u8> {
@@ -38,7 +84,71 @@ impl Frame for SocketConnected {
 
         final_buff.put_u16(SOCKET_CONNECTED);
         final_buff.put_u32(self.connection_id);
+        final_buff.put_u16(self.listener_port);
+        final_buff.put_socket_addr(&self.peer_addr);
+        final_buff.put_socket_addr(&self.listener_addr);
 
         final_buff
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::net::SocketAddr;
+    use std::str::FromStr;
+
+    use crate::framing::SocketConnected;
+    use crate::{is_type, Frame, FrameDecodeError, TcpFrame};
+
+    #[test]
+    pub fn should_encode_and_decode_listener_port() {
+        // Arrange
+        let frame = SocketConnected::new(&7).with_listener_port(&15080);
+
+        // Act
+        let encoded = frame.encode();
+        let mut cursor = Cursor::new(&encoded[..]);
+        let result = TcpFrame::parse(&mut cursor).unwrap();
+
+        // Assert
+        match result {
+            TcpFrame::SocketConnected(decoded) => assert_eq!(decoded, frame),
+            actual => panic!("expected SocketConnected, got {}", actual),
+        }
+    }
+
+    #[test]
+    pub fn should_encode_and_decode_peer_and_listener_addresses() {
+        // Arrange
+        let peer_addr = SocketAddr::from_str("[2001:db8::7]:52344").unwrap();
+        let listener_addr = SocketAddr::from_str("203.0.113.5:15080").unwrap();
+        let frame = SocketConnected::new(&7)
+            .with_listener_port(&15080)
+            .with_peer_addr(&peer_addr)
+            .with_listener_addr(&listener_addr);
+
+        // Act
+        let encoded = frame.encode();
+        let mut cursor = Cursor::new(&encoded[..]);
+        let result = SocketConnected::decode(&mut cursor).unwrap();
+
+        // Assert
+        assert_eq!(result.peer_addr(), &Some(peer_addr));
+        assert_eq!(result.listener_addr(), &Some(listener_addr));
+    }
+
+    #[test]
+    pub fn should_return_incomplete_when_address_is_cut() {
+        // Arrange
+        let peer_addr = SocketAddr::from_str("198.51.100.20:40000").unwrap();
+        let encoded = SocketConnected::new(&7).with_peer_addr(&peer_addr).encode();
+        let mut cursor = Cursor::new(&encoded[..encoded.len() - 2]);
+
+        // Act
+        let result = SocketConnected::decode(&mut cursor);
+
+        // Assert
+        assert!(is_type!(result.unwrap_err(), FrameDecodeError::Incomplete));
+    }
+}
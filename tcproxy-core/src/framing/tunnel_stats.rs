@@ -0,0 +1,137 @@
+use crate::framing::frame_types::TUNNEL_STATS;
+use crate::framing::utils::assert_connection_type;
+use crate::io::{get_u16, get_u64};
+use crate::{Frame, FrameDecodeError, TcpFrame};
+use bytes::BufMut;
+use std::io::Cursor;
+
+/// Periodically sent by the server with the current throughput of the tunnel.
+/// `rate_limit` is the limit (bytes per second) being enforced, if any.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct TunnelStats {
+    bytes_in_per_second: u64,
+    bytes_out_per_second: u64,
+    total_bytes_in: u64,
+    total_bytes_out: u64,
+    rate_limit: Option<u64>,
+}
+
+impl TunnelStats {
+    pub fn new(
+        bytes_in_per_second: &u64,
+        bytes_out_per_second: &u64,
+        total_bytes_in: &u64,
+        total_bytes_out: &u64,
+        rate_limit: &Option<u64>,
+    ) -> Self {
+        Self {
+            bytes_in_per_second: *bytes_in_per_second,
+            bytes_out_per_second: *bytes_out_per_second,
+            total_bytes_in: *total_bytes_in,
+            total_bytes_out: *total_bytes_out,
+            rate_limit: *rate_limit,
+        }
+    }
+
+    pub fn bytes_in_per_second(&self) -> &u64 {
+        &self.bytes_in_per_second
+    }
+
+    pub fn bytes_out_per_second(&self) -> &u64 {
+        &self.bytes_out_per_second
+    }
+
+    pub fn total_bytes_in(&self) -> &u64 {
+        &self.total_bytes_in
+    }
+
+    pub fn total_bytes_out(&self) -> &u64 {
+        &self.total_bytes_out
+    }
+
+    pub fn rate_limit(&self) -> &Option<u64> {
+        &self.rate_limit
+    }
+}
+
+impl From<TunnelStats> for TcpFrame {
+    fn from(value: TunnelStats) -> Self {
+        TcpFrame::TunnelStats(value)
+    }
+}
+
+impl Frame for TunnelStats {
+    fn decode(buffer: &mut Cursor<&[u8]>) -> Result<Self, FrameDecodeError>
+    where
+        Self: Sized,
+    {
+        assert_connection_type(&get_u16(buffer)?, &TUNNEL_STATS)?;
+
+        let bytes_in_per_second = get_u64(buffer)?;
+        let bytes_out_per_second = get_u64(buffer)?;
+        let total_bytes_in = get_u64(buffer)?;
+        let total_bytes_out = get_u64(buffer)?;
+        let rate_limit = match get_u64(buffer)? {
+            0 => None,
+            value => Some(value),
+        };
+
+        Ok(Self {
+            bytes_in_per_second,
+            bytes_out_per_second,
+            total_bytes_in,
+            total_bytes_out,
+            rate_limit,
+        })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        buffer.put_u16(TUNNEL_STATS);
+        buffer.put_u64(self.bytes_in_per_second);
+        buffer.put_u64(self.bytes_out_per_second);
+        buffer.put_u64(self.total_bytes_in);
+        buffer.put_u64(self.total_bytes_out);
+        buffer.put_u64(self.rate_limit.unwrap_or(0));
+
+        buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::framing::TunnelStats;
+    use crate::{is_type, Frame, FrameDecodeError, TcpFrame};
+
+    #[test]
+    pub fn should_encode_and_decode_tunnel_stats() {
+        // Arrange
+        let frame = TunnelStats::new(&1024, &2048, &10_000, &20_000, &Some(4096));
+
+        // Act
+        let encoded = frame.encode();
+        let mut cursor = Cursor::new(&encoded[..]);
+        let result = TcpFrame::parse(&mut cursor).unwrap();
+
+        // Assert
+        match result {
+            TcpFrame::TunnelStats(decoded) => assert_eq!(decoded, frame),
+            actual => panic!("expected TunnelStats, got {}", actual),
+        }
+    }
+
+    #[test]
+    pub fn should_return_incomplete() {
+        // Arrange
+        let encoded = TunnelStats::default().encode();
+        let mut cursor = Cursor::new(&encoded[..encoded.len() - 1]);
+
+        // Act
+        let result = TunnelStats::decode(&mut cursor);
+
+        // Assert
+        assert!(is_type!(result.unwrap_err(), FrameDecodeError::Incomplete));
+    }
+}
@@ -0,0 +1,136 @@
+use crate::framing::frame_types::USAGE_REPORT;
+use crate::framing::utils::assert_connection_type;
+use crate::io::{get_u16, get_u32_string, get_u64};
+use crate::{Frame, FrameDecodeError, PutU32String, TcpFrame};
+use bytes::BufMut;
+use std::io::Cursor;
+
+/// Sent by the server in response to `UsageRequest`.
+/// `period` is the month (YYYY-MM) the usage refers to, `quota` is the monthly
+/// limit of bytes (in + out) for the account, if any.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct UsageReport {
+    period: String,
+    bytes_in: u64,
+    bytes_out: u64,
+    quota: Option<u64>,
+}
+
+impl UsageReport {
+    pub fn new(period: &str, bytes_in: &u64, bytes_out: &u64, quota: &Option<u64>) -> Self {
+        Self {
+            period: String::from(period),
+            bytes_in: *bytes_in,
+            bytes_out: *bytes_out,
+            quota: *quota,
+        }
+    }
+
+    pub fn period(&self) -> &str {
+        &self.period
+    }
+
+    pub fn bytes_in(&self) -> &u64 {
+        &self.bytes_in
+    }
+
+    pub fn bytes_out(&self) -> &u64 {
+        &self.bytes_out
+    }
+
+    pub fn quota(&self) -> &Option<u64> {
+        &self.quota
+    }
+}
+
+impl From<UsageReport> for TcpFrame {
+    fn from(value: UsageReport) -> Self {
+        TcpFrame::UsageReport(value)
+    }
+}
+
+impl Frame for UsageReport {
+    fn decode(buffer: &mut Cursor<&[u8]>) -> Result<Self, FrameDecodeError>
+    where
+        Self: Sized,
+    {
+        assert_connection_type(&get_u16(buffer)?, &USAGE_REPORT)?;
+
+        let period = get_u32_string(buffer)?;
+        let bytes_in = get_u64(buffer)?;
+        let bytes_out = get_u64(buffer)?;
+        let quota = match get_u64(buffer)? {
+            0 => None,
+            value => Some(value),
+        };
+
+        Ok(Self {
+            period,
+            bytes_in,
+            bytes_out,
+            quota,
+        })
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        buffer.put_u16(USAGE_REPORT);
+        buffer.put_u32_sized_str(&self.period);
+        buffer.put_u64(self.bytes_in);
+        buffer.put_u64(self.bytes_out);
+        buffer.put_u64(self.quota.unwrap_or(0));
+
+        buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::framing::{UsageReport, UsageRequest};
+    use crate::{is_type, Frame, FrameDecodeError, TcpFrame};
+
+    #[test]
+    pub fn should_encode_and_decode_usage_report() {
+        // Arrange
+        let frame = UsageReport::new("2026-10", &1024, &2048, &Some(1024 * 1024));
+
+        // Act
+        let encoded = frame.encode();
+        let mut cursor = Cursor::new(&encoded[..]);
+        let result = TcpFrame::parse(&mut cursor).unwrap();
+
+        // Assert
+        match result {
+            TcpFrame::UsageReport(decoded) => assert_eq!(decoded, frame),
+            actual => panic!("expected UsageReport, got {}", actual),
+        }
+    }
+
+    #[test]
+    pub fn should_parse_usage_request() {
+        // Arrange
+        let encoded = UsageRequest::new().encode();
+        let mut cursor = Cursor::new(&encoded[..]);
+
+        // Act
+        let result = TcpFrame::parse(&mut cursor).unwrap();
+
+        // Assert
+        assert!(is_type!(result, TcpFrame::UsageRequest(_)));
+    }
+
+    #[test]
+    pub fn should_return_incomplete_when_quota_is_missing() {
+        // Arrange
+        let encoded = UsageReport::new("2026-10", &1, &2, &None).encode();
+        let mut cursor = Cursor::new(&encoded[..encoded.len() - 8]);
+
+        // Act
+        let result = UsageReport::decode(&mut cursor);
+
+        // Assert
+        assert!(is_type!(result.unwrap_err(), FrameDecodeError::Incomplete));
+    }
+}
@@ -0,0 +1,39 @@
+use crate::framing::frame_types::USAGE_REQUEST;
+use crate::framing::utils::assert_connection_type;
+use crate::io::get_u16;
+use crate::{Frame, FrameDecodeError, TcpFrame};
+use bytes::BufMut;
+use std::io::Cursor;
+
+/// Sent by the client asking for the traffic usage of the authenticated account.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct UsageRequest;
+
+impl UsageRequest {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl From<UsageRequest> for TcpFrame {
+    fn from(value: UsageRequest) -> Self {
+        TcpFrame::UsageRequest(value)
+    }
+}
+
+impl Frame for UsageRequest {
+    fn decode(buffer: &mut Cursor<&[u8]>) -> Result<Self, FrameDecodeError>
+    where
+        Self: Sized,
+    {
+        assert_connection_type(&get_u16(buffer)?, &USAGE_REQUEST)?;
+        Ok(Self)
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        buffer.put_u16(USAGE_REQUEST);
+
+        buffer
+    }
+}
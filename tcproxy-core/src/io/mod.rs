@@ -1,6 +1,7 @@
 use crate::FrameDecodeError;
 use bytes::Buf;
 use std::io::{Cursor, Read};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 
 fn check_cursor_size<T>(src: &mut Cursor<&[u8]>) -> Result<(), FrameDecodeError>
 where
@@ -49,3 +50,37 @@ pub fn get_i64(src: &mut Cursor<&[u8]>) -> Result<i64, FrameDecodeError> {
     check_cursor_size::<i64>(src)?;
     Ok(src.get_i64())
 }
+
+pub fn get_u64(src: &mut Cursor<&[u8]>) -> Result<u64, FrameDecodeError> {
+    check_cursor_size::<u64>(src)?;
+    Ok(src.get_u64())
+}
+
+/// reads an ip address prefixed by its version (4 or 6).
+pub fn get_ip_addr(src: &mut Cursor<&[u8]>) -> Result<IpAddr, FrameDecodeError> {
+    let version = get_u8(src)?;
+    get_ip_addr_octets(src, &version)
+}
+
+/// reads an ip address followed by its port, version 0 means there is no address.
+pub fn get_socket_addr(src: &mut Cursor<&[u8]>) -> Result<Option<SocketAddr>, FrameDecodeError> {
+    let ip = match get_u8(src)? {
+        0 => return Ok(None),
+        version => get_ip_addr_octets(src, &version)?,
+    };
+
+    Ok(Some(SocketAddr::new(ip, get_u16(src)?)))
+}
+
+fn get_ip_addr_octets(src: &mut Cursor<&[u8]>, version: &u8) -> Result<IpAddr, FrameDecodeError> {
+    match version {
+        4 => Ok(IpAddr::V4(Ipv4Addr::from(get_u32(src)?))),
+        6 => {
+            let buffer: [u8; 16] = get_buffer(src, 16)?
+                .try_into()
+                .map_err(|_| FrameDecodeError::CorruptedFrame)?;
+            Ok(IpAddr::V6(Ipv6Addr::from(buffer)))
+        }
+        _ => Err(FrameDecodeError::CorruptedFrame),
+    }
+}
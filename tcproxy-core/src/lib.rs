@@ -14,6 +14,7 @@ pub mod transport;
 use bytes::{Buf, BufMut};
 use mongodb::bson::Uuid;
 use std::io::{Cursor, Read};
+use std::net::{IpAddr, SocketAddr};
 
 pub use command::*;
 pub use frame_error::*;
@@ -34,6 +35,14 @@ pub trait PutBsonUuid: BufMut {
     fn put_bson_uuid(&mut self, value: &Uuid);
 }
 
+pub trait PutIpAddr: BufMut {
+    fn put_ip_addr(&mut self, value: &IpAddr);
+}
+
+pub trait PutSocketAddr: BufMut {
+    fn put_socket_addr(&mut self, value: &Option<SocketAddr>);
+}
+
 pub trait ReadBsonUuid: Read {
     fn read_bson_uuid(&mut self) -> std::io::Result<Uuid>;
 }
@@ -67,6 +76,35 @@ impl PutBsonUuid for Vec<u8> {
     }
 }
 
+impl PutSocketAddr for Vec<u8> {
+    /// writes the ip address followed by the port, or a single 0 when there is no address.
+    fn put_socket_addr(&mut self, value: &Option<SocketAddr>) {
+        match value {
+            Some(addr) => {
+                self.put_ip_addr(&addr.ip());
+                self.put_u16(addr.port());
+            }
+            None => self.put_u8(0),
+        }
+    }
+}
+
+impl PutIpAddr for Vec<u8> {
+    /// writes the ip version (4 or 6) followed by the address octets.
+    fn put_ip_addr(&mut self, value: &IpAddr) {
+        match value {
+            IpAddr::V4(ip) => {
+                self.put_u8(4);
+                self.put_slice(&ip.octets());
+            }
+            IpAddr::V6(ip) => {
+                self.put_u8(6);
+                self.put_slice(&ip.octets());
+            }
+        }
+    }
+}
+
 impl PutU32String for Vec<u8> {
     fn put_u32_sized_str(&mut self, value: &str) {
         self.put_u32(value.len() as u32);
@@ -23,6 +23,12 @@ impl AsyncStream for TcpStream {}
 
 impl AsyncStream for TlsStream<TcpStream> {}
 
+#[cfg(feature = "rustls")]
+impl AsyncStream for tokio_rustls::server::TlsStream<TcpStream> {}
+
+#[cfg(feature = "rustls")]
+impl AsyncStream for tokio_rustls::client::TlsStream<TcpStream> {}
+
 pub struct Stream {
     inner: Box<dyn AsyncStream + Send + 'static>,
 }
@@ -98,6 +104,12 @@ impl Inner {
     }
 }
 
+// SAFETY: the stream is only reached through an `InnerGuard`, which `poll_lock` hands out
+// after taking `lock` and which releases it on drop, so a single half at a time gets a
+// mutable reference to it. the boxed stream itself is `Send`.
+unsafe impl Send for Inner {}
+unsafe impl Sync for Inner {}
+
 impl InnerGuard<'_> {
     fn stream_pin(&mut self) -> Pin<&mut Stream> {
         unsafe { Pin::new_unchecked(&mut *self.inner.stream.get()) }
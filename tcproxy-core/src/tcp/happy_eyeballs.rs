@@ -0,0 +1,111 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::task::JoinSet;
+use tracing::debug;
+
+use crate::Result;
+
+/// delay before racing the next address while earlier attempts are pending (RFC 8305).
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// connects to the first of `addrs` to answer, alternating address families and
+/// starting a new attempt every 250ms, or right away when the previous one fails.
+pub async fn connect_happy_eyeballs(addrs: &[SocketAddr]) -> Result<TcpStream> {
+    let mut remaining = interleave_families(addrs).into_iter().peekable();
+    let mut attempts = JoinSet::new();
+    let mut last_error = String::from("no addresses to connect to");
+
+    loop {
+        if let Some(addr) = remaining.next() {
+            attempts.spawn(async move {
+                TcpStream::connect(addr)
+                    .await
+                    .map_err(|err| format!("{}: {}", addr, err))
+            });
+        }
+
+        let next = match remaining.peek() {
+            Some(_) => {
+                match tokio::time::timeout(CONNECTION_ATTEMPT_DELAY, attempts.join_next()).await {
+                    Ok(next) => next,
+                    Err(_) => continue,
+                }
+            }
+            None => attempts.join_next().await,
+        };
+
+        match next {
+            Some(Ok(Ok(stream))) => return Ok(stream),
+            Some(Ok(Err(err))) => {
+                debug!("connection attempt to {} failed", err);
+                last_error = err;
+            }
+            Some(Err(err)) => last_error = err.to_string(),
+            None => return Err(format!("failed to connect: {}", last_error).into()),
+        }
+    }
+}
+
+/// orders `addrs` alternating between IPv6 and IPv4, starting with the family of the first one.
+fn interleave_families(addrs: &[SocketAddr]) -> Vec<SocketAddr> {
+    let first_is_ipv6 = addrs.first().map(|addr| addr.is_ipv6()).unwrap_or(false);
+    let (preferred, other): (Vec<SocketAddr>, Vec<SocketAddr>) = addrs
+        .iter()
+        .partition(|addr| addr.is_ipv6() == first_is_ipv6);
+
+    let mut result = Vec::with_capacity(addrs.len());
+    for i in 0..preferred.len().max(other.len()) {
+        result.extend(preferred.get(i));
+        result.extend(other.get(i));
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::str::FromStr;
+
+    use super::{connect_happy_eyeballs, interleave_families};
+
+    #[test]
+    pub fn should_alternate_address_families() {
+        // Arrange
+        let addrs: Vec<SocketAddr> = ["[::1]:80", "[::2]:80", "127.0.0.1:80", "127.0.0.2:80"]
+            .iter()
+            .map(|addr| SocketAddr::from_str(addr).unwrap())
+            .collect();
+
+        // Act
+        let result = interleave_families(&addrs);
+
+        // Assert
+        assert_eq!(result, vec![addrs[0], addrs[2], addrs[1], addrs[3]]);
+    }
+
+    #[tokio::test]
+    async fn should_fall_back_to_next_address() {
+        // Arrange
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let closed_addr = closed.local_addr().unwrap();
+        drop(closed);
+
+        // Act
+        let result = connect_happy_eyeballs(&[closed_addr, listener.local_addr().unwrap()]).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_fail_without_addresses() {
+        // Act
+        let result = connect_happy_eyeballs(&[]).await;
+
+        // Assert
+        assert!(result.is_err());
+    }
+}
@@ -1,8 +1,12 @@
+mod happy_eyeballs;
+mod network;
 mod socket_connection;
 mod socket_listener;
 mod stream_reader;
 mod tcp_listener;
 
+pub use happy_eyeballs::*;
+pub use network::*;
 pub use socket_connection::*;
 pub use socket_listener::*;
 pub use stream_reader::*;
@@ -0,0 +1,39 @@
+use ipnet::IpNet;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use crate::Result;
+
+/// parses a network in cidr notation, bare ip addresses are treated as a single host.
+pub fn parse_network(value: &str) -> Result<IpNet> {
+    if let Ok(network) = IpNet::from_str(value) {
+        return Ok(network);
+    }
+
+    match IpAddr::from_str(value) {
+        Ok(ip) => Ok(IpNet::from(ip)),
+        Err(_) => Err(format!(
+            "invalid network {}, expected CIDR notation or an IP address",
+            value
+        )
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_network;
+
+    #[test]
+    fn should_parse_cidr_and_bare_addresses() {
+        // Act
+        let cidr = parse_network("10.0.0.0/8").unwrap();
+        let host = parse_network("fd00::12").unwrap();
+        let invalid = parse_network("10.0.0.0/33");
+
+        // Assert
+        assert_eq!(cidr.to_string(), "10.0.0.0/8");
+        assert_eq!(host.to_string(), "fd00::12/128");
+        assert!(invalid.is_err());
+    }
+}
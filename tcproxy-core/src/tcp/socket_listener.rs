@@ -1,9 +1,9 @@
 use std::{fmt::Debug, net::SocketAddr};
 
+use crate::tls::ServerTls;
 use crate::Result;
 use async_trait::async_trait;
 use mockall::automock;
-use tokio_native_tls::native_tls::Identity;
 
 use super::RemoteConnection;
 
@@ -11,7 +11,7 @@ use super::RemoteConnection;
 #[async_trait]
 pub trait SocketListener: Debug + Sync + Send {
     /// Creates a new SocketListener, which will be bound to the specific address.
-    async fn bind(addr: SocketAddr, identity: Option<Identity>) -> Result<Self>
+    async fn bind(addr: SocketAddr, tls: Option<ServerTls>) -> Result<Self>
     where
         Self: Sized;
 
@@ -19,4 +19,9 @@ pub trait SocketListener: Debug + Sync + Send {
     async fn accept(&self) -> Result<RemoteConnection>;
 
     fn listen_ip(&self) -> Result<SocketAddr>;
+
+    /// Replaces the tls certificate used for connections accepted from now on.
+    fn set_tls(&self, _tls: Option<ServerTls>) -> Result<()> {
+        Err("listener does not support replacing its tls certificate".into())
+    }
 }
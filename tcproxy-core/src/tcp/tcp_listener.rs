@@ -1,25 +1,53 @@
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::RwLock;
 
 use async_trait::async_trait;
+use socket2::{Domain, Protocol, Socket, Type};
 use tokio::net::TcpListener as TokioTcpListener;
-use tokio_native_tls::native_tls::{Identity, TlsAcceptor};
-use tokio_native_tls::TlsAcceptor as TokioTlsAcceptor;
-use tracing::error;
+use tracing::{error, warn};
 
 use crate::stream::Stream;
 use crate::tcp::SocketListener;
+use crate::tls::{PeerCertificate, ServerTls, TlsAcceptor};
 use crate::Result;
 
-#[derive(Debug)]
 pub struct TcpListener {
     inner: TokioTcpListener,
-    acceptor: Option<Arc<TokioTlsAcceptor>>,
+    acceptor: RwLock<Option<TlsAcceptor>>,
+}
+
+impl std::fmt::Debug for TcpListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TcpListener")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+fn create_acceptor(tls: Option<ServerTls>) -> Result<Option<TlsAcceptor>> {
+    match tls {
+        None => Ok(None),
+        Some(tls) => Ok(Some(TlsAcceptor::new(tls)?)),
+    }
+}
+
+/// binds to every ipv6 and ipv4 interface, so `[::]` listeners accept both families.
+fn bind_dual_stack(addr: SocketAddr) -> Result<TokioTcpListener> {
+    let socket = Socket::new(Domain::IPV6, Type::STREAM, Some(Protocol::TCP))?;
+    socket.set_only_v6(false)?;
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+
+    Ok(TokioTcpListener::from_std(socket.into())?)
 }
 
 pub struct RemoteConnection {
     pub stream: Stream,
     remote_addr: SocketAddr,
+    local_addr: Option<SocketAddr>,
+    peer_certificate: Option<PeerCertificate>,
 }
 
 impl RemoteConnection {
@@ -27,29 +55,50 @@ impl RemoteConnection {
         Self {
             stream,
             remote_addr,
+            local_addr: None,
+            peer_certificate: None,
         }
     }
 
+    /// address of the listener the peer connected to.
+    pub fn with_local_addr(mut self, local_addr: Option<SocketAddr>) -> Self {
+        self.local_addr = local_addr;
+        self
+    }
+
+    pub fn with_peer_certificate(mut self, peer_certificate: Option<PeerCertificate>) -> Self {
+        self.peer_certificate = peer_certificate;
+        self
+    }
+
     pub fn remote_addr(&self) -> &SocketAddr {
         &self.remote_addr
     }
+
+    pub fn local_addr(&self) -> Option<&SocketAddr> {
+        self.local_addr.as_ref()
+    }
+
+    /// certificate the client authenticated with during the tls handshake.
+    pub fn peer_certificate(&self) -> Option<&PeerCertificate> {
+        self.peer_certificate.as_ref()
+    }
 }
 
 #[async_trait]
 impl SocketListener for TcpListener {
-    async fn bind(addr: SocketAddr, identity: Option<Identity>) -> Result<Self>
+    async fn bind(addr: SocketAddr, tls: Option<ServerTls>) -> Result<Self>
     where
         Self: Sized,
     {
+        let inner = match addr {
+            SocketAddr::V6(v6) if v6.ip().is_unspecified() => bind_dual_stack(addr)?,
+            _ => TokioTcpListener::bind(addr).await?,
+        };
+
         Ok(TcpListener {
-            inner: TokioTcpListener::bind(addr).await?,
-            acceptor: match identity {
-                None => None,
-                Some(identity) => {
-                    let internal_acceptor = TlsAcceptor::new(identity)?;
-                    Some(Arc::new(TokioTlsAcceptor::from(internal_acceptor)))
-                }
-            },
+            inner,
+            acceptor: RwLock::new(create_acceptor(tls)?),
         })
     }
 
@@ -58,15 +107,28 @@ impl SocketListener for TcpListener {
         loop {
             match self.inner.accept().await {
                 Ok((stream, addr)) => {
-                    let result = match &self.acceptor {
-                        None => Stream::new(stream),
-                        Some(acceptor) => Stream::new(acceptor.accept(stream).await?),
+                    // ipv4 clients of a dual stack listener show up as ipv4-mapped ipv6 addresses.
+                    let addr = SocketAddr::new(addr.ip().to_canonical(), addr.port());
+                    let local_addr = stream
+                        .local_addr()
+                        .ok()
+                        .map(|local| SocketAddr::new(local.ip().to_canonical(), local.port()));
+                    let acceptor = self.acceptor.read().unwrap().clone();
+                    let (stream, peer_certificate) = match acceptor {
+                        None => (Stream::new(stream), None),
+                        Some(acceptor) => match acceptor.accept(stream).await {
+                            Ok(accepted) => accepted,
+                            Err(err) => {
+                                // a single client failing the handshake must not stop the listener.
+                                warn!("tls handshake with {} failed: {}", addr, err);
+                                continue;
+                            }
+                        },
                     };
 
-                    return Ok(RemoteConnection {
-                        stream: result,
-                        remote_addr: addr,
-                    });
+                    return Ok(RemoteConnection::new(stream, addr)
+                        .with_local_addr(local_addr)
+                        .with_peer_certificate(peer_certificate));
                 }
                 Err(err) => {
                     error!(
@@ -88,4 +150,10 @@ impl SocketListener for TcpListener {
     fn listen_ip(&self) -> Result<SocketAddr> {
         Ok(self.inner.local_addr()?)
     }
+
+    fn set_tls(&self, tls: Option<ServerTls>) -> Result<()> {
+        let acceptor = create_acceptor(tls)?;
+        *self.acceptor.write().unwrap() = acceptor;
+        Ok(())
+    }
 }
@@ -26,6 +26,10 @@ pub enum TcpFrame {
     ClientConnectedAck(ClientConnectedAck),
     ClientConnected(ClientConnected),
     SocketDisconnected(SocketDisconnected),
+    TunnelStats(TunnelStats),
+    UsageRequest(UsageRequest),
+    UsageReport(UsageReport),
+    ServerShutdown(ServerShutdown),
 }
 
 impl TcpFrame {
@@ -48,7 +52,13 @@ impl TcpFrame {
             DATA_PACKET => TcpFrame::DataPacket(DataPacket::decode(cursor)?),
             AUTHENTICATE => TcpFrame::Authenticate(Authenticate::decode(cursor)?),
             AUTHENTICATE_ACK => TcpFrame::AuthenticateAck(AuthenticateAck::decode(cursor)?),
-            SOCKET_DISCONNECTED => TcpFrame::SocketDisconnected(SocketDisconnected::decode(cursor)?),
+            SOCKET_DISCONNECTED => {
+                TcpFrame::SocketDisconnected(SocketDisconnected::decode(cursor)?)
+            }
+            TUNNEL_STATS => TcpFrame::TunnelStats(TunnelStats::decode(cursor)?),
+            USAGE_REQUEST => TcpFrame::UsageRequest(UsageRequest::decode(cursor)?),
+            USAGE_REPORT => TcpFrame::UsageReport(UsageReport::decode(cursor)?),
+            SERVER_SHUTDOWN => TcpFrame::ServerShutdown(ServerShutdown::decode(cursor)?),
             actual => return Err(format!("proto error. invalid frame type. {}", actual).into()),
         };
 
@@ -68,6 +78,10 @@ impl TcpFrame {
             TcpFrame::SocketDisconnected(data) => data.encode(),
             TcpFrame::Error(data) => data.encode(),
             TcpFrame::DataPacket(data) => data.encode(),
+            TcpFrame::TunnelStats(data) => data.encode(),
+            TcpFrame::UsageRequest(data) => data.encode(),
+            TcpFrame::UsageReport(data) => data.encode(),
+            TcpFrame::ServerShutdown(data) => data.encode(),
         };
 
         BytesMut::from(&buffer[..])
@@ -80,15 +94,9 @@ impl Display for TcpFrame {
             TcpFrame::ClientConnected(_) => "ClientConnected".to_string(),
             TcpFrame::Ping(_) => "Ping".to_string(),
             TcpFrame::Pong(_) => "Pong".to_string(),
-            TcpFrame::Authenticate(_) => {
-                "Authenticate".to_string()
-            }
-            TcpFrame::AuthenticateAck(_) => {
-                "AuthenticateAck".to_string()
-            }
-            TcpFrame::ClientConnectedAck(_) => {
-                "ClientConnectedACK".to_string()
-            }
+            TcpFrame::Authenticate(_) => "Authenticate".to_string(),
+            TcpFrame::AuthenticateAck(_) => "AuthenticateAck".to_string(),
+            TcpFrame::ClientConnectedAck(_) => "ClientConnectedACK".to_string(),
             TcpFrame::SocketConnected(data) => {
                 format!("IncomingSocket ({})", data.connection_id())
             }
@@ -105,6 +113,10 @@ impl Display for TcpFrame {
             TcpFrame::Error(data) => {
                 format!("Error[reason = {}]", data.reason())
             }
+            TcpFrame::TunnelStats(_) => "TunnelStats".to_string(),
+            TcpFrame::UsageRequest(_) => "UsageRequest".to_string(),
+            TcpFrame::UsageReport(data) => format!("UsageReport ({})", data.period()),
+            TcpFrame::ServerShutdown(data) => format!("ServerShutdown ({})", data.reason()),
         };
 
         let msg = format!("tcpframe: {}", data_type);
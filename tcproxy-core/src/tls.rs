@@ -1,9 +1,355 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
 use tokio::net::TcpStream;
-use tokio_native_tls::TlsAcceptor;
+use tokio_native_tls::native_tls::{self, Identity};
+use tokio_native_tls::TlsAcceptor as NativeTlsAcceptor;
+use tokio_native_tls::TlsConnector as NativeTlsConnector;
+use tracing::warn;
 
 use crate::stream::Stream;
+use crate::Result;
+
+/// Certificate used by a listener to accept tls connections.
+#[derive(Clone)]
+pub enum ServerTls {
+    /// pkcs12 identity, served with native-tls.
+    Native(Identity),
+
+    /// PEM certificate chain and key, served with rustls.
+    #[cfg(feature = "rustls")]
+    Rustls(Arc<tokio_rustls::rustls::ServerConfig>),
+}
+
+impl std::fmt::Debug for ServerTls {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServerTls::Native(_) => write!(f, "ServerTls::Native"),
+            #[cfg(feature = "rustls")]
+            ServerTls::Rustls(_) => write!(f, "ServerTls::Rustls"),
+        }
+    }
+}
+
+/// Accepts tls connections with the backend matching the given `ServerTls`.
+#[derive(Clone)]
+pub enum TlsAcceptor {
+    Native(Arc<NativeTlsAcceptor>),
+    #[cfg(feature = "rustls")]
+    Rustls(tokio_rustls::TlsAcceptor),
+}
+
+impl TlsAcceptor {
+    pub fn new(tls: ServerTls) -> Result<Self> {
+        match tls {
+            ServerTls::Native(identity) => {
+                let acceptor = native_tls::TlsAcceptor::new(identity)?;
+                Ok(TlsAcceptor::Native(Arc::new(NativeTlsAcceptor::from(
+                    acceptor,
+                ))))
+            }
+            #[cfg(feature = "rustls")]
+            ServerTls::Rustls(config) => Ok(TlsAcceptor::Rustls(config.into())),
+        }
+    }
+
+    /// completes the handshake, returning the certificate the client presented, if any.
+    pub async fn accept(&self, stream: TcpStream) -> Result<(Stream, Option<PeerCertificate>)> {
+        match self {
+            TlsAcceptor::Native(acceptor) => {
+                Ok((Stream::new(acceptor.accept(stream).await?), None))
+            }
+            #[cfg(feature = "rustls")]
+            TlsAcceptor::Rustls(acceptor) => {
+                let stream = acceptor.accept(stream).await?;
+                let peer_certificate = stream
+                    .get_ref()
+                    .1
+                    .peer_certificates()
+                    .and_then(|certificates| certificates.first())
+                    .map(|certificate| PeerCertificate::from_der(&certificate.0));
+
+                Ok((Stream::new(stream), peer_certificate))
+            }
+        }
+    }
+}
+
+/// Certificate presented by a client during the tls handshake, already verified against the client CA.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerCertificate {
+    fingerprint: String,
+    subject: Option<String>,
+}
+
+impl PeerCertificate {
+    pub fn new(fingerprint: &str, subject: Option<&str>) -> Self {
+        Self {
+            fingerprint: String::from(fingerprint),
+            subject: subject.map(String::from),
+        }
+    }
+
+    #[cfg(feature = "rustls")]
+    pub fn from_der(der: &[u8]) -> Self {
+        use x509_parser::prelude::{FromDer, X509Certificate};
+
+        let subject = X509Certificate::from_der(der)
+            .ok()
+            .map(|(_, certificate)| certificate.subject().to_string());
+
+        Self {
+            fingerprint: certificate_fingerprint(der),
+            subject,
+        }
+    }
+
+    /// sha256 fingerprint, as lowercase hex.
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    /// distinguished name of the subject, e.g. `CN=build-agent, O=acme`.
+    pub fn subject(&self) -> Option<&str> {
+        self.subject.as_deref()
+    }
+}
+
+/// How the client verifies the certificate of the server it connects to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientTls {
+    domain: String,
+    ca_bundle: Option<PathBuf>,
+    pinned_fingerprint: Option<String>,
+    client_identity: Option<(PathBuf, PathBuf)>,
+    insecure: bool,
+}
+
+impl ClientTls {
+    /// `domain` is sent as SNI and must match the server certificate.
+    pub fn new(domain: &str) -> Self {
+        Self {
+            domain: String::from(domain),
+            ca_bundle: None,
+            pinned_fingerprint: None,
+            client_identity: None,
+            insecure: false,
+        }
+    }
+
+    /// presents the PEM certificate and pkcs8 key to servers requesting client certificates.
+    pub fn with_client_identity(mut self, certificate_path: &Path, key_path: &Path) -> Self {
+        self.client_identity = Some((certificate_path.to_path_buf(), key_path.to_path_buf()));
+        self
+    }
+
+    /// trusts the PEM certificates in `path`, besides the system roots.
+    pub fn with_ca_bundle(mut self, path: &Path) -> Self {
+        self.ca_bundle = Some(path.to_path_buf());
+        self
+    }
+
+    /// accepts only the certificate with the given sha256 fingerprint, even if self signed.
+    pub fn with_pinned_fingerprint(mut self, fingerprint: &str) -> Self {
+        self.pinned_fingerprint = Some(String::from(fingerprint));
+        self
+    }
+
+    /// skips certificate verification altogether.
+    pub fn with_insecure(mut self, insecure: bool) -> Self {
+        self.insecure = insecure;
+        self
+    }
+
+    pub fn get_domain(&self) -> &str {
+        &self.domain
+    }
+
+    pub async fn connect(&self, stream: TcpStream) -> Result<Stream> {
+        let mut builder = native_tls::TlsConnector::builder();
+        if let Some(path) = &self.ca_bundle {
+            let contents = std::fs::read_to_string(path)
+                .map_err(|err| format!("failed to read ca bundle {}: {}", path.display(), err))?;
+
+            for certificate in pem_certificates(&contents) {
+                builder.add_root_certificate(native_tls::Certificate::from_pem(
+                    certificate.as_bytes(),
+                )?);
+            }
+        }
+
+        if let Some((certificate_path, key_path)) = &self.client_identity {
+            let certificate = std::fs::read(certificate_path).map_err(|err| {
+                format!(
+                    "failed to read client certificate {}: {}",
+                    certificate_path.display(),
+                    err
+                )
+            })?;
+
+            let key = std::fs::read(key_path).map_err(|err| {
+                format!("failed to read client key {}: {}", key_path.display(), err)
+            })?;
+
+            builder.identity(Identity::from_pkcs8(&certificate, &key)?);
+        }
+
+        if self.insecure {
+            warn!("tls certificate verification is disabled");
+        }
+
+        // a pinned certificate is usually self signed, the fingerprint replaces chain verification.
+        if self.insecure || self.pinned_fingerprint.is_some() {
+            builder
+                .danger_accept_invalid_certs(true)
+                .danger_accept_invalid_hostnames(true);
+        }
+
+        let connector = NativeTlsConnector::from(builder.build()?);
+        let stream = connector.connect(&self.domain, stream).await?;
+
+        if let Some(pinned_fingerprint) = &self.pinned_fingerprint {
+            let certificate = stream
+                .get_ref()
+                .peer_certificate()?
+                .ok_or("server did not present a certificate")?;
+
+            let fingerprint = certificate_fingerprint(&certificate.to_der()?);
+            if fingerprint != parse_fingerprint(pinned_fingerprint)? {
+                return Err(format!(
+                    "server certificate fingerprint {} does not match the pinned one",
+                    fingerprint
+                )
+                .into());
+            }
+        }
+
+        Ok(Stream::new(stream))
+    }
+}
+
+/// sha256 fingerprint of a DER certificate, as lowercase hex.
+pub fn certificate_fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// normalizes a sha256 fingerprint, accepting upper case and `:` separated bytes.
+pub fn parse_fingerprint(fingerprint: &str) -> Result<String> {
+    let fingerprint = fingerprint.replace(':', "").to_ascii_lowercase();
+    let is_hex = fingerprint.chars().all(|c| c.is_ascii_hexdigit());
+    if fingerprint.len() != 64 || !is_hex {
+        return Err("fingerprint must be a sha256 hash (64 hex digits)".into());
+    }
+
+    Ok(fingerprint)
+}
+
+fn pem_certificates(contents: &str) -> Vec<&str> {
+    const END: &str = "-----END CERTIFICATE-----";
+
+    contents
+        .match_indices("-----BEGIN CERTIFICATE-----")
+        .filter_map(|(start, _)| {
+            let end = contents[start..].find(END)?;
+            Some(&contents[start..start + end + END.len()])
+        })
+        .collect()
+}
+
+/// Builds a rustls server config from a PEM certificate chain and private key.
+/// when `client_ca` (PEM) is given, clients may present a certificate signed by it.
+#[cfg(feature = "rustls")]
+pub fn rustls_config_from_pem(
+    cert_chain: &[u8],
+    private_key: &[u8],
+    client_ca: Option<&[u8]>,
+) -> Result<ServerTls> {
+    use tokio_rustls::rustls::server::{AllowAnyAnonymousOrAuthenticatedClient, NoClientAuth};
+    use tokio_rustls::rustls::{Certificate, PrivateKey, RootCertStore, ServerConfig};
+
+    let cert_chain: Vec<Certificate> = rustls_pemfile::certs(&mut &cert_chain[..])?
+        .into_iter()
+        .map(Certificate)
+        .collect();
+
+    if cert_chain.is_empty() {
+        return Err("no certificate found in PEM file".into());
+    }
+
+    let private_key = rustls_pemfile::read_all(&mut &private_key[..])?
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or("no private key found in PEM file")?;
+
+    // clients without a certificate are still accepted, they authenticate with a password or token.
+    let client_verifier = match client_ca {
+        None => NoClientAuth::boxed(),
+        Some(client_ca) => {
+            let mut roots = RootCertStore::empty();
+            for certificate in rustls_pemfile::certs(&mut &client_ca[..])? {
+                roots.add(&Certificate(certificate))?;
+            }
+
+            if roots.is_empty() {
+                return Err("no certificate found in client CA file".into());
+            }
+
+            AllowAnyAnonymousOrAuthenticatedClient::new(roots).boxed()
+        }
+    };
+
+    let config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_client_cert_verifier(client_verifier)
+        .with_single_cert(cert_chain, private_key)?;
+
+    Ok(ServerTls::Rustls(Arc::new(config)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{certificate_fingerprint, parse_fingerprint, pem_certificates};
+
+    #[test]
+    pub fn should_normalize_fingerprint() {
+        // Arrange
+        let fingerprint = certificate_fingerprint(b"certificate").to_ascii_uppercase();
+        let separated = fingerprint
+            .as_bytes()
+            .chunks(2)
+            .map(|byte| std::str::from_utf8(byte).unwrap())
+            .collect::<Vec<&str>>()
+            .join(":");
+
+        // Act
+        let result = parse_fingerprint(&separated);
+
+        // Assert
+        assert_eq!(result.unwrap(), certificate_fingerprint(b"certificate"));
+        assert!(parse_fingerprint("abcd").is_err());
+    }
+
+    #[test]
+    pub fn should_split_pem_bundle() {
+        // Arrange
+        let bundle = "-----BEGIN CERTIFICATE-----\nAAA\n-----END CERTIFICATE-----\n\
+                      # comment\n-----BEGIN CERTIFICATE-----\nBBB\n-----END CERTIFICATE-----\n";
+
+        // Act
+        let certificates = pem_certificates(bundle);
 
-pub async fn accept_tls(stream: TcpStream, acceptor: &TlsAcceptor) -> crate::Result<Stream> {
-    let stream = acceptor.accept(stream).await?;
-    Ok(Stream::new(stream))
+        // Assert
+        assert_eq!(certificates.len(), 2);
+        assert!(certificates[1].contains("BBB"));
+        assert!(certificates[1].ends_with("-----END CERTIFICATE-----"));
+    }
 }
@@ -2,15 +2,14 @@ pub mod reader;
 pub mod writer;
 
 use std::net::SocketAddr;
-use tokio::net::TcpStream as TokioTcpStream;
-use tokio_native_tls::native_tls::{TlsConnector};
-use tokio_native_tls::TlsConnector as TokioTlsConnector;
 use tracing::{debug, error};
 
 pub use reader::*;
 pub use writer::*;
 
 use crate::stream::Stream;
+use crate::tcp::connect_happy_eyeballs;
+use crate::tls::ClientTls;
 use crate::{Result, TcpFrame};
 
 /// represents TcpFrame buffer transport reader.
@@ -18,6 +17,7 @@ use crate::{Result, TcpFrame};
 pub struct TcpFrameTransport {
     reader: TransportReader,
     writer: TransportWriter,
+    peer_addr: Option<SocketAddr>,
 }
 
 impl TcpFrameTransport {
@@ -27,9 +27,15 @@ impl TcpFrameTransport {
         Self {
             writer: TransportWriter::new(writer),
             reader: TransportReader::new(reader, 1024 * 8),
+            peer_addr: None,
         }
     }
 
+    /// address of the server, when connected through `connect`.
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.peer_addr
+    }
+
     /// fetches new tcpframe from underlying reader.
     pub async fn next(&mut self) -> Result<Option<TcpFrame>> {
         self.reader.next().await
@@ -45,30 +51,34 @@ impl TcpFrameTransport {
         (self.reader, self.writer)
     }
 
-    pub async fn connect(addr: SocketAddr, tls: bool) -> Result<TcpFrameTransport> {
-        match TokioTcpStream::connect(addr).await {
+    /// connects to the first of `addrs` to answer, verifying the server certificate with `tls` when given.
+    pub async fn connect(
+        addrs: &[SocketAddr],
+        tls: Option<&ClientTls>,
+    ) -> Result<TcpFrameTransport> {
+        match connect_happy_eyeballs(addrs).await {
             Ok(stream) => {
                 debug!("Connected to server..");
+                let peer_addr = stream.peer_addr().ok();
                 let stream = match tls {
-                    false => Stream::new(stream),
-                    true => {
-                        let connector = match TlsConnector::builder().build() {
-                            Ok(c) => c,
+                    None => Stream::new(stream),
+                    Some(tls) => {
+                        let stream = match tls.connect(stream).await {
+                            Ok(stream) => stream,
                             Err(err) => {
-                                tracing::error!("error when trying to create TlsAcceptor: {}", err);
-                                return Err(err.into());
+                                error!("TLS handshake with {} failed: {}", tls.get_domain(), err);
+                                return Err(err);
                             }
                         };
 
-                        let connector = TokioTlsConnector::from(connector);
-                        let stream = connector.connect("127.0.0.1", stream).await?;
                         tracing::debug!("successfully made TLS handshake! :rocket:");
-
-                        Stream::new(stream)
+                        stream
                     }
                 };
 
-                Ok(Self::new(stream))
+                let mut transport = Self::new(stream);
+                transport.peer_addr = peer_addr;
+                Ok(transport)
             }
             Err(err) => {
                 error!("Failed to connect to server. Check you network connection and try again.");
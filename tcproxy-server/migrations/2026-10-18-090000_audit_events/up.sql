@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+CREATE TABLE audit_events (
+  id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+  event_type VARCHAR(50) NOT NULL,
+  account_id BINARY(16),
+  port INTEGER,
+  remote_addr VARCHAR(64),
+  bytes_in BIGINT,
+  bytes_out BIGINT,
+  details VARCHAR(255),
+  created_at BIGINT NOT NULL
+);
+
+CREATE INDEX audit_events_account_id_idx ON audit_events (account_id);
+CREATE INDEX audit_events_created_at_idx ON audit_events (created_at);
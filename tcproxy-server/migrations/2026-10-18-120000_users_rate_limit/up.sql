@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+-- bandwidth limit in bytes per second, NULL falls back to the server-wide limit.
+ALTER TABLE users ADD COLUMN rate_limit BIGINT
@@ -0,0 +1,15 @@
+-- Your SQL goes here
+
+-- bytes forwarded by the tunnels of each account, rolled up per month (YYYY-MM) and tunnel port.
+CREATE TABLE usage_rollups (
+  account_id BINARY(16) NOT NULL,
+  period VARCHAR(7) NOT NULL,
+  port INTEGER NOT NULL,
+  bytes_in BIGINT NOT NULL DEFAULT 0,
+  bytes_out BIGINT NOT NULL DEFAULT 0,
+  updated_at BIGINT NOT NULL,
+  PRIMARY KEY (account_id, period, port)
+);
+
+-- monthly quota in bytes (in + out), NULL falls back to the server-wide quota.
+ALTER TABLE users ADD COLUMN monthly_quota BIGINT;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+
+-- sha256 fingerprint or subject of the client certificate the user may authenticate with.
+ALTER TABLE users ADD COLUMN client_certificate TEXT
@@ -0,0 +1,145 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ring::digest::{digest, SHA256};
+use ring::rand::SystemRandom;
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+use serde_json::{json, Value};
+
+use tcproxy_core::Result;
+
+/// P-256 key identifying the ACME account, used to sign every request (JWS ES256).
+pub struct AccountKey {
+    key_pair: EcdsaKeyPair,
+    pkcs8: Vec<u8>,
+    rng: SystemRandom,
+}
+
+impl AccountKey {
+    pub fn generate() -> Result<Self> {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng)
+            .map_err(|_| "failed to generate acme account key")?;
+
+        Self::from_pkcs8(pkcs8.as_ref())
+    }
+
+    pub fn from_pkcs8(pkcs8: &[u8]) -> Result<Self> {
+        let rng = SystemRandom::new();
+        let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8, &rng)
+            .map_err(|_| "invalid acme account key")?;
+
+        Ok(Self {
+            key_pair,
+            pkcs8: pkcs8.to_vec(),
+            rng,
+        })
+    }
+
+    pub fn pkcs8(&self) -> &[u8] {
+        &self.pkcs8
+    }
+
+    /// public key as a JWK, members in the lexical order required for the thumbprint.
+    pub fn jwk(&self) -> Value {
+        // uncompressed point: 0x04 || x || y
+        let public_key = self.key_pair.public_key().as_ref();
+        json!({
+            "crv": "P-256",
+            "kty": "EC",
+            "x": URL_SAFE_NO_PAD.encode(&public_key[1..33]),
+            "y": URL_SAFE_NO_PAD.encode(&public_key[33..65]),
+        })
+    }
+
+    /// RFC 7638 thumbprint of the public key.
+    pub fn thumbprint(&self) -> String {
+        let jwk = self.jwk().to_string();
+        URL_SAFE_NO_PAD.encode(digest(&SHA256, jwk.as_bytes()))
+    }
+
+    /// answer expected by the ACME server for a http-01 challenge token.
+    pub fn key_authorization(&self, token: &str) -> String {
+        format!("{}.{}", token, self.thumbprint())
+    }
+
+    /// signs `payload` as a flattened JWS, `None` is sent as an empty payload (POST-as-GET).
+    pub fn sign(&self, protected: &Value, payload: Option<&Value>) -> Result<Value> {
+        let protected = URL_SAFE_NO_PAD.encode(protected.to_string());
+        let payload = match payload {
+            Some(payload) => URL_SAFE_NO_PAD.encode(payload.to_string()),
+            None => String::new(),
+        };
+
+        let signing_input = format!("{}.{}", protected, payload);
+        let signature = self
+            .key_pair
+            .sign(&self.rng, signing_input.as_bytes())
+            .map_err(|_| "failed to sign acme request")?;
+
+        Ok(json!({
+            "protected": protected,
+            "payload": payload,
+            "signature": URL_SAFE_NO_PAD.encode(signature.as_ref()),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use ring::signature::{UnparsedPublicKey, ECDSA_P256_SHA256_FIXED};
+    use serde_json::json;
+
+    use super::AccountKey;
+
+    #[test]
+    pub fn should_sign_verifiable_jws() {
+        // Arrange
+        let key = AccountKey::generate().unwrap();
+        let protected = json!({ "alg": "ES256", "nonce": "abc", "url": "https://acme/new-order" });
+
+        // Act
+        let jws = key.sign(&protected, Some(&json!({ "a": 1 }))).unwrap();
+
+        // Assert
+        let signing_input = format!(
+            "{}.{}",
+            jws["protected"].as_str().unwrap(),
+            jws["payload"].as_str().unwrap()
+        );
+        let signature = URL_SAFE_NO_PAD
+            .decode(jws["signature"].as_str().unwrap())
+            .unwrap();
+        let public_key = [
+            vec![4u8],
+            URL_SAFE_NO_PAD
+                .decode(key.jwk()["x"].as_str().unwrap())
+                .unwrap(),
+            URL_SAFE_NO_PAD
+                .decode(key.jwk()["y"].as_str().unwrap())
+                .unwrap(),
+        ]
+        .concat();
+
+        UnparsedPublicKey::new(&ECDSA_P256_SHA256_FIXED, public_key)
+            .verify(signing_input.as_bytes(), &signature)
+            .unwrap();
+    }
+
+    #[test]
+    pub fn should_keep_thumbprint_when_reloaded() {
+        // Arrange
+        let key = AccountKey::generate().unwrap();
+
+        // Act
+        let reloaded = AccountKey::from_pkcs8(key.pkcs8()).unwrap();
+
+        // Assert
+        assert_eq!(key.thumbprint(), reloaded.thumbprint());
+        assert_eq!(
+            key.key_authorization("token"),
+            format!("token.{}", key.thumbprint())
+        );
+    }
+}
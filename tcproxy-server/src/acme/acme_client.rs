@@ -0,0 +1,222 @@
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rcgen::{Certificate, CertificateParams, DistinguishedName};
+use serde_json::{json, Value};
+use tracing::{debug, info};
+
+use tcproxy_core::Result;
+
+use crate::acme::{AccountKey, ChallengeStore, HttpsClient, HttpsResponse};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const POLL_ATTEMPTS: usize = 30;
+
+/// Certificate chain and private key issued by the ACME server, both PEM encoded.
+pub struct IssuedCertificate {
+    cert_chain: String,
+    private_key: String,
+}
+
+impl IssuedCertificate {
+    pub fn cert_chain(&self) -> &str {
+        &self.cert_chain
+    }
+
+    pub fn private_key(&self) -> &str {
+        &self.private_key
+    }
+}
+
+/// RFC 8555 client, requesting certificates through http-01 challenges.
+pub struct AcmeClient {
+    http: HttpsClient,
+    key: AccountKey,
+    directory: Value,
+    account_url: String,
+    nonce: Option<String>,
+}
+
+impl AcmeClient {
+    /// fetches the directory and registers (or finds) the account of `key`.
+    pub async fn connect(
+        directory_url: &str,
+        http: &HttpsClient,
+        key: AccountKey,
+        email: &Option<String>,
+    ) -> Result<Self> {
+        let response = http.request("GET", directory_url, None, &[]).await?;
+        if !response.is_success() {
+            return Err(format!("failed to fetch acme directory: {}", response.status()).into());
+        }
+
+        let mut client = Self {
+            http: http.clone(),
+            key,
+            directory: response.json()?,
+            account_url: String::new(),
+            nonce: None,
+        };
+
+        let contact: Vec<String> = email
+            .iter()
+            .map(|email| format!("mailto:{}", email))
+            .collect();
+
+        let new_account = client.directory_url("newAccount")?;
+        let payload = json!({ "termsOfServiceAgreed": true, "contact": contact });
+        let response = client.post(&new_account, Some(&payload)).await?;
+
+        client.account_url = response
+            .header("location")
+            .ok_or("acme server did not return the account url")?
+            .to_owned();
+
+        debug!("using acme account {}", client.account_url);
+        Ok(client)
+    }
+
+    /// orders a certificate for `domain`, publishing the challenge answers in `challenges`.
+    pub async fn order_certificate(
+        &mut self,
+        domain: &str,
+        challenges: &ChallengeStore,
+    ) -> Result<IssuedCertificate> {
+        let new_order = self.directory_url("newOrder")?;
+        let payload = json!({ "identifiers": [{ "type": "dns", "value": domain }] });
+        let response = self.post(&new_order, Some(&payload)).await?;
+
+        let order_url = response
+            .header("location")
+            .ok_or("acme server did not return the order url")?
+            .to_owned();
+        let order = response.json()?;
+
+        for authorization in order["authorizations"].as_array().into_iter().flatten() {
+            let authorization = authorization.as_str().ok_or("invalid authorization url")?;
+            self.complete_authorization(authorization, challenges)
+                .await?;
+        }
+
+        let mut params = CertificateParams::new(vec![String::from(domain)]);
+        params.distinguished_name = DistinguishedName::new();
+        let certificate = Certificate::from_params(params)?;
+        let csr = certificate.serialize_request_der()?;
+
+        let finalize = order["finalize"]
+            .as_str()
+            .ok_or("acme order has no finalize url")?
+            .to_owned();
+        let payload = json!({ "csr": URL_SAFE_NO_PAD.encode(csr) });
+        self.post(&finalize, Some(&payload)).await?;
+
+        let order = self.poll(&order_url, "valid").await?;
+        let certificate_url = order["certificate"]
+            .as_str()
+            .ok_or("acme order has no certificate url")?;
+
+        let response = self.post(certificate_url, None).await?;
+        let cert_chain = String::from_utf8(response.body().to_vec())?;
+        info!("acme certificate issued for {}", domain);
+
+        Ok(IssuedCertificate {
+            cert_chain,
+            private_key: certificate.serialize_private_key_pem(),
+        })
+    }
+
+    async fn complete_authorization(
+        &mut self,
+        url: &str,
+        challenges: &ChallengeStore,
+    ) -> Result<()> {
+        let authorization = self.post(url, None).await?.json()?;
+        if authorization["status"] == "valid" {
+            return Ok(());
+        }
+
+        let challenge = authorization["challenges"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|challenge| challenge["type"] == "http-01")
+            .ok_or("acme server did not offer a http-01 challenge")?
+            .clone();
+
+        let token = challenge["token"]
+            .as_str()
+            .ok_or("challenge has no token")?;
+        let challenge_url = challenge["url"].as_str().ok_or("challenge has no url")?;
+
+        challenges.insert(token, &self.key.key_authorization(token));
+        let result = async {
+            self.post(challenge_url, Some(&json!({}))).await?;
+            self.poll(url, "valid").await
+        }
+        .await;
+
+        challenges.remove(token);
+        result.map(|_| ())
+    }
+
+    /// polls `url` until its status is `expected`, failing on `invalid`.
+    async fn poll(&mut self, url: &str, expected: &str) -> Result<Value> {
+        for _ in 0..POLL_ATTEMPTS {
+            let resource = self.post(url, None).await?.json()?;
+            match resource["status"].as_str() {
+                Some(status) if status == expected => return Ok(resource),
+                Some("invalid") => {
+                    return Err(format!("acme validation failed: {}", resource).into());
+                }
+                _ => tokio::time::sleep(POLL_INTERVAL).await,
+            }
+        }
+
+        Err(format!("timed out waiting for {} to become {}", url, expected).into())
+    }
+
+    async fn post(&mut self, url: &str, payload: Option<&Value>) -> Result<HttpsResponse> {
+        let nonce = match self.nonce.take() {
+            Some(nonce) => nonce,
+            None => self.new_nonce().await?,
+        };
+
+        let mut protected = json!({ "alg": "ES256", "nonce": nonce, "url": url });
+        match self.account_url.is_empty() {
+            true => protected["jwk"] = self.key.jwk(),
+            false => protected["kid"] = json!(self.account_url),
+        };
+
+        let body = self.key.sign(&protected, payload)?.to_string();
+        let response = self
+            .http
+            .request("POST", url, Some("application/jose+json"), body.as_bytes())
+            .await?;
+
+        self.nonce = response.header("replay-nonce").map(String::from);
+        if !response.is_success() {
+            let problem = String::from_utf8_lossy(response.body());
+            return Err(format!("acme request to {} failed: {}", url, problem).into());
+        }
+
+        Ok(response)
+    }
+
+    async fn new_nonce(&self) -> Result<String> {
+        let url = self.directory_url("newNonce")?;
+        let response = self.http.request("HEAD", &url, None, &[]).await?;
+
+        match response.header("replay-nonce") {
+            Some(nonce) => Ok(String::from(nonce)),
+            None => Err("acme server did not return a nonce".into()),
+        }
+    }
+
+    fn directory_url(&self, name: &str) -> Result<String> {
+        match self.directory[name].as_str() {
+            Some(url) => Ok(String::from(url)),
+            None => Err(format!("acme directory has no {} url", name).into()),
+        }
+    }
+}
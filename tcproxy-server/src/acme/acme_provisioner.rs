@@ -0,0 +1,164 @@
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use tcproxy_core::tcp::SocketListener;
+use tcproxy_core::tls::{rustls_config_from_pem, ServerTls};
+use tcproxy_core::Result;
+
+use crate::acme::{AccountKey, AcmeClient, ChallengeListener, ChallengeStore, HttpsClient};
+use crate::tls::read_client_ca;
+use crate::ServerConfig;
+
+/// certificates are renewed once they are this old, a month before a 90 days certificate expires.
+const RENEW_AFTER: Duration = Duration::from_secs(60 * 24 * 60 * 60);
+const RENEW_CHECK_INTERVAL: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// Keeps a certificate for the server fqdn issued by an ACME server, caching it on disk.
+pub struct AcmeProvisioner {
+    directory_url: String,
+    email: Option<String>,
+    domain: String,
+    http_addr: SocketAddr,
+    cache_dir: PathBuf,
+    ca_path: Option<PathBuf>,
+    client_ca_path: Option<PathBuf>,
+}
+
+impl AcmeProvisioner {
+    /// `None` when `acme_directory` is not set.
+    pub fn new(config: &ServerConfig) -> Option<Self> {
+        let directory_url = config.get_acme_directory().clone()?;
+        Some(Self {
+            directory_url,
+            email: config.get_acme_email().clone(),
+            domain: config.get_server_fqdn(),
+            http_addr: config
+                .get_acme_http_addr()
+                .unwrap_or_else(|| SocketAddr::new(IpAddr::from([0, 0, 0, 0]), 80)),
+            cache_dir: config
+                .get_acme_cache_dir()
+                .clone()
+                .unwrap_or_else(|| PathBuf::from("./acme")),
+            ca_path: config.get_acme_ca_path().clone(),
+            client_ca_path: config.get_client_ca_path().clone(),
+        })
+    }
+
+    /// loads the cached certificate, requesting a new one when missing or due for renewal.
+    pub async fn load_or_issue(&self) -> Result<ServerTls> {
+        if self.needs_renewal() {
+            self.issue().await?;
+        }
+
+        self.load()
+    }
+
+    /// checks twice a day whether the certificate is due, replacing the one used by `listener`.
+    pub fn spawn_renewal(
+        self,
+        listener: &Arc<dyn SocketListener>,
+        cancellation_token: CancellationToken,
+    ) -> JoinHandle<()> {
+        let listener = listener.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(RENEW_CHECK_INTERVAL) => {},
+                    _ = cancellation_token.cancelled() => break,
+                };
+
+                if !self.needs_renewal() {
+                    continue;
+                }
+
+                let result = match self.issue().await {
+                    Ok(_) => self.load().and_then(|tls| listener.set_tls(Some(tls))),
+                    Err(err) => Err(err),
+                };
+
+                match result {
+                    Ok(_) => info!("acme certificate renewed"),
+                    Err(err) => warn!("failed to renew acme certificate: {}", err),
+                }
+            }
+        })
+    }
+
+    fn needs_renewal(&self) -> bool {
+        let modified = std::fs::metadata(self.cert_path()).and_then(|metadata| metadata.modified());
+        match modified {
+            Ok(modified) => SystemTime::now()
+                .duration_since(modified)
+                .map(|age| age > RENEW_AFTER)
+                .unwrap_or(false),
+            Err(_) => true,
+        }
+    }
+
+    async fn issue(&self) -> Result<()> {
+        std::fs::create_dir_all(&self.cache_dir)?;
+
+        let http = HttpsClient::new(self.ca_path.as_deref())?;
+        let key = self.account_key()?;
+        let mut client = AcmeClient::connect(&self.directory_url, &http, key, &self.email).await?;
+
+        let challenges = ChallengeStore::new();
+        let listener = ChallengeListener::bind(&self.http_addr, &challenges).await?;
+        let cancellation_token = CancellationToken::new();
+        listener.spawn(cancellation_token.clone());
+
+        let result = client.order_certificate(&self.domain, &challenges).await;
+        cancellation_token.cancel();
+
+        let certificate = result?;
+        write_private(&self.key_path(), certificate.private_key().as_bytes())?;
+        std::fs::write(self.cert_path(), certificate.cert_chain())?;
+
+        Ok(())
+    }
+
+    fn load(&self) -> Result<ServerTls> {
+        let cert_chain = std::fs::read(self.cert_path())?;
+        let private_key = std::fs::read(self.key_path())?;
+        let client_ca = read_client_ca(&self.client_ca_path)?;
+        rustls_config_from_pem(&cert_chain, &private_key, client_ca.as_deref())
+    }
+
+    /// account key is kept across renewals, so the same ACME account is used.
+    fn account_key(&self) -> Result<AccountKey> {
+        let path = self.cache_dir.join("account.key");
+        if let Ok(pkcs8) = std::fs::read(&path) {
+            return AccountKey::from_pkcs8(&pkcs8);
+        }
+
+        let key = AccountKey::generate()?;
+        write_private(&path, key.pkcs8())?;
+        Ok(key)
+    }
+
+    fn cert_path(&self) -> PathBuf {
+        self.cache_dir.join("cert.pem")
+    }
+
+    fn key_path(&self) -> PathBuf {
+        self.cache_dir.join("key.pem")
+    }
+}
+
+fn write_private(path: &Path, contents: &[u8]) -> Result<()> {
+    std::fs::write(path, contents)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info};
+
+use tcproxy_core::Result;
+
+use crate::http::{HttpRequest, HttpResponse};
+
+const CHALLENGE_PATH: &str = "/.well-known/acme-challenge/";
+
+/// Key authorizations of the http-01 challenges currently pending, by token.
+#[derive(Clone, Default)]
+pub struct ChallengeStore {
+    challenges: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl ChallengeStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&self, token: &str, key_authorization: &str) {
+        let mut challenges = self.challenges.lock().unwrap();
+        challenges.insert(String::from(token), String::from(key_authorization));
+    }
+
+    pub fn remove(&self, token: &str) {
+        let mut challenges = self.challenges.lock().unwrap();
+        challenges.remove(token);
+    }
+
+    pub fn get(&self, token: &str) -> Option<String> {
+        let challenges = self.challenges.lock().unwrap();
+        challenges.get(token).cloned()
+    }
+}
+
+/// Http listener answering ACME http-01 challenges at `/.well-known/acme-challenge/{token}`.
+pub struct ChallengeListener {
+    listener: TcpListener,
+    challenges: ChallengeStore,
+}
+
+impl ChallengeListener {
+    pub async fn bind(addr: &SocketAddr, challenges: &ChallengeStore) -> Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        info!(
+            "acme challenge listener running at: {}",
+            listener.local_addr()?
+        );
+
+        Ok(Self {
+            listener,
+            challenges: challenges.clone(),
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    pub fn spawn(self, cancellation_token: CancellationToken) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = self.start() => {},
+                _ = cancellation_token.cancelled() => {},
+            };
+
+            debug!("acme challenge listener stopped");
+        })
+    }
+
+    async fn start(&self) -> Result<()> {
+        loop {
+            let (stream, addr) = self.listener.accept().await?;
+            let challenges = self.challenges.clone();
+
+            tokio::spawn(async move {
+                if let Err(err) = handle_request(stream, &challenges).await {
+                    debug!(
+                        "failed when answering acme challenge from {}: {}",
+                        addr, err
+                    );
+                }
+            });
+        }
+    }
+}
+
+async fn handle_request(mut stream: TcpStream, challenges: &ChallengeStore) -> Result<()> {
+    let request = HttpRequest::read(&mut stream).await?;
+    let key_authorization = match request.method() {
+        "GET" => request
+            .path()
+            .strip_prefix(CHALLENGE_PATH)
+            .and_then(|token| challenges.get(token)),
+        _ => None,
+    };
+
+    let response = match key_authorization {
+        Some(key_authorization) => {
+            HttpResponse::new("200 OK", "application/octet-stream", &key_authorization)
+        }
+        None => HttpResponse::not_found(),
+    };
+
+    response.write(&mut stream).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::str::FromStr;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio_util::sync::CancellationToken;
+
+    use super::{ChallengeListener, ChallengeStore};
+
+    async fn get(addr: &SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let request = format!("GET {} HTTP/1.1\r\nHost: proxy.server.local\r\n\r\n", path);
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn should_answer_pending_challenges() {
+        // Arrange
+        let challenges = ChallengeStore::new();
+        challenges.insert("token", "token.thumbprint");
+        let addr = SocketAddr::from_str("127.0.0.1:0").unwrap();
+        let listener = ChallengeListener::bind(&addr, &challenges).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let cancellation_token = CancellationToken::new();
+        listener.spawn(cancellation_token.clone());
+
+        // Act
+        let pending = get(&addr, "/.well-known/acme-challenge/token").await;
+        let unknown = get(&addr, "/.well-known/acme-challenge/other").await;
+        cancellation_token.cancel();
+
+        // Assert
+        assert!(pending.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(pending.ends_with("\r\n\r\ntoken.thumbprint"));
+        assert!(unknown.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+}
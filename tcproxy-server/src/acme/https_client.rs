@@ -0,0 +1,237 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::{Certificate, ClientConfig, RootCertStore, ServerName};
+use tokio_rustls::TlsConnector;
+
+use tcproxy_core::Result;
+
+/// Minimal https/1.1 client used to talk to the ACME directory.
+/// every request opens a new connection, which is closed by the server after answering.
+#[derive(Clone)]
+pub struct HttpsClient {
+    connector: TlsConnector,
+}
+
+impl HttpsClient {
+    /// trusts the system roots, plus `extra_root` (PEM) when given.
+    pub fn new(extra_root: Option<&Path>) -> Result<Self> {
+        let mut roots = RootCertStore::empty();
+        for certificate in rustls_native_certs::load_native_certs().unwrap_or_default() {
+            let _ = roots.add(&Certificate(certificate.0));
+        }
+
+        if let Some(path) = extra_root {
+            let contents = std::fs::read(path)?;
+            for certificate in rustls_pemfile::certs(&mut &contents[..])? {
+                roots.add(&Certificate(certificate))?;
+            }
+        }
+
+        let config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        Ok(Self {
+            connector: TlsConnector::from(Arc::new(config)),
+        })
+    }
+
+    pub async fn request(
+        &self,
+        method: &str,
+        url: &str,
+        content_type: Option<&str>,
+        body: &[u8],
+    ) -> Result<HttpsResponse> {
+        let url = HttpsUrl::parse(url)?;
+        let stream = TcpStream::connect((url.host.as_str(), url.port)).await?;
+        let server_name = ServerName::try_from(url.host.as_str())?;
+        let mut stream = self.connector.connect(server_name, stream).await?;
+
+        let mut request = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: tcproxy-server\r\nConnection: close\r\nContent-Length: {}\r\n",
+            method,
+            url.path,
+            url.host,
+            body.len()
+        );
+
+        if let Some(content_type) = content_type {
+            request.push_str(&format!("Content-Type: {}\r\n", content_type));
+        }
+
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await?;
+        stream.write_all(body).await?;
+        stream.flush().await?;
+
+        let mut response = Vec::new();
+        match stream.read_to_end(&mut response).await {
+            Ok(_) => {}
+            // some servers close the connection without sending close_notify.
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => {}
+            Err(err) => return Err(err.into()),
+        };
+
+        HttpsResponse::parse(&response)
+    }
+}
+
+struct HttpsUrl {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl HttpsUrl {
+    fn parse(url: &str) -> Result<Self> {
+        let rest = url
+            .strip_prefix("https://")
+            .ok_or_else(|| format!("only https urls are supported: {}", url))?;
+
+        let (authority, path) = match rest.find('/') {
+            Some(index) => (&rest[..index], &rest[index..]),
+            None => (rest, "/"),
+        };
+
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse::<u16>()?),
+            None => (authority, 443),
+        };
+
+        Ok(Self {
+            host: String::from(host),
+            port,
+            path: String::from(path),
+        })
+    }
+}
+
+pub struct HttpsResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl HttpsResponse {
+    pub(crate) fn parse(response: &[u8]) -> Result<Self> {
+        let header_end = response
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+            .ok_or("incomplete http response")?;
+
+        let head = std::str::from_utf8(&response[..header_end])?;
+        let mut lines = head.split("\r\n");
+        let status = lines
+            .next()
+            .and_then(|line| line.split(' ').nth(1))
+            .ok_or("invalid http status line")?
+            .parse::<u16>()?;
+
+        let headers: Vec<(String, String)> = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_owned()))
+            .collect();
+
+        let mut body = response[header_end + 4..].to_vec();
+        let chunked = headers.iter().any(|(name, value)| {
+            name == "transfer-encoding" && value.eq_ignore_ascii_case("chunked")
+        });
+
+        if chunked {
+            body = decode_chunked(&body)?;
+        }
+
+        Ok(Self {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_ascii_lowercase();
+        self.headers
+            .iter()
+            .find(|(header, _)| *header == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    pub fn json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::from_slice(&self.body)?)
+    }
+}
+
+fn decode_chunked(mut body: &[u8]) -> Result<Vec<u8>> {
+    let mut decoded = Vec::new();
+    loop {
+        let line_end = body
+            .windows(2)
+            .position(|window| window == b"\r\n")
+            .ok_or("invalid chunked body")?;
+
+        let size = std::str::from_utf8(&body[..line_end])?;
+        let size = size.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size, 16)?;
+        if size == 0 {
+            return Ok(decoded);
+        }
+
+        let start = line_end + 2;
+        if body.len() < start + size + 2 {
+            return Err("invalid chunked body".into());
+        }
+
+        decoded.extend_from_slice(&body[start..start + size]);
+        body = &body[start + size + 2..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HttpsResponse, HttpsUrl};
+
+    #[test]
+    pub fn should_parse_chunked_response() {
+        // Arrange
+        let response = b"HTTP/1.1 201 Created\r\nReplay-Nonce: abc\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n{\"a\"\r\n3\r\n:1}\r\n0\r\n\r\n";
+
+        // Act
+        let response = HttpsResponse::parse(response).unwrap();
+
+        // Assert
+        assert_eq!(response.status(), 201);
+        assert_eq!(response.header("replay-nonce"), Some("abc"));
+        assert_eq!(response.json().unwrap()["a"], 1);
+    }
+
+    #[test]
+    pub fn should_parse_url_with_port() {
+        // Arrange
+        let url = "https://localhost:14000/dir";
+
+        // Act
+        let url = HttpsUrl::parse(url).unwrap();
+
+        // Assert
+        assert_eq!(url.host, "localhost");
+        assert_eq!(url.port, 14000);
+        assert_eq!(url.path, "/dir");
+    }
+}
@@ -0,0 +1,11 @@
+mod account_key;
+mod acme_client;
+mod acme_provisioner;
+mod challenge_listener;
+mod https_client;
+
+pub use account_key::*;
+pub use acme_client::*;
+pub use acme_provisioner::*;
+pub use challenge_listener::*;
+pub use https_client::*;
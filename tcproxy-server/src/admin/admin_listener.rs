@@ -0,0 +1,169 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info};
+
+use tcproxy_core::Result;
+
+use crate::admin::handle_request;
+use crate::http::{accept, HttpRequest, HttpResponse};
+use crate::SessionRegistry;
+
+/// Http listener serving the admin api, every request must carry `Authorization: Bearer <token>`.
+pub struct AdminListener {
+    listener: TcpListener,
+    token: Arc<String>,
+    sessions: Arc<SessionRegistry>,
+}
+
+impl AdminListener {
+    pub async fn bind(
+        addr: &SocketAddr,
+        token: &str,
+        sessions: &Arc<SessionRegistry>,
+    ) -> Result<Self> {
+        if token.is_empty() {
+            return Err(
+                "admin api requires a token, set admin_token or TCPROXY_ADMIN_TOKEN".into(),
+            );
+        }
+
+        let listener = TcpListener::bind(addr).await?;
+        info!("admin api running at: {}", listener.local_addr()?);
+
+        Ok(Self {
+            listener,
+            token: Arc::new(String::from(token)),
+            sessions: sessions.clone(),
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    pub fn spawn(self, cancellation_token: CancellationToken) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = self.start() => {},
+                _ = cancellation_token.cancelled() => {},
+            };
+
+            debug!("admin api stopped");
+        })
+    }
+
+    async fn start(&self) {
+        loop {
+            let (stream, addr) = accept(&self.listener).await;
+            let token = self.token.clone();
+            let sessions = self.sessions.clone();
+
+            tokio::spawn(async move {
+                if let Err(err) = serve(stream, &token, &sessions).await {
+                    debug!("failed when answering admin request from {}: {}", addr, err);
+                }
+            });
+        }
+    }
+}
+
+async fn serve(mut stream: TcpStream, token: &str, sessions: &SessionRegistry) -> Result<()> {
+    let request = HttpRequest::read(&mut stream).await?;
+    let response = match is_authorized(&request, token) {
+        true => handle_request(&request, sessions),
+        false => HttpResponse::json("401 Unauthorized", r#"{"error":"unauthorized"}"#),
+    };
+
+    response.write(&mut stream).await
+}
+
+fn is_authorized(request: &HttpRequest, token: &str) -> bool {
+    let provided = match request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+    {
+        Some(provided) => provided.trim().as_bytes(),
+        None => return false,
+    };
+
+    // compares every byte, so the response time doesn't tell how much of the token matched.
+    provided.len() == token.len()
+        && provided
+            .iter()
+            .zip(token.as_bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio_util::sync::CancellationToken;
+
+    use super::AdminListener;
+    use crate::SessionRegistry;
+
+    async fn send_request(addr: &SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn should_require_bearer_token() {
+        // Arrange
+        let sessions = Arc::new(SessionRegistry::new());
+        let addr = SocketAddr::from_str("127.0.0.1:0").unwrap();
+        let listener = AdminListener::bind(&addr, "secret", &sessions)
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let token = CancellationToken::new();
+        listener.spawn(token.clone());
+
+        // Act
+        let unauthorized = send_request(&addr, "GET /sessions HTTP/1.1\r\n\r\n").await;
+        let wrong_token = send_request(
+            &addr,
+            "GET /sessions HTTP/1.1\r\nAuthorization: Bearer secreT\r\n\r\n",
+        )
+        .await;
+        let authorized = send_request(
+            &addr,
+            "GET /sessions HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n",
+        )
+        .await;
+        token.cancel();
+
+        // Assert
+        assert!(unauthorized.starts_with("HTTP/1.1 401 Unauthorized\r\n"));
+        assert!(wrong_token.starts_with("HTTP/1.1 401 Unauthorized\r\n"));
+        assert!(authorized.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(authorized.ends_with("\r\n\r\n[]"));
+    }
+
+    #[tokio::test]
+    async fn should_refuse_to_start_without_token() {
+        // Arrange
+        let sessions = Arc::new(SessionRegistry::new());
+        let addr = SocketAddr::from_str("127.0.0.1:0").unwrap();
+
+        // Act
+        let result = AdminListener::bind(&addr, "", &sessions).await;
+
+        // Assert
+        assert!(result.is_err());
+    }
+}
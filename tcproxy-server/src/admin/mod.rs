@@ -0,0 +1,7 @@
+mod admin_listener;
+mod routes;
+mod session_view;
+
+pub use admin_listener::AdminListener;
+pub use routes::handle_request;
+pub use session_view::*;
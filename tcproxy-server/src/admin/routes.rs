@@ -0,0 +1,175 @@
+use tracing::info;
+
+use crate::admin::SessionView;
+use crate::http::{HttpRequest, HttpResponse};
+use crate::SessionRegistry;
+
+/// Routes an already authorized admin request.
+///
+/// - `GET /sessions`
+/// - `GET /sessions/{id}`
+/// - `DELETE /sessions/{id}`
+/// - `DELETE /sessions/{id}/connections/{connection_id}`
+pub fn handle_request(request: &HttpRequest, sessions: &SessionRegistry) -> HttpResponse {
+    let segments: Vec<&str> = request
+        .path()
+        .trim_matches('/')
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+
+    match (request.method(), segments.as_slice()) {
+        ("GET", ["sessions"]) => {
+            let sessions: Vec<SessionView> = sessions
+                .list_sessions()
+                .iter()
+                .map(|state| SessionView::from(state.as_ref()))
+                .collect();
+
+            to_json(&sessions)
+        }
+        ("GET", ["sessions", session_id]) => {
+            match parse_id(session_id).and_then(|id| sessions.get_session(&id)) {
+                Some(state) => to_json(&SessionView::from(state.as_ref())),
+                None => HttpResponse::not_found(),
+            }
+        }
+        ("DELETE", ["sessions", session_id]) => match parse_id(session_id) {
+            Some(id) if sessions.close_session(&id) => {
+                info!("session {} closed through the admin api", id);
+                HttpResponse::no_content()
+            }
+            _ => HttpResponse::not_found(),
+        },
+        ("DELETE", ["sessions", session_id, "connections", connection_id]) => {
+            let state = parse_id(session_id).and_then(|id| sessions.get_session(&id));
+            match (state, parse_id(connection_id)) {
+                (Some(state), Some(connection_id))
+                    if state
+                        .get_connection_manager()
+                        .close_connection(&connection_id) =>
+                {
+                    info!(
+                        "connection {} of session {} closed through the admin api",
+                        connection_id,
+                        state.get_session_id()
+                    );
+                    HttpResponse::no_content()
+                }
+                _ => HttpResponse::not_found(),
+            }
+        }
+        _ => HttpResponse::not_found(),
+    }
+}
+
+fn parse_id(value: &str) -> Option<u32> {
+    value.parse::<u32>().ok()
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> HttpResponse {
+    match serde_json::to_string(value) {
+        Ok(body) => HttpResponse::json("200 OK", &body),
+        Err(err) => HttpResponse::json(
+            "500 Internal Server Error",
+            &serde_json::json!({ "error": err.to_string() }).to_string(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    use tokio::sync::mpsc;
+    use tokio_util::sync::CancellationToken;
+
+    use super::handle_request;
+    use crate::http::HttpRequest;
+    use crate::managers::ConnectionInfo;
+    use crate::tcp::ConnectionStats;
+    use crate::tests::utils::create_client_state;
+    use crate::SessionRegistry;
+
+    fn request(method: &str, path: &str) -> HttpRequest {
+        HttpRequest::parse(&format!("{} {} HTTP/1.1\r\n\r\n", method, path))
+    }
+
+    #[test]
+    pub fn should_list_sessions_with_their_connections() {
+        // Arrange
+        let registry = SessionRegistry::new();
+        let state = create_client_state();
+        let (sender, _receiver) = mpsc::channel::<Vec<u8>>(1);
+        let stats = Arc::new(ConnectionStats::new());
+        let remote_addr = SocketAddr::from_str("203.0.113.9:40000").unwrap();
+
+        stats.add_bytes_in(42);
+        state.get_connection_manager().insert_connection(
+            sender,
+            CancellationToken::new(),
+            ConnectionInfo::new(&15001, &remote_addr, &stats),
+        );
+        registry.register(&state);
+
+        // Act
+        let response = handle_request(&request("GET", "/sessions"), &registry);
+
+        // Assert
+        let body: serde_json::Value = serde_json::from_str(response.body()).unwrap();
+        assert_eq!(response.status(), "200 OK");
+        assert_eq!(body[0]["id"], *state.get_session_id());
+        assert_eq!(body[0]["remote_addr"], "127.0.0.1:54321");
+        assert_eq!(body[0]["connections"][0]["port"], 15001);
+        assert_eq!(body[0]["connections"][0]["bytes_in"], 42);
+    }
+
+    #[test]
+    pub fn should_close_remote_connection() {
+        // Arrange
+        let registry = SessionRegistry::new();
+        let state = create_client_state();
+        let (sender, _receiver) = mpsc::channel::<Vec<u8>>(1);
+        let token = CancellationToken::new();
+        let remote_addr = SocketAddr::from_str("203.0.113.9:40000").unwrap();
+        let connection_id = state.get_connection_manager().insert_connection(
+            sender,
+            token.clone(),
+            ConnectionInfo::new(&15001, &remote_addr, &Arc::new(ConnectionStats::new())),
+        );
+        registry.register(&state);
+
+        // Act
+        let path = format!(
+            "/sessions/{}/connections/{}",
+            state.get_session_id(),
+            connection_id
+        );
+        let response = handle_request(&request("DELETE", &path), &registry);
+
+        // Assert
+        assert_eq!(response.status(), "204 No Content");
+        assert!(token.is_cancelled());
+        assert!(state.get_connection_manager().list_connections().is_empty());
+    }
+
+    #[test]
+    pub fn should_close_session() {
+        // Arrange
+        let registry = SessionRegistry::new();
+        let state = create_client_state();
+        registry.register(&state);
+
+        // Act
+        let path = format!("/sessions/{}", state.get_session_id());
+        let response = handle_request(&request("DELETE", &path), &registry);
+        let missing = handle_request(&request("DELETE", "/sessions/0"), &registry);
+
+        // Assert
+        assert_eq!(response.status(), "204 No Content");
+        assert!(state.get_session_token().is_cancelled());
+        assert_eq!(missing.status(), "404 Not Found");
+    }
+}
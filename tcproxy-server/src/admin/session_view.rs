@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::net::SocketAddr;
+
+use crate::managers::ConnectionInfo;
+use crate::ClientState;
+
+/// Snapshot of a control session, as returned by the admin api.
+#[derive(Debug, Serialize)]
+pub struct SessionView {
+    id: u32,
+    account_id: Option<String>,
+    email: Option<String>,
+    remote_addr: SocketAddr,
+    connected_since: DateTime<Utc>,
+    tunnels: Vec<TunnelView>,
+    connections: Vec<ConnectionView>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TunnelView {
+    port: u16,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConnectionView {
+    id: u32,
+    port: u16,
+    remote_addr: SocketAddr,
+    connected_since: DateTime<Utc>,
+    bytes_in: u64,
+    bytes_out: u64,
+}
+
+impl From<&ClientState> for SessionView {
+    fn from(state: &ClientState) -> Self {
+        let user = state.get_auth_manager().user_details();
+        let tunnels = state
+            .get_port_manager()
+            .connection_ports(state.get_session_id())
+            .into_iter()
+            .map(|port| TunnelView { port })
+            .collect();
+
+        let connections = state
+            .get_connection_manager()
+            .list_connections()
+            .iter()
+            .map(|(id, info)| ConnectionView::new(id, info))
+            .collect();
+
+        Self {
+            id: *state.get_session_id(),
+            account_id: user.as_ref().map(|user| user.id().to_string()),
+            email: user.as_ref().map(|user| String::from(user.email())),
+            remote_addr: *state.get_remote_addr(),
+            connected_since: *state.get_connected_at(),
+            tunnels,
+            connections,
+        }
+    }
+}
+
+impl ConnectionView {
+    fn new(id: &u32, info: &ConnectionInfo) -> Self {
+        Self {
+            id: *id,
+            port: *info.listener_port(),
+            remote_addr: *info.remote_addr(),
+            connected_since: *info.connected_at(),
+            bytes_in: info.stats().bytes_in(),
+            bytes_out: info.stats().bytes_out(),
+        }
+    }
+}
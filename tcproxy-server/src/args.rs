@@ -1,22 +1,167 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::{net::IpAddr, ops::Range};
 
-use clap::Parser;
+use chrono::{DateTime, Utc};
+use clap::{Arg, ArgMatches, Command, FromArgMatches, Parser};
 use tcproxy_core::Result;
 
+use crate::CONFIG_KEYS;
+
 #[derive(Parser, Debug, Default)]
 #[clap(author, version, about, long_about = None)]
 pub struct AppArguments {
-    #[clap(short, long, value_parser)]
+    /// Config file, json, toml or yaml
+    #[clap(short, long = "config", value_name = "PATH")]
+    config_file: Option<PathBuf>,
+
+    /// Ports available for tunnels, e.g. 15000:25000, same as --port-min and --port-max
+    #[clap(
+        short = 'D',
+        long,
+        value_parser = parse_port_range,
+        conflicts_with_all = ["port_min", "port_max"]
+    )]
+    port_range: Option<Range<u16>>,
+
+    #[clap(flatten)]
+    config: ConfigArgs,
+
+    #[clap(subcommand)]
+    command: Option<ServerCommand>,
+}
+
+/// One argument per config key, as declared in `CONFIG_KEYS`.
+/// values are kept as given and parsed when applied to the config.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigArgs {
+    values: BTreeMap<&'static str, String>,
+}
+
+impl ConfigArgs {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(|value| value.as_str())
+    }
+
+    fn set(&mut self, key: &'static str, value: &str) {
+        self.values.insert(key, String::from(value));
+    }
+}
+
+impl FromArgMatches for ConfigArgs {
+    fn from_arg_matches(matches: &ArgMatches) -> std::result::Result<Self, clap::Error> {
+        let mut args = Self::default();
+        for key in CONFIG_KEYS {
+            if let Some(value) = matches.get_one::<String>(key.name()) {
+                args.set(key.name(), value);
+            }
+        }
+
+        Ok(args)
+    }
+
+    fn update_from_arg_matches(
+        &mut self,
+        matches: &ArgMatches,
+    ) -> std::result::Result<(), clap::Error> {
+        *self = Self::from_arg_matches(matches)?;
+        Ok(())
+    }
+}
+
+impl clap::Args for ConfigArgs {
+    fn augment_args(cmd: Command) -> Command {
+        CONFIG_KEYS.iter().fold(cmd, |cmd, key| {
+            let arg = Arg::new(key.name())
+                .long(key.arg())
+                .value_name(key.value_name())
+                .help(format!("{} [env: {}]", key.help(), key.env()));
+
+            match key.short_arg() {
+                Some(short) => cmd.arg(arg.short(short)),
+                None => cmd.arg(arg),
+            }
+        })
+    }
+
+    fn augment_args_for_update(cmd: Command) -> Command {
+        Self::augment_args(cmd)
+    }
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+/// Available Sub commands, when none is given the server is started.
+pub enum ServerCommand {
+    /// Account operations.
+    #[clap(subcommand)]
+    Account(AccountCommand),
+
+    /// Audit log operations.
+    #[clap(subcommand)]
+    Audit(AuditCommand),
+
+    /// Config file operations.
+    #[clap(subcommand)]
+    Config(ConfigCommand),
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum ConfigCommand {
+    /// Validates the config file, environment variables and arguments without starting the server.
+    Check,
+
+    /// Prints the json schema of the config file.
+    Schema,
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum AccountCommand {
+    /// Lets the account log in with a client certificate.
+    BindCertificate(BindCertificateArgs),
+}
+
+#[derive(Parser, Debug, Clone, Default)]
+pub struct BindCertificateArgs {
+    /// account id or email.
+    #[clap(long)]
+    account: String,
+
+    /// sha256 fingerprint or subject of the certificate, e.g. CN=build-agent
+    #[clap(long, required_unless_present = "remove")]
+    certificate: Option<String>,
+
+    /// Removes the certificate bound to the account.
+    #[clap(long, conflicts_with = "certificate")]
+    remove: bool,
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum AuditCommand {
+    /// Prints recorded audit events as json lines.
+    Query(AuditQueryArgs),
+}
+
+#[derive(Parser, Debug, Clone, Default)]
+pub struct AuditQueryArgs {
+    /// account id or email.
+    #[clap(long)]
+    account: Option<String>,
+
+    /// public port opened for the tunnel.
+    #[clap(long)]
     port: Option<u16>,
 
-    #[clap(short, long, value_parser)]
-    ip: Option<IpAddr>,
+    /// RFC 3339 timestamp, e.g. 2023-06-11T19:22:08Z
+    #[clap(long, value_parser = parse_date_time)]
+    since: Option<DateTime<Utc>>,
 
-    #[clap(short = 'D', long, value_parser = parse_port_range)]
-    port_range: Option<Range<u16>>,
+    /// RFC 3339 timestamp, e.g. 2023-06-11T19:22:08Z
+    #[clap(long, value_parser = parse_date_time)]
+    until: Option<DateTime<Utc>>,
 
-    #[clap(long = "max-connections-per-proxy")]
-    max_connections_per_proxy: Option<u16>,
+    /// Show at most this many events, the most recent ones
+    #[clap(long, default_value = "1000")]
+    limit: i64,
 }
 
 impl AppArguments {
@@ -26,28 +171,75 @@ impl AppArguments {
         port_range: Option<Range<u16>>,
         max_connections_per_proxy: Option<u16>,
     ) -> Self {
+        let mut config = ConfigArgs::default();
+        if let Some(port) = port {
+            config.set("listen_port", &port.to_string());
+        }
+
+        if let Some(ip) = ip {
+            config.set("listen_ip", &ip.to_string());
+        }
+
+        if let Some(max_connections) = max_connections_per_proxy {
+            config.set("max_connections_per_proxy", &max_connections.to_string());
+        }
+
         Self {
-            port,
-            ip,
+            config_file: None,
             port_range,
-            max_connections_per_proxy,
+            config,
+            command: None,
         }
     }
 
-    pub fn get_port(&self) -> Option<u16> {
+    pub fn get_config_file(&self) -> Option<&PathBuf> {
+        self.config_file.as_ref()
+    }
+
+    pub fn get_port_range(&self) -> Option<Range<u16>> {
+        self.port_range.clone()
+    }
+
+    /// config values given as arguments, by config key.
+    pub fn get_config(&self) -> &ConfigArgs {
+        &self.config
+    }
+
+    pub fn get_command(&self) -> Option<&ServerCommand> {
+        self.command.as_ref()
+    }
+}
+
+impl BindCertificateArgs {
+    pub fn account(&self) -> &str {
+        &self.account
+    }
+
+    /// `None` when the certificate is being removed.
+    pub fn certificate(&self) -> Option<&String> {
+        self.certificate.as_ref()
+    }
+}
+
+impl AuditQueryArgs {
+    pub fn account(&self) -> Option<&String> {
+        self.account.as_ref()
+    }
+
+    pub fn port(&self) -> Option<u16> {
         self.port
     }
 
-    pub fn get_ip(&self) -> Option<IpAddr> {
-        self.ip
+    pub fn since(&self) -> Option<DateTime<Utc>> {
+        self.since
     }
 
-    pub fn get_port_range(&self) -> Option<Range<u16>> {
-        self.port_range.clone()
+    pub fn until(&self) -> Option<DateTime<Utc>> {
+        self.until
     }
 
-    pub fn get_max_connections_per_proxy(&self) -> Option<u16> {
-        self.max_connections_per_proxy
+    pub fn limit(&self) -> i64 {
+        self.limit
     }
 }
 
@@ -66,3 +258,38 @@ fn parse_port_range(s: &str) -> Result<Range<u16>> {
 
     Ok(initial_port?..final_port?)
 }
+
+fn parse_date_time(s: &str) -> Result<DateTime<Utc>> {
+    match DateTime::parse_from_rfc3339(s) {
+        Ok(date) => Ok(date.with_timezone(&Utc)),
+        Err(err) => Err(format!("Invalid date {}: {}", s, err).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::AppArguments;
+
+    #[test]
+    pub fn port_range_should_conflict_with_port_min_and_max() {
+        // Act
+        let with_min =
+            AppArguments::try_parse_from(["tcproxy-server", "-D", "10:20", "--port-min", "10"]);
+        let with_max =
+            AppArguments::try_parse_from(["tcproxy-server", "--port-max", "20", "-D", "10:20"]);
+        let alone = AppArguments::try_parse_from(["tcproxy-server", "-D", "10:20"]);
+
+        // Assert
+        assert_eq!(
+            with_min.unwrap_err().kind(),
+            clap::error::ErrorKind::ArgumentConflict
+        );
+        assert_eq!(
+            with_max.unwrap_err().kind(),
+            clap::error::ErrorKind::ArgumentConflict
+        );
+        assert_eq!(alone.unwrap().get_port_range(), Some(10..20));
+    }
+}
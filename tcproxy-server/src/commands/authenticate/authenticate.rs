@@ -10,11 +10,7 @@ use crate::managers::AccountManagerError;
 use crate::proxy::DefaultTokenHandler;
 use crate::ClientState;
 use tcproxy_core::auth::token_handler::{AuthToken, Claims, TokenHandler, TokenHandlerError};
-use tcproxy_core::framing::{
-    GrantType, PasswordAuthArgs,
-    TokenAuthenticationArgs,
-};
-
+use tcproxy_core::framing::{GrantType, PasswordAuthArgs, TokenAuthenticationArgs};
 
 pub enum AuthenticateCommandError {
     AuthenticationFailed,
@@ -68,7 +64,7 @@ fn create_user_token(
     user: &User,
     state: &Arc<ClientState>,
 ) -> std::result::Result<AuthToken, AuthenticateCommandError> {
-    let token_handler = DefaultTokenHandler::new(state.get_server_config());
+    let token_handler = DefaultTokenHandler::new(&state.get_server_config());
     let now = Utc::now();
     let expiration = (now + Duration::hours(2)).timestamp_millis() as usize;
     let now = now.timestamp_millis() as usize;
@@ -105,13 +101,10 @@ async fn authenticate_with_token(
     args: &TokenAuthenticationArgs,
     state: &Arc<ClientState>,
 ) -> std::result::Result<User, AuthenticateCommandError> {
-    let token_handler = DefaultTokenHandler::new(state.get_server_config());
+    let token_handler = DefaultTokenHandler::new(&state.get_server_config());
     let account_manager = state.get_accounts_manager();
 
-    let maybe_claims = match token_handler.decode(args.token()) {
-        Ok(claims) => Some(claims),
-        Err(_) => None,
-    };
+    let maybe_claims = token_handler.decode(args.token()).ok();
 
     let token_details = match maybe_claims {
         Some(claims) => claims,
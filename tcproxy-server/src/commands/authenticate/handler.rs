@@ -1,13 +1,17 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use tcproxy_core::auth::User;
+use tcproxy_core::tls::PeerCertificate;
 use tcproxy_core::{
-    framing::{Authenticate, AuthenticateAck, Error, Reason},
+    framing::{Authenticate, AuthenticateAck, Error, GrantType, Reason},
     TcpFrame,
 };
 use tokio::sync::mpsc::Sender;
 
 use super::authenticate;
+use crate::managers::{record_audit_event, AccountManagerError};
+use crate::models::{AuditEvent, AuditEventType};
 use crate::{
     commands::{authenticate::authenticate::AuthenticateCommandError, NewFrameHandler},
     ClientState,
@@ -15,6 +19,16 @@ use crate::{
 
 pub struct AuthenticateFrameHandler(tcproxy_core::framing::Authenticate);
 
+impl AuthenticateFrameHandler {
+    /// who tried to authenticate, as far as the frame tells.
+    fn attempted_identity(&self) -> String {
+        match self.0.grant_type() {
+            GrantType::PASSWORD(args) => format!("username: {}", args.username()),
+            GrantType::TOKEN(_) => String::from("token authentication"),
+        }
+    }
+}
+
 impl From<Authenticate> for AuthenticateFrameHandler {
     fn from(value: Authenticate) -> Self {
         Self(value)
@@ -45,6 +59,12 @@ impl NewFrameHandler for AuthenticateFrameHandler {
         let (user, token) = match authenticate::challenge(self.0.grant_type(), state).await {
             Ok(acc_details) => acc_details,
             Err(AuthenticateCommandError::AuthenticationFailed) => {
+                state.get_metrics().authentication(false);
+                let event = AuditEvent::new(AuditEventType::LoginFailed)
+                    .with_remote_addr(state.get_remote_addr())
+                    .with_details(&self.attempted_identity());
+
+                record_audit_event(state.get_audit_manager().as_ref(), event);
                 return Ok(Some(TcpFrame::Error(Error::new(
                     &Reason::AuthenticationFailed,
                     &[],
@@ -61,7 +81,7 @@ impl NewFrameHandler for AuthenticateFrameHandler {
         };
 
         tracing::info!("successfully authenticated, sending AuthenticateAck frame back");
-        auth_manager.set_authentication_details(&user);
+        complete_authentication(&user, state, None);
 
         Ok(Some(TcpFrame::AuthenticateAck(AuthenticateAck::new(
             &user.id().to_string(),
@@ -70,3 +90,166 @@ impl NewFrameHandler for AuthenticateFrameHandler {
         ))))
     }
 }
+
+/// authenticates the session with the certificate the client presented during the tls handshake.
+/// unknown certificates leave the session unauthenticated, so it can still log in with a password.
+pub(crate) fn authenticate_with_certificate(
+    certificate: &PeerCertificate,
+    state: &Arc<ClientState>,
+) {
+    let details = format!("client certificate: {}", certificate.fingerprint());
+    match state
+        .get_accounts_manager()
+        .find_user_by_certificate(certificate)
+    {
+        Ok(user) => {
+            tracing::info!("authenticated {} with client certificate", user.email());
+            complete_authentication(&user, state, Some(&details));
+        }
+        Err(AccountManagerError::NotFound) => {
+            state.get_metrics().authentication(false);
+            let event = AuditEvent::new(AuditEventType::LoginFailed)
+                .with_remote_addr(state.get_remote_addr())
+                .with_details(&details);
+
+            record_audit_event(state.get_audit_manager().as_ref(), event);
+        }
+        Err(AccountManagerError::Other(err)) => {
+            tracing::error!("failed when trying to find certificate account: {}", err);
+        }
+    }
+}
+
+fn complete_authentication(user: &User, state: &Arc<ClientState>, details: Option<&str>) {
+    state.get_auth_manager().set_authentication_details(user);
+    state.get_metrics().authentication(true);
+    state.set_account_bandwidth(user.id(), *user.rate_limit());
+
+    let mut event = AuditEvent::new(AuditEventType::LoginSucceeded)
+        .with_account(Some(user.id()))
+        .with_remote_addr(state.get_remote_addr());
+
+    if let Some(details) = details {
+        event = event.with_details(details);
+    }
+
+    record_audit_event(state.get_audit_manager().as_ref(), event);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+
+    use tcproxy_core::auth::User;
+    use tcproxy_core::tls::PeerCertificate;
+    use uuid::Uuid;
+
+    use super::authenticate_with_certificate;
+    use crate::managers::{
+        AccountManagerError, AuditFilter, AuditManager, AuditManagerError, AuthenticationManager,
+        AuthenticationManagerGuard, DefaultUsageManager, NetworkPortPool, PortManager, UserManager,
+    };
+    use crate::metrics::ServerMetrics;
+    use crate::models::{AuditEvent, AuditEventType};
+    use crate::tcp::BandwidthRegistry;
+    use crate::tests::utils::create_feature_manager;
+    use crate::{ClientState, ServerConfig};
+
+    struct CertificateAccountManager(User);
+
+    impl UserManager for CertificateAccountManager {
+        fn find_account_by_id(&self, _account_id: &Uuid) -> Result<User, AccountManagerError> {
+            Err(AccountManagerError::NotFound)
+        }
+
+        fn find_user_by_email(&self, _email: &str) -> Result<User, AccountManagerError> {
+            Err(AccountManagerError::NotFound)
+        }
+
+        fn find_user_by_certificate(
+            &self,
+            certificate: &PeerCertificate,
+        ) -> Result<User, AccountManagerError> {
+            match certificate.subject() {
+                Some("CN=build-agent") => Ok(self.0.clone()),
+                _ => Err(AccountManagerError::NotFound),
+            }
+        }
+
+        fn set_client_certificate(
+            &self,
+            _account_id: &Uuid,
+            _certificate: Option<&str>,
+        ) -> Result<(), AccountManagerError> {
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct InMemoryAuditManager(Mutex<Vec<AuditEvent>>);
+
+    impl AuditManager for InMemoryAuditManager {
+        fn record(&self, event: &AuditEvent) -> Result<(), AuditManagerError> {
+            self.0.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+
+        fn query(&self, _filter: &AuditFilter) -> Result<Vec<AuditEvent>, AuditManagerError> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+    }
+
+    fn create_state(user: &User, audit_manager: &Arc<InMemoryAuditManager>) -> Arc<ClientState> {
+        let auth_guard = Arc::new(AuthenticationManagerGuard::new(AuthenticationManager::new()));
+        let port_manager = PortManager::from(NetworkPortPool::new(15000..15010));
+        let metrics = Arc::new(ServerMetrics::new(&port_manager));
+
+        ClientState::new(
+            port_manager,
+            auth_guard,
+            &create_feature_manager(ServerConfig::default()),
+            &Arc::new(CertificateAccountManager(user.clone())),
+            audit_manager,
+            &Arc::new(DefaultUsageManager::new()),
+            &metrics,
+            &Arc::new(BandwidthRegistry::new(None)),
+            &SocketAddr::from_str("127.0.0.1:54321").unwrap(),
+        )
+    }
+
+    #[test]
+    fn should_authenticate_known_certificate() {
+        // Arrange
+        let user = User::new(&Uuid::new_v4(), "build agent", "agent@email.com", "hash");
+        let audit_manager = Arc::new(InMemoryAuditManager::default());
+        let state = create_state(&user, &audit_manager);
+        let certificate = PeerCertificate::new("ab12", Some("CN=build-agent"));
+
+        // Act
+        authenticate_with_certificate(&certificate, &state);
+
+        // Assert
+        assert_eq!(state.get_auth_manager().account_id(), Some(*user.id()));
+        let events = audit_manager.query(&AuditFilter::default()).unwrap();
+        assert_eq!(events[0].event_type(), &AuditEventType::LoginSucceeded);
+    }
+
+    #[test]
+    fn should_keep_unknown_certificate_unauthenticated() {
+        // Arrange
+        let user = User::new(&Uuid::new_v4(), "build agent", "agent@email.com", "hash");
+        let audit_manager = Arc::new(InMemoryAuditManager::default());
+        let state = create_state(&user, &audit_manager);
+        let certificate = PeerCertificate::new("ab12", Some("CN=someone-else"));
+
+        // Act
+        authenticate_with_certificate(&certificate, &state);
+
+        // Assert
+        assert!(!state.get_auth_manager().is_authenticated());
+        let events = audit_manager.query(&AuditFilter::default()).unwrap();
+        assert_eq!(events[0].event_type(), &AuditEventType::LoginFailed);
+    }
+}
@@ -4,13 +4,15 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use tcproxy_core::tcp::{SocketListener, TcpListener};
 use tokio::sync::mpsc::Sender;
-use tokio_util::sync::CancellationToken;
 
-use tcproxy_core::framing::{ClientConnected, ClientConnectedAck};
+use tcproxy_core::framing::{ClientConnected, ClientConnectedAck, Error, Reason};
 use tcproxy_core::{Result, TcpFrame};
 
 use super::NewFrameHandler;
-use crate::proxy::ProxyServer;
+use crate::managers::{record_audit_event, PortError};
+use crate::models::{AuditEvent, AuditEventType};
+use crate::proxy::{AccessPolicy, ProxyServer, QuotaEnforcer};
+use crate::tcp::TunnelLimits;
 use crate::ClientState;
 
 pub struct ClientConnectedHandler(ClientConnected);
@@ -36,25 +38,93 @@ impl NewFrameHandler for ClientConnectedHandler {
     ) -> Result<Option<TcpFrame>> {
         tracing::debug!("received connection client command");
 
-        let port_permit = state.get_port_manager().reserve_port(&1234, "")?;
+        // tunnels must be attributed to an account, so their traffic can be accounted for.
+        if !state.get_auth_manager().is_authenticated() {
+            return Ok(Some(TcpFrame::Error(Error::new(
+                &Reason::AuthenticationFailed,
+                &[],
+            ))));
+        }
+
+        if quota_exceeded(state) {
+            tracing::info!("refusing tunnel, account went over its monthly quota");
+            return Ok(Some(TcpFrame::Error(Error::new(
+                &Reason::QuotaExceeded,
+                &[],
+            ))));
+        }
+
+        let bandwidth_limits = state
+            .get_bandwidth_limits()
+            .ok_or("authenticated session without account bandwidth")?;
+
+        let port_manager = state.get_port_manager();
+        let port_permit = match self.0.requested_port() {
+            Some(port) => {
+                match port_manager.reserve_specific_port(state.get_session_id(), "", port) {
+                    Ok(permit) => permit,
+                    Err(PortError::PortUnavailable) => {
+                        tracing::info!("refusing tunnel, port {} is not available", port);
+                        return Ok(Some(TcpFrame::Error(Error::new(
+                            &Reason::PortUnavailable,
+                            &port.to_be_bytes(),
+                        ))));
+                    }
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            None => port_manager.reserve_port(state.get_session_id(), "")?,
+        };
         let target_addr = state.get_server_config().get_listen_ip();
 
         tracing::debug!("spawning new TcpListener at {}", &target_addr);
 
         let target_socket = SocketAddr::new(target_addr, *port_permit.port());
         let listener = TcpListener::bind(target_socket, None).await?;
-        let proxy_server = ProxyServer::new(port_permit, state, tx, listener);
+        // the server-wide deny list can be reloaded, so the proxy server checks it on every connection.
+        let access_policy = AccessPolicy::new(&[], self.0.allow_list(), self.0.deny_list());
+
+        let tunnel_limits = TunnelLimits::new(&bandwidth_limits, *self.0.rate_limit());
+        let proxy_server = ProxyServer::new(
+            port_permit,
+            state,
+            tx,
+            listener,
+            access_policy,
+            tunnel_limits,
+        );
+
+        let tunnel_token = state.get_session_token().child_token();
+        QuotaEnforcer::new(state, tx, &tunnel_token).spawn();
 
         tokio::spawn(async move {
-            let _ = proxy_server.spawn(CancellationToken::new());
+            let _ = proxy_server.spawn(tunnel_token);
             tracing::info!("proxy listener stopped");
             // TODO: send message to client when server shuts down for any reason.
         });
 
         tracing::info!("new TcpListener running at {}", &target_socket);
 
-        Ok(Some(TcpFrame::from(ClientConnectedAck::new(
-            &target_socket.port(),
-        ))))
+        let event = AuditEvent::new(AuditEventType::TunnelOpened)
+            .with_account(state.get_auth_manager().account_id().as_ref())
+            .with_port(&target_socket.port())
+            .with_remote_addr(state.get_remote_addr());
+
+        record_audit_event(state.get_audit_manager().as_ref(), event);
+
+        Ok(Some(TcpFrame::from(
+            ClientConnectedAck::new(&target_socket.port()).with_listening_ip(&target_addr),
+        )))
+    }
+}
+
+fn quota_exceeded(state: &Arc<ClientState>) -> bool {
+    match state.get_usage_recorder().flush(state) {
+        Ok(Some(usage)) => usage.exceeds(&state.get_monthly_quota()),
+        Ok(None) => false,
+        Err(err) => {
+            tracing::warn!("unable to check account usage: {}", err);
+            false
+        }
     }
 }
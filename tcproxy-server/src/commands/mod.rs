@@ -2,6 +2,7 @@ mod client_connected;
 mod data_packet_client;
 mod local_client_disconnected;
 mod ping;
+mod usage;
 
 use std::sync::Arc;
 
@@ -12,6 +13,7 @@ pub use local_client_disconnected::*;
 pub use ping::*;
 use tcproxy_core::TcpFrame;
 use tokio::sync::mpsc::Sender;
+pub use usage::*;
 
 use crate::ClientState;
 
@@ -9,7 +9,7 @@ use crate::ClientState;
 
 use super::NewFrameHandler;
 
-pub struct PingFrameHandler(tcproxy_core::framing::Ping);
+pub struct PingFrameHandler(#[allow(dead_code)] tcproxy_core::framing::Ping);
 
 #[async_trait]
 impl NewFrameHandler for PingFrameHandler {
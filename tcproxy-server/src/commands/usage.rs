@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tcproxy_core::framing::{Error, Reason, UsageReport, UsageRequest};
+use tcproxy_core::TcpFrame;
+use tokio::sync::mpsc::Sender;
+
+use super::NewFrameHandler;
+use crate::ClientState;
+
+pub struct UsageRequestHandler(#[allow(dead_code)] UsageRequest);
+
+impl From<UsageRequest> for UsageRequestHandler {
+    fn from(value: UsageRequest) -> Self {
+        Self(value)
+    }
+}
+
+impl From<UsageRequestHandler> for Box<dyn NewFrameHandler> {
+    fn from(val: UsageRequestHandler) -> Self {
+        Box::new(val)
+    }
+}
+
+#[async_trait]
+impl NewFrameHandler for UsageRequestHandler {
+    async fn execute(
+        &self,
+        _tx: &Sender<TcpFrame>,
+        state: &Arc<ClientState>,
+    ) -> tcproxy_core::Result<Option<TcpFrame>> {
+        // flushes traffic of this connection, so the report is up to date.
+        let usage = match state.get_usage_recorder().flush(state) {
+            Ok(Some(usage)) => usage,
+            Ok(None) => {
+                return Ok(Some(TcpFrame::Error(Error::new(
+                    &Reason::AuthenticationFailed,
+                    &[],
+                ))));
+            }
+            Err(err) => {
+                tracing::error!("failed when trying to fetch account usage: {}", err);
+                return Ok(Some(TcpFrame::Error(Error::new(
+                    &Reason::UnexpectedError,
+                    &[],
+                ))));
+            }
+        };
+
+        Ok(Some(TcpFrame::from(UsageReport::new(
+            usage.period(),
+            usage.bytes_in(),
+            usage.bytes_out(),
+            &state.get_monthly_quota(),
+        ))))
+    }
+}
@@ -1,18 +1,22 @@
+use ipnet::IpNet;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::{
     fs,
     net::{IpAddr, SocketAddr},
     ops::Range,
     str::FromStr,
 };
-use tcproxy_core::config::{Config, ConfigLoader};
-use tracing::error;
-
+use tcproxy_core::config::{
+    Config, ConfigError, ConfigLoader, ConfigOrigin, ConfigOrigins, InvalidConfig,
+};
+use tcproxy_core::tcp::parse_network;
 use tcproxy_core::Result;
 
-use crate::AppArguments;
+use crate::{AppArguments, ConfigKey, CONFIG_KEYS};
 
 pub mod env {
     pub const PORT_MIN: &str = "TCPROXY_PORT_MIN";
@@ -25,6 +29,20 @@ pub mod env {
     pub const JWT_SECRET: &str = "TCPROXY_JWT_SECRET";
     pub const CERTIFICATE_PATH: &str = "TCPROXY_CERTIFICATE_PATH";
     pub const CERTIFICATE_PASS: &str = "TCPROXY_CERTIFICATE_PASS";
+    pub const GLOBAL_DENY_LIST: &str = "TCPROXY_GLOBAL_DENY_LIST";
+    pub const RATE_LIMIT: &str = "TCPROXY_RATE_LIMIT";
+    pub const MONTHLY_QUOTA: &str = "TCPROXY_MONTHLY_QUOTA";
+    pub const METRICS_ADDR: &str = "TCPROXY_METRICS_ADDR";
+    pub const ADMIN_ADDR: &str = "TCPROXY_ADMIN_ADDR";
+    pub const ADMIN_TOKEN: &str = "TCPROXY_ADMIN_TOKEN";
+    pub const SHUTDOWN_TIMEOUT: &str = "TCPROXY_SHUTDOWN_TIMEOUT";
+    pub const CERTIFICATE_KEY_PATH: &str = "TCPROXY_CERTIFICATE_KEY_PATH";
+    pub const ACME_DIRECTORY: &str = "TCPROXY_ACME_DIRECTORY";
+    pub const ACME_EMAIL: &str = "TCPROXY_ACME_EMAIL";
+    pub const ACME_HTTP_ADDR: &str = "TCPROXY_ACME_HTTP_ADDR";
+    pub const ACME_CACHE_DIR: &str = "TCPROXY_ACME_CACHE_DIR";
+    pub const ACME_CA_PATH: &str = "TCPROXY_ACME_CA_PATH";
+    pub const CLIENT_CA_PATH: &str = "TCPROXY_CLIENT_CA_PATH";
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +56,67 @@ pub struct ServerConfig {
     jwt_secret: String,
     certificate_path: Option<PathBuf>, //TODO: maybe change this to use Option<&str>
     certificate_pass: Option<String>,
+    #[serde(default)]
+    global_deny_list: Vec<IpNet>,
+    #[serde(default)]
+    rate_limit: Option<u64>,
+    #[serde(default)]
+    monthly_quota: Option<u64>,
+    #[serde(default)]
+    metrics_addr: Option<SocketAddr>,
+    #[serde(default)]
+    admin_addr: Option<SocketAddr>,
+    #[serde(default)]
+    admin_token: Option<String>,
+    #[serde(default = "default_shutdown_timeout")]
+    shutdown_timeout: u32,
+    #[serde(default)]
+    certificate_key_path: Option<PathBuf>,
+    #[serde(default)]
+    acme_directory: Option<String>,
+    #[serde(default)]
+    acme_email: Option<String>,
+    #[serde(default)]
+    acme_http_addr: Option<SocketAddr>,
+    #[serde(default)]
+    acme_cache_dir: Option<PathBuf>,
+    #[serde(default)]
+    acme_ca_path: Option<PathBuf>,
+    #[serde(default)]
+    client_ca_path: Option<PathBuf>,
+    #[serde(skip)]
+    origins: ConfigOrigins,
+}
+
+/// secret written by older versions when creating the default config file.
+const DEFAULT_JWT_SECRET: &str = "some_secret";
+
+fn default_shutdown_timeout() -> u32 {
+    30
+}
+
+fn generate_secret() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(48)
+        .map(char::from)
+        .collect()
+}
+
+/// checks the name is made of dot separated labels of letters, digits and hyphens.
+fn is_valid_fqdn(value: &str) -> bool {
+    let value = value.strip_suffix('.').unwrap_or(value);
+    if value.is_empty() || value.len() > 253 {
+        return false;
+    }
+
+    value.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
 }
 
 // FILE
@@ -45,6 +124,7 @@ pub struct ServerConfig {
 // App Arguments
 
 impl ServerConfig {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         port_min: u16,
         port_max: u16,
@@ -66,6 +146,21 @@ impl ServerConfig {
             certificate_path,
             certificate_pass,
             max_connections_per_proxy,
+            global_deny_list: Vec::new(),
+            rate_limit: None,
+            monthly_quota: None,
+            metrics_addr: None,
+            admin_addr: None,
+            admin_token: None,
+            shutdown_timeout: default_shutdown_timeout(),
+            certificate_key_path: None,
+            acme_directory: None,
+            acme_email: None,
+            acme_http_addr: None,
+            acme_cache_dir: None,
+            acme_ca_path: None,
+            client_ca_path: None,
+            origins: ConfigOrigins::default(),
         }
     }
 
@@ -109,6 +204,75 @@ impl ServerConfig {
         &self.certificate_pass
     }
 
+    /// networks that are denied on every proxy listener, regardless of the tunnel lists.
+    pub fn get_global_deny_list(&self) -> &[IpNet] {
+        &self.global_deny_list
+    }
+
+    /// default bandwidth limit (bytes per second) of each tunnel, in both directions.
+    pub fn get_rate_limit(&self) -> &Option<u64> {
+        &self.rate_limit
+    }
+
+    /// default monthly traffic quota (bytes in + out) of each account.
+    pub fn get_monthly_quota(&self) -> &Option<u64> {
+        &self.monthly_quota
+    }
+
+    /// address of the prometheus metrics listener, disabled when not set.
+    pub fn get_metrics_addr(&self) -> &Option<SocketAddr> {
+        &self.metrics_addr
+    }
+
+    /// address of the admin api listener, disabled when not set.
+    pub fn get_admin_addr(&self) -> &Option<SocketAddr> {
+        &self.admin_addr
+    }
+
+    /// bearer token required by every admin api request.
+    pub fn get_admin_token(&self) -> &Option<String> {
+        &self.admin_token
+    }
+
+    /// seconds remote connections are given to finish when the server shuts down.
+    pub fn get_shutdown_timeout(&self) -> &u32 {
+        &self.shutdown_timeout
+    }
+
+    /// PEM private key of `certificate_path`, when set the certificate is read as a PEM chain.
+    pub fn get_certificate_key_path(&self) -> &Option<PathBuf> {
+        &self.certificate_key_path
+    }
+
+    /// ACME directory url, when set the certificate for `server_fqdn` is requested automatically.
+    pub fn get_acme_directory(&self) -> &Option<String> {
+        &self.acme_directory
+    }
+
+    pub fn get_acme_email(&self) -> &Option<String> {
+        &self.acme_email
+    }
+
+    /// address answering ACME http-01 challenges, port 80 when not set.
+    pub fn get_acme_http_addr(&self) -> &Option<SocketAddr> {
+        &self.acme_http_addr
+    }
+
+    /// where the ACME account key and issued certificates are stored, `./acme` when not set.
+    pub fn get_acme_cache_dir(&self) -> &Option<PathBuf> {
+        &self.acme_cache_dir
+    }
+
+    /// extra root certificate trusted when talking to the ACME directory, e.g. Pebble's.
+    pub fn get_acme_ca_path(&self) -> &Option<PathBuf> {
+        &self.acme_ca_path
+    }
+
+    /// PEM CA certificates, clients presenting a certificate signed by them skip the password login.
+    pub fn get_client_ca_path(&self) -> &Option<PathBuf> {
+        &self.client_ca_path
+    }
+
     fn set_port_min(&mut self, min_port: u16) {
         self.port_min = min_port;
     }
@@ -117,7 +281,7 @@ impl ServerConfig {
         self.port_max = max_port;
     }
 
-    fn set_listen_port(&mut self, listen_port: u16) {
+    pub(crate) fn set_listen_port(&mut self, listen_port: u16) {
         self.listen_port = listen_port;
     }
 
@@ -125,108 +289,324 @@ impl ServerConfig {
         self.server_fqdn = server_fqdn.to_owned();
     }
 
-    fn set_connections_per_proxy(&mut self, connections_per_proxy: u16) {
+    pub(crate) fn set_connections_per_proxy(&mut self, connections_per_proxy: u16) {
         self.max_connections_per_proxy = connections_per_proxy;
     }
 
-    fn set_listen_ip(&mut self, ip: IpAddr) {
+    pub(crate) fn set_listen_ip(&mut self, ip: IpAddr) {
         self.listen_ip = ip;
     }
 
-    fn set_certificate_path(&mut self, path: Option<PathBuf>) {
+    pub(crate) fn set_certificate_path(&mut self, path: Option<PathBuf>) {
         tracing::debug!("setting certificate path to: {:?}", path);
         self.certificate_path = path;
     }
 
-    fn set_certificate_pass(&mut self, path: Option<String>) {
+    pub(crate) fn set_certificate_pass(&mut self, path: Option<String>) {
         tracing::debug!("setting certificate password");
         self.certificate_pass = path;
     }
+
+    pub(crate) fn set_global_deny_list(&mut self, deny_list: Vec<IpNet>) {
+        self.global_deny_list = deny_list;
+    }
+
+    fn set_rate_limit(&mut self, rate_limit: Option<u64>) {
+        self.rate_limit = rate_limit;
+    }
+
+    fn set_monthly_quota(&mut self, monthly_quota: Option<u64>) {
+        self.monthly_quota = monthly_quota;
+    }
+
+    pub(crate) fn set_metrics_addr(&mut self, metrics_addr: Option<SocketAddr>) {
+        self.metrics_addr = metrics_addr;
+    }
+
+    pub(crate) fn set_admin_addr(&mut self, admin_addr: Option<SocketAddr>) {
+        self.admin_addr = admin_addr;
+    }
+
+    pub(crate) fn set_admin_token(&mut self, admin_token: Option<String>) {
+        self.admin_token = admin_token;
+    }
+
+    fn set_shutdown_timeout(&mut self, shutdown_timeout: u32) {
+        self.shutdown_timeout = shutdown_timeout;
+    }
+
+    pub(crate) fn set_certificate_key_path(&mut self, path: Option<PathBuf>) {
+        self.certificate_key_path = path;
+    }
+
+    pub(crate) fn set_acme_directory(&mut self, directory: Option<String>) {
+        self.acme_directory = directory;
+    }
+
+    pub(crate) fn set_acme_email(&mut self, email: Option<String>) {
+        self.acme_email = email;
+    }
+
+    pub(crate) fn set_acme_http_addr(&mut self, addr: Option<SocketAddr>) {
+        self.acme_http_addr = addr;
+    }
+
+    pub(crate) fn set_acme_cache_dir(&mut self, path: Option<PathBuf>) {
+        self.acme_cache_dir = path;
+    }
+
+    pub(crate) fn set_acme_ca_path(&mut self, path: Option<PathBuf>) {
+        self.acme_ca_path = path;
+    }
+
+    pub(crate) fn set_client_ca_path(&mut self, path: Option<PathBuf>) {
+        self.client_ca_path = path;
+    }
 }
 
-impl Config<AppArguments> for ServerConfig {
-    fn apply_env(&mut self, app_vars: &HashMap<String, String>) -> Result<()> {
-        for (name, value) in app_vars {
-            match name.as_str() {
-                env::PORT_MIN => self.set_port_min(value.parse::<u16>()?),
-                env::PORT_MAX => self.set_port_max(value.parse::<u16>()?),
-                env::LISTEN_PORT => self.set_listen_port(value.parse::<u16>()?),
-                env::LISTEN_IP => self.set_listen_ip(IpAddr::from_str(value)?),
-                env::SERVER_FQDN => self.set_server_fqdn(value),
-                env::CONNECTIONS_PER_PROXY => self.set_connections_per_proxy(value.parse::<u16>()?),
-                env::JWT_SECRET => self.set_jwt_secret(value),
-                env::CERTIFICATE_PATH => self.set_certificate_path(Some(PathBuf::from(value))),
-                env::CERTIFICATE_PASS => self.set_certificate_pass(Some(String::from(value))),
-                _ => continue,
-            }
+impl ServerConfig {
+    /// sets `key` from its textual form, recording where the value came from.
+    fn apply_value(&mut self, key: &ConfigKey, value: &str, origin: ConfigOrigin) -> Result<()> {
+        if let Err(err) = self.set_from_str(key.name(), value) {
+            return Err(ConfigError::new(key.name(), &origin, &err.to_string()).into());
         }
 
+        self.origins.set(key.name(), origin);
         Ok(())
     }
 
-    fn apply_args(&mut self, args: &AppArguments) {
-        if let Some(ip) = args.get_ip() {
-            self.set_listen_ip(ip);
+    /// sets the value of `key` from its textual form, as given by env vars and arguments.
+    fn set_from_str(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "port_min" => self.set_port_min(value.parse::<u16>()?),
+            "port_max" => self.set_port_max(value.parse::<u16>()?),
+            "listen_port" => self.set_listen_port(value.parse::<u16>()?),
+            "listen_ip" => self.set_listen_ip(IpAddr::from_str(value)?),
+            "server_fqdn" => self.set_server_fqdn(value),
+            "max_connections_per_proxy" => self.set_connections_per_proxy(value.parse::<u16>()?),
+            "jwt_secret" => self.set_jwt_secret(value),
+            "certificate_path" => self.set_certificate_path(Some(PathBuf::from(value))),
+            "certificate_pass" => self.set_certificate_pass(Some(String::from(value))),
+            "global_deny_list" => self.set_global_deny_list(parse_network_list(value)?),
+            "rate_limit" => self.set_rate_limit(Some(value.parse::<u64>()?)),
+            "monthly_quota" => self.set_monthly_quota(Some(value.parse::<u64>()?)),
+            "metrics_addr" => self.set_metrics_addr(Some(SocketAddr::from_str(value)?)),
+            "admin_addr" => self.set_admin_addr(Some(SocketAddr::from_str(value)?)),
+            "admin_token" => self.set_admin_token(Some(String::from(value))),
+            "shutdown_timeout" => self.set_shutdown_timeout(value.parse::<u32>()?),
+            "certificate_key_path" => self.set_certificate_key_path(Some(PathBuf::from(value))),
+            "acme_directory" => self.set_acme_directory(Some(String::from(value))),
+            "acme_email" => self.set_acme_email(Some(String::from(value))),
+            "acme_http_addr" => self.set_acme_http_addr(Some(SocketAddr::from_str(value)?)),
+            "acme_cache_dir" => self.set_acme_cache_dir(Some(PathBuf::from(value))),
+            "acme_ca_path" => self.set_acme_ca_path(Some(PathBuf::from(value))),
+            "client_ca_path" => self.set_client_ca_path(Some(PathBuf::from(value))),
+            _ => return Err(format!("unknown config key {}", key).into()),
         }
 
-        if let Some(max_conn) = args.get_max_connections_per_proxy() {
-            self.set_connections_per_proxy(max_conn);
-        }
+        Ok(())
+    }
+}
 
-        if let Some(port) = args.get_port() {
-            self.set_listen_port(port);
+/// parses a comma separated list of networks, bare ip addresses are treated as a single host.
+pub fn parse_network_list(value: &str) -> Result<Vec<IpNet>> {
+    let mut networks = Vec::new();
+    for item in value
+        .split(',')
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+    {
+        networks.push(parse_network(item)?);
+    }
+
+    Ok(networks)
+}
+
+impl Config<AppArguments> for ServerConfig {
+    fn apply_env(&mut self, app_vars: &HashMap<String, String>) -> Result<()> {
+        for key in CONFIG_KEYS {
+            if let Some(value) = app_vars.get(key.env()) {
+                self.apply_value(key, value, ConfigOrigin::Env(key.env().to_owned()))?;
+            }
         }
 
+        Ok(())
+    }
+
+    fn apply_args(&mut self, args: &AppArguments) -> Result<()> {
         if let Some(range) = args.get_port_range() {
             self.set_port_min(range.start);
             self.set_port_max(range.end);
+            self.origins
+                .set("port_min", ConfigOrigin::Arg("--port-range".into()));
+            self.origins
+                .set("port_max", ConfigOrigin::Arg("--port-range".into()));
         }
+
+        for key in CONFIG_KEYS {
+            if let Some(value) = args.get_config().get(key.name()) {
+                let origin = ConfigOrigin::Arg(format!("--{}", key.arg()));
+                self.apply_value(key, value, origin)?;
+            }
+        }
+
+        Ok(())
     }
 
     fn validate(&self) -> Result<()> {
+        let mut errors = Vec::new();
+        let mut invalid = |key: &str, message: String| {
+            errors.push(ConfigError::new(key, &self.origins.get(key), &message));
+        };
+
         if self.port_min == 0 {
-            return Err("Min port cannot be zero".into());
+            invalid("port_min", String::from("min port cannot be zero"));
         }
 
         if self.port_min > self.port_max {
-            return Err("Min port is greater than max_port".into());
+            invalid(
+                "port_min",
+                format!("min port is greater than port_max ({})", self.port_max),
+            );
+        } else if self.get_port_range().is_empty() {
+            invalid(
+                "port_max",
+                format!(
+                    "proxy port range {}..{} is empty, port_max is excluded",
+                    self.port_min, self.port_max
+                ),
+            );
         }
 
-        Ok(())
-    }
-}
+        if self.get_port_range().contains(&self.listen_port) {
+            invalid(
+                "listen_port",
+                format!(
+                    "port {} is inside the proxy port range {}..{}",
+                    self.listen_port, self.port_min, self.port_max
+                ),
+            );
+        }
 
-impl ConfigLoader<'_, AppArguments> for ServerConfig {
-    fn named_environment_variables() -> HashSet<String> {
-        let available_env_vars: Vec<String> = vec![
-            env::PORT_MIN.to_owned(),
-            env::CONNECTIONS_PER_PROXY.to_owned(),
-            env::LISTEN_PORT.to_owned(),
-            env::CONFIG_FILE.to_owned(),
-            env::LISTEN_IP.to_owned(),
-            env::SERVER_FQDN.to_owned(),
-            env::PORT_MAX.to_owned(),
-            env::CERTIFICATE_PASS.to_owned(),
-            env::CERTIFICATE_PATH.to_owned(),
-        ];
+        if let Some(path) = &self.certificate_path {
+            if let Err(err) = fs::File::open(path) {
+                invalid(
+                    "certificate_path",
+                    format!("certificate {} is not readable: {}", path.display(), err),
+                );
+            }
+        }
 
-        HashSet::from_iter(available_env_vars.iter().cloned())
-    }
+        if let Some(path) = &self.certificate_key_path {
+            if self.certificate_path.is_none() {
+                invalid(
+                    "certificate_key_path",
+                    String::from("certificate_path must be set together with its key"),
+                );
+            }
 
-    fn read_from_file(path: &Path) -> Result<ServerConfig> {
-        let file_contents = match fs::read_to_string(path) {
-            Ok(file_contents) => file_contents,
-            Err(err) => {
-                error!("Failed when trying to read config file: {}", err);
-                return Err(err.into());
+            if let Err(err) = fs::File::open(path) {
+                invalid(
+                    "certificate_key_path",
+                    format!("key {} is not readable: {}", path.display(), err),
+                );
             }
-        };
 
-        let config = serde_json::from_str::<Self>(&file_contents)?;
-        Ok(config)
+            if !cfg!(feature = "rustls") {
+                invalid(
+                    "certificate_key_path",
+                    String::from("PEM certificates require the `rustls` feature"),
+                );
+            }
+        }
+
+        if self.acme_directory.is_some() {
+            if self.certificate_path.is_some() {
+                invalid(
+                    "acme_directory",
+                    String::from("can't be used together with certificate_path"),
+                );
+            }
+
+            if !cfg!(feature = "rustls") {
+                invalid(
+                    "acme_directory",
+                    String::from("ACME requires the `rustls` feature"),
+                );
+            }
+        }
+
+        if let Some(path) = &self.acme_ca_path {
+            if let Err(err) = fs::File::open(path) {
+                invalid(
+                    "acme_ca_path",
+                    format!("certificate {} is not readable: {}", path.display(), err),
+                );
+            }
+        }
+
+        if let Some(path) = &self.client_ca_path {
+            if self.certificate_key_path.is_none() && self.acme_directory.is_none() {
+                invalid(
+                    "client_ca_path",
+                    String::from("client certificates require a PEM certificate or ACME"),
+                );
+            }
+
+            if let Err(err) = fs::File::open(path) {
+                invalid(
+                    "client_ca_path",
+                    format!("certificate {} is not readable: {}", path.display(), err),
+                );
+            }
+        }
+
+        if self.admin_addr.is_some() && self.admin_token.as_deref().unwrap_or("").is_empty() {
+            invalid(
+                "admin_addr",
+                String::from("the admin api requires admin_token to be set"),
+            );
+        }
+
+        if self.jwt_secret.is_empty() || self.jwt_secret == DEFAULT_JWT_SECRET {
+            invalid(
+                "jwt_secret",
+                String::from("secret must be set to a non default value"),
+            );
+        }
+
+        if !is_valid_fqdn(&self.server_fqdn) {
+            invalid(
+                "server_fqdn",
+                format!("{} is not a valid domain name", self.server_fqdn),
+            );
+        }
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(InvalidConfig::new(errors).into()),
+        }
     }
 
-    fn get_config_path(env_vars: &HashMap<String, String>) -> PathBuf {
+    fn origins_mut(&mut self) -> &mut ConfigOrigins {
+        &mut self.origins
+    }
+}
+
+impl ConfigLoader<AppArguments> for ServerConfig {
+    fn named_environment_variables() -> HashSet<String> {
+        CONFIG_KEYS
+            .iter()
+            .map(|key| key.env().to_owned())
+            .chain([env::CONFIG_FILE.to_owned()])
+            .collect()
+    }
+
+    fn get_config_path(env_vars: &HashMap<String, String>, args: &AppArguments) -> PathBuf {
+        if let Some(path) = args.get_config_file() {
+            return path.to_owned();
+        }
+
         if env_vars.contains_key(env::CONFIG_FILE) {
             let config_path = env_vars.get(env::CONFIG_FILE).unwrap().to_owned();
 
@@ -246,19 +626,37 @@ impl Default for ServerConfig {
             listen_port: 8080,
             server_fqdn: "proxy.server.local".to_owned(),
             max_connections_per_proxy: 120,
-            jwt_secret: "some_secret".to_owned(),
+            jwt_secret: generate_secret(),
             certificate_path: None,
             certificate_pass: None,
+            global_deny_list: Vec::new(),
+            rate_limit: None,
+            monthly_quota: None,
+            metrics_addr: None,
+            admin_addr: None,
+            admin_token: None,
+            shutdown_timeout: default_shutdown_timeout(),
+            certificate_key_path: None,
+            acme_directory: None,
+            acme_email: None,
+            acme_http_addr: None,
+            acme_cache_dir: None,
+            acme_ca_path: None,
+            client_ca_path: None,
+            origins: ConfigOrigins::default(),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::ConfigLoader;
+    use super::{Config, ConfigLoader};
     use crate::{env, AppArguments, ServerConfig};
-    use std::net::IpAddr;
+    use clap::Parser;
+    use std::net::{IpAddr, SocketAddr};
+    use std::path::Path;
     use std::str::FromStr;
+    use tcproxy_core::config::{ConfigError, ConfigFormat, ConfigOrigin, InvalidConfig};
     use uuid::Uuid;
 
     #[test]
@@ -355,15 +753,18 @@ mod tests {
     #[test]
     pub fn should_create_file_if_doesnt_exist() {
         // Arrange
-        let file_id = Uuid::new_v4();
-        let file_name = format!("{}.json", &file_id);
+        let file_name = format!("{}.json", Uuid::new_v4());
+        let other_file_name = format!("{}.toml", Uuid::new_v4());
         let args = AppArguments::default();
 
         let env_vars: Vec<(String, String)> =
             vec![(env::CONFIG_FILE.to_owned(), file_name.to_owned())];
+        let other_env_vars: Vec<(String, String)> =
+            vec![(env::CONFIG_FILE.to_owned(), other_file_name.to_owned())];
 
         // Act
         let created_config = ServerConfig::load(&env_vars, &args).unwrap();
+        let other_config = ServerConfig::load(&other_env_vars, &args).unwrap();
 
         // Assert
         assert!(std::fs::metadata(&file_name).is_ok());
@@ -372,9 +773,302 @@ mod tests {
             std::fs::read_to_string(&file_name).unwrap()
         );
 
+        // every new file gets its own secret, one `validate` accepts on the first start.
+        assert!(created_config.validate().is_ok());
+        assert_ne!(created_config.get_jwt_secret(), super::DEFAULT_JWT_SECRET);
+        assert_ne!(
+            created_config.get_jwt_secret(),
+            other_config.get_jwt_secret()
+        );
+
+        remove_file(&file_name);
+        remove_file(&other_file_name);
+    }
+
+    #[test]
+    pub fn should_parse_global_deny_list_from_env() {
+        // Arrange
+        let file_id = Uuid::new_v4();
+        let file_name = format!("{}.json", file_id);
+        let args = AppArguments::default();
+        create_default_file(&file_name);
+
+        let env_vars: Vec<(String, String)> = vec![
+            (env::CONFIG_FILE.to_owned(), file_name.to_owned()),
+            (
+                env::GLOBAL_DENY_LIST.to_owned(),
+                "203.0.113.0/24, 198.51.100.7".to_owned(),
+            ),
+        ];
+
+        // Act
+        let parsed_config = ServerConfig::load(&env_vars, &args).unwrap();
+
+        // Assert
+        let deny_list: Vec<String> = parsed_config
+            .get_global_deny_list()
+            .iter()
+            .map(|net| net.to_string())
+            .collect();
+
+        assert_eq!(deny_list, vec!["203.0.113.0/24", "198.51.100.7/32"]);
+
+        remove_file(&file_name);
+    }
+
+    #[test]
+    pub fn should_parse_metrics_addr_from_env() {
+        // Arrange
+        let file_id = Uuid::new_v4();
+        let file_name = format!("{}.json", file_id);
+        let args = AppArguments::default();
+        create_default_file(&file_name);
+
+        let env_vars: Vec<(String, String)> = vec![
+            (env::CONFIG_FILE.to_owned(), file_name.to_owned()),
+            (env::METRICS_ADDR.to_owned(), "127.0.0.1:9100".to_owned()),
+        ];
+
+        // Act
+        let parsed_config = ServerConfig::load(&env_vars, &args).unwrap();
+
+        // Assert
+        assert_eq!(
+            parsed_config.get_metrics_addr(),
+            &Some(SocketAddr::from_str("127.0.0.1:9100").unwrap())
+        );
+
         remove_file(&file_name);
     }
 
+    #[test]
+    pub fn should_read_toml_and_yaml_files() {
+        for extension in ["toml", "yaml"] {
+            // Arrange
+            let file_name = format!("{}.{}", Uuid::new_v4(), extension);
+            let args = AppArguments::default();
+            let config = create_default_file(&file_name);
+
+            let env_vars: Vec<(String, String)> =
+                vec![(env::CONFIG_FILE.to_owned(), file_name.to_owned())];
+
+            // Act
+            let parsed_config = ServerConfig::load(&env_vars, &args).unwrap();
+
+            // Assert
+            assert_eq!(parsed_config.get_socket_addr(), config.get_socket_addr());
+            assert_eq!(parsed_config.get_port_range(), config.get_port_range());
+            assert_eq!(parsed_config.get_jwt_secret(), config.get_jwt_secret());
+
+            remove_file(&file_name);
+        }
+    }
+
+    #[test]
+    pub fn should_name_key_and_source_of_invalid_values() {
+        // Arrange
+        let file_name = format!("{}.json", Uuid::new_v4());
+        let args = AppArguments::new(Some(15), None, None, None);
+        create_default_file(&file_name);
+
+        let env_vars: Vec<(String, String)> = vec![
+            (env::CONFIG_FILE.to_owned(), file_name.to_owned()),
+            (env::SERVER_FQDN.to_owned(), "proxy..local".to_owned()),
+        ];
+
+        // Act
+        let err = ServerConfig::load(&env_vars, &args).unwrap_err();
+
+        // Assert
+        let invalid = err.downcast_ref::<InvalidConfig>().unwrap();
+        assert_eq!(
+            invalid.errors(),
+            &[
+                ConfigError::new(
+                    "listen_port",
+                    &ConfigOrigin::Arg("--port".to_owned()),
+                    "port 15 is inside the proxy port range 10..20"
+                ),
+                ConfigError::new(
+                    "server_fqdn",
+                    &ConfigOrigin::Env(env::SERVER_FQDN.to_owned()),
+                    "proxy..local is not a valid domain name"
+                ),
+            ]
+        );
+
+        remove_file(&file_name);
+    }
+
+    #[test]
+    pub fn should_validate_the_port_range_used_by_the_pool() {
+        // Arrange
+        let file_name = format!("{}.json", Uuid::new_v4());
+        let last_port = AppArguments::new(Some(20), None, None, None);
+        create_default_file(&file_name);
+
+        let env_vars: Vec<(String, String)> =
+            vec![(env::CONFIG_FILE.to_owned(), file_name.to_owned())];
+        let empty_range_env_vars: Vec<(String, String)> = vec![
+            (env::CONFIG_FILE.to_owned(), file_name.to_owned()),
+            (env::PORT_MIN.to_owned(), "20".to_owned()),
+            (env::PORT_MAX.to_owned(), "20".to_owned()),
+        ];
+
+        // Act
+        let after_range = ServerConfig::load(&env_vars, &last_port);
+        let empty_range = ServerConfig::load(&empty_range_env_vars, &AppArguments::default());
+
+        // Assert
+        assert!(after_range.is_ok());
+        let err = empty_range.unwrap_err();
+        let invalid = err.downcast_ref::<InvalidConfig>().unwrap();
+        assert_eq!(invalid.errors().len(), 1);
+        assert_eq!(invalid.errors()[0].key(), "port_max");
+
+        remove_file(&file_name);
+    }
+
+    #[test]
+    pub fn should_reject_admin_addr_without_token() {
+        // Arrange
+        let file_name = format!("{}.json", Uuid::new_v4());
+        create_default_file(&file_name);
+
+        let env_vars: Vec<(String, String)> = vec![
+            (env::CONFIG_FILE.to_owned(), file_name.to_owned()),
+            (env::ADMIN_ADDR.to_owned(), "127.0.0.1:9200".to_owned()),
+        ];
+
+        // Act
+        let err = ServerConfig::load(&env_vars, &AppArguments::default()).unwrap_err();
+
+        // Assert
+        let invalid = err.downcast_ref::<InvalidConfig>().unwrap();
+        assert_eq!(invalid.errors().len(), 1);
+        assert_eq!(invalid.errors()[0].key(), "admin_addr");
+
+        remove_file(&file_name);
+    }
+
+    #[test]
+    pub fn should_reject_unreadable_certificate() {
+        // Arrange
+        let file_name = format!("{}.yml", Uuid::new_v4());
+        let args = AppArguments::default();
+        create_default_file(&file_name);
+
+        let env_vars: Vec<(String, String)> = vec![
+            (env::CONFIG_FILE.to_owned(), file_name.to_owned()),
+            (
+                env::CERTIFICATE_PATH.to_owned(),
+                format!("{}.pfx", Uuid::new_v4()),
+            ),
+        ];
+
+        // Act
+        let err = ServerConfig::load(&env_vars, &args).unwrap_err();
+
+        // Assert
+        let invalid = err.downcast_ref::<InvalidConfig>().unwrap();
+        assert_eq!(invalid.errors().len(), 1);
+        assert_eq!(invalid.errors()[0].key(), "certificate_path");
+
+        remove_file(&file_name);
+    }
+
+    #[test]
+    pub fn should_name_env_variable_that_cant_be_parsed() {
+        // Arrange
+        let file_name = format!("{}.json", Uuid::new_v4());
+        let args = AppArguments::default();
+        create_default_file(&file_name);
+
+        let env_vars: Vec<(String, String)> = vec![
+            (env::CONFIG_FILE.to_owned(), file_name.to_owned()),
+            (env::PORT_MAX.to_owned(), "abc".to_owned()),
+        ];
+
+        // Act
+        let err = ServerConfig::load(&env_vars, &args).unwrap_err();
+
+        // Assert
+        let err = err.downcast_ref::<ConfigError>().unwrap();
+        assert_eq!(err.key(), "port_max");
+        assert_eq!(err.origin(), &ConfigOrigin::Env(env::PORT_MAX.to_owned()));
+
+        remove_file(&file_name);
+    }
+
+    #[test]
+    pub fn should_apply_file_then_env_then_args() {
+        // Arrange
+        let file_name = format!("{}.json", Uuid::new_v4());
+        create_default_file(&file_name);
+
+        let env_vars: Vec<(String, String)> = vec![
+            (env::CONFIG_FILE.to_owned(), file_name.to_owned()),
+            (env::JWT_SECRET.to_owned(), "ENV_SECRET".to_owned()),
+            (env::SERVER_FQDN.to_owned(), "env.server.local".to_owned()),
+        ];
+        let args = AppArguments::parse_from([
+            "tcproxy-server",
+            "--jwt-secret",
+            "ARG_SECRET",
+            "--rate-limit",
+            "1024",
+        ]);
+
+        // Act
+        let parsed_config = ServerConfig::load(&env_vars, &args).unwrap();
+        let env_only = ServerConfig::load(&env_vars, &AppArguments::default()).unwrap();
+
+        // Assert
+        assert_eq!(parsed_config.get_jwt_secret(), "ARG_SECRET");
+        assert_eq!(parsed_config.get_server_fqdn(), "env.server.local");
+        assert_eq!(parsed_config.get_rate_limit(), &Some(1024));
+        assert_eq!(parsed_config.get_listen_port(), 8080);
+        assert_eq!(env_only.get_jwt_secret(), "ENV_SECRET");
+
+        remove_file(&file_name);
+    }
+
+    #[test]
+    pub fn config_file_argument_should_override_env() {
+        // Arrange
+        let file_name = format!("{}.yaml", Uuid::new_v4());
+        let config = create_default_file(&file_name);
+
+        let env_vars: Vec<(String, String)> = vec![(
+            env::CONFIG_FILE.to_owned(),
+            format!("{}.json", Uuid::new_v4()),
+        )];
+        let args = AppArguments::parse_from(["tcproxy-server", "--config", &file_name]);
+
+        // Act
+        let parsed_config = ServerConfig::load(&env_vars, &args).unwrap();
+
+        // Assert
+        assert_eq!(parsed_config.get_port_range(), config.get_port_range());
+        assert!(std::fs::metadata(&env_vars[0].1).is_err());
+
+        remove_file(&file_name);
+    }
+
+    #[test]
+    pub fn should_create_default_config_with_random_secret() {
+        // Arrange
+        let first = ServerConfig::default();
+        let second = ServerConfig::default();
+
+        // Act
+        let result = first.validate();
+
+        // Assert
+        assert!(result.is_ok());
+        assert_ne!(first.get_jwt_secret(), second.get_jwt_secret());
+    }
+
     /// Util function for removing the file after each test.
     fn remove_file(file_name: &str) {
         std::fs::remove_file(file_name).unwrap();
@@ -394,7 +1088,9 @@ mod tests {
             None,
         );
 
-        let config_str = serde_json::to_string(&config).unwrap();
+        let config_str = ConfigFormat::from_path(Path::new(file_name))
+            .serialize(&config)
+            .unwrap();
         std::fs::write(file_name, config_str).unwrap();
 
         config
@@ -0,0 +1,378 @@
+use serde_json::{json, Map, Value};
+
+use crate::env;
+
+/// Kind of value held by a config key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigValueKind {
+    Port,
+    Integer,
+    String,
+    IpAddr,
+    SocketAddr,
+    Path,
+    NetworkList,
+}
+
+impl ConfigValueKind {
+    fn value_name(&self) -> &'static str {
+        match self {
+            ConfigValueKind::Port => "PORT",
+            ConfigValueKind::Integer => "NUMBER",
+            ConfigValueKind::String => "VALUE",
+            ConfigValueKind::IpAddr => "IP",
+            ConfigValueKind::SocketAddr => "IP:PORT",
+            ConfigValueKind::Path => "PATH",
+            ConfigValueKind::NetworkList => "NETWORKS",
+        }
+    }
+
+    fn schema(&self) -> Value {
+        match self {
+            ConfigValueKind::Port => json!({ "type": "integer", "minimum": 0, "maximum": 65535 }),
+            ConfigValueKind::Integer => json!({ "type": "integer", "minimum": 0 }),
+            ConfigValueKind::NetworkList => {
+                json!({ "type": "array", "items": { "type": "string" } })
+            }
+            _ => json!({ "type": "string" }),
+        }
+    }
+}
+
+/// A server config key, with the names it takes in the config file, env vars and arguments.
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigKey {
+    name: &'static str,
+    env: &'static str,
+    arg: &'static str,
+    short: Option<char>,
+    kind: ConfigValueKind,
+    required: bool,
+    nullable: bool,
+    help: &'static str,
+}
+
+impl ConfigKey {
+    const fn new(
+        name: &'static str,
+        env: &'static str,
+        arg: &'static str,
+        kind: ConfigValueKind,
+        help: &'static str,
+    ) -> Self {
+        Self {
+            name,
+            env,
+            arg,
+            short: None,
+            kind,
+            required: false,
+            nullable: true,
+            help,
+        }
+    }
+
+    const fn short(mut self, short: char) -> Self {
+        self.short = Some(short);
+        self
+    }
+
+    const fn required(mut self) -> Self {
+        self.required = true;
+        self.nullable = false;
+        self
+    }
+
+    /// optional in the config file, but falls back to a default value instead of null.
+    const fn defaulted(mut self) -> Self {
+        self.nullable = false;
+        self
+    }
+
+    /// name of the key in the config file.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn env(&self) -> &'static str {
+        self.env
+    }
+
+    /// long argument name, without the leading dashes.
+    pub fn arg(&self) -> &'static str {
+        self.arg
+    }
+
+    pub fn short_arg(&self) -> Option<char> {
+        self.short
+    }
+
+    pub fn kind(&self) -> ConfigValueKind {
+        self.kind
+    }
+
+    pub fn value_name(&self) -> &'static str {
+        self.kind.value_name()
+    }
+
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+
+    pub fn is_nullable(&self) -> bool {
+        self.nullable
+    }
+
+    pub fn help(&self) -> &'static str {
+        self.help
+    }
+}
+
+/// every key of `ServerConfig`, in the order they are applied.
+pub const CONFIG_KEYS: &[ConfigKey] = &[
+    ConfigKey::new(
+        "port_min",
+        env::PORT_MIN,
+        "port-min",
+        ConfigValueKind::Port,
+        "First port available for tunnels",
+    )
+    .required(),
+    ConfigKey::new(
+        "port_max",
+        env::PORT_MAX,
+        "port-max",
+        ConfigValueKind::Port,
+        "Last port available for tunnels",
+    )
+    .required(),
+    ConfigKey::new(
+        "listen_ip",
+        env::LISTEN_IP,
+        "ip",
+        ConfigValueKind::IpAddr,
+        "Ip the server and tunnels listen on, :: listens on both ipv6 and ipv4",
+    )
+    .short('i')
+    .required(),
+    ConfigKey::new(
+        "listen_port",
+        env::LISTEN_PORT,
+        "port",
+        ConfigValueKind::Port,
+        "Port the server listens on for clients",
+    )
+    .short('p')
+    .required(),
+    ConfigKey::new(
+        "server_fqdn",
+        env::SERVER_FQDN,
+        "fqdn",
+        ConfigValueKind::String,
+        "Domain name the server is reachable at",
+    )
+    .required(),
+    ConfigKey::new(
+        "max_connections_per_proxy",
+        env::CONNECTIONS_PER_PROXY,
+        "max-connections-per-proxy",
+        ConfigValueKind::Integer,
+        "Max remote connections open at once on each tunnel",
+    )
+    .required(),
+    ConfigKey::new(
+        "jwt_secret",
+        env::JWT_SECRET,
+        "jwt-secret",
+        ConfigValueKind::String,
+        "Secret used to sign authentication tokens",
+    )
+    .required(),
+    ConfigKey::new(
+        "certificate_path",
+        env::CERTIFICATE_PATH,
+        "certificate-path",
+        ConfigValueKind::Path,
+        "Tls certificate, Pkcs12 or (with rustls) a PEM chain paired with certificate_key_path",
+    ),
+    ConfigKey::new(
+        "certificate_pass",
+        env::CERTIFICATE_PASS,
+        "certificate-pass",
+        ConfigValueKind::String,
+        "Password of the tls certificate",
+    ),
+    ConfigKey::new(
+        "global_deny_list",
+        env::GLOBAL_DENY_LIST,
+        "global-deny-list",
+        ConfigValueKind::NetworkList,
+        "Comma separated networks denied on every tunnel",
+    )
+    .defaulted(),
+    ConfigKey::new(
+        "rate_limit",
+        env::RATE_LIMIT,
+        "rate-limit",
+        ConfigValueKind::Integer,
+        "Bandwidth limit of each tunnel, in bytes per second",
+    ),
+    ConfigKey::new(
+        "monthly_quota",
+        env::MONTHLY_QUOTA,
+        "monthly-quota",
+        ConfigValueKind::Integer,
+        "Monthly traffic quota of each account, in bytes",
+    ),
+    ConfigKey::new(
+        "metrics_addr",
+        env::METRICS_ADDR,
+        "metrics-addr",
+        ConfigValueKind::SocketAddr,
+        "Address of the prometheus metrics listener",
+    ),
+    ConfigKey::new(
+        "admin_addr",
+        env::ADMIN_ADDR,
+        "admin-addr",
+        ConfigValueKind::SocketAddr,
+        "Address of the admin api listener",
+    ),
+    ConfigKey::new(
+        "admin_token",
+        env::ADMIN_TOKEN,
+        "admin-token",
+        ConfigValueKind::String,
+        "Bearer token required by the admin api",
+    ),
+    ConfigKey::new(
+        "shutdown_timeout",
+        env::SHUTDOWN_TIMEOUT,
+        "shutdown-timeout",
+        ConfigValueKind::Integer,
+        "Seconds remote connections are given to finish on shutdown",
+    )
+    .defaulted(),
+    ConfigKey::new(
+        "certificate_key_path",
+        env::CERTIFICATE_KEY_PATH,
+        "certificate-key-path",
+        ConfigValueKind::Path,
+        "PEM private key, when set certificate_path is read as a PEM chain",
+    ),
+    ConfigKey::new(
+        "acme_directory",
+        env::ACME_DIRECTORY,
+        "acme-directory",
+        ConfigValueKind::String,
+        "ACME directory url used to request a certificate for the fqdn",
+    ),
+    ConfigKey::new(
+        "acme_email",
+        env::ACME_EMAIL,
+        "acme-email",
+        ConfigValueKind::String,
+        "Contact email of the ACME account",
+    ),
+    ConfigKey::new(
+        "acme_http_addr",
+        env::ACME_HTTP_ADDR,
+        "acme-http-addr",
+        ConfigValueKind::SocketAddr,
+        "Address answering ACME http-01 challenges, defaults to 0.0.0.0:80",
+    ),
+    ConfigKey::new(
+        "acme_cache_dir",
+        env::ACME_CACHE_DIR,
+        "acme-cache-dir",
+        ConfigValueKind::Path,
+        "Where the ACME account and certificates are stored, defaults to ./acme",
+    ),
+    ConfigKey::new(
+        "acme_ca_path",
+        env::ACME_CA_PATH,
+        "acme-ca-path",
+        ConfigValueKind::Path,
+        "Extra PEM root certificate trusted for the ACME directory",
+    ),
+    ConfigKey::new(
+        "client_ca_path",
+        env::CLIENT_CA_PATH,
+        "client-ca-path",
+        ConfigValueKind::Path,
+        "PEM CA certificates accepted for client certificate authentication",
+    ),
+];
+
+/// json schema of the config file, generated from `CONFIG_KEYS`.
+pub fn config_schema() -> Value {
+    let mut properties = Map::new();
+    for key in CONFIG_KEYS {
+        let mut schema = key.kind().schema();
+        if key.is_nullable() {
+            let kind = schema["type"].clone();
+            schema["type"] = json!([kind, "null"]);
+        }
+
+        schema["description"] = json!(key.help());
+        properties.insert(String::from(key.name()), schema);
+    }
+
+    let required: Vec<&str> = CONFIG_KEYS
+        .iter()
+        .filter(|key| key.is_required())
+        .map(|key| key.name())
+        .collect();
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "tcproxy-server config",
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::{config_schema, CONFIG_KEYS};
+    use crate::ServerConfig;
+
+    #[test]
+    pub fn should_declare_every_config_field() {
+        // Arrange
+        let config = serde_json::to_value(ServerConfig::default()).unwrap();
+
+        // Act
+        let fields: BTreeSet<&str> = config
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(|key| key.as_str())
+            .collect();
+        let declared: BTreeSet<&str> = CONFIG_KEYS.iter().map(|key| key.name()).collect();
+
+        // Assert
+        assert_eq!(fields, declared);
+    }
+
+    #[test]
+    pub fn should_mark_optional_keys_as_nullable_in_schema() {
+        // Arrange
+        let schema = config_schema();
+
+        // Act
+        let listen_port = &schema["properties"]["listen_port"];
+        let rate_limit = &schema["properties"]["rate_limit"];
+
+        // Assert
+        assert_eq!(listen_port["type"], "integer");
+        assert_eq!(rate_limit["type"], serde_json::json!(["integer", "null"]));
+        assert!(schema["required"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!("jwt_secret")));
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time;
+use tracing::warn;
+
+use tcproxy_core::Result;
+
+/// request line and headers can't take more than this.
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+
+/// how long a client has to send the request line and headers.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// how long to wait before accepting again after a failed accept.
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// accepts the next connection. failures such as running out of file descriptors
+/// are transient, so they are logged and the listener keeps going.
+pub async fn accept(listener: &TcpListener) -> (TcpStream, SocketAddr) {
+    loop {
+        match listener.accept().await {
+            Ok(accepted) => return accepted,
+            Err(err) => {
+                warn!("failed to accept http connection, retrying: {}", err);
+                time::sleep(ACCEPT_RETRY_DELAY).await;
+            }
+        }
+    }
+}
+
+/// Bare minimum http/1.1 request, as needed by the metrics and admin listeners.
+/// request bodies are not supported.
+#[derive(Debug, Default)]
+pub struct HttpRequest {
+    method: String,
+    path: String,
+    headers: HashMap<String, String>,
+}
+
+impl HttpRequest {
+    /// reads the request line and headers of a request.
+    pub async fn read<T>(stream: &mut T) -> Result<Self>
+    where
+        T: AsyncRead + Unpin,
+    {
+        Self::read_with_timeout(stream, READ_TIMEOUT).await
+    }
+
+    pub(crate) async fn read_with_timeout<T>(stream: &mut T, timeout: Duration) -> Result<Self>
+    where
+        T: AsyncRead + Unpin,
+    {
+        match time::timeout(timeout, Self::read_head(stream)).await {
+            Ok(result) => result,
+            Err(_) => Err("timed out reading http request".into()),
+        }
+    }
+
+    async fn read_head<T>(stream: &mut T) -> Result<Self>
+    where
+        T: AsyncRead + Unpin,
+    {
+        let mut buffer = Vec::with_capacity(1024);
+        let mut chunk = [0u8; 1024];
+        while !buffer.windows(4).any(|window| window == b"\r\n\r\n") {
+            let read = stream.read(&mut chunk).await?;
+            if read == 0 {
+                break;
+            }
+
+            if buffer.len() + read > MAX_REQUEST_SIZE {
+                return Err("http request is too large".into());
+            }
+
+            buffer.extend_from_slice(&chunk[..read]);
+        }
+
+        Ok(Self::parse(&String::from_utf8_lossy(&buffer)))
+    }
+
+    pub(crate) fn parse(request: &str) -> Self {
+        let mut lines = request.lines();
+        let mut request_line = lines.next().unwrap_or_default().split(' ');
+        let method = request_line.next().unwrap_or_default();
+        let path = request_line.next().unwrap_or_default();
+
+        let headers = lines
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_lowercase(), String::from(value.trim())))
+            .collect();
+
+        Self {
+            method: String::from(method),
+            path: String::from(path),
+            headers,
+        }
+    }
+
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// header names are case insensitive.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_lowercase())
+            .map(|value| value.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub struct HttpResponse {
+    status: &'static str,
+    content_type: &'static str,
+    body: String,
+}
+
+impl HttpResponse {
+    pub fn new(status: &'static str, content_type: &'static str, body: &str) -> Self {
+        Self {
+            status,
+            content_type,
+            body: String::from(body),
+        }
+    }
+
+    pub fn json(status: &'static str, body: &str) -> Self {
+        Self::new(status, "application/json", body)
+    }
+
+    pub fn no_content() -> Self {
+        Self::new("204 No Content", "text/plain; charset=utf-8", "")
+    }
+
+    pub fn not_found() -> Self {
+        Self::new("404 Not Found", "text/plain; charset=utf-8", "not found\n")
+    }
+
+    pub fn status(&self) -> &str {
+        self.status
+    }
+
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    /// writes the response and closes the write half, as connections are not kept alive.
+    pub async fn write<T>(&self, stream: &mut T) -> Result<()>
+    where
+        T: AsyncWrite + Unpin,
+    {
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            self.content_type,
+            self.body.len(),
+            self.body
+        );
+
+        stream.write_all(response.as_bytes()).await?;
+        stream.shutdown().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use tokio::io::AsyncWriteExt;
+
+    use super::{HttpRequest, MAX_REQUEST_SIZE};
+
+    #[test]
+    pub fn should_parse_request_line_and_headers() {
+        // Arrange
+        let request =
+            "DELETE /sessions/1 HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer abc\r\n\r\n";
+
+        // Act
+        let result = HttpRequest::parse(request);
+
+        // Assert
+        assert_eq!(result.method(), "DELETE");
+        assert_eq!(result.path(), "/sessions/1");
+        assert_eq!(result.header("authorization"), Some("Bearer abc"));
+        assert_eq!(result.header("x-missing"), None);
+    }
+
+    #[tokio::test]
+    pub async fn should_give_up_on_slow_requests() {
+        // Arrange
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        client
+            .write_all(b"GET /metrics HTTP/1.1\r\n")
+            .await
+            .unwrap();
+
+        // Act
+        let result = HttpRequest::read_with_timeout(&mut server, Duration::from_millis(50)).await;
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    pub async fn should_reject_oversized_headers() {
+        // Arrange
+        let (mut client, mut server) = tokio::io::duplex(MAX_REQUEST_SIZE * 2);
+        let header = format!("X-Padding: {}\r\n", "a".repeat(MAX_REQUEST_SIZE));
+        let request = format!("GET /metrics HTTP/1.1\r\n{}\r\n", header);
+        client.write_all(request.as_bytes()).await.unwrap();
+
+        // Act
+        let result = HttpRequest::read(&mut server).await;
+
+        // Assert
+        assert_eq!(result.unwrap_err().to_string(), "http request is too large");
+    }
+}
@@ -4,16 +4,30 @@ mod args;
 mod server;
 mod tests;
 
+#[cfg(feature = "rustls")]
+pub mod acme;
+pub mod admin;
 pub mod commands;
 pub mod config;
+mod config_keys;
+pub mod http;
 pub mod managers;
+pub mod metrics;
+pub mod migrations;
 pub mod models;
 pub mod proxy;
+pub mod reload;
 pub mod schema;
 pub mod state;
+pub mod subcommands;
 pub mod tcp;
+pub mod tls;
 
-pub use args::AppArguments;
+pub use args::{
+    AccountCommand, AppArguments, AuditCommand, AuditQueryArgs, BindCertificateArgs, ConfigArgs,
+    ConfigCommand, ServerCommand,
+};
 pub use config::*;
+pub use config_keys::*;
 pub use server::*;
 pub use state::*;
@@ -1,6 +1,3 @@
-use std::io::ErrorKind;
-use std::path::PathBuf;
-
 use clap::Parser;
 use tokio::signal;
 use tracing::{error, info, warn};
@@ -8,24 +5,35 @@ use tracing::{error, info, warn};
 use tcproxy_core::config::ConfigLoader;
 use tcproxy_core::tcp::{SocketListener, TcpListener};
 use tcproxy_core::Result;
+#[cfg(feature = "rustls")]
+use tcproxy_server::acme::AcmeProvisioner;
 use tcproxy_server::managers::DefaultFeatureManager;
-use tcproxy_server::{AppArguments, Server, ServerConfig};
-use tokio_native_tls::native_tls::Identity;
-
-fn get_identity_from_file(path: &PathBuf, password: &str) -> Result<Option<Identity>> {
-    let file_contents = match std::fs::read(path) {
-        Ok(contents) => {
-            tracing::debug!("Successfully loadd certificate file");
-            contents
-        }
-        Err(err) => match err.kind() {
-            ErrorKind::NotFound => return Ok(None),
-            _ => return Err(err.into()),
-        },
-    };
+use tcproxy_server::tls::load_tls;
+use tcproxy_server::{subcommands, AppArguments, Server, ServerConfig};
 
-    let identity = Identity::from_pkcs12(&file_contents, password)?;
-    Ok(Some(identity))
+/// resolves on ctrl-c or, on unix, when SIGTERM is received.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate = match signal::unix::signal(signal::unix::SignalKind::terminate()) {
+            Ok(terminate) => terminate,
+            Err(err) => {
+                warn!("unable to listen for SIGTERM: {}", err);
+                let _ = signal::ctrl_c().await;
+                return;
+            }
+        };
+
+        tokio::select! {
+            _ = signal::ctrl_c() => {},
+            _ = terminate.recv() => {},
+        };
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = signal::ctrl_c().await;
+    }
 }
 
 #[tokio::main]
@@ -35,6 +43,10 @@ async fn main() -> Result<()> {
     let env_vars: Vec<(String, String)> = std::env::vars().collect();
     let args = AppArguments::parse();
 
+    if let Some(command) = args.get_command() {
+        return subcommands::run(command, &env_vars, &args);
+    }
+
     let config = match ServerConfig::load(&env_vars, &args) {
         Ok(config) => config,
         Err(err) => {
@@ -46,30 +58,46 @@ async fn main() -> Result<()> {
         }
     };
 
-    let password = config.get_certificate_pass().to_owned().unwrap_or_default();
-    let identity = match config.get_certificate_path() {
-        None => None,
-        Some(path) => match get_identity_from_file(path, &password) {
-            Ok(identity) => {
-                tracing::debug!("successfully loaded certificate identity");
-                identity
-            }
+    let tls = match load_tls(&config) {
+        Ok(tls) => tls,
+        Err(err) => {
+            error!("Failed when trying to load tls certificate: {}", err);
+            return Err(err);
+        }
+    };
+
+    #[cfg(feature = "rustls")]
+    let acme_provisioner = AcmeProvisioner::new(&config);
+    #[cfg(feature = "rustls")]
+    let tls = match &acme_provisioner {
+        Some(acme_provisioner) => match acme_provisioner.load_or_issue().await {
+            Ok(tls) => Some(tls),
             Err(err) => {
-                error!("Failed when trying to load ssl certificate: {}", err);
-                warn!("ignoring property certificate_path due invalid certificate file. check the path and/or the provided password");
-                None
+                error!("Failed when requesting acme certificate: {}", err);
+                return Err(err);
             }
         },
+        None => tls,
     };
 
-    tracing::debug!("loaded identity: {}", identity.is_some());
+    tracing::debug!("tls enabled: {}", tls.is_some());
     let socket_addr = config.get_socket_addr();
     let feature_manager = DefaultFeatureManager::new(config);
-    let listener = TcpListener::bind(socket_addr, identity).await?;
+    let listener = TcpListener::bind(socket_addr, tls).await?;
+
+    let config_path =
+        ServerConfig::get_config_path(&ServerConfig::parse_environment_variables(&env_vars), &args);
+    let config_source = Box::new(move || ServerConfig::load(&env_vars, &args));
+
+    let mut server =
+        Server::new(feature_manager, listener).with_config_reload(&config_path, config_source);
+
+    #[cfg(feature = "rustls")]
+    if let Some(acme_provisioner) = acme_provisioner {
+        server = server.with_acme_renewal(acme_provisioner);
+    }
 
-    Server::new(feature_manager, listener)
-        .run(signal::ctrl_c())
-        .await?;
+    server.run(shutdown_signal()).await?;
 
     info!("server stopped");
     Ok(())
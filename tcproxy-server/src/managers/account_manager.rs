@@ -2,6 +2,7 @@ use bcrypt::DEFAULT_COST;
 use diesel::result::Error::NotFound;
 use diesel::{insert_into, prelude::*};
 use tcproxy_core::auth::User;
+use tcproxy_core::tls::PeerCertificate;
 use tracing::{error, info};
 use uuid::Uuid;
 
@@ -20,22 +21,43 @@ impl TryFrom<UserModel> for User {
     fn try_from(value: UserModel) -> Result<Self, Self::Error> {
         let user_id = Uuid::from_slice(value.id())?;
 
-        Ok(Self::new(
-            &user_id,
-            value.name(),
-            value.email(),
-            value.password(),
-        ))
+        let rate_limit = value.rate_limit().map(|limit| limit as u64);
+        let monthly_quota = value.monthly_quota().map(|quota| quota as u64);
+
+        Ok(
+            Self::new(&user_id, value.name(), value.email(), value.password())
+                .with_rate_limit(rate_limit)
+                .with_monthly_quota(monthly_quota),
+        )
     }
 }
 
 pub trait UserManager: Send + Sync {
     fn find_account_by_id(&self, account_id: &Uuid) -> Result<User, AccountManagerError>;
     fn find_user_by_email(&self, email: &str) -> Result<User, AccountManagerError>;
+
+    /// finds the user whose `client_certificate` is the certificate fingerprint or subject.
+    fn find_user_by_certificate(
+        &self,
+        certificate: &PeerCertificate,
+    ) -> Result<User, AccountManagerError>;
+
+    /// sets the fingerprint or subject the account authenticates with, `None` removes it.
+    fn set_client_certificate(
+        &self,
+        account_id: &Uuid,
+        certificate: Option<&str>,
+    ) -> Result<(), AccountManagerError>;
 }
 
 pub struct DefaultAccountManager {}
 
+impl Default for DefaultAccountManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DefaultAccountManager {
     pub fn new() -> Self {
         Self {}
@@ -116,7 +138,9 @@ impl UserManager for DefaultAccountManager {
                     return Err(AccountManagerError::NotFound);
                 }
 
-                user.get(0).ok_or(AccountManagerError::NotFound)?.to_owned()
+                user.first()
+                    .ok_or(AccountManagerError::NotFound)?
+                    .to_owned()
             }
             Err(err) => {
                 error!("Failed when trying to find user: {}", err);
@@ -126,4 +150,53 @@ impl UserManager for DefaultAccountManager {
 
         Ok(User::try_from(user_details).unwrap())
     }
+
+    fn find_user_by_certificate(
+        &self,
+        certificate: &PeerCertificate,
+    ) -> Result<User, AccountManagerError> {
+        let connection = &mut SqliteConnection::establish("file:tcproxy.db").unwrap();
+        let mut identities = vec![certificate.fingerprint()];
+        identities.extend(certificate.subject());
+
+        let maybe_user: Result<Vec<UserModel>, diesel::result::Error> = users::dsl::users
+            .filter(schema::users::client_certificate.eq_any(identities))
+            .limit(1)
+            .select(models::UserModel::as_select())
+            .load(connection);
+
+        let user_details = match maybe_user {
+            Ok(users) => users
+                .into_iter()
+                .next()
+                .ok_or(AccountManagerError::NotFound)?,
+            Err(err) => {
+                error!("Failed when trying to find user: {}", err);
+                return Err(AccountManagerError::Other(err.into()));
+            }
+        };
+
+        User::try_from(user_details).map_err(AccountManagerError::Other)
+    }
+
+    fn set_client_certificate(
+        &self,
+        account_id: &Uuid,
+        certificate: Option<&str>,
+    ) -> Result<(), AccountManagerError> {
+        let connection = &mut SqliteConnection::establish("file:tcproxy.db").unwrap();
+        let id_bytes = account_id.as_bytes().to_vec();
+
+        match diesel::update(users::dsl::users.find(id_bytes))
+            .set(schema::users::client_certificate.eq(certificate))
+            .execute(connection)
+        {
+            Ok(0) => Err(AccountManagerError::NotFound),
+            Ok(_) => Ok(()),
+            Err(err) => {
+                error!("Failed when trying to update user: {}", err);
+                Err(AccountManagerError::Other(err.into()))
+            }
+        }
+    }
 }
@@ -0,0 +1,257 @@
+use chrono::{DateTime, Utc};
+use diesel::{insert_into, prelude::*};
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use crate::models::{AuditEvent, AuditEventModel, NewAuditEventModel};
+use crate::schema::audit_events;
+
+#[derive(Debug)]
+pub enum AuditManagerError {
+    Other(tcproxy_core::Error),
+}
+
+/// Filters applied when querying the audit log.
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    account_id: Option<Uuid>,
+    port: Option<u16>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    limit: Option<i64>,
+}
+
+impl AuditFilter {
+    pub fn new(
+        account_id: Option<Uuid>,
+        port: Option<u16>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        limit: Option<i64>,
+    ) -> Self {
+        Self {
+            account_id,
+            port,
+            since,
+            until,
+            limit,
+        }
+    }
+
+    pub fn account_id(&self) -> &Option<Uuid> {
+        &self.account_id
+    }
+
+    pub fn port(&self) -> &Option<u16> {
+        &self.port
+    }
+}
+
+pub trait AuditManager: Send + Sync {
+    fn record(&self, event: &AuditEvent) -> Result<(), AuditManagerError>;
+    fn query(&self, filter: &AuditFilter) -> Result<Vec<AuditEvent>, AuditManagerError>;
+}
+
+pub struct DefaultAuditManager {}
+
+impl Default for DefaultAuditManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DefaultAuditManager {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl AuditManager for DefaultAuditManager {
+    fn record(&self, event: &AuditEvent) -> Result<(), AuditManagerError> {
+        let connection = &mut SqliteConnection::establish("file:tcproxy.db")?;
+        let model = NewAuditEventModel::from(event);
+
+        match insert_into(audit_events::table)
+            .values(&model)
+            .execute(connection)
+        {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                error!("failed when trying to record audit event: {}", err);
+                Err(err.into())
+            }
+        }
+    }
+
+    fn query(&self, filter: &AuditFilter) -> Result<Vec<AuditEvent>, AuditManagerError> {
+        use crate::schema::audit_events::dsl;
+
+        let connection = &mut SqliteConnection::establish("file:tcproxy.db")?;
+        let mut query = dsl::audit_events.into_boxed();
+
+        if let Some(account_id) = filter.account_id {
+            query = query.filter(dsl::account_id.eq(account_id.into_bytes().to_vec()));
+        }
+
+        if let Some(port) = filter.port {
+            query = query.filter(dsl::port.eq(port as i32));
+        }
+
+        if let Some(since) = filter.since {
+            query = query.filter(dsl::created_at.ge(since.timestamp_millis()));
+        }
+
+        if let Some(until) = filter.until {
+            query = query.filter(dsl::created_at.le(until.timestamp_millis()));
+        }
+
+        if let Some(limit) = filter.limit {
+            query = query.limit(limit);
+        }
+
+        // newest first, so the limit keeps the latest events.
+        let models: Vec<AuditEventModel> = match query
+            .order((dsl::created_at.desc(), dsl::id.desc()))
+            .select(AuditEventModel::as_select())
+            .load(connection)
+        {
+            Ok(models) => models,
+            Err(err) => {
+                error!("failed when trying to query audit events: {}", err);
+                return Err(err.into());
+            }
+        };
+
+        let mut events = Vec::with_capacity(models.len());
+        for model in models.into_iter().rev() {
+            events.push(AuditEvent::try_from(model).map_err(AuditManagerError::Other)?);
+        }
+
+        Ok(events)
+    }
+}
+
+/// Records events from a single writer thread, so the tasks reporting them never wait
+/// on the database. events are dropped, with a warning, once `capacity` of them are pending.
+pub struct QueuedAuditManager {
+    inner: Arc<dyn AuditManager + 'static>,
+    sender: SyncSender<AuditEvent>,
+}
+
+impl QueuedAuditManager {
+    pub fn new(inner: &Arc<impl AuditManager + 'static>, capacity: usize) -> Self {
+        let (sender, receiver) = sync_channel::<AuditEvent>(capacity);
+        let writer: Arc<dyn AuditManager + 'static> = inner.clone();
+
+        // ends once the manager, and so the sender, is dropped.
+        thread::spawn(move || {
+            for event in receiver {
+                record_audit_event(writer.as_ref(), event);
+            }
+        });
+
+        Self {
+            inner: inner.clone(),
+            sender,
+        }
+    }
+}
+
+impl AuditManager for QueuedAuditManager {
+    fn record(&self, event: &AuditEvent) -> Result<(), AuditManagerError> {
+        match self.sender.try_send(event.clone()) {
+            Ok(_) => Ok(()),
+            Err(TrySendError::Full(_)) => Err(AuditManagerError::Other("queue is full".into())),
+            Err(TrySendError::Disconnected(_)) => {
+                Err(AuditManagerError::Other("writer is gone".into()))
+            }
+        }
+    }
+
+    fn query(&self, filter: &AuditFilter) -> Result<Vec<AuditEvent>, AuditManagerError> {
+        self.inner.query(filter)
+    }
+}
+
+/// Records the event, logging instead of failing when the audit log is unavailable,
+/// since auditing must never interrupt the tunnel it's describing.
+pub fn record_audit_event(manager: &dyn AuditManager, event: AuditEvent) {
+    if let Err(err) = manager.record(&event) {
+        warn!(
+            "unable to record audit event {}: {}",
+            event.event_type(),
+            err
+        );
+    }
+}
+
+impl std::fmt::Display for AuditManagerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditManagerError::Other(err) => write!(f, "audit log error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for AuditManagerError {}
+
+impl From<diesel::result::Error> for AuditManagerError {
+    fn from(value: diesel::result::Error) -> Self {
+        Self::Other(value.into())
+    }
+}
+
+impl From<diesel::ConnectionError> for AuditManagerError {
+    fn from(value: diesel::ConnectionError) -> Self {
+        Self::Other(value.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use super::{AuditFilter, AuditManager, AuditManagerError, QueuedAuditManager};
+    use crate::models::{AuditEvent, AuditEventType};
+
+    #[derive(Default)]
+    struct InMemoryAuditManager(Mutex<Vec<AuditEvent>>);
+
+    impl AuditManager for InMemoryAuditManager {
+        fn record(&self, event: &AuditEvent) -> Result<(), AuditManagerError> {
+            self.0.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+
+        fn query(&self, _filter: &AuditFilter) -> Result<Vec<AuditEvent>, AuditManagerError> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+    }
+
+    #[test]
+    pub fn should_record_events_from_writer_thread() {
+        // Arrange
+        let inner = Arc::new(InMemoryAuditManager::default());
+        let audit_manager = QueuedAuditManager::new(&inner, 10);
+
+        // Act
+        let result = audit_manager.record(&AuditEvent::new(AuditEventType::TunnelOpened));
+        for _ in 0..100 {
+            if !inner.0.lock().unwrap().is_empty() {
+                break;
+            }
+
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        // Assert
+        assert!(result.is_ok());
+        let events = audit_manager.query(&AuditFilter::default()).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type(), &AuditEventType::TunnelOpened);
+    }
+}
@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc};
 use std::sync::Mutex;
 use tcproxy_core::auth::User;
+use uuid::Uuid;
 
 pub struct AuthenticationManager {
     is_authenticated: bool,
@@ -36,6 +37,24 @@ impl AuthenticationManagerGuard {
 
         lock.revoke_authentication();
     }
+
+    pub fn user_details(&self) -> Option<User> {
+        let lock = self.manager.lock().unwrap();
+
+        lock.user_details().clone()
+    }
+
+    pub fn account_id(&self) -> Option<Uuid> {
+        let lock = self.manager.lock().unwrap();
+
+        lock.user_details().as_ref().map(|user| *user.id())
+    }
+}
+
+impl Default for AuthenticationManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AuthenticationManager {
@@ -1,11 +1,55 @@
+use chrono::{DateTime, Utc};
+use std::net::SocketAddr;
+use std::sync::Arc;
 use std::{collections::HashMap, sync::Mutex};
 use tokio::sync::mpsc::Sender;
 use tokio_util::sync::CancellationToken;
 use tracing::trace;
 
-type ConnectionCollection = HashMap<u32, (Sender<Vec<u8>>, CancellationToken)>;
+use crate::tcp::ConnectionStats;
+
+type ConnectionCollection = HashMap<u32, (Sender<Vec<u8>>, CancellationToken, ConnectionInfo)>;
+
+/// Describes a remote connection accepted by one of the client tunnels.
+#[derive(Clone)]
+pub struct ConnectionInfo {
+    listener_port: u16,
+    remote_addr: SocketAddr,
+    connected_at: DateTime<Utc>,
+    stats: Arc<ConnectionStats>,
+}
+
+impl ConnectionInfo {
+    pub fn new(
+        listener_port: &u16,
+        remote_addr: &SocketAddr,
+        stats: &Arc<ConnectionStats>,
+    ) -> Self {
+        Self {
+            listener_port: *listener_port,
+            remote_addr: *remote_addr,
+            connected_at: Utc::now(),
+            stats: stats.clone(),
+        }
+    }
+
+    pub fn listener_port(&self) -> &u16 {
+        &self.listener_port
+    }
+
+    pub fn remote_addr(&self) -> &SocketAddr {
+        &self.remote_addr
+    }
+
+    pub fn connected_at(&self) -> &DateTime<Utc> {
+        &self.connected_at
+    }
+
+    pub fn stats(&self) -> &Arc<ConnectionStats> {
+        &self.stats
+    }
+}
 
-#[derive(Debug)]
 pub struct ConnectionsManager {
     last_connection_id: Mutex<u32>,
     connections: Mutex<ConnectionCollection>,
@@ -29,6 +73,7 @@ impl ConnectionsManager {
         &self,
         sender: Sender<Vec<u8>>,
         cancellation_token: CancellationToken,
+        info: ConnectionInfo,
     ) -> u32 {
         let mut last_id = self.last_connection_id.lock().unwrap();
         let mut state = self.connections.lock().unwrap();
@@ -36,7 +81,7 @@ impl ConnectionsManager {
         let new_id = *last_id + 1u32;
         *last_id = new_id;
 
-        state.insert(new_id, (sender, cancellation_token));
+        state.insert(new_id, (sender, cancellation_token, info));
 
         new_id
     }
@@ -50,7 +95,31 @@ impl ConnectionsManager {
             return None;
         }
 
-        Some(state.remove(connection_id).unwrap())
+        let (sender, token, _) = state.remove(connection_id).unwrap();
+        Some((sender, token))
+    }
+
+    /// removes the connection and cancels its tasks, returning whether it existed.
+    pub fn close_connection(&self, connection_id: &u32) -> bool {
+        match self.remove_connection(connection_id) {
+            Some((_, token)) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// lists every open connection, ordered by id.
+    pub fn list_connections(&self) -> Vec<(u32, ConnectionInfo)> {
+        let state = self.connections.lock().unwrap();
+        let mut connections: Vec<(u32, ConnectionInfo)> = state
+            .iter()
+            .map(|(id, (_, _, info))| (*id, info.clone()))
+            .collect();
+
+        connections.sort_by_key(|(id, _)| *id);
+        connections
     }
 
     pub fn get_connection(
@@ -59,7 +128,7 @@ impl ConnectionsManager {
     ) -> Option<(Sender<Vec<u8>>, CancellationToken)> {
         let state = self.connections.lock().unwrap();
         match state.get(connection_id) {
-            Some(item) => Some(item.clone()),
+            Some((sender, token, _)) => Some((sender.clone(), token.clone())),
             None => {
                 trace!("connection {} not found in state", connection_id);
                 None
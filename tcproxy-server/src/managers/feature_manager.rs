@@ -1,26 +1,33 @@
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use crate::ServerConfig;
 
 pub trait FeatureManager: Sync + Send {
     fn get_config(&self) -> Arc<ServerConfig>;
+
+    /// replaces the config, connections accepted from now on will see the new one.
+    fn set_config(&self, config: ServerConfig);
 }
 
 #[derive(Debug)]
 pub struct DefaultFeatureManager {
-    server_config: Arc<ServerConfig>,
+    server_config: RwLock<Arc<ServerConfig>>,
 }
 
 impl DefaultFeatureManager {
     pub fn new(server_config: ServerConfig) -> Self {
         Self {
-            server_config: Arc::new(server_config),
+            server_config: RwLock::new(Arc::new(server_config)),
         }
     }
 }
 
 impl FeatureManager for DefaultFeatureManager {
     fn get_config(&self) -> Arc<ServerConfig> {
-        self.server_config.clone()
+        self.server_config.read().unwrap().clone()
+    }
+
+    fn set_config(&self, config: ServerConfig) {
+        *self.server_config.write().unwrap() = Arc::new(config);
     }
 }
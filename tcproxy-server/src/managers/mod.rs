@@ -1,10 +1,13 @@
 mod account_manager;
+mod audit_manager;
 mod authentication_manager;
 mod connections_manager;
 mod feature_manager;
 mod port_manager;
+mod usage_manager;
 
 pub use account_manager::*;
+pub use audit_manager::*;
 pub use authentication_manager::*;
 pub use connections_manager::*;
 pub use feature_manager::*;
@@ -12,6 +15,7 @@ pub use port_manager::*;
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
 use std::sync::Arc;
+pub use usage_manager::*;
 
 pub type IFeatureManager = Box<dyn FeatureManager>;
 
@@ -49,6 +53,12 @@ fn downcast_owned<T: 'static>(boxed: Box<dyn Any>) -> Option<T> {
     boxed.downcast().ok().map(|boxed| *boxed)
 }
 
+impl Default for AppContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl AppContext {
     pub fn new() -> Self {
         Self {
@@ -35,6 +35,7 @@ impl PortPermit {
     }
 }
 
+#[derive(Clone)]
 pub struct PortManager(Arc<Mutex<NetworkPortPool>>);
 
 impl From<NetworkPortPool> for PortManager {
@@ -44,6 +45,33 @@ impl From<NetworkPortPool> for PortManager {
 }
 
 impl PortManager {
+    /// returns how many ports are in use and how many are still available.
+    pub fn usage(&self) -> (usize, usize) {
+        let lock = self.0.lock().unwrap();
+        (lock.used_ports().len(), lock.available_ports().len())
+    }
+
+    pub fn resize(&self, port_range: Range<u16>) {
+        let mut lock = self.0.lock().unwrap();
+
+        debug!("resizing port pool to {:?}", port_range);
+        lock.resize(port_range);
+    }
+
+    /// ports currently reserved by the given connection.
+    pub fn connection_ports(&self, conn_id: &u32) -> Vec<u16> {
+        let lock = self.0.lock().unwrap();
+        let mut ports: Vec<u16> = lock
+            .used_ports()
+            .iter()
+            .filter(|permit| permit.connection_id() == conn_id)
+            .map(|permit| *permit.port())
+            .collect();
+
+        ports.sort();
+        ports
+    }
+
     pub fn free_port(&self, permit: PortPermit) {
         let mut lock = self.0.lock().unwrap();
 
@@ -51,6 +79,17 @@ impl PortManager {
         lock.free_port(permit);
     }
 
+    /// reserves the given port, failing when it is in use or outside of the port range.
+    pub fn reserve_specific_port(
+        &self,
+        conn_id: &u32,
+        conn_token: &str,
+        port: &u16,
+    ) -> Result<PortPermit, PortError> {
+        let mut lock = self.0.lock().unwrap();
+        lock.reserve_specific_port(conn_id, conn_token, port)
+    }
+
     pub fn reserve_port(&self, conn_id: &u32, conn_token: &str) -> Result<PortPermit, PortError> {
         let mut lock = self.0.lock().unwrap();
 
@@ -70,6 +109,7 @@ impl PortManager {
 
 #[derive(Debug, Clone)]
 pub struct NetworkPortPool {
+    port_range: Range<u16>,
     used_ports: HashSet<PortPermit>,
     available_ports: Vec<u16>,
 }
@@ -77,22 +117,40 @@ pub struct NetworkPortPool {
 #[derive(Debug)]
 pub enum PortError {
     PortLimitReached,
+    PortUnavailable,
     Other(Error),
 }
 
 impl NetworkPortPool {
     pub fn new(port_range: Range<u16>) -> Self {
         let mut available_ports = Vec::new();
-        for i in port_range.start..port_range.end {
+        for i in port_range.clone() {
             available_ports.push(i);
         }
 
         Self {
+            port_range,
             used_ports: HashSet::new(),
             available_ports,
         }
     }
 
+    /// changes the range ports are handed out from.
+    /// ports in use are kept until freed, even when they fall outside of the new range.
+    pub fn resize(&mut self, port_range: Range<u16>) {
+        let used_ports: HashSet<u16> = self
+            .used_ports
+            .iter()
+            .map(|permit| *permit.port())
+            .collect();
+
+        self.available_ports = port_range
+            .clone()
+            .filter(|port| !used_ports.contains(port))
+            .collect();
+        self.port_range = port_range;
+    }
+
     pub fn used_ports(&self) -> &HashSet<PortPermit> {
         &self.used_ports
     }
@@ -108,7 +166,9 @@ impl NetworkPortPool {
         }
 
         self.used_ports.remove(&permit);
-        self.available_ports.push(*permit.port());
+        if self.port_range.contains(permit.port()) {
+            self.available_ports.push(*permit.port());
+        }
     }
 
     pub fn reserve_port(
@@ -129,13 +189,32 @@ impl NetworkPortPool {
 
         Ok(port_permit)
     }
+
+    pub fn reserve_specific_port(
+        &mut self,
+        conn_id: &u32,
+        conn_token: &str,
+        port: &u16,
+    ) -> Result<PortPermit, PortError> {
+        let idx = match self.available_ports.iter().position(|item| item == port) {
+            Some(idx) => idx,
+            None => return Err(PortError::PortUnavailable),
+        };
+
+        self.available_ports.remove(idx);
+        let port_permit = PortPermit::new(conn_id, conn_token, port);
+        self.used_ports.insert(port_permit.clone());
+
+        Ok(port_permit)
+    }
 }
 
 impl Display for PortError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let msg = match self {
             PortError::PortLimitReached => "PortLimit has reached".to_owned(),
-            err => format!("unknow error: {}", err),
+            PortError::PortUnavailable => "port is not available".to_owned(),
+            PortError::Other(err) => format!("unknow error: {}", err),
         };
 
         write!(f, "{}", msg)
@@ -156,7 +235,7 @@ impl std::error::Error for PortError {}
 
 #[cfg(test)]
 pub mod tests {
-    use super::NetworkPortPool;
+    use super::{NetworkPortPool, PortError};
 
     #[test]
     pub fn should_be_able_to_reserve_port() {
@@ -195,6 +274,22 @@ pub mod tests {
         assert!(port_permit.port() <= &max_port);
     }
 
+    #[test]
+    pub fn should_reserve_requested_port_only_once() {
+        // Arrange
+        let mut port_manager = NetworkPortPool::new(10..20);
+
+        // Act
+        let first = port_manager.reserve_specific_port(&2, "some_token", &15);
+        let second = port_manager.reserve_specific_port(&3, "some_token", &15);
+        let outside_range = port_manager.reserve_specific_port(&3, "some_token", &25);
+
+        // Assert
+        assert_eq!(first.unwrap().port(), &15);
+        assert!(matches!(second, Err(PortError::PortUnavailable)));
+        assert!(matches!(outside_range, Err(PortError::PortUnavailable)));
+    }
+
     #[test]
     pub fn should_be_able_to_free_port() {
         // Arrange
@@ -213,4 +308,35 @@ pub mod tests {
         assert_eq!(0, port_manager.used_ports().len());
         assert!(port_manager.available_ports.contains(port_permit.port()));
     }
+
+    #[test]
+    pub fn should_keep_used_ports_when_resizing() {
+        // Arrange
+        let mut port_manager = NetworkPortPool::new(10..20);
+        let port_permit = port_manager.reserve_port(&2, "some_token").unwrap();
+
+        // Act
+        port_manager.resize(30..35);
+
+        // Assert
+        assert_eq!(port_manager.available_ports().len(), 5);
+        assert!(port_manager.used_ports().contains(&port_permit));
+
+        port_manager.free_port(port_permit.clone());
+        assert!(!port_manager.available_ports().contains(port_permit.port()));
+    }
+
+    #[test]
+    pub fn should_not_hand_out_used_ports_after_resizing() {
+        // Arrange
+        let mut port_manager = NetworkPortPool::new(10..12);
+        let port_permit = port_manager.reserve_port(&2, "some_token").unwrap();
+
+        // Act
+        port_manager.resize(10..13);
+
+        // Assert
+        assert_eq!(port_manager.available_ports().len(), 2);
+        assert!(!port_manager.available_ports().contains(port_permit.port()));
+    }
 }
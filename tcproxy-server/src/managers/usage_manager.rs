@@ -0,0 +1,131 @@
+use chrono::Utc;
+use diesel::upsert::excluded;
+use diesel::{insert_into, prelude::*};
+use tracing::error;
+use uuid::Uuid;
+
+use crate::models::{Usage, UsageRollupModel};
+use crate::schema::usage_rollups;
+
+#[derive(Debug)]
+pub enum UsageManagerError {
+    Other(tcproxy_core::Error),
+}
+
+pub trait UsageManager: Send + Sync {
+    /// adds the bytes forwarded by the tunnel on `port` to the account usage,
+    /// returning the updated totals of the account.
+    fn add_usage(
+        &self,
+        account_id: &Uuid,
+        period: &str,
+        port: &u16,
+        bytes_in: &u64,
+        bytes_out: &u64,
+    ) -> Result<Usage, UsageManagerError>;
+    fn get_usage(&self, account_id: &Uuid, period: &str) -> Result<Usage, UsageManagerError>;
+}
+
+pub struct DefaultUsageManager {}
+
+impl Default for DefaultUsageManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DefaultUsageManager {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl UsageManager for DefaultUsageManager {
+    fn add_usage(
+        &self,
+        account_id: &Uuid,
+        period: &str,
+        port: &u16,
+        bytes_in: &u64,
+        bytes_out: &u64,
+    ) -> Result<Usage, UsageManagerError> {
+        use crate::schema::usage_rollups::dsl;
+
+        let connection = &mut SqliteConnection::establish("file:tcproxy.db")?;
+        let model = UsageRollupModel::new(account_id, period, port, bytes_in, bytes_out);
+
+        let result = insert_into(usage_rollups::table)
+            .values(&model)
+            .on_conflict((dsl::account_id, dsl::period, dsl::port))
+            .do_update()
+            .set((
+                dsl::bytes_in.eq(dsl::bytes_in + excluded(dsl::bytes_in)),
+                dsl::bytes_out.eq(dsl::bytes_out + excluded(dsl::bytes_out)),
+                dsl::updated_at.eq(excluded(dsl::updated_at)),
+            ))
+            .execute(connection);
+
+        if let Err(err) = result {
+            error!("failed when trying to add usage: {}", err);
+            return Err(err.into());
+        }
+
+        self.get_usage(account_id, period)
+    }
+
+    fn get_usage(&self, account_id: &Uuid, period: &str) -> Result<Usage, UsageManagerError> {
+        use crate::schema::usage_rollups::dsl;
+
+        let connection = &mut SqliteConnection::establish("file:tcproxy.db")?;
+        let result = dsl::usage_rollups
+            .filter(dsl::account_id.eq(account_id.into_bytes().to_vec()))
+            .filter(dsl::period.eq(period))
+            .select(UsageRollupModel::as_select())
+            .load(connection);
+
+        let rollups = match result {
+            Ok(rollups) => rollups,
+            Err(err) => {
+                error!("failed when trying to fetch usage: {}", err);
+                return Err(err.into());
+            }
+        };
+
+        // one rollup per tunnel port, the account usage adds them up.
+        let (mut bytes_in, mut bytes_out) = (0, 0);
+        for rollup in rollups {
+            let usage = Usage::try_from(rollup).map_err(UsageManagerError::Other)?;
+            bytes_in += usage.bytes_in();
+            bytes_out += usage.bytes_out();
+        }
+
+        Ok(Usage::new(account_id, period, &bytes_in, &bytes_out))
+    }
+}
+
+/// period the server is currently accounting usage for.
+pub fn current_usage_period() -> String {
+    crate::models::usage_period(&Utc::now())
+}
+
+impl std::fmt::Display for UsageManagerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UsageManagerError::Other(err) => write!(f, "usage accounting error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for UsageManagerError {}
+
+impl From<diesel::result::Error> for UsageManagerError {
+    fn from(value: diesel::result::Error) -> Self {
+        Self::Other(value.into())
+    }
+}
+
+impl From<diesel::ConnectionError> for UsageManagerError {
+    fn from(value: diesel::ConnectionError) -> Self {
+        Self::Other(value.into())
+    }
+}
@@ -0,0 +1,126 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info};
+
+use tcproxy_core::Result;
+
+use crate::http::{accept, HttpRequest, HttpResponse};
+use crate::metrics::ServerMetrics;
+
+/// Minimal http listener answering `GET /metrics` with the server metrics.
+pub struct MetricsListener {
+    listener: TcpListener,
+    metrics: Arc<ServerMetrics>,
+}
+
+impl MetricsListener {
+    pub async fn bind(addr: &SocketAddr, metrics: &Arc<ServerMetrics>) -> Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        info!("metrics listener running at: {}", listener.local_addr()?);
+
+        Ok(Self {
+            listener,
+            metrics: metrics.clone(),
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    pub fn spawn(self, cancellation_token: CancellationToken) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = self.start() => {},
+                _ = cancellation_token.cancelled() => {},
+            };
+
+            debug!("metrics listener stopped");
+        })
+    }
+
+    async fn start(&self) {
+        loop {
+            let (stream, addr) = accept(&self.listener).await;
+            let metrics = self.metrics.clone();
+
+            tokio::spawn(async move {
+                if let Err(err) = handle_request(stream, &metrics).await {
+                    debug!(
+                        "failed when answering metrics request from {}: {}",
+                        addr, err
+                    );
+                }
+            });
+        }
+    }
+}
+
+async fn handle_request(mut stream: TcpStream, metrics: &ServerMetrics) -> Result<()> {
+    let request = HttpRequest::read(&mut stream).await?;
+    let response = match (request.method(), request.path()) {
+        ("GET", "/metrics") => HttpResponse::new(
+            "200 OK",
+            "text/plain; version=0.0.4; charset=utf-8",
+            &metrics.render(),
+        ),
+        _ => HttpResponse::not_found(),
+    };
+
+    response.write(&mut stream).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio_util::sync::CancellationToken;
+
+    use super::MetricsListener;
+    use crate::managers::{NetworkPortPool, PortManager};
+    use crate::metrics::ServerMetrics;
+
+    async fn send_request(addr: &SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn should_serve_metrics() {
+        // Arrange
+        let metrics = Arc::new(ServerMetrics::new(&PortManager::from(
+            NetworkPortPool::new(10..20),
+        )));
+        let listener =
+            MetricsListener::bind(&SocketAddr::from_str("127.0.0.1:0").unwrap(), &metrics)
+                .await
+                .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let token = CancellationToken::new();
+        listener.spawn(token.clone());
+
+        // Act
+        metrics.tunnel_opened();
+        let found = send_request(&addr, "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").await;
+        let not_found = send_request(&addr, "GET / HTTP/1.1\r\nHost: localhost\r\n\r\n").await;
+        token.cancel();
+
+        // Assert
+        assert!(found.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(found.contains("text/plain; version=0.0.4"));
+        assert!(found.contains("tcproxy_tunnels 1\n"));
+        assert!(not_found.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+}
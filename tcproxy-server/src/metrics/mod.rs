@@ -0,0 +1,5 @@
+mod metrics_listener;
+mod server_metrics;
+
+pub use metrics_listener::MetricsListener;
+pub use server_metrics::ServerMetrics;
@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::managers::PortManager;
+
+/// Counters and gauges describing what the server is doing,
+/// rendered in the prometheus text exposition format.
+pub struct ServerMetrics {
+    port_manager: PortManager,
+    control_connections: AtomicU64,
+    tunnels: AtomicU64,
+    remote_connections: Mutex<BTreeMap<u16, u64>>,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    frame_decode_errors: AtomicU64,
+    authentication_successes: AtomicU64,
+    authentication_failures: AtomicU64,
+}
+
+impl ServerMetrics {
+    pub fn new(port_manager: &PortManager) -> Self {
+        Self {
+            port_manager: port_manager.clone(),
+            control_connections: AtomicU64::new(0),
+            tunnels: AtomicU64::new(0),
+            remote_connections: Mutex::new(BTreeMap::new()),
+            bytes_in: AtomicU64::new(0),
+            bytes_out: AtomicU64::new(0),
+            frame_decode_errors: AtomicU64::new(0),
+            authentication_successes: AtomicU64::new(0),
+            authentication_failures: AtomicU64::new(0),
+        }
+    }
+
+    pub fn control_connection_opened(&self) {
+        self.control_connections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn control_connection_closed(&self) {
+        self.control_connections.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn tunnel_opened(&self) {
+        self.tunnels.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn tunnel_closed(&self) {
+        self.tunnels.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn remote_connection_opened(&self, port: &u16) {
+        let mut connections = self.remote_connections.lock().unwrap();
+        *connections.entry(*port).or_insert(0) += 1;
+    }
+
+    pub fn remote_connection_closed(&self, port: &u16) {
+        let mut connections = self.remote_connections.lock().unwrap();
+        if let Some(count) = connections.get_mut(port) {
+            *count -= 1;
+            if *count == 0 {
+                connections.remove(port);
+            }
+        }
+    }
+
+    pub fn add_bytes_in(&self, bytes: u64) {
+        self.bytes_in.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn add_bytes_out(&self, bytes: u64) {
+        self.bytes_out.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn frame_decode_error(&self) {
+        self.frame_decode_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn authentication(&self, succeeded: bool) {
+        let counter = match succeeded {
+            true => &self.authentication_successes,
+            false => &self.authentication_failures,
+        };
+
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn control_connections(&self) -> u64 {
+        self.control_connections.load(Ordering::Relaxed)
+    }
+
+    pub fn tunnels(&self) -> u64 {
+        self.tunnels.load(Ordering::Relaxed)
+    }
+
+    pub fn remote_connections(&self, port: &u16) -> u64 {
+        let connections = self.remote_connections.lock().unwrap();
+        connections.get(port).copied().unwrap_or(0)
+    }
+
+    /// renders every metric in the prometheus text format (version 0.0.4).
+    pub fn render(&self) -> String {
+        let (used_ports, available_ports) = self.port_manager.usage();
+        let mut output = String::new();
+
+        write_metric(
+            &mut output,
+            "tcproxy_control_connections",
+            "gauge",
+            "Control connections currently open.",
+            &[("", self.control_connections())],
+        );
+        write_metric(
+            &mut output,
+            "tcproxy_tunnels",
+            "gauge",
+            "Tunnels currently listening.",
+            &[("", self.tunnels())],
+        );
+        write_metric(
+            &mut output,
+            "tcproxy_ports_used",
+            "gauge",
+            "Ports of the pool reserved by tunnels.",
+            &[("", used_ports as u64)],
+        );
+        write_metric(
+            &mut output,
+            "tcproxy_ports_available",
+            "gauge",
+            "Ports of the pool still available.",
+            &[("", available_ports as u64)],
+        );
+
+        let remote_connections: Vec<(String, u64)> = self
+            .remote_connections
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(port, count)| (format!("port=\"{}\"", port), *count))
+            .collect();
+
+        let remote_connections: Vec<(&str, u64)> = remote_connections
+            .iter()
+            .map(|(labels, count)| (labels.as_str(), *count))
+            .collect();
+
+        write_metric(
+            &mut output,
+            "tcproxy_remote_connections",
+            "gauge",
+            "Remote connections currently open, per tunnel port.",
+            &remote_connections,
+        );
+        write_metric(
+            &mut output,
+            "tcproxy_bytes_total",
+            "counter",
+            "Bytes transferred through tunnels.",
+            &[
+                ("direction=\"in\"", self.bytes_in.load(Ordering::Relaxed)),
+                ("direction=\"out\"", self.bytes_out.load(Ordering::Relaxed)),
+            ],
+        );
+        write_metric(
+            &mut output,
+            "tcproxy_frame_decode_errors_total",
+            "counter",
+            "Frames received from clients that could not be decoded.",
+            &[("", self.frame_decode_errors.load(Ordering::Relaxed))],
+        );
+        write_metric(
+            &mut output,
+            "tcproxy_authentications_total",
+            "counter",
+            "Authentication attempts, by outcome.",
+            &[
+                (
+                    "outcome=\"success\"",
+                    self.authentication_successes.load(Ordering::Relaxed),
+                ),
+                (
+                    "outcome=\"failure\"",
+                    self.authentication_failures.load(Ordering::Relaxed),
+                ),
+            ],
+        );
+
+        output
+    }
+}
+
+fn write_metric(output: &mut String, name: &str, kind: &str, help: &str, values: &[(&str, u64)]) {
+    let _ = writeln!(output, "# HELP {} {}", name, help);
+    let _ = writeln!(output, "# TYPE {} {}", name, kind);
+
+    for (labels, value) in values {
+        let _ = match labels.is_empty() {
+            true => writeln!(output, "{} {}", name, value),
+            false => writeln!(output, "{}{{{}}} {}", name, labels, value),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ServerMetrics;
+    use crate::managers::{NetworkPortPool, PortManager};
+
+    #[test]
+    pub fn should_render_port_pool_usage() {
+        // Arrange
+        let port_manager = PortManager::from(NetworkPortPool::new(10..20));
+        let metrics = ServerMetrics::new(&port_manager);
+
+        // Act
+        port_manager.reserve_port(&1, "token").unwrap();
+        let result = metrics.render();
+
+        // Assert
+        assert!(result.contains("tcproxy_ports_used 1\n"));
+        assert!(result.contains("tcproxy_ports_available 9\n"));
+    }
+
+    #[test]
+    pub fn should_render_labeled_metrics() {
+        // Arrange
+        let metrics = ServerMetrics::new(&PortManager::from(NetworkPortPool::new(10..20)));
+
+        // Act
+        metrics.remote_connection_opened(&15000);
+        metrics.remote_connection_opened(&15000);
+        metrics.add_bytes_in(128);
+        metrics.authentication(false);
+        let result = metrics.render();
+
+        // Assert
+        assert!(result.contains("tcproxy_remote_connections{port=\"15000\"} 2\n"));
+        assert!(result.contains("tcproxy_bytes_total{direction=\"in\"} 128\n"));
+        assert!(result.contains("tcproxy_bytes_total{direction=\"out\"} 0\n"));
+        assert!(result.contains("tcproxy_authentications_total{outcome=\"failure\"} 1\n"));
+        assert!(result.contains("# TYPE tcproxy_tunnels gauge\n"));
+    }
+
+    #[test]
+    pub fn should_drop_tunnel_port_when_last_connection_closes() {
+        // Arrange
+        let metrics = ServerMetrics::new(&PortManager::from(NetworkPortPool::new(10..20)));
+        metrics.remote_connection_opened(&15000);
+
+        // Act
+        metrics.remote_connection_closed(&15000);
+
+        // Assert
+        assert_eq!(metrics.remote_connections(&15000), 0);
+        assert!(!metrics.render().contains("port=\"15000\""));
+    }
+}
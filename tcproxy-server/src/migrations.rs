@@ -0,0 +1,140 @@
+use diesel::connection::SimpleConnection;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::Text;
+use tcproxy_core::Result;
+use tracing::info;
+
+/// migrations under `migrations/`, in the order they apply. versions are the
+/// ones the diesel cli records, so both can be used on the same database.
+const MIGRATIONS: &[(&str, &str)] = &[
+    (
+        "20230611192208",
+        include_str!("../migrations/2023-06-11-192208_initial_migration/up.sql"),
+    ),
+    (
+        "20261018090000",
+        include_str!("../migrations/2026-10-18-090000_audit_events/up.sql"),
+    ),
+    (
+        "20261018120000",
+        include_str!("../migrations/2026-10-18-120000_users_rate_limit/up.sql"),
+    ),
+    (
+        "20261018150000",
+        include_str!("../migrations/2026-10-18-150000_usage_rollups/up.sql"),
+    ),
+    (
+        "20261018180000",
+        include_str!("../migrations/2026-10-18-180000_users_client_certificate/up.sql"),
+    ),
+];
+
+#[derive(QueryableByName)]
+struct AppliedMigration {
+    #[diesel(sql_type = Text)]
+    version: String,
+}
+
+/// applies the migrations the database doesn't have yet, each one in a transaction.
+pub fn run_pending_migrations(connection: &mut SqliteConnection) -> Result<()> {
+    connection.batch_execute(
+        "CREATE TABLE IF NOT EXISTS __diesel_schema_migrations (
+           version VARCHAR(50) PRIMARY KEY NOT NULL,
+           run_on TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )?;
+
+    let applied: Vec<String> = sql_query("SELECT version FROM __diesel_schema_migrations")
+        .load::<AppliedMigration>(connection)?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect();
+
+    for (version, up) in MIGRATIONS {
+        if applied.iter().any(|applied| applied == version) {
+            continue;
+        }
+
+        connection.transaction::<_, diesel::result::Error, _>(|connection| {
+            connection.batch_execute(up)?;
+            sql_query("INSERT INTO __diesel_schema_migrations (version) VALUES (?)")
+                .bind::<Text, _>(version)
+                .execute(connection)?;
+            Ok(())
+        })?;
+
+        info!("applied database migration {}", version);
+    }
+
+    Ok(())
+}
+
+/// opens the server database and brings it up to date.
+pub fn migrate_database() -> Result<()> {
+    let connection = &mut SqliteConnection::establish("file:tcproxy.db")?;
+    run_pending_migrations(connection)
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel::connection::SimpleConnection;
+    use diesel::prelude::*;
+    use diesel::sql_query;
+
+    use super::{run_pending_migrations, AppliedMigration, MIGRATIONS};
+    use crate::models::UserModel;
+    use crate::schema::users;
+
+    fn applied_versions(connection: &mut SqliteConnection) -> Vec<String> {
+        sql_query("SELECT version FROM __diesel_schema_migrations ORDER BY version")
+            .load::<AppliedMigration>(connection)
+            .unwrap()
+            .into_iter()
+            .map(|migration| migration.version)
+            .collect()
+    }
+
+    #[test]
+    pub fn should_apply_every_migration_once() {
+        // Arrange
+        let connection = &mut SqliteConnection::establish(":memory:").unwrap();
+
+        // Act
+        run_pending_migrations(connection).unwrap();
+        run_pending_migrations(connection).unwrap();
+
+        // Assert
+        let expected: Vec<String> = MIGRATIONS.iter().map(|(v, _)| v.to_string()).collect();
+        assert_eq!(applied_versions(connection), expected);
+    }
+
+    #[test]
+    pub fn should_upgrade_database_created_by_initial_migration() {
+        // Arrange
+        let connection = &mut SqliteConnection::establish(":memory:").unwrap();
+        let (version, up) = MIGRATIONS[0];
+        connection
+            .batch_execute(&format!(
+                "CREATE TABLE __diesel_schema_migrations (
+                   version VARCHAR(50) PRIMARY KEY NOT NULL,
+                   run_on TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                );
+                {up};
+                INSERT INTO __diesel_schema_migrations (version) VALUES ('{version}');"
+            ))
+            .unwrap();
+
+        // Act
+        let result = run_pending_migrations(connection);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(applied_versions(connection).len(), MIGRATIONS.len());
+        let users: Vec<UserModel> = users::table
+            .select(UserModel::as_select())
+            .load(connection)
+            .unwrap();
+        assert!(users.is_empty());
+    }
+}
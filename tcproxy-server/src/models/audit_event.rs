@@ -0,0 +1,271 @@
+use chrono::{DateTime, TimeZone, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEventType {
+    LoginSucceeded,
+    LoginFailed,
+    TunnelOpened,
+    TunnelClosed,
+    RemoteConnectionAccepted,
+    RemoteConnectionClosed,
+    RemoteConnectionDenied,
+}
+
+/// Represents a single entry of the audit log.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AuditEvent {
+    event_type: AuditEventType,
+    account_id: Option<Uuid>,
+    port: Option<u16>,
+    remote_addr: Option<SocketAddr>,
+    bytes_in: Option<u64>,
+    bytes_out: Option<u64>,
+    details: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable)]
+#[diesel(table_name = crate::schema::audit_events)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct AuditEventModel {
+    id: i32,
+    event_type: String,
+    account_id: Option<Vec<u8>>,
+    port: Option<i32>,
+    remote_addr: Option<String>,
+    bytes_in: Option<i64>,
+    bytes_out: Option<i64>,
+    details: Option<String>,
+    created_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Insertable)]
+#[diesel(table_name = crate::schema::audit_events)]
+pub struct NewAuditEventModel {
+    event_type: String,
+    account_id: Option<Vec<u8>>,
+    port: Option<i32>,
+    remote_addr: Option<String>,
+    bytes_in: Option<i64>,
+    bytes_out: Option<i64>,
+    details: Option<String>,
+    created_at: i64,
+}
+
+impl AuditEvent {
+    pub fn new(event_type: AuditEventType) -> Self {
+        Self {
+            event_type,
+            account_id: None,
+            port: None,
+            remote_addr: None,
+            bytes_in: None,
+            bytes_out: None,
+            details: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn with_account(mut self, account_id: Option<&Uuid>) -> Self {
+        self.account_id = account_id.copied();
+        self
+    }
+
+    pub fn with_port(mut self, port: &u16) -> Self {
+        self.port = Some(*port);
+        self
+    }
+
+    pub fn with_remote_addr(mut self, addr: &SocketAddr) -> Self {
+        self.remote_addr = Some(*addr);
+        self
+    }
+
+    pub fn with_bytes(mut self, bytes_in: &u64, bytes_out: &u64) -> Self {
+        self.bytes_in = Some(*bytes_in);
+        self.bytes_out = Some(*bytes_out);
+        self
+    }
+
+    pub fn with_details(mut self, details: &str) -> Self {
+        self.details = Some(String::from(details));
+        self
+    }
+
+    pub fn event_type(&self) -> &AuditEventType {
+        &self.event_type
+    }
+
+    pub fn account_id(&self) -> &Option<Uuid> {
+        &self.account_id
+    }
+
+    pub fn port(&self) -> &Option<u16> {
+        &self.port
+    }
+
+    pub fn remote_addr(&self) -> &Option<SocketAddr> {
+        &self.remote_addr
+    }
+
+    pub fn bytes_in(&self) -> &Option<u64> {
+        &self.bytes_in
+    }
+
+    pub fn bytes_out(&self) -> &Option<u64> {
+        &self.bytes_out
+    }
+
+    pub fn details(&self) -> &Option<String> {
+        &self.details
+    }
+
+    pub fn created_at(&self) -> &DateTime<Utc> {
+        &self.created_at
+    }
+}
+
+impl AuditEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditEventType::LoginSucceeded => "login_succeeded",
+            AuditEventType::LoginFailed => "login_failed",
+            AuditEventType::TunnelOpened => "tunnel_opened",
+            AuditEventType::TunnelClosed => "tunnel_closed",
+            AuditEventType::RemoteConnectionAccepted => "remote_connection_accepted",
+            AuditEventType::RemoteConnectionClosed => "remote_connection_closed",
+            AuditEventType::RemoteConnectionDenied => "remote_connection_denied",
+        }
+    }
+}
+
+impl FromStr for AuditEventType {
+    type Err = tcproxy_core::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "login_succeeded" => Ok(AuditEventType::LoginSucceeded),
+            "login_failed" => Ok(AuditEventType::LoginFailed),
+            "tunnel_opened" => Ok(AuditEventType::TunnelOpened),
+            "tunnel_closed" => Ok(AuditEventType::TunnelClosed),
+            "remote_connection_accepted" => Ok(AuditEventType::RemoteConnectionAccepted),
+            "remote_connection_closed" => Ok(AuditEventType::RemoteConnectionClosed),
+            "remote_connection_denied" => Ok(AuditEventType::RemoteConnectionDenied),
+            actual => Err(format!("invalid audit event type: {}", actual).into()),
+        }
+    }
+}
+
+impl Display for AuditEventType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl From<&AuditEvent> for NewAuditEventModel {
+    fn from(value: &AuditEvent) -> Self {
+        Self {
+            event_type: value.event_type.to_string(),
+            account_id: value.account_id.map(|id| id.into_bytes().to_vec()),
+            port: value.port.map(|port| port as i32),
+            remote_addr: value.remote_addr.map(|addr| addr.to_string()),
+            bytes_in: value.bytes_in.map(|bytes| bytes as i64),
+            bytes_out: value.bytes_out.map(|bytes| bytes as i64),
+            details: value.details.clone(),
+            created_at: value.created_at.timestamp_millis(),
+        }
+    }
+}
+
+impl TryFrom<AuditEventModel> for AuditEvent {
+    type Error = tcproxy_core::Error;
+
+    fn try_from(value: AuditEventModel) -> Result<Self, Self::Error> {
+        let account_id = match value.account_id {
+            Some(id) => Some(Uuid::from_slice(&id)?),
+            None => None,
+        };
+
+        let remote_addr = match value.remote_addr {
+            Some(addr) => Some(SocketAddr::from_str(&addr)?),
+            None => None,
+        };
+
+        let created_at = match Utc.timestamp_millis_opt(value.created_at).single() {
+            Some(date) => date,
+            None => return Err(format!("invalid timestamp: {}", value.created_at).into()),
+        };
+
+        Ok(Self {
+            event_type: AuditEventType::from_str(&value.event_type)?,
+            account_id,
+            port: value.port.map(|port| port as u16),
+            remote_addr,
+            bytes_in: value.bytes_in.map(|bytes| bytes as u64),
+            bytes_out: value.bytes_out.map(|bytes| bytes as u64),
+            details: value.details,
+            created_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn should_convert_event_back_and_forth() {
+        // Arrange
+        let account_id = Uuid::new_v4();
+        let remote_addr = SocketAddr::from_str("10.0.0.1:54321").unwrap();
+        let event = AuditEvent::new(AuditEventType::RemoteConnectionClosed)
+            .with_account(Some(&account_id))
+            .with_port(&15000)
+            .with_remote_addr(&remote_addr)
+            .with_bytes(&1024, &2048);
+
+        let new_model = NewAuditEventModel::from(&event);
+        let model = AuditEventModel {
+            id: 1,
+            event_type: new_model.event_type,
+            account_id: new_model.account_id,
+            port: new_model.port,
+            remote_addr: new_model.remote_addr,
+            bytes_in: new_model.bytes_in,
+            bytes_out: new_model.bytes_out,
+            details: new_model.details,
+            created_at: new_model.created_at,
+        };
+
+        // Act
+        let result = AuditEvent::try_from(model).unwrap();
+
+        // Assert
+        assert_eq!(result.event_type(), &AuditEventType::RemoteConnectionClosed);
+        assert_eq!(result.account_id(), &Some(account_id));
+        assert_eq!(result.port(), &Some(15000));
+        assert_eq!(result.remote_addr(), &Some(remote_addr));
+        assert_eq!(result.bytes_in(), &Some(1024));
+        assert_eq!(result.bytes_out(), &Some(2048));
+        assert_eq!(
+            result.created_at().timestamp_millis(),
+            event.created_at().timestamp_millis()
+        );
+    }
+
+    #[test]
+    pub fn should_return_err_when_event_type_is_invalid() {
+        // Act
+        let result = AuditEventType::from_str("some_invalid_event");
+
+        // Assert
+        assert!(result.is_err());
+    }
+}
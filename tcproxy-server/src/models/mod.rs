@@ -1,3 +1,7 @@
+mod audit_event;
+mod usage;
 mod user;
 
+pub use audit_event::*;
+pub use usage::*;
 pub use user::*;
@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+/// Bytes moved by an account during a billing period (YYYY-MM).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Usage {
+    account_id: Uuid,
+    period: String,
+    bytes_in: u64,
+    bytes_out: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::usage_rollups)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct UsageRollupModel {
+    account_id: Vec<u8>,
+    period: String,
+    port: i32,
+    bytes_in: i64,
+    bytes_out: i64,
+    updated_at: i64,
+}
+
+impl Usage {
+    pub fn new(account_id: &Uuid, period: &str, bytes_in: &u64, bytes_out: &u64) -> Self {
+        Self {
+            account_id: *account_id,
+            period: String::from(period),
+            bytes_in: *bytes_in,
+            bytes_out: *bytes_out,
+        }
+    }
+
+    pub fn account_id(&self) -> &Uuid {
+        &self.account_id
+    }
+
+    pub fn period(&self) -> &str {
+        &self.period
+    }
+
+    pub fn bytes_in(&self) -> &u64 {
+        &self.bytes_in
+    }
+
+    pub fn bytes_out(&self) -> &u64 {
+        &self.bytes_out
+    }
+
+    pub fn total(&self) -> u64 {
+        self.bytes_in + self.bytes_out
+    }
+
+    pub fn exceeds(&self, quota: &Option<u64>) -> bool {
+        match quota {
+            Some(quota) => self.total() >= *quota,
+            None => false,
+        }
+    }
+}
+
+/// billing period the given date belongs to.
+pub fn usage_period(date: &DateTime<Utc>) -> String {
+    date.format("%Y-%m").to_string()
+}
+
+impl UsageRollupModel {
+    pub fn new(
+        account_id: &Uuid,
+        period: &str,
+        port: &u16,
+        bytes_in: &u64,
+        bytes_out: &u64,
+    ) -> Self {
+        Self {
+            account_id: account_id.into_bytes().to_vec(),
+            period: String::from(period),
+            port: *port as i32,
+            bytes_in: *bytes_in as i64,
+            bytes_out: *bytes_out as i64,
+            updated_at: Utc::now().timestamp_millis(),
+        }
+    }
+}
+
+impl TryFrom<UsageRollupModel> for Usage {
+    type Error = tcproxy_core::Error;
+
+    fn try_from(value: UsageRollupModel) -> Result<Self, Self::Error> {
+        Ok(Self {
+            account_id: Uuid::from_slice(&value.account_id)?,
+            period: value.period,
+            bytes_in: value.bytes_in as u64,
+            bytes_out: value.bytes_out as u64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    pub fn should_exceed_quota() {
+        // Arrange
+        let usage = Usage::new(&Uuid::new_v4(), "2026-10", &600, &400);
+
+        // Act
+        let exceeded = usage.exceeds(&Some(1000));
+        let not_exceeded = usage.exceeds(&Some(1001));
+        let unlimited = usage.exceeds(&None);
+
+        // Assert
+        assert!(exceeded);
+        assert!(!not_exceeded);
+        assert!(!unlimited);
+    }
+
+    #[test]
+    pub fn should_format_usage_period() {
+        // Arrange
+        let date = Utc.with_ymd_and_hms(2026, 3, 31, 23, 59, 59).unwrap();
+
+        // Act
+        let period = usage_period(&date);
+
+        // Assert
+        assert_eq!(period, "2026-03");
+    }
+}
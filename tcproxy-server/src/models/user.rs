@@ -13,6 +13,9 @@ pub struct UserModel {
     name: String,
     email: String,
     password_hash: String,
+    rate_limit: Option<i64>,
+    monthly_quota: Option<i64>,
+    client_certificate: Option<String>,
 }
 
 impl UserModel {
@@ -22,9 +25,17 @@ impl UserModel {
             name: String::from(name),
             email: String::from(email),
             password_hash: String::from(password),
+            rate_limit: None,
+            monthly_quota: None,
+            client_certificate: None,
         }
     }
 
+    pub fn with_monthly_quota(mut self, monthly_quota: Option<i64>) -> Self {
+        self.monthly_quota = monthly_quota;
+        self
+    }
+
     pub fn id(&self) -> &[u8] {
         &self.id
     }
@@ -40,4 +51,17 @@ impl UserModel {
     pub fn password(&self) -> &str {
         &self.password_hash
     }
+
+    pub fn rate_limit(&self) -> &Option<i64> {
+        &self.rate_limit
+    }
+
+    pub fn monthly_quota(&self) -> &Option<i64> {
+        &self.monthly_quota
+    }
+
+    /// sha256 fingerprint or subject of the client certificate this user authenticates with.
+    pub fn client_certificate(&self) -> &Option<String> {
+        &self.client_certificate
+    }
 }
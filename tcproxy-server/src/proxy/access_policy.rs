@@ -0,0 +1,130 @@
+use ipnet::IpNet;
+use std::net::{IpAddr, IpAddr::V4, IpAddr::V6};
+
+/// Decides which remote peers are allowed to connect to a proxy listener.
+/// deny lists always win, an empty allow list means everyone is allowed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessPolicy {
+    allow_list: Vec<IpNet>,
+    deny_list: Vec<IpNet>,
+}
+
+impl AccessPolicy {
+    /// merges the server-wide deny list with the lists requested by the client.
+    pub fn new(global_deny_list: &[IpNet], allow_list: &[IpNet], deny_list: &[IpNet]) -> Self {
+        let mut merged_deny_list = global_deny_list.to_vec();
+        merged_deny_list.extend_from_slice(deny_list);
+
+        Self {
+            allow_list: allow_list.to_vec(),
+            deny_list: merged_deny_list,
+        }
+    }
+
+    pub fn allow_list(&self) -> &[IpNet] {
+        &self.allow_list
+    }
+
+    pub fn deny_list(&self) -> &[IpNet] {
+        &self.deny_list
+    }
+
+    pub fn is_allowed(&self, addr: &IpAddr) -> bool {
+        let addr = canonicalize(addr);
+        if self.deny_list.iter().any(|net| net.contains(&addr)) {
+            return false;
+        }
+
+        self.allow_list.is_empty() || self.allow_list.iter().any(|net| net.contains(&addr))
+    }
+}
+
+/// dual-stack listeners report ipv4 peers as ipv4-mapped ipv6 addresses.
+fn canonicalize(addr: &IpAddr) -> IpAddr {
+    match addr {
+        V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ipv4) => V4(ipv4),
+            None => *addr,
+        },
+        V4(_) => *addr,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AccessPolicy;
+    use ipnet::IpNet;
+    use std::net::IpAddr;
+    use std::str::FromStr;
+
+    fn nets(values: &[&str]) -> Vec<IpNet> {
+        values.iter().map(|v| IpNet::from_str(v).unwrap()).collect()
+    }
+
+    fn ip(value: &str) -> IpAddr {
+        IpAddr::from_str(value).unwrap()
+    }
+
+    #[test]
+    pub fn should_allow_everyone_when_lists_are_empty() {
+        // Arrange
+        let policy = AccessPolicy::default();
+
+        // Act
+        let result = policy.is_allowed(&ip("8.8.8.8"));
+
+        // Assert
+        assert!(result);
+    }
+
+    #[test]
+    pub fn should_only_allow_addresses_in_allow_list() {
+        // Arrange
+        let policy = AccessPolicy::new(&[], &nets(&["10.0.0.0/8"]), &[]);
+
+        // Act
+        let inside = policy.is_allowed(&ip("10.1.2.3"));
+        let outside = policy.is_allowed(&ip("192.168.0.1"));
+
+        // Assert
+        assert!(inside);
+        assert!(!outside);
+    }
+
+    #[test]
+    pub fn deny_list_should_take_precedence_over_allow_list() {
+        // Arrange
+        let policy = AccessPolicy::new(&[], &nets(&["10.0.0.0/8"]), &nets(&["10.1.0.0/16"]));
+
+        // Act
+        let result = policy.is_allowed(&ip("10.1.2.3"));
+
+        // Assert
+        assert!(!result);
+    }
+
+    #[test]
+    pub fn should_deny_addresses_in_global_deny_list() {
+        // Arrange
+        let policy = AccessPolicy::new(&nets(&["203.0.113.0/24"]), &[], &[]);
+
+        // Act
+        let result = policy.is_allowed(&ip("203.0.113.7"));
+
+        // Assert
+        assert!(!result);
+        assert_eq!(policy.deny_list(), &nets(&["203.0.113.0/24"])[..]);
+    }
+
+    #[test]
+    pub fn should_match_ipv4_mapped_addresses() {
+        // Arrange
+        let policy = AccessPolicy::new(&[], &[], &nets(&["10.0.0.0/8"]));
+
+        // Act
+        let result = policy.is_allowed(&ip("::ffff:10.0.0.1"));
+
+        // Assert
+        assert!(!result);
+    }
+}
@@ -1,31 +1,58 @@
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tcproxy_core::stream::Stream;
 use tcproxy_core::transport::TcpFrameTransport;
 use tcproxy_core::{Result, TcpFrame};
-use tokio::sync::mpsc;
+use tokio::sync::mpsc::{self, Sender};
+use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 use tracing::debug;
 
-use crate::managers::{AuthenticationManagerGuard, PortManager, UserManager};
-use crate::proxy::{ClientFrameReader, ClientFrameWriter};
-use crate::{ClientState, ServerConfig};
+use crate::managers::{
+    AuditManager, AuthenticationManagerGuard, IFeatureManager, PortManager, UsageManager,
+    UserManager,
+};
+use crate::metrics::ServerMetrics;
+use crate::proxy::{ClientFrameReader, ClientFrameWriter, TunnelStatsReporter};
+use crate::tcp::BandwidthRegistry;
+use crate::ClientState;
 
 pub struct ClientConnection {
     state: Arc<ClientState>,
 }
 
 impl ClientConnection {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         port_guard: PortManager,
         auth_guard: Arc<AuthenticationManagerGuard>,
-        server_config: &Arc<ServerConfig>,
+        feature_manager: &Arc<IFeatureManager>,
         account_manager: &Arc<impl UserManager + 'static>,
+        audit_manager: &Arc<impl AuditManager + 'static>,
+        usage_manager: &Arc<impl UsageManager + 'static>,
+        metrics: &Arc<ServerMetrics>,
+        bandwidth_registry: &Arc<BandwidthRegistry>,
+        remote_addr: &SocketAddr,
     ) -> Self {
         Self {
-            state: ClientState::new(port_guard, auth_guard, server_config, account_manager),
+            state: ClientState::new(
+                port_guard,
+                auth_guard,
+                feature_manager,
+                account_manager,
+                audit_manager,
+                usage_manager,
+                metrics,
+                bandwidth_registry,
+                remote_addr,
+            ),
         }
     }
 
+    pub fn get_state(&self) -> &Arc<ClientState> {
+        &self.state
+    }
+
     /// Starts reading and writing to client.
     pub async fn start_streaming(
         &mut self,
@@ -38,6 +65,9 @@ impl ClientConnection {
         let client_reader = ClientFrameReader::new(transport_reader, &self.state, &frame_tx);
         let proxy_writer =
             ClientFrameWriter::new(frame_rx, transport_writer, &local_cancellation_token);
+        let shutdown_notifier = spawn_shutdown_notifier(&self.state, &frame_tx);
+        TunnelStatsReporter::new(&self.state, &frame_tx)
+            .spawn(self.state.get_session_token().clone());
 
         tokio::select! {
             res = proxy_writer.spawn() => {
@@ -49,9 +79,34 @@ impl ClientConnection {
             _ = cancellation_token.cancelled() => {
                 debug!("received global stop signal..");
             },
+            _ = self.state.get_session_token().cancelled() => {
+                debug!("session was closed..");
+            },
         };
 
+        shutdown_notifier.abort();
         local_cancellation_token.cancel();
+        self.state.get_session_token().cancel();
         Ok(())
     }
 }
+
+/// forwards the server shutdown notice to the client, once there is one.
+fn spawn_shutdown_notifier(state: &Arc<ClientState>, sender: &Sender<TcpFrame>) -> JoinHandle<()> {
+    let mut receiver = state.subscribe_shutdown();
+    let sender = sender.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let notice = receiver.borrow().clone();
+            if let Some(notice) = notice {
+                let _ = sender.send(TcpFrame::from(notice)).await;
+                return;
+            }
+
+            if receiver.changed().await.is_err() {
+                return;
+            }
+        }
+    })
+}
@@ -1,11 +1,17 @@
+mod access_policy;
 mod connection;
 mod proxy_auth;
 mod proxy_client_reader;
 mod proxy_client_writer;
 mod proxy_server;
+mod quota_enforcer;
+mod tunnel_stats_reporter;
 
+pub use access_policy::AccessPolicy;
 pub use connection::*;
 pub use proxy_auth::*;
 pub use proxy_client_reader::ClientFrameReader;
 pub use proxy_client_writer::ClientFrameWriter;
 pub use proxy_server::*;
+pub use quota_enforcer::QuotaEnforcer;
+pub use tunnel_stats_reporter::TunnelStatsReporter;
@@ -6,12 +6,12 @@ use tokio_util::sync::CancellationToken;
 use tracing::{debug, info};
 
 use tcproxy_core::transport::TransportReader;
-use tcproxy_core::{Result, TcpFrame};
+use tcproxy_core::{FrameDecodeError, Result, TcpFrame};
 
 use crate::commands::authenticate::AuthenticateFrameHandler;
 use crate::commands::{
     ClientConnectedHandler, DataPacketHandler, NewFrameHandler, PingFrameHandler,
-    SocketDisconnectedHandler,
+    SocketDisconnectedHandler, UsageRequestHandler,
 };
 use crate::ClientState;
 
@@ -41,13 +41,19 @@ impl ClientFrameReader {
     /// Start listening for frames, and handling them.
     async fn start(mut self, cancellation_token: CancellationToken) -> Result<()> {
         while !cancellation_token.is_cancelled() {
-            let maybe_frame = self.reader.next().await?;
-            let frame = match maybe_frame {
-                Some(f) => f,
-                None => {
+            let frame = match self.reader.next().await {
+                Ok(Some(f)) => f,
+                Ok(None) => {
                     info!("received none from frame reader");
                     break;
                 }
+                Err(err) => {
+                    if err.downcast_ref::<FrameDecodeError>().is_some() {
+                        self.state.get_metrics().frame_decode_error();
+                    }
+
+                    return Err(err);
+                }
             };
 
             debug!("received new frame from client {}", frame);
@@ -70,6 +76,7 @@ async fn handle_frame(
         F::Authenticate(data) => AuthenticateFrameHandler::from(data).into(),
         F::ClientConnected(data) => ClientConnectedHandler::from(data).into(),
         F::SocketDisconnected(data) => SocketDisconnectedHandler::from(data).into(),
+        F::UsageRequest(data) => UsageRequestHandler::from(data).into(),
         actual => {
             debug!("invalid frame received. {}", actual);
             return Ok(());
@@ -1,3 +1,4 @@
+use std::net::IpAddr;
 use std::sync::Arc;
 use tcproxy_core::TcpFrame;
 
@@ -10,8 +11,10 @@ use tcproxy_core::framing::SocketConnected;
 use tcproxy_core::tcp::SocketListener;
 use tcproxy_core::Result;
 
-use crate::managers::PortPermit;
-use crate::tcp::RemoteConnection;
+use crate::managers::{record_audit_event, ConnectionInfo, PortPermit};
+use crate::models::{AuditEvent, AuditEventType};
+use crate::proxy::AccessPolicy;
+use crate::tcp::{ConnectionStats, RemoteConnection, TunnelLimits};
 use crate::ClientState;
 
 pub struct ProxyServer {
@@ -19,6 +22,9 @@ pub struct ProxyServer {
     listener: Box<dyn SocketListener + 'static>,
     proxy_state: Arc<ClientState>,
     client_sender: Sender<TcpFrame>,
+    access_policy: AccessPolicy,
+    tunnel_limits: Arc<TunnelLimits>,
+    tunnel_stats: Arc<ConnectionStats>,
 }
 
 impl ProxyServer {
@@ -27,41 +33,89 @@ impl ProxyServer {
         state: &Arc<ClientState>,
         sender: &Sender<TcpFrame>,
         listener: T,
+        access_policy: AccessPolicy,
+        tunnel_limits: TunnelLimits,
     ) -> Self
     where
         T: SocketListener + 'static,
     {
+        // bytes forwarded by the tunnel, accounted into the usage of its port.
+        let tunnel_stats = Arc::new(ConnectionStats::with_parent(state.get_traffic()));
+        state
+            .get_usage_recorder()
+            .track(port_permit.port(), &tunnel_stats);
+
         Self {
             port_permit,
             proxy_state: state.clone(),
             client_sender: sender.clone(),
             listener: Box::new(listener),
+            access_policy,
+            tunnel_limits: Arc::new(tunnel_limits),
+            tunnel_stats,
         }
     }
 
     pub fn spawn(mut self, cancellation_token: CancellationToken) {
         tokio::spawn(async move {
+            let metrics = self.proxy_state.get_metrics().clone();
             let token = cancellation_token.child_token();
+
+            metrics.tunnel_opened();
             tokio::select! {
-                _ = self.start() => {},
+                _ = self.start(&token) => {},
                 _ = token.cancelled() => {},
             };
 
+            // closes remote connections still attached to this listener.
+            token.cancel();
+            metrics.tunnel_closed();
+
             tracing::debug!("socket server {} is being shut down..", self.port_permit);
+            let event = AuditEvent::new(AuditEventType::TunnelClosed)
+                .with_account(self.proxy_state.get_auth_manager().account_id().as_ref())
+                .with_port(self.port_permit.port())
+                .with_remote_addr(self.proxy_state.get_remote_addr());
+
+            record_audit_event(self.proxy_state.get_audit_manager().as_ref(), event);
+
+            let usage_recorder = self.proxy_state.get_usage_recorder();
+            if let Err(err) = usage_recorder.untrack(self.port_permit.port(), &self.proxy_state) {
+                tracing::warn!("unable to persist tunnel usage: {}", err);
+            }
+
             self.proxy_state
                 .get_port_manager()
                 .free_port(self.port_permit);
         });
     }
 
-    async fn start(&mut self) -> Result<()> {
-        let semaphore = Arc::new(Semaphore::new(120));
+    async fn start(&mut self, cancellation_token: &CancellationToken) -> Result<()> {
+        let max_connections = self
+            .proxy_state
+            .get_server_config()
+            .get_max_connections_per_proxy();
+        let semaphore = Arc::new(Semaphore::new(max_connections as usize));
 
         loop {
             let permit = semaphore.clone().acquire_owned().await.unwrap();
 
             let connection = self.listener.accept().await?;
-            self.spawn_remote_connection(connection, permit).await?;
+            if self.proxy_state.is_draining() {
+                tracing::debug!(
+                    "server is shutting down, refusing connection from {}",
+                    connection.remote_addr()
+                );
+                continue;
+            }
+
+            if !self.is_allowed(&connection.remote_addr().ip()) {
+                self.reject_remote_connection(connection);
+                continue;
+            }
+
+            self.spawn_remote_connection(connection, permit, cancellation_token)
+                .await?;
         }
     }
 
@@ -69,33 +123,85 @@ impl ProxyServer {
         &self,
         connection: tcproxy_core::tcp::RemoteConnection,
         permit: OwnedSemaphorePermit,
+        cancellation_token: &CancellationToken,
     ) -> Result<()> {
-        let (connection_id, receiver) = self.create_connection_state();
-        let remote_connection = RemoteConnection::new(&connection_id, permit, &self.client_sender);
+        let connection_token = cancellation_token.child_token();
+        let stats = Arc::new(ConnectionStats::with_parent(&self.tunnel_stats));
+        let info = ConnectionInfo::new(self.port_permit.port(), connection.remote_addr(), &stats);
+        let (connection_id, receiver) = self.create_connection_state(&connection_token, info);
+        let remote_connection = RemoteConnection::new(
+            &connection_id,
+            self.port_permit.port(),
+            permit,
+            &self.proxy_state,
+            &self.client_sender,
+            &stats,
+            &self.tunnel_limits,
+        );
+
+        let event = AuditEvent::new(AuditEventType::RemoteConnectionAccepted)
+            .with_account(self.proxy_state.get_auth_manager().account_id().as_ref())
+            .with_port(self.port_permit.port())
+            .with_remote_addr(connection.remote_addr());
+
+        record_audit_event(self.proxy_state.get_audit_manager().as_ref(), event);
+
+        let frame = SocketConnected::new(&connection_id)
+            .with_listener_port(self.port_permit.port())
+            .with_peer_addr(connection.remote_addr());
+        let frame = match connection.local_addr() {
+            Some(local_addr) => frame.with_listener_addr(local_addr),
+            None => frame,
+        };
 
-        self.send_incoming_connection_frame(&connection_id).await?;
+        self.client_sender
+            .send(TcpFrame::SocketConnected(frame))
+            .await?;
         tokio::spawn(async move {
-            let _ = remote_connection.start(connection, receiver).await;
+            let _ = remote_connection
+                .start(connection, receiver, connection_token)
+                .await;
         });
         Ok(())
     }
 
-    async fn send_incoming_connection_frame(&self, connection_id: &u32) -> Result<()> {
-        self.client_sender
-            .send(TcpFrame::SocketConnected(SocketConnected::new(
-                connection_id,
-            )))
-            .await?;
+    /// checks the tunnel lists and the server-wide deny list currently in use.
+    fn is_allowed(&self, addr: &IpAddr) -> bool {
+        let server_config = self.proxy_state.get_server_config();
+        let global_policy = AccessPolicy::new(server_config.get_global_deny_list(), &[], &[]);
 
-        Ok(())
+        global_policy.is_allowed(addr) && self.access_policy.is_allowed(addr)
+    }
+
+    /// drops the connection before the client ever hears about it.
+    fn reject_remote_connection(&self, connection: tcproxy_core::tcp::RemoteConnection) {
+        tracing::info!(
+            "connection from {} denied on port {}",
+            connection.remote_addr(),
+            self.port_permit.port()
+        );
+
+        let event = AuditEvent::new(AuditEventType::RemoteConnectionDenied)
+            .with_account(self.proxy_state.get_auth_manager().account_id().as_ref())
+            .with_port(self.port_permit.port())
+            .with_remote_addr(connection.remote_addr());
+
+        record_audit_event(self.proxy_state.get_audit_manager().as_ref(), event);
     }
 
-    fn create_connection_state(&self) -> (u32, Receiver<Vec<u8>>) {
+    fn create_connection_state(
+        &self,
+        cancellation_token: &CancellationToken,
+        info: ConnectionInfo,
+    ) -> (u32, Receiver<Vec<u8>>) {
         let connection_manager = self.proxy_state.get_connection_manager();
 
         let (connection_sender, connection_receiver) = mpsc::channel::<Vec<u8>>(100);
-        let connection_id =
-            connection_manager.insert_connection(connection_sender, CancellationToken::new());
+        let connection_id = connection_manager.insert_connection(
+            connection_sender,
+            cancellation_token.clone(),
+            info,
+        );
 
         (connection_id, connection_receiver)
     }
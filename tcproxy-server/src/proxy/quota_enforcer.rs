@@ -0,0 +1,78 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use tcproxy_core::framing::{Error, Reason};
+use tcproxy_core::TcpFrame;
+
+use crate::ClientState;
+
+/// Periodically persists the tunnel traffic into the account usage,
+/// closing the tunnel once the account goes over its monthly quota.
+/// the last bytes are flushed by the tunnel itself, when it closes.
+pub struct QuotaEnforcer {
+    state: Arc<ClientState>,
+    client_sender: Sender<TcpFrame>,
+    tunnel_token: CancellationToken,
+    interval: Duration,
+}
+
+impl QuotaEnforcer {
+    pub fn new(
+        state: &Arc<ClientState>,
+        client_sender: &Sender<TcpFrame>,
+        tunnel_token: &CancellationToken,
+    ) -> Self {
+        Self {
+            state: state.clone(),
+            client_sender: client_sender.clone(),
+            tunnel_token: tunnel_token.clone(),
+            interval: Duration::from_secs(30),
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.interval);
+            interval.tick().await;
+
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {},
+                    _ = self.tunnel_token.cancelled() => break,
+                    _ = self.client_sender.closed() => break,
+                };
+
+                if self.flush() {
+                    self.close_tunnel().await;
+                    return;
+                }
+            }
+
+            debug!("tunnel closed, stopping quota enforcer");
+        })
+    }
+
+    /// returns whether the account went over its quota.
+    fn flush(&self) -> bool {
+        match self.state.get_usage_recorder().flush(&self.state) {
+            Ok(Some(usage)) => usage.exceeds(&self.state.get_monthly_quota()),
+            Ok(None) => false,
+            Err(err) => {
+                warn!("unable to persist tunnel usage: {}", err);
+                false
+            }
+        }
+    }
+
+    async fn close_tunnel(&self) {
+        info!("account went over its monthly quota, closing tunnel");
+
+        let frame = TcpFrame::Error(Error::new(&Reason::QuotaExceeded, &[]));
+        let _ = self.client_sender.send(frame).await;
+        self.tunnel_token.cancel();
+    }
+}
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+use tcproxy_core::framing::TunnelStats;
+use tcproxy_core::TcpFrame;
+
+use crate::ClientState;
+
+/// Periodically reports the throughput of the session tunnels back to the client.
+pub struct TunnelStatsReporter {
+    state: Arc<ClientState>,
+    client_sender: Sender<TcpFrame>,
+    interval: Duration,
+}
+
+impl TunnelStatsReporter {
+    pub fn new(state: &Arc<ClientState>, client_sender: &Sender<TcpFrame>) -> Self {
+        Self {
+            state: state.clone(),
+            client_sender: client_sender.clone(),
+            interval: Duration::from_secs(1),
+        }
+    }
+
+    /// runs until the session is closed, reporting only while it has tunnels open.
+    pub fn spawn(self, session_token: CancellationToken) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let traffic = self.state.get_traffic();
+            let mut last_bytes_in = traffic.bytes_in();
+            let mut last_bytes_out = traffic.bytes_out();
+            let mut interval = tokio::time::interval(self.interval);
+            interval.tick().await;
+
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {},
+                    _ = session_token.cancelled() => break,
+                };
+
+                let total_bytes_in = traffic.bytes_in();
+                let total_bytes_out = traffic.bytes_out();
+                let frame = TunnelStats::new(
+                    &per_second(total_bytes_in - last_bytes_in, &self.interval),
+                    &per_second(total_bytes_out - last_bytes_out, &self.interval),
+                    &total_bytes_in,
+                    &total_bytes_out,
+                    &self
+                        .state
+                        .get_bandwidth_limits()
+                        .and_then(|limits| limits.effective_limit()),
+                );
+
+                last_bytes_in = total_bytes_in;
+                last_bytes_out = total_bytes_out;
+
+                if !self.has_tunnels() {
+                    continue;
+                }
+
+                if self.client_sender.send(frame.into()).await.is_err() {
+                    debug!("client is gone, stopping tunnel stats reporter");
+                    break;
+                }
+            }
+        })
+    }
+
+    fn has_tunnels(&self) -> bool {
+        !self
+            .state
+            .get_port_manager()
+            .connection_ports(self.state.get_session_id())
+            .is_empty()
+    }
+}
+
+fn per_second(bytes: u64, interval: &Duration) -> u64 {
+    (bytes as f64 / interval.as_secs_f64()) as u64
+}
@@ -0,0 +1,186 @@
+use crate::ServerConfig;
+
+/// Differences between the running config and the one read from disk.
+/// changes that can't be applied while running are reverted and reported back.
+pub struct ConfigChanges {
+    config: ServerConfig,
+    rejected: Vec<&'static str>,
+    port_range_changed: bool,
+    certificate_changed: bool,
+    rate_limit_changed: bool,
+}
+
+impl ConfigChanges {
+    pub fn new(current: &ServerConfig, desired: ServerConfig) -> Self {
+        let mut config = desired;
+        let mut rejected = Vec::new();
+
+        if config.get_listen_ip() != current.get_listen_ip() {
+            config.set_listen_ip(current.get_listen_ip());
+            rejected.push("listen_ip");
+        }
+
+        if config.get_listen_port() != current.get_listen_port() {
+            config.set_listen_port(current.get_listen_port());
+            rejected.push("listen_port");
+        }
+
+        if config.get_metrics_addr() != current.get_metrics_addr() {
+            config.set_metrics_addr(*current.get_metrics_addr());
+            rejected.push("metrics_addr");
+        }
+
+        if config.get_admin_addr() != current.get_admin_addr() {
+            config.set_admin_addr(*current.get_admin_addr());
+            rejected.push("admin_addr");
+        }
+
+        if config.get_admin_token() != current.get_admin_token() {
+            config.set_admin_token(current.get_admin_token().clone());
+            rejected.push("admin_token");
+        }
+
+        if config.get_acme_directory() != current.get_acme_directory()
+            || config.get_acme_email() != current.get_acme_email()
+            || config.get_acme_http_addr() != current.get_acme_http_addr()
+            || config.get_acme_cache_dir() != current.get_acme_cache_dir()
+            || config.get_acme_ca_path() != current.get_acme_ca_path()
+        {
+            config.set_acme_directory(current.get_acme_directory().clone());
+            config.set_acme_email(current.get_acme_email().clone());
+            config.set_acme_http_addr(*current.get_acme_http_addr());
+            config.set_acme_cache_dir(current.get_acme_cache_dir().clone());
+            config.set_acme_ca_path(current.get_acme_ca_path().clone());
+            rejected.push("acme");
+        }
+
+        Self {
+            port_range_changed: config.get_port_range() != current.get_port_range(),
+            certificate_changed: config.get_certificate_path() != current.get_certificate_path()
+                || config.get_certificate_pass() != current.get_certificate_pass()
+                || config.get_certificate_key_path() != current.get_certificate_key_path()
+                || config.get_client_ca_path() != current.get_client_ca_path(),
+            rate_limit_changed: config.get_rate_limit() != current.get_rate_limit(),
+            config,
+            rejected,
+        }
+    }
+
+    /// config to be applied, restart only keys keep their current values.
+    pub fn config(&self) -> &ServerConfig {
+        &self.config
+    }
+
+    pub fn into_config(self) -> ServerConfig {
+        self.config
+    }
+
+    /// keys that changed but only take effect after a restart.
+    pub fn rejected(&self) -> &[&'static str] {
+        &self.rejected
+    }
+
+    pub fn port_range_changed(&self) -> bool {
+        self.port_range_changed
+    }
+
+    pub fn certificate_changed(&self) -> bool {
+        self.certificate_changed
+    }
+
+    pub fn rate_limit_changed(&self) -> bool {
+        self.rate_limit_changed
+    }
+
+    /// keeps the current certificate, used when the new one can't be loaded.
+    /// the load error is reported by the caller, the change is not waiting for a restart.
+    pub fn reject_certificate(&mut self, current: &ServerConfig) {
+        self.config
+            .set_certificate_path(current.get_certificate_path().clone());
+        self.config
+            .set_certificate_pass(current.get_certificate_pass().clone());
+        self.config
+            .set_certificate_key_path(current.get_certificate_key_path().clone());
+        self.config
+            .set_client_ca_path(current.get_client_ca_path().clone());
+        self.certificate_changed = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+    use std::path::PathBuf;
+    use std::str::FromStr;
+
+    use super::ConfigChanges;
+    use crate::ServerConfig;
+
+    fn create_config(
+        listen_port: u16,
+        port_range: (u16, u16),
+        max_connections: u16,
+    ) -> ServerConfig {
+        ServerConfig::new(
+            port_range.0,
+            port_range.1,
+            IpAddr::from_str("127.0.0.1").unwrap(),
+            listen_port,
+            "proxy.server.local",
+            max_connections,
+            "SOME_SECRET",
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    pub fn should_apply_safe_changes() {
+        // Arrange
+        let current = create_config(8080, (10, 20), 120);
+        let desired = create_config(8080, (10, 40), 300);
+
+        // Act
+        let changes = ConfigChanges::new(&current, desired);
+
+        // Assert
+        assert!(changes.rejected().is_empty());
+        assert!(changes.port_range_changed());
+        assert!(!changes.certificate_changed());
+        assert_eq!(changes.config().get_port_range(), 10..40);
+        assert_eq!(changes.config().get_max_connections_per_proxy(), 300);
+    }
+
+    #[test]
+    pub fn should_reject_changes_requiring_restart() {
+        // Arrange
+        let current = create_config(8080, (10, 20), 120);
+        let desired = create_config(9090, (10, 20), 120);
+
+        // Act
+        let changes = ConfigChanges::new(&current, desired);
+
+        // Assert
+        assert_eq!(changes.rejected(), &["listen_port"]);
+        assert_eq!(changes.config().get_listen_port(), 8080);
+    }
+
+    #[test]
+    pub fn should_revert_rejected_certificate() {
+        // Arrange
+        let current = create_config(8080, (10, 20), 120);
+        let mut desired = create_config(8080, (10, 20), 120);
+        desired.set_certificate_path(Some(PathBuf::from("cert.pfx")));
+
+        // Act
+        let mut changes = ConfigChanges::new(&current, desired);
+        let certificate_changed = changes.certificate_changed();
+        changes.reject_certificate(&current);
+
+        // Assert
+        assert!(certificate_changed);
+        assert!(!changes.certificate_changed());
+        assert_eq!(changes.config().get_certificate_path(), &None);
+        assert!(changes.rejected().is_empty());
+    }
+}
@@ -0,0 +1,238 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use tcproxy_core::tcp::SocketListener;
+use tcproxy_core::Result;
+
+use crate::managers::{IFeatureManager, PortManager};
+use crate::reload::ConfigChanges;
+use crate::tcp::BandwidthRegistry;
+use crate::tls::load_tls;
+use crate::ServerConfig;
+
+pub type ConfigSource = Box<dyn Fn() -> Result<ServerConfig> + Send + Sync>;
+
+/// Reloads the server config when its file changes or SIGHUP is received,
+/// applying the changes that are safe to apply while tunnels are running.
+/// certificates renewed in place are picked up when their files change, or on SIGHUP.
+pub struct ConfigReloader {
+    config_path: PathBuf,
+    config_source: ConfigSource,
+    feature_manager: Arc<IFeatureManager>,
+    listener: Arc<dyn SocketListener>,
+    port_manager: PortManager,
+    bandwidth: Arc<BandwidthRegistry>,
+    certificate_modified: Mutex<Vec<Option<SystemTime>>>,
+    interval: Duration,
+}
+
+impl ConfigReloader {
+    pub fn new(
+        config_path: &Path,
+        config_source: ConfigSource,
+        feature_manager: &Arc<IFeatureManager>,
+        listener: &Arc<dyn SocketListener>,
+        port_manager: &PortManager,
+        bandwidth: &Arc<BandwidthRegistry>,
+    ) -> Self {
+        Self {
+            config_path: config_path.to_path_buf(),
+            config_source,
+            feature_manager: feature_manager.clone(),
+            listener: listener.clone(),
+            port_manager: port_manager.clone(),
+            bandwidth: bandwidth.clone(),
+            certificate_modified: Mutex::new(certificate_modified_at(
+                &feature_manager.get_config(),
+            )),
+            interval: Duration::from_secs(2),
+        }
+    }
+
+    pub fn spawn(self, cancellation_token: CancellationToken) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut hangup = HangupSignal::new();
+            let mut interval = tokio::time::interval(self.interval);
+            let mut last_modified = self.modified_at();
+
+            loop {
+                let hangup_received = tokio::select! {
+                    _ = interval.tick() => {
+                        let modified = self.modified_at();
+                        let config = self.feature_manager.get_config();
+                        if modified == last_modified && !self.certificate_renewed(&config) {
+                            continue;
+                        }
+
+                        debug!("config file {:?} or its certificate changed", self.config_path);
+                        last_modified = modified;
+                        false
+                    },
+                    _ = hangup.recv() => {
+                        info!("received SIGHUP");
+                        true
+                    },
+                    _ = cancellation_token.cancelled() => break,
+                };
+
+                self.reload(hangup_received);
+            }
+        })
+    }
+
+    fn modified_at(&self) -> Option<SystemTime> {
+        std::fs::metadata(&self.config_path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    /// whether the certificate files changed since they were last loaded.
+    fn certificate_renewed(&self, config: &ServerConfig) -> bool {
+        *self.certificate_modified.lock().unwrap() != certificate_modified_at(config)
+    }
+
+    /// `reload_certificate` rebuilds the certificate even if its files look the same.
+    fn reload(&self, reload_certificate: bool) {
+        let desired = match (self.config_source)() {
+            Ok(config) => config,
+            Err(err) => {
+                warn!("config reload failed, keeping the current config: {}", err);
+                return;
+            }
+        };
+
+        let current = self.feature_manager.get_config();
+        let mut changes = ConfigChanges::new(&current, desired);
+
+        // certificates issued through ACME are not read from files, the renewal task takes care of them.
+        let renewed = changes.config().get_certificate_path().is_some()
+            && (reload_certificate || self.certificate_renewed(changes.config()));
+
+        if changes.certificate_changed() || renewed {
+            let result = load_tls(changes.config()).and_then(|tls| self.listener.set_tls(tls));
+            // also on failure, so a broken file is reported once and retried when written again.
+            *self.certificate_modified.lock().unwrap() = certificate_modified_at(changes.config());
+
+            match result {
+                Ok(_) => info!("certificate reloaded"),
+                Err(err) => {
+                    warn!(
+                        "unable to load the certificate, keeping the current one: {}",
+                        err
+                    );
+                    changes.reject_certificate(&current);
+                }
+            }
+        }
+
+        for key in changes.rejected() {
+            warn!("change of `{}` requires a restart, ignoring it", key);
+        }
+
+        if changes.port_range_changed() {
+            self.port_manager.resize(changes.config().get_port_range());
+        }
+
+        if changes.rate_limit_changed() {
+            self.bandwidth
+                .set_global_limit(*changes.config().get_rate_limit());
+        }
+
+        self.feature_manager.set_config(changes.into_config());
+        info!("configuration reloaded");
+    }
+}
+
+/// modification times of the certificate, key and client CA files.
+fn certificate_modified_at(config: &ServerConfig) -> Vec<Option<SystemTime>> {
+    [
+        config.get_certificate_path(),
+        config.get_certificate_key_path(),
+        config.get_client_ca_path(),
+    ]
+    .iter()
+    .map(|path| {
+        path.as_ref()
+            .and_then(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+    })
+    .collect()
+}
+
+/// SIGHUP listener, never resolves on platforms without it.
+struct HangupSignal {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl HangupSignal {
+    fn new() -> Self {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+
+            let signal = match signal(SignalKind::hangup()) {
+                Ok(signal) => Some(signal),
+                Err(err) => {
+                    warn!("unable to listen for SIGHUP: {}", err);
+                    None
+                }
+            };
+
+            Self { signal }
+        }
+
+        #[cfg(not(unix))]
+        {
+            Self {}
+        }
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = self.signal.as_mut() {
+            signal.recv().await;
+            return;
+        }
+
+        std::future::pending::<()>().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::time::{Duration, SystemTime};
+    use uuid::Uuid;
+
+    use super::certificate_modified_at;
+    use crate::ServerConfig;
+
+    #[test]
+    pub fn should_notice_certificate_written_in_place() {
+        // Arrange
+        let path = std::env::temp_dir().join(format!("{}.pem", Uuid::new_v4()));
+        let file = File::create(&path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH).unwrap();
+
+        let mut config = ServerConfig::default();
+        config.set_certificate_path(Some(path.clone()));
+        let loaded = certificate_modified_at(&config);
+
+        // Act
+        file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(60))
+            .unwrap();
+        let renewed = certificate_modified_at(&config);
+
+        // Assert
+        assert_eq!(loaded.len(), 3);
+        assert!(loaded[0].is_some());
+        assert_ne!(loaded, renewed);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,5 @@
+mod config_changes;
+mod config_reloader;
+
+pub use config_changes::ConfigChanges;
+pub use config_reloader::*;
@@ -1,10 +1,40 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    audit_events (id) {
+        id -> Integer,
+        event_type -> Text,
+        account_id -> Nullable<Binary>,
+        port -> Nullable<Integer>,
+        remote_addr -> Nullable<Text>,
+        bytes_in -> Nullable<BigInt>,
+        bytes_out -> Nullable<BigInt>,
+        details -> Nullable<Text>,
+        created_at -> BigInt,
+    }
+}
+
+diesel::table! {
+    usage_rollups (account_id, period, port) {
+        account_id -> Binary,
+        period -> Text,
+        port -> Integer,
+        bytes_in -> BigInt,
+        bytes_out -> BigInt,
+        updated_at -> BigInt,
+    }
+}
+
 diesel::table! {
     users (id) {
         id -> Binary,
         name -> Text,
         email -> Text,
         password_hash -> Text,
+        rate_limit -> Nullable<BigInt>,
+        monthly_quota -> Nullable<BigInt>,
+        client_certificate -> Nullable<Text>,
     }
 }
+
+diesel::allow_tables_to_appear_in_same_query!(audit_events, usage_rollups, users,);
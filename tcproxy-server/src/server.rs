@@ -1,23 +1,48 @@
 use std::future::Future;
 use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
+use tcproxy_core::framing::ServerShutdown;
 use tcproxy_core::{tcp::RemoteConnection, Result};
 use tokio::task::JoinHandle;
+use tokio::time::Instant;
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, info};
 
 use crate::managers::{
-    AuthenticationManager, AuthenticationManagerGuard, DefaultAccountManager, FeatureManager,
-    IFeatureManager, NetworkPortPool, PortManager,
+    AuthenticationManager, AuthenticationManagerGuard, DefaultAccountManager, DefaultAuditManager,
+    DefaultUsageManager, FeatureManager, IFeatureManager, NetworkPortPool, PortManager,
+    QueuedAuditManager,
 };
-use tcproxy_core::tcp::{ISocketListener, SocketListener};
+use tcproxy_core::tcp::SocketListener;
 
+#[cfg(feature = "rustls")]
+use crate::acme::AcmeProvisioner;
+use crate::admin::AdminListener;
+use crate::commands::authenticate::authenticate_with_certificate;
+use crate::metrics::{MetricsListener, ServerMetrics};
+use crate::migrations::migrate_database;
 use crate::proxy::ClientConnection;
+use crate::reload::{ConfigReloader, ConfigSource};
+use crate::tcp::BandwidthRegistry;
+use crate::SessionRegistry;
+
+/// audit events waiting to be written before new ones are dropped.
+const AUDIT_QUEUE_CAPACITY: usize = 10_000;
 
 /// Represents the ser ver application
 pub struct Server {
     feature_manager: Arc<IFeatureManager>,
-    server_listener: ISocketListener,
+    server_listener: Arc<dyn SocketListener>,
+    config_reloader: Option<(PathBuf, ConfigSource)>,
+    #[cfg(feature = "rustls")]
+    acme_provisioner: Option<AcmeProvisioner>,
+    port_manager: PortManager,
+    metrics: Arc<ServerMetrics>,
+    sessions: Arc<SessionRegistry>,
+    bandwidth: Arc<BandwidthRegistry>,
+    audit_manager: Arc<QueuedAuditManager>,
 }
 
 impl Server {
@@ -29,27 +54,127 @@ impl Server {
         TListener: SocketListener + 'static,
         TFeatureManager: FeatureManager + 'static,
     {
+        // every connection reserves ports from the same pool, so tunnels never collide.
+        let port_range = feature_manager.get_config().get_port_range();
+        let port_manager = PortManager::from(NetworkPortPool::new(port_range));
+        // sessions of the same account share its bandwidth.
+        let bandwidth = BandwidthRegistry::new(*feature_manager.get_config().get_rate_limit());
+
         Self {
             feature_manager: Arc::new(Box::new(feature_manager)),
-            server_listener: Box::new(listener),
+            server_listener: Arc::new(listener),
+            config_reloader: None,
+            #[cfg(feature = "rustls")]
+            acme_provisioner: None,
+            metrics: Arc::new(ServerMetrics::new(&port_manager)),
+            sessions: Arc::new(SessionRegistry::new()),
+            bandwidth: Arc::new(bandwidth),
+            // remote connections are audited from the accept loop, so writes are queued.
+            audit_manager: Arc::new(QueuedAuditManager::new(
+                &Arc::new(DefaultAuditManager::new()),
+                AUDIT_QUEUE_CAPACITY,
+            )),
+            port_manager,
         }
     }
 
+    /// reloads the config from `config_source` when the file at `config_path` changes.
+    pub fn with_config_reload(mut self, config_path: &Path, config_source: ConfigSource) -> Self {
+        self.config_reloader = Some((config_path.to_path_buf(), config_source));
+        self
+    }
+
+    /// renews the ACME certificate of the server listener while running.
+    #[cfg(feature = "rustls")]
+    pub fn with_acme_renewal(mut self, acme_provisioner: AcmeProvisioner) -> Self {
+        self.acme_provisioner = Some(acme_provisioner);
+        self
+    }
+
+    pub fn get_metrics(&self) -> &Arc<ServerMetrics> {
+        &self.metrics
+    }
+
+    pub fn get_sessions(&self) -> &Arc<SessionRegistry> {
+        &self.sessions
+    }
+
     pub async fn run(&mut self, shutdown_signal: impl Future) -> Result<()> {
+        migrate_database()?;
         DefaultAccountManager::new().create_default_user()?;
 
+        let server_config = self.feature_manager.get_config();
         let cancellation_token = CancellationToken::new();
-        tokio::select! {
-            _ = self.start(cancellation_token.child_token()) => {},
-            _ = shutdown_signal => {
-                info!("server is being shut down.");
-                cancellation_token.cancel();
-            }
+        if let Some(metrics_addr) = server_config.get_metrics_addr() {
+            MetricsListener::bind(metrics_addr, &self.metrics)
+                .await?
+                .spawn(cancellation_token.child_token());
+        }
+
+        if let Some(admin_addr) = server_config.get_admin_addr() {
+            let admin_token = server_config.get_admin_token().clone().unwrap_or_default();
+            AdminListener::bind(admin_addr, &admin_token, &self.sessions)
+                .await?
+                .spawn(cancellation_token.child_token());
+        }
+
+        if let Some((config_path, config_source)) = self.config_reloader.take() {
+            ConfigReloader::new(
+                &config_path,
+                config_source,
+                &self.feature_manager,
+                &self.server_listener,
+                &self.port_manager,
+                &self.bandwidth,
+            )
+            .spawn(cancellation_token.child_token());
+        }
+
+        #[cfg(feature = "rustls")]
+        if let Some(acme_provisioner) = self.acme_provisioner.take() {
+            acme_provisioner.spawn_renewal(&self.server_listener, cancellation_token.child_token());
+        }
+
+        let shutdown_requested = tokio::select! {
+            _ = self.start(cancellation_token.child_token()) => false,
+            _ = shutdown_signal => true,
         };
 
+        if shutdown_requested {
+            info!("server is being shut down.");
+            self.drain(server_config.get_shutdown_timeout()).await;
+        }
+
+        cancellation_token.cancel();
         Ok(())
     }
 
+    /// notifies every client and waits for open remote connections to finish,
+    /// up to `timeout` seconds.
+    async fn drain(&self, timeout: &u32) {
+        let notice = ServerShutdown::new("server is shutting down", timeout);
+        self.sessions.begin_shutdown(&notice);
+
+        let deadline = Instant::now() + Duration::from_secs(*timeout as u64);
+        loop {
+            // also gives the shutdown notice some time to reach the clients.
+            tokio::time::sleep(Duration::from_millis(250)).await;
+
+            let active_connections = self.sessions.active_connections();
+            if active_connections == 0 {
+                break;
+            }
+
+            if Instant::now() >= deadline {
+                info!(
+                    "drain timeout reached, closing {} remote connections",
+                    active_connections
+                );
+                break;
+            }
+        }
+    }
+
     pub fn get_listen_ip(&self) -> Result<SocketAddr> {
         self.server_listener.listen_ip()
     }
@@ -72,18 +197,35 @@ impl Server {
         socket: RemoteConnection,
         cancellation_token: CancellationToken,
     ) -> JoinHandle<Result<()>> {
-        let server_config = self.feature_manager.get_config();
         let auth_manager = AuthenticationManager::new();
-        let network_port_pool = NetworkPortPool::new(server_config.get_port_range());
-        let port_manager = PortManager::from(network_port_pool);
+        let metrics = self.metrics.clone();
+        let sessions = self.sessions.clone();
 
         let account_manager = Arc::new(DefaultAccountManager::new());
+        let usage_manager = Arc::new(DefaultUsageManager::new());
         let auth_guard = Arc::new(AuthenticationManagerGuard::new(auth_manager));
-        let mut proxy_client =
-            ClientConnection::new(port_manager, auth_guard, &server_config, &account_manager);
+        let socket_addr = *socket.remote_addr();
+        let mut proxy_client = ClientConnection::new(
+            self.port_manager.clone(),
+            auth_guard,
+            &self.feature_manager,
+            &account_manager,
+            &self.audit_manager,
+            &usage_manager,
+            &metrics,
+            &self.bandwidth,
+            &socket_addr,
+        );
+
+        let session_id = *proxy_client.get_state().get_session_id();
+        sessions.register(proxy_client.get_state());
 
         tokio::spawn(async move {
-            let socket_addr = *socket.remote_addr();
+            metrics.control_connection_opened();
+            if let Some(certificate) = socket.peer_certificate() {
+                authenticate_with_certificate(certificate, proxy_client.get_state());
+            }
+
             match proxy_client
                 .start_streaming(socket.stream, cancellation_token)
                 .await
@@ -95,6 +237,8 @@ impl Server {
                 ),
             };
 
+            metrics.control_connection_closed();
+            sessions.unregister(&session_id);
             Ok(())
         })
     }
@@ -1,3 +1,7 @@
 mod proxy_state;
+mod session_registry;
+mod usage_recorder;
 
 pub use proxy_state::*;
+pub use session_registry::*;
+pub use usage_recorder::*;
@@ -1,34 +1,103 @@
-use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use tcproxy_core::framing::ServerShutdown;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
 
 use crate::managers::{
-    AuthenticationManagerGuard, ConnectionsManager, PortManager, UserManager,
+    AuditManager, AuthenticationManagerGuard, ConnectionsManager, IFeatureManager, PortManager,
+    UsageManager, UserManager,
 };
-use crate::ServerConfig;
+use crate::metrics::ServerMetrics;
+use crate::tcp::{BandwidthLimits, BandwidthRegistry, ConnectionStats};
+use crate::{ServerConfig, UsageRecorder};
+
+static LAST_SESSION_ID: AtomicU32 = AtomicU32::new(0);
 
 pub struct ClientState {
-    server_config: Arc<ServerConfig>,
+    session_id: u32,
+    connected_at: DateTime<Utc>,
+    session_token: CancellationToken,
+    shutdown_notice: watch::Sender<Option<ServerShutdown>>,
+    remote_addr: SocketAddr,
+    feature_manager: Arc<IFeatureManager>,
     port_manager: PortManager,
     auth_manager: Arc<AuthenticationManagerGuard>,
     accounts_manager: Arc<dyn UserManager + 'static>,
+    audit_manager: Arc<dyn AuditManager + 'static>,
+    usage_manager: Arc<dyn UsageManager + 'static>,
+    usage_recorder: UsageRecorder,
     connection_manager: Arc<ConnectionsManager>,
+    bandwidth_registry: Arc<BandwidthRegistry>,
+    bandwidth_limits: Mutex<Option<Arc<BandwidthLimits>>>,
+    traffic: Arc<ConnectionStats>,
+    metrics: Arc<ServerMetrics>,
 }
 
 impl ClientState {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         port_manager: PortManager,
         auth_manager: Arc<AuthenticationManagerGuard>,
-        server_config: &Arc<ServerConfig>,
+        feature_manager: &Arc<IFeatureManager>,
         account_manager: &Arc<impl UserManager + 'static>,
+        audit_manager: &Arc<impl AuditManager + 'static>,
+        usage_manager: &Arc<impl UsageManager + 'static>,
+        metrics: &Arc<ServerMetrics>,
+        bandwidth_registry: &Arc<BandwidthRegistry>,
+        remote_addr: &SocketAddr,
     ) -> Arc<Self> {
         Arc::new(Self {
+            session_id: LAST_SESSION_ID.fetch_add(1, Ordering::Relaxed) + 1,
+            connected_at: Utc::now(),
+            session_token: CancellationToken::new(),
+            shutdown_notice: watch::channel(None).0,
             auth_manager,
             port_manager,
-            server_config: server_config.clone(),
+            remote_addr: *remote_addr,
+            feature_manager: feature_manager.clone(),
             accounts_manager: account_manager.clone(),
+            audit_manager: audit_manager.clone(),
+            usage_manager: usage_manager.clone(),
+            usage_recorder: UsageRecorder::new(),
             connection_manager: Arc::new(ConnectionsManager::new()),
+            bandwidth_registry: bandwidth_registry.clone(),
+            bandwidth_limits: Mutex::new(None),
+            traffic: Arc::new(ConnectionStats::with_metrics(metrics)),
+            metrics: metrics.clone(),
         })
     }
 
+    /// unique id of the control connection, within this server process.
+    pub fn get_session_id(&self) -> &u32 {
+        &self.session_id
+    }
+
+    pub fn get_connected_at(&self) -> &DateTime<Utc> {
+        &self.connected_at
+    }
+
+    /// cancelled when the control connection goes away, closing its tunnels with it.
+    pub fn get_session_token(&self) -> &CancellationToken {
+        &self.session_token
+    }
+
+    /// tells the session the server is going down, so it stops accepting remote connections.
+    pub fn begin_shutdown(&self, notice: &ServerShutdown) {
+        self.shutdown_notice.send_replace(Some(notice.clone()));
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.shutdown_notice.borrow().is_some()
+    }
+
+    pub fn subscribe_shutdown(&self) -> watch::Receiver<Option<ServerShutdown>> {
+        self.shutdown_notice.subscribe()
+    }
+
     pub fn get_port_manager(&self) -> &PortManager {
         &self.port_manager
     }
@@ -41,11 +110,106 @@ impl ClientState {
         &self.accounts_manager
     }
 
-    pub fn get_server_config(&self) -> &Arc<ServerConfig> {
-        &self.server_config
+    pub fn get_audit_manager(&self) -> &Arc<dyn AuditManager + 'static> {
+        &self.audit_manager
+    }
+
+    pub fn get_usage_manager(&self) -> &Arc<dyn UsageManager + 'static> {
+        &self.usage_manager
+    }
+
+    pub fn get_usage_recorder(&self) -> &UsageRecorder {
+        &self.usage_recorder
+    }
+
+    /// monthly quota of the authenticated account, falling back to the server-wide one.
+    pub fn get_monthly_quota(&self) -> Option<u64> {
+        let account_quota = self
+            .auth_manager
+            .user_details()
+            .and_then(|user| *user.monthly_quota());
+
+        account_quota.or(*self.get_server_config().get_monthly_quota())
+    }
+
+    /// shares the bandwidth of the other sessions of the account from now on.
+    pub fn set_account_bandwidth(&self, account_id: &Uuid, rate_limit: Option<u64>) {
+        let limits = self.bandwidth_registry.for_account(account_id, rate_limit);
+        *self.bandwidth_limits.lock().unwrap() = Some(limits);
+    }
+
+    /// bandwidth of the authenticated account, `None` until the session is authenticated.
+    pub fn get_bandwidth_limits(&self) -> Option<Arc<BandwidthLimits>> {
+        self.bandwidth_limits.lock().unwrap().clone()
+    }
+
+    /// bytes forwarded by every tunnel of the session.
+    pub fn get_traffic(&self) -> &Arc<ConnectionStats> {
+        &self.traffic
+    }
+
+    pub fn get_metrics(&self) -> &Arc<ServerMetrics> {
+        &self.metrics
+    }
+
+    /// config currently in use, so changes reloaded while the session is open apply to it.
+    pub fn get_server_config(&self) -> Arc<ServerConfig> {
+        self.feature_manager.get_config()
     }
 
     pub fn get_auth_manager(&self) -> &Arc<AuthenticationManagerGuard> {
         &self.auth_manager
     }
+
+    /// address of the control connection this state belongs to.
+    pub fn get_remote_addr(&self) -> &SocketAddr {
+        &self.remote_addr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ipnet::IpNet;
+    use std::str::FromStr;
+    use tcproxy_core::auth::User;
+    use uuid::Uuid;
+
+    use crate::models::UserModel;
+    use crate::tests::utils::{
+        create_client_state, create_client_state_with_config, create_feature_manager,
+    };
+    use crate::ServerConfig;
+
+    #[test]
+    pub fn open_sessions_should_see_reloaded_config() {
+        // Arrange
+        let feature_manager = create_feature_manager(ServerConfig::default());
+        let state = create_client_state_with_config(&feature_manager);
+        let mut reloaded = ServerConfig::default();
+        reloaded.set_connections_per_proxy(7);
+        reloaded.set_global_deny_list(vec![IpNet::from_str("203.0.113.0/24").unwrap()]);
+
+        // Act
+        feature_manager.set_config(reloaded);
+
+        // Assert
+        let server_config = state.get_server_config();
+        assert_eq!(server_config.get_max_connections_per_proxy(), 7);
+        assert_eq!(server_config.get_global_deny_list().len(), 1);
+    }
+
+    #[test]
+    pub fn should_use_monthly_quota_of_the_account() {
+        // Arrange
+        let state = create_client_state();
+        let model = UserModel::new(&Uuid::new_v4(), "some name", "some@email.com", "hash")
+            .with_monthly_quota(Some(5000));
+        let user = User::try_from(model).unwrap();
+
+        // Act
+        state.get_auth_manager().set_authentication_details(&user);
+
+        // Assert
+        assert_eq!(state.get_monthly_quota(), Some(5000));
+    }
 }
@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use tcproxy_core::framing::ServerShutdown;
+
+use crate::ClientState;
+
+/// Keeps track of every control connection currently open on the server.
+#[derive(Default)]
+pub struct SessionRegistry {
+    sessions: Mutex<BTreeMap<u32, Arc<ClientState>>>,
+}
+
+impl SessionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&self, state: &Arc<ClientState>) {
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.insert(*state.get_session_id(), state.clone());
+    }
+
+    pub fn unregister(&self, session_id: &u32) {
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.remove(session_id);
+    }
+
+    pub fn get_session(&self, session_id: &u32) -> Option<Arc<ClientState>> {
+        let sessions = self.sessions.lock().unwrap();
+        sessions.get(session_id).cloned()
+    }
+
+    /// lists the open sessions, ordered by id.
+    pub fn list_sessions(&self) -> Vec<Arc<ClientState>> {
+        let sessions = self.sessions.lock().unwrap();
+        sessions.values().cloned().collect()
+    }
+
+    /// notifies every session that the server is going down.
+    pub fn begin_shutdown(&self, notice: &ServerShutdown) {
+        for state in self.list_sessions() {
+            state.begin_shutdown(notice);
+        }
+    }
+
+    /// remote connections still open across every session.
+    pub fn active_connections(&self) -> usize {
+        self.list_sessions()
+            .iter()
+            .map(|state| state.get_connection_manager().list_connections().len())
+            .sum()
+    }
+
+    /// closes the control connection and its tunnels, returning whether it existed.
+    pub fn close_session(&self, session_id: &u32) -> bool {
+        match self.get_session(session_id) {
+            Some(state) => {
+                state.get_session_token().cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tcproxy_core::framing::ServerShutdown;
+
+    use super::SessionRegistry;
+    use crate::tests::utils::create_client_state;
+
+    #[test]
+    pub fn should_mark_every_session_as_draining() {
+        // Arrange
+        let registry = SessionRegistry::new();
+        let first = create_client_state();
+        let second = create_client_state();
+        registry.register(&first);
+        registry.register(&second);
+
+        // Act
+        registry.begin_shutdown(&ServerShutdown::new("server is shutting down", &30));
+
+        // Assert
+        assert!(first.is_draining());
+        assert!(second.is_draining());
+        assert_eq!(
+            first
+                .subscribe_shutdown()
+                .borrow()
+                .as_ref()
+                .unwrap()
+                .deadline(),
+            &30
+        );
+    }
+}
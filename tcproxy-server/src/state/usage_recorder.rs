@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use crate::managers::{current_usage_period, UsageManagerError};
+use crate::models::Usage;
+use crate::tcp::ConnectionStats;
+use crate::ClientState;
+
+/// Keeps track of how much of the traffic of each tunnel was already persisted,
+/// so every tunnel of the connection can flush it without counting bytes twice.
+#[derive(Default)]
+pub struct UsageRecorder {
+    tunnels: Mutex<BTreeMap<u16, TunnelUsage>>,
+}
+
+struct TunnelUsage {
+    stats: Arc<ConnectionStats>,
+    flushed: (u64, u64),
+}
+
+impl UsageRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// accounts the bytes forwarded by the tunnel on `port` from now on.
+    pub fn track(&self, port: &u16, stats: &Arc<ConnectionStats>) {
+        let tunnel = TunnelUsage {
+            stats: stats.clone(),
+            flushed: (0, 0),
+        };
+
+        self.tunnels.lock().unwrap().insert(*port, tunnel);
+    }
+
+    /// flushes the tunnel a last time, then stops accounting it.
+    pub fn untrack(
+        &self,
+        port: &u16,
+        state: &ClientState,
+    ) -> Result<Option<Usage>, UsageManagerError> {
+        let usage = self.flush(state)?;
+        self.tunnels.lock().unwrap().remove(port);
+        Ok(usage)
+    }
+
+    /// persists the bytes forwarded since the last flush into the account usage, per tunnel port.
+    /// returns `None` while the connection is not authenticated.
+    pub fn flush(&self, state: &ClientState) -> Result<Option<Usage>, UsageManagerError> {
+        let account_id = match state.get_auth_manager().account_id() {
+            Some(account_id) => account_id,
+            None => return Ok(None),
+        };
+
+        let period = current_usage_period();
+        let usage_manager = state.get_usage_manager();
+        let mut tunnels = self.tunnels.lock().unwrap();
+        let mut usage = None;
+
+        for (port, tunnel) in tunnels.iter_mut() {
+            let total = (tunnel.stats.bytes_in(), tunnel.stats.bytes_out());
+            if total == tunnel.flushed {
+                continue;
+            }
+
+            usage = Some(usage_manager.add_usage(
+                &account_id,
+                &period,
+                port,
+                &(total.0 - tunnel.flushed.0),
+                &(total.1 - tunnel.flushed.1),
+            )?);
+
+            tunnel.flushed = total;
+        }
+
+        match usage {
+            Some(usage) => Ok(Some(usage)),
+            None => usage_manager.get_usage(&account_id, &period).map(Some),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::net::SocketAddr;
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+
+    use tcproxy_core::auth::User;
+    use uuid::Uuid;
+
+    use crate::managers::current_usage_period;
+    use crate::managers::{
+        AuthenticationManager, AuthenticationManagerGuard, DefaultAccountManager,
+        DefaultAuditManager, NetworkPortPool, PortManager, UsageManager, UsageManagerError,
+    };
+    use crate::metrics::ServerMetrics;
+    use crate::models::Usage;
+    use crate::tcp::{BandwidthRegistry, ConnectionStats};
+    use crate::tests::utils::create_feature_manager;
+    use crate::{ClientState, ServerConfig};
+
+    /// account, period and tunnel port of a rollup.
+    type RollupKey = (Uuid, String, u16);
+
+    #[derive(Default)]
+    struct InMemoryUsageManager {
+        usage: Mutex<HashMap<RollupKey, (u64, u64)>>,
+    }
+
+    impl InMemoryUsageManager {
+        fn port_usage(&self, account_id: &Uuid, period: &str, port: &u16) -> (u64, u64) {
+            let usage = self.usage.lock().unwrap();
+            let key = (*account_id, String::from(period), *port);
+            usage.get(&key).cloned().unwrap_or_default()
+        }
+    }
+
+    impl UsageManager for InMemoryUsageManager {
+        fn add_usage(
+            &self,
+            account_id: &Uuid,
+            period: &str,
+            port: &u16,
+            bytes_in: &u64,
+            bytes_out: &u64,
+        ) -> Result<Usage, UsageManagerError> {
+            let mut usage = self.usage.lock().unwrap();
+            let entry = usage
+                .entry((*account_id, String::from(period), *port))
+                .or_insert((0, 0));
+
+            entry.0 += bytes_in;
+            entry.1 += bytes_out;
+            drop(usage);
+
+            self.get_usage(account_id, period)
+        }
+
+        fn get_usage(&self, account_id: &Uuid, period: &str) -> Result<Usage, UsageManagerError> {
+            let usage = self.usage.lock().unwrap();
+            let (bytes_in, bytes_out) = usage
+                .iter()
+                .filter(|((id, usage_period, _), _)| id == account_id && usage_period == period)
+                .fold((0, 0), |total, (_, bytes)| {
+                    (total.0 + bytes.0, total.1 + bytes.1)
+                });
+
+            Ok(Usage::new(account_id, period, &bytes_in, &bytes_out))
+        }
+    }
+
+    fn create_state(
+        user: Option<&User>,
+        usage_manager: &Arc<InMemoryUsageManager>,
+    ) -> Arc<ClientState> {
+        let auth_guard = Arc::new(AuthenticationManagerGuard::new(AuthenticationManager::new()));
+
+        if let Some(user) = user {
+            auth_guard.set_authentication_details(user);
+        }
+
+        let port_manager = PortManager::from(NetworkPortPool::new(15000..15010));
+        let metrics = Arc::new(ServerMetrics::new(&port_manager));
+
+        ClientState::new(
+            port_manager,
+            auth_guard,
+            &create_feature_manager(ServerConfig::default()),
+            &Arc::new(DefaultAccountManager::new()),
+            &Arc::new(DefaultAuditManager::new()),
+            usage_manager,
+            &metrics,
+            &Arc::new(BandwidthRegistry::new(None)),
+            &SocketAddr::from_str("127.0.0.1:54321").unwrap(),
+        )
+    }
+
+    #[test]
+    fn should_only_flush_new_bytes() {
+        // Arrange
+        let user = User::new(&Uuid::new_v4(), "some name", "some@email.com", "hash");
+        let usage_manager = Arc::new(InMemoryUsageManager::default());
+        let state = create_state(Some(&user), &usage_manager);
+        let stats = Arc::new(ConnectionStats::with_parent(state.get_traffic()));
+        state.get_usage_recorder().track(&15001, &stats);
+
+        // Act
+        stats.add_bytes_in(100);
+        stats.add_bytes_out(50);
+        let first = state.get_usage_recorder().flush(&state).unwrap().unwrap();
+
+        stats.add_bytes_in(10);
+        let second = state.get_usage_recorder().flush(&state).unwrap().unwrap();
+
+        // Assert
+        assert_eq!(first.total(), 150);
+        assert_eq!(second.bytes_in(), &110);
+        assert_eq!(second.bytes_out(), &50);
+    }
+
+    #[test]
+    fn should_store_usage_per_tunnel_port() {
+        // Arrange
+        let user = User::new(&Uuid::new_v4(), "some name", "some@email.com", "hash");
+        let usage_manager = Arc::new(InMemoryUsageManager::default());
+        let state = create_state(Some(&user), &usage_manager);
+        let first = Arc::new(ConnectionStats::with_parent(state.get_traffic()));
+        let second = Arc::new(ConnectionStats::with_parent(state.get_traffic()));
+        state.get_usage_recorder().track(&15001, &first);
+        state.get_usage_recorder().track(&15002, &second);
+
+        // Act
+        first.add_bytes_in(100);
+        second.add_bytes_out(40);
+        let closed = state.get_usage_recorder().untrack(&15001, &state).unwrap();
+        first.add_bytes_in(1000);
+        let usage = state.get_usage_recorder().flush(&state).unwrap().unwrap();
+
+        // Assert
+        let period = current_usage_period();
+        assert_eq!(closed.unwrap().total(), 140);
+        assert_eq!(usage.total(), 140);
+        assert_eq!(
+            usage_manager.port_usage(user.id(), &period, &15001),
+            (100, 0)
+        );
+        assert_eq!(
+            usage_manager.port_usage(user.id(), &period, &15002),
+            (0, 40)
+        );
+    }
+
+    #[test]
+    fn should_not_flush_when_not_authenticated() {
+        // Arrange
+        let state = create_state(None, &Arc::new(InMemoryUsageManager::default()));
+
+        // Act
+        let result = state.get_usage_recorder().flush(&state).unwrap();
+
+        // Assert
+        assert!(result.is_none());
+    }
+}
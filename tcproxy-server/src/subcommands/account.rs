@@ -0,0 +1,205 @@
+use std::io::Write;
+
+use tcproxy_core::tls::parse_fingerprint;
+use tcproxy_core::Result;
+
+use super::resolve_account_id;
+use crate::managers::UserManager;
+use crate::BindCertificateArgs;
+
+/// Binds a client certificate to an account, by its fingerprint or subject.
+pub fn bind_certificate(
+    args: &BindCertificateArgs,
+    account_manager: &impl UserManager,
+    output: &mut impl Write,
+) -> Result<()> {
+    let account_id = resolve_account_id(args.account(), account_manager)?;
+
+    // fingerprints are stored the way they are computed, anything else is a subject.
+    let certificate = args
+        .certificate()
+        .map(|certificate| parse_fingerprint(certificate).unwrap_or(certificate.to_owned()));
+
+    if account_manager
+        .set_client_certificate(&account_id, certificate.as_deref())
+        .is_err()
+    {
+        return Err(format!("unable to update account {}.", args.account()).into());
+    }
+
+    match certificate {
+        Some(certificate) => writeln!(
+            output,
+            "account {} now authenticates with certificate {}",
+            args.account(),
+            certificate
+        )?,
+        None => writeln!(
+            output,
+            "removed the certificate of account {}",
+            args.account()
+        )?,
+    };
+
+    output.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::AccountManagerError;
+    use clap::Parser;
+    use std::sync::Mutex;
+    use tcproxy_core::auth::User;
+    use tcproxy_core::tls::PeerCertificate;
+    use uuid::Uuid;
+
+    struct InMemoryAccountManager {
+        user: User,
+        certificate: Mutex<Option<String>>,
+    }
+
+    impl InMemoryAccountManager {
+        fn new() -> Self {
+            Self {
+                user: User::new(&Uuid::new_v4(), "some name", "some@email.com", "hash"),
+                certificate: Mutex::new(Some(String::from("CN=old"))),
+            }
+        }
+    }
+
+    impl UserManager for InMemoryAccountManager {
+        fn find_account_by_id(
+            &self,
+            _account_id: &Uuid,
+        ) -> std::result::Result<User, AccountManagerError> {
+            Ok(self.user.clone())
+        }
+
+        fn find_user_by_email(
+            &self,
+            email: &str,
+        ) -> std::result::Result<User, AccountManagerError> {
+            match self.user.email() == email {
+                true => Ok(self.user.clone()),
+                false => Err(AccountManagerError::NotFound),
+            }
+        }
+
+        fn find_user_by_certificate(
+            &self,
+            _certificate: &PeerCertificate,
+        ) -> std::result::Result<User, AccountManagerError> {
+            Err(AccountManagerError::NotFound)
+        }
+
+        fn set_client_certificate(
+            &self,
+            account_id: &Uuid,
+            certificate: Option<&str>,
+        ) -> std::result::Result<(), AccountManagerError> {
+            if account_id != self.user.id() {
+                return Err(AccountManagerError::NotFound);
+            }
+
+            *self.certificate.lock().unwrap() = certificate.map(String::from);
+            Ok(())
+        }
+    }
+
+    #[test]
+    pub fn should_store_normalized_fingerprint() {
+        // Arrange
+        let account_manager = InMemoryAccountManager::new();
+        let fingerprint = ["AB"; 32].join(":");
+        let args = BindCertificateArgs::parse_from([
+            "bind-certificate",
+            "--account",
+            "some@email.com",
+            "--certificate",
+            &fingerprint,
+        ]);
+        let mut output = Vec::new();
+
+        // Act
+        let result = bind_certificate(&args, &account_manager, &mut output);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(
+            account_manager.certificate.lock().unwrap().as_deref(),
+            Some("ab".repeat(32).as_str())
+        );
+    }
+
+    #[test]
+    pub fn should_store_subject_as_given() {
+        // Arrange
+        let account_manager = InMemoryAccountManager::new();
+        let account_id = account_manager.user.id().to_string();
+        let args = BindCertificateArgs::parse_from([
+            "bind-certificate",
+            "--account",
+            &account_id,
+            "--certificate",
+            "CN=build-agent",
+        ]);
+        let mut output = Vec::new();
+
+        // Act
+        let result = bind_certificate(&args, &account_manager, &mut output);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(
+            account_manager.certificate.lock().unwrap().as_deref(),
+            Some("CN=build-agent")
+        );
+    }
+
+    #[test]
+    pub fn should_remove_certificate() {
+        // Arrange
+        let account_manager = InMemoryAccountManager::new();
+        let args = BindCertificateArgs::parse_from([
+            "bind-certificate",
+            "--account",
+            "some@email.com",
+            "--remove",
+        ]);
+        let mut output = Vec::new();
+
+        // Act
+        let result = bind_certificate(&args, &account_manager, &mut output);
+
+        // Assert
+        assert!(result.is_ok());
+        assert!(account_manager.certificate.lock().unwrap().is_none());
+    }
+
+    #[test]
+    pub fn should_fail_when_account_is_unknown() {
+        // Arrange
+        let account_manager = InMemoryAccountManager::new();
+        let args = BindCertificateArgs::parse_from([
+            "bind-certificate",
+            "--account",
+            "other@email.com",
+            "--certificate",
+            "CN=build-agent",
+        ]);
+        let mut output = Vec::new();
+
+        // Act
+        let result = bind_certificate(&args, &account_manager, &mut output);
+
+        // Assert
+        assert!(result.is_err());
+        assert!(output.is_empty());
+        assert_eq!(
+            account_manager.certificate.lock().unwrap().as_deref(),
+            Some("CN=old")
+        );
+    }
+}
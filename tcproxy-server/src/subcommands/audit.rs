@@ -0,0 +1,154 @@
+use std::io::Write;
+
+use tcproxy_core::Result;
+
+use super::resolve_account_id;
+use crate::managers::{AuditFilter, AuditManager, UserManager};
+use crate::AuditQueryArgs;
+
+/// Writes every audit event matching the given filters as a json line.
+pub fn query(
+    args: &AuditQueryArgs,
+    audit_manager: &impl AuditManager,
+    account_manager: &impl UserManager,
+    output: &mut impl Write,
+) -> Result<()> {
+    let account_id = match args.account() {
+        Some(account) => Some(resolve_account_id(account, account_manager)?),
+        None => None,
+    };
+
+    let filter = AuditFilter::new(
+        account_id,
+        args.port(),
+        args.since(),
+        args.until(),
+        Some(args.limit()),
+    );
+
+    for event in audit_manager.query(&filter)? {
+        writeln!(output, "{}", serde_json::to_string(&event)?)?;
+    }
+
+    output.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::{AccountManagerError, AuditManagerError};
+    use crate::models::{AuditEvent, AuditEventType};
+    use clap::Parser;
+    use std::sync::Mutex;
+    use tcproxy_core::auth::User;
+    use tcproxy_core::tls::PeerCertificate;
+    use uuid::Uuid;
+
+    struct InMemoryAuditManager {
+        filters: Mutex<Vec<AuditFilter>>,
+        events: Vec<AuditEvent>,
+    }
+
+    impl AuditManager for InMemoryAuditManager {
+        fn record(&self, _event: &AuditEvent) -> std::result::Result<(), AuditManagerError> {
+            Ok(())
+        }
+
+        fn query(
+            &self,
+            filter: &AuditFilter,
+        ) -> std::result::Result<Vec<AuditEvent>, AuditManagerError> {
+            self.filters.lock().unwrap().push(filter.clone());
+            Ok(self.events.clone())
+        }
+    }
+
+    struct InMemoryAccountManager(User);
+
+    impl UserManager for InMemoryAccountManager {
+        fn find_account_by_id(
+            &self,
+            _account_id: &Uuid,
+        ) -> std::result::Result<User, AccountManagerError> {
+            Ok(self.0.clone())
+        }
+
+        fn find_user_by_email(
+            &self,
+            email: &str,
+        ) -> std::result::Result<User, AccountManagerError> {
+            match self.0.email() == email {
+                true => Ok(self.0.clone()),
+                false => Err(AccountManagerError::NotFound),
+            }
+        }
+
+        fn find_user_by_certificate(
+            &self,
+            _certificate: &PeerCertificate,
+        ) -> std::result::Result<User, AccountManagerError> {
+            Err(AccountManagerError::NotFound)
+        }
+
+        fn set_client_certificate(
+            &self,
+            _account_id: &Uuid,
+            _certificate: Option<&str>,
+        ) -> std::result::Result<(), AccountManagerError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    pub fn should_write_one_line_per_event() {
+        // Arrange
+        let user = User::new(&Uuid::new_v4(), "some name", "some@email.com", "hash");
+        let audit_manager = InMemoryAuditManager {
+            filters: Mutex::new(vec![]),
+            events: vec![
+                AuditEvent::new(AuditEventType::TunnelOpened).with_port(&15000),
+                AuditEvent::new(AuditEventType::TunnelClosed).with_port(&15000),
+            ],
+        };
+
+        let account_manager = InMemoryAccountManager(user.clone());
+        let args = AuditQueryArgs::parse_from(["query", "--account", "some@email.com"]);
+        let mut output: Vec<u8> = vec![];
+
+        // Act
+        let result = query(&args, &audit_manager, &account_manager, &mut output);
+
+        // Assert
+        assert!(result.is_ok());
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("\"tunnel_opened\""));
+        assert!(lines[1].contains("\"tunnel_closed\""));
+
+        let filters = audit_manager.filters.lock().unwrap();
+        assert_eq!(filters[0].account_id(), &Some(*user.id()));
+    }
+
+    #[test]
+    pub fn should_return_err_when_account_doesnt_exist() {
+        // Arrange
+        let user = User::new(&Uuid::new_v4(), "some name", "some@email.com", "hash");
+        let audit_manager = InMemoryAuditManager {
+            filters: Mutex::new(vec![]),
+            events: vec![],
+        };
+
+        let account_manager = InMemoryAccountManager(user);
+        let args = AuditQueryArgs::parse_from(["query", "--account", "other@email.com"]);
+        let mut output: Vec<u8> = vec![];
+
+        // Act
+        let result = query(&args, &audit_manager, &account_manager, &mut output);
+
+        // Assert
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use std::io::Write;
+
+use tcproxy_core::config::{ConfigLoader, InvalidConfig};
+use tcproxy_core::Result;
+
+use crate::{config_schema, AppArguments, ServerConfig};
+
+/// Loads the config the server would start with and prints every invalid key.
+/// unlike starting the server, a missing config file is reported instead of created.
+pub fn check(
+    env_vars: &[(String, String)],
+    args: &AppArguments,
+    output: &mut impl Write,
+) -> Result<()> {
+    let parsed_env_vars = ServerConfig::parse_environment_variables(env_vars);
+    let config_path = ServerConfig::get_config_path(&parsed_env_vars, args);
+
+    if !ServerConfig::file_exists(&config_path) {
+        writeln!(
+            output,
+            "config file {} does not exist",
+            config_path.display()
+        )?;
+        return Err("config check failed".into());
+    }
+
+    let err = match ServerConfig::load_existing(env_vars, args) {
+        Ok(_) => {
+            writeln!(output, "config {} is valid", config_path.display())?;
+            return Ok(());
+        }
+        Err(err) => err,
+    };
+
+    match err.downcast_ref::<InvalidConfig>() {
+        Some(invalid) => {
+            for error in invalid.errors() {
+                writeln!(output, "{}", error)?;
+            }
+        }
+        None => writeln!(output, "{}", err)?,
+    };
+
+    output.flush()?;
+    Err("config check failed".into())
+}
+
+/// Prints the json schema of the config file.
+pub fn schema(output: &mut impl Write) -> Result<()> {
+    writeln!(
+        output,
+        "{}",
+        serde_json::to_string_pretty(&config_schema())?
+    )?;
+    output.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tcproxy_core::config::ConfigFormat;
+    use uuid::Uuid;
+
+    use super::check;
+    use crate::{env, AppArguments, ServerConfig};
+
+    #[test]
+    pub fn should_print_every_invalid_key() {
+        // Arrange
+        let file_name = format!("{}.toml", Uuid::new_v4());
+        let mut config = ServerConfig::default();
+        config.set_jwt_secret("some_secret");
+        let contents = ConfigFormat::Toml.serialize(&config).unwrap();
+        std::fs::write(&file_name, contents).unwrap();
+
+        let env_vars: Vec<(String, String)> = vec![
+            (env::CONFIG_FILE.to_owned(), file_name.to_owned()),
+            (env::LISTEN_PORT.to_owned(), "15001".to_owned()),
+        ];
+        let mut output = Vec::new();
+
+        // Act
+        let result = check(&env_vars, &AppArguments::default(), &mut output);
+
+        // Assert
+        let output = String::from_utf8(output).unwrap();
+        assert!(result.is_err());
+        assert_eq!(
+            output,
+            format!(
+                "`listen_port` (from env TCPROXY_LISTEN_PORT): port 15001 is inside the proxy port range 15000..25000\n\
+                 `jwt_secret` (from file {}): secret must be set to a non default value\n",
+                file_name
+            )
+        );
+
+        std::fs::remove_file(&file_name).unwrap();
+    }
+
+    #[test]
+    pub fn should_not_create_missing_file() {
+        // Arrange
+        let file_name = format!("{}.json", Uuid::new_v4());
+        let env_vars: Vec<(String, String)> =
+            vec![(env::CONFIG_FILE.to_owned(), file_name.to_owned())];
+        let mut output = Vec::new();
+
+        // Act
+        let result = check(&env_vars, &AppArguments::default(), &mut output);
+
+        // Assert
+        assert!(result.is_err());
+        assert!(std::fs::metadata(&file_name).is_err());
+    }
+}
@@ -0,0 +1,50 @@
+mod account;
+mod audit;
+mod config;
+
+use std::io::stdout;
+use std::str::FromStr;
+
+use tcproxy_core::Result;
+use uuid::Uuid;
+
+use crate::managers::{DefaultAccountManager, DefaultAuditManager, UserManager};
+use crate::migrations::migrate_database;
+use crate::{AccountCommand, AppArguments, AuditCommand, ConfigCommand, ServerCommand};
+
+/// Runs a one-off server sub command, such as `audit query`.
+pub fn run(
+    command: &ServerCommand,
+    env_vars: &[(String, String)],
+    args: &AppArguments,
+) -> Result<()> {
+    match command {
+        ServerCommand::Account(AccountCommand::BindCertificate(args)) => {
+            migrate_database()?;
+            account::bind_certificate(args, &DefaultAccountManager::new(), &mut stdout())
+        }
+        ServerCommand::Audit(AuditCommand::Query(args)) => {
+            migrate_database()?;
+            audit::query(
+                args,
+                &DefaultAuditManager::new(),
+                &DefaultAccountManager::new(),
+                &mut stdout(),
+            )
+        }
+        ServerCommand::Config(ConfigCommand::Check) => config::check(env_vars, args, &mut stdout()),
+        ServerCommand::Config(ConfigCommand::Schema) => config::schema(&mut stdout()),
+    }
+}
+
+/// accounts can be given either by id or by email.
+fn resolve_account_id(account: &str, account_manager: &impl UserManager) -> Result<Uuid> {
+    if let Ok(account_id) = Uuid::from_str(account) {
+        return Ok(account_id);
+    }
+
+    match account_manager.find_user_by_email(account) {
+        Ok(user) => Ok(*user.id()),
+        Err(_) => Err(format!("account {} was not found.", account).into()),
+    }
+}
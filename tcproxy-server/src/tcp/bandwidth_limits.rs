@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use uuid::Uuid;
+
+use crate::tcp::RateLimiter;
+
+/// Resolves the rate limit (bytes per second) of an account from its sources,
+/// and keeps the limiters of both directions in sync with it.
+/// the account limit overrides the server-wide one.
+pub struct BandwidthLimits {
+    sources: Mutex<LimitSources>,
+    inbound: Arc<RateLimiter>,
+    outbound: Arc<RateLimiter>,
+}
+
+#[derive(Debug, Default)]
+struct LimitSources {
+    global: Option<u64>,
+    account: Option<u64>,
+}
+
+impl LimitSources {
+    fn effective(&self) -> Option<u64> {
+        self.account.or(self.global)
+    }
+}
+
+impl BandwidthLimits {
+    pub fn new(global_limit: Option<u64>) -> Self {
+        Self {
+            sources: Mutex::new(LimitSources {
+                global: global_limit,
+                ..Default::default()
+            }),
+            inbound: Arc::new(RateLimiter::new(global_limit)),
+            outbound: Arc::new(RateLimiter::new(global_limit)),
+        }
+    }
+
+    pub fn set_global_limit(&self, limit: Option<u64>) {
+        let mut sources = self.sources.lock().unwrap();
+        sources.global = limit;
+        self.apply(&sources);
+    }
+
+    pub fn set_account_limit(&self, limit: Option<u64>) {
+        let mut sources = self.sources.lock().unwrap();
+        sources.account = limit;
+        self.apply(&sources);
+    }
+
+    pub fn effective_limit(&self) -> Option<u64> {
+        self.sources.lock().unwrap().effective()
+    }
+
+    /// limiter for bytes coming from remote peers.
+    pub fn inbound(&self) -> &Arc<RateLimiter> {
+        &self.inbound
+    }
+
+    /// limiter for bytes written to remote peers.
+    pub fn outbound(&self) -> &Arc<RateLimiter> {
+        &self.outbound
+    }
+
+    fn apply(&self, sources: &LimitSources) {
+        let limit = sources.effective();
+        self.inbound.set_rate(limit);
+        self.outbound.set_rate(limit);
+    }
+}
+
+/// Bandwidth of the accounts with an open session, so every session
+/// of an account takes from the same limiters.
+pub struct BandwidthRegistry {
+    accounts: Mutex<RegisteredAccounts>,
+}
+
+struct RegisteredAccounts {
+    global_limit: Option<u64>,
+    limits: HashMap<Uuid, Weak<BandwidthLimits>>,
+}
+
+impl BandwidthRegistry {
+    pub fn new(global_limit: Option<u64>) -> Self {
+        Self {
+            accounts: Mutex::new(RegisteredAccounts {
+                global_limit,
+                limits: HashMap::new(),
+            }),
+        }
+    }
+
+    /// limits of the account, shared with its other sessions. `account_limit` comes
+    /// from the latest account details, so it replaces the one already in place.
+    pub fn for_account(
+        &self,
+        account_id: &Uuid,
+        account_limit: Option<u64>,
+    ) -> Arc<BandwidthLimits> {
+        let mut accounts = self.accounts.lock().unwrap();
+        accounts
+            .limits
+            .retain(|_, limits| limits.strong_count() > 0);
+
+        let limits = match accounts.limits.get(account_id).and_then(Weak::upgrade) {
+            Some(limits) => limits,
+            None => {
+                let limits = Arc::new(BandwidthLimits::new(accounts.global_limit));
+                accounts.limits.insert(*account_id, Arc::downgrade(&limits));
+                limits
+            }
+        };
+
+        limits.set_account_limit(account_limit);
+        limits
+    }
+
+    pub fn set_global_limit(&self, limit: Option<u64>) {
+        let mut accounts = self.accounts.lock().unwrap();
+        accounts.global_limit = limit;
+
+        for limits in accounts.limits.values().filter_map(Weak::upgrade) {
+            limits.set_global_limit(limit);
+        }
+    }
+}
+
+/// Limiters of a single tunnel. they take from the limiters of the account,
+/// and the rate requested by the client can only lower the account one.
+pub struct TunnelLimits {
+    account: Arc<BandwidthLimits>,
+    requested: Option<u64>,
+    inbound: Arc<RateLimiter>,
+    outbound: Arc<RateLimiter>,
+}
+
+impl TunnelLimits {
+    pub fn new(account: &Arc<BandwidthLimits>, requested: Option<u64>) -> Self {
+        Self {
+            account: account.clone(),
+            requested,
+            inbound: Arc::new(RateLimiter::with_parent(requested, account.inbound())),
+            outbound: Arc::new(RateLimiter::with_parent(requested, account.outbound())),
+        }
+    }
+
+    pub fn effective_limit(&self) -> Option<u64> {
+        match (self.account.effective_limit(), self.requested) {
+            (Some(account_limit), Some(requested)) => Some(account_limit.min(requested)),
+            (account_limit, requested) => account_limit.or(requested),
+        }
+    }
+
+    /// limiter for bytes coming from remote peers.
+    pub fn inbound(&self) -> &Arc<RateLimiter> {
+        &self.inbound
+    }
+
+    /// limiter for bytes written to remote peers.
+    pub fn outbound(&self) -> &Arc<RateLimiter> {
+        &self.outbound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    use super::{BandwidthLimits, BandwidthRegistry, TunnelLimits};
+
+    #[test]
+    pub fn should_use_global_limit_by_default() {
+        // Arrange
+        let limits = BandwidthLimits::new(Some(1000));
+
+        // Act
+        let result = limits.effective_limit();
+
+        // Assert
+        assert_eq!(result, Some(1000));
+        assert_eq!(limits.inbound().rate(), Some(1000));
+        assert_eq!(limits.outbound().rate(), Some(1000));
+    }
+
+    #[test]
+    pub fn account_limit_should_override_global_limit() {
+        // Arrange
+        let limits = BandwidthLimits::new(Some(1000));
+
+        // Act
+        limits.set_account_limit(Some(5000));
+
+        // Assert
+        assert_eq!(limits.effective_limit(), Some(5000));
+        assert_eq!(limits.inbound().rate(), Some(5000));
+    }
+
+    #[test]
+    pub fn requested_limit_should_only_lower_the_limit() {
+        // Arrange
+        let limits = Arc::new(BandwidthLimits::new(Some(1000)));
+
+        // Act
+        let higher = TunnelLimits::new(&limits, Some(5000));
+        let lower = TunnelLimits::new(&limits, Some(500));
+
+        // Assert
+        assert_eq!(higher.effective_limit(), Some(1000));
+        assert_eq!(lower.effective_limit(), Some(500));
+        assert_eq!(limits.effective_limit(), Some(1000));
+    }
+
+    #[test]
+    pub fn requested_limit_should_apply_when_server_is_unlimited() {
+        // Arrange
+        let limits = Arc::new(BandwidthLimits::new(None));
+
+        // Act
+        let tunnel_limits = TunnelLimits::new(&limits, Some(500));
+
+        // Assert
+        assert_eq!(tunnel_limits.effective_limit(), Some(500));
+        assert_eq!(tunnel_limits.outbound().rate(), Some(500));
+    }
+
+    #[tokio::test]
+    pub async fn sessions_of_an_account_should_share_its_limiters() {
+        // Arrange
+        let registry = BandwidthRegistry::new(Some(1000));
+        let account_id = Uuid::new_v4();
+        let first = registry.for_account(&account_id, None);
+        let second = registry.for_account(&account_id, Some(2000));
+        let other = registry.for_account(&Uuid::new_v4(), None);
+
+        // Act
+        TunnelLimits::new(&first, None).inbound().acquire(100).await;
+        TunnelLimits::new(&second, None).inbound().acquire(50).await;
+        registry.set_global_limit(Some(500));
+
+        // Assert
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(first.inbound().total_bytes(), 150);
+        assert_eq!(first.effective_limit(), Some(2000));
+        assert_eq!(other.inbound().total_bytes(), 0);
+        assert_eq!(other.effective_limit(), Some(500));
+    }
+}
@@ -1,5 +1,7 @@
+use std::sync::Arc;
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::sync::OwnedSemaphorePermit;
+use tokio_util::sync::CancellationToken;
 use tracing::debug;
 
 use tcproxy_core::framing::SocketDisconnected;
@@ -7,19 +9,38 @@ use tcproxy_core::tcp::DefaultStreamReader;
 use tcproxy_core::Result;
 use tcproxy_core::TcpFrame;
 
-use crate::tcp::{RemoteConnectionReader, RemoteConnectionWriter};
+use crate::managers::record_audit_event;
+use crate::models::{AuditEvent, AuditEventType};
+use crate::tcp::{ConnectionStats, RemoteConnectionReader, RemoteConnectionWriter, TunnelLimits};
+use crate::ClientState;
 
 pub struct RemoteConnection {
     connection_id: u32,
+    listener_port: u16,
     client_sender: Sender<TcpFrame>,
+    state: Arc<ClientState>,
+    stats: Arc<ConnectionStats>,
+    limits: Arc<TunnelLimits>,
     _permit: OwnedSemaphorePermit,
 }
 
 impl RemoteConnection {
-    pub fn new(id: &u32, permit: OwnedSemaphorePermit, client_sender: &Sender<TcpFrame>) -> Self {
+    pub fn new(
+        id: &u32,
+        listener_port: &u16,
+        permit: OwnedSemaphorePermit,
+        state: &Arc<ClientState>,
+        client_sender: &Sender<TcpFrame>,
+        stats: &Arc<ConnectionStats>,
+        limits: &Arc<TunnelLimits>,
+    ) -> Self {
         Self {
+            stats: stats.clone(),
+            limits: limits.clone(),
             _permit: permit,
             connection_id: *id,
+            listener_port: *listener_port,
+            state: state.clone(),
             client_sender: client_sender.clone(),
         }
     }
@@ -28,35 +49,66 @@ impl RemoteConnection {
         self,
         connection: tcproxy_core::tcp::RemoteConnection,
         receiver: Receiver<Vec<u8>>,
+        cancellation_token: CancellationToken,
     ) -> Result<()> {
         let connection_addr = *connection.remote_addr();
         let (reader, writer) = connection.stream.into_split();
 
+        let metrics = self.state.get_metrics().clone();
+        let stats = self.stats.clone();
         let stream_reader = DefaultStreamReader::new(1024 * 8, reader);
-        let mut reader =
-            RemoteConnectionReader::new(&self.connection_id, &self.client_sender, stream_reader);
-        let mut writer = RemoteConnectionWriter::new(receiver, connection_addr, writer);
+        let mut reader = RemoteConnectionReader::new(
+            &self.connection_id,
+            &self.client_sender,
+            stream_reader,
+            &stats,
+            self.limits.inbound(),
+        );
+        let mut writer = RemoteConnectionWriter::new(
+            receiver,
+            connection_addr,
+            writer,
+            &stats,
+            self.limits.outbound(),
+        );
 
         tokio::spawn(async move {
-            let reader_task = tokio::spawn(async move {
+            metrics.remote_connection_opened(&self.listener_port);
+            let mut reader_task = tokio::spawn(async move {
                 let _ = reader.start().await;
             });
 
-            let writer_task = tokio::spawn(async move {
+            let mut writer_task = tokio::spawn(async move {
                 let _ = writer.start().await;
             });
 
             tokio::select! {
-                _ = reader_task => {},
-                _ = writer_task => {},
+                _ = &mut reader_task => {},
+                _ = &mut writer_task => {},
+                _ = cancellation_token.cancelled() => {
+                    reader_task.abort();
+                    writer_task.abort();
+                },
             };
 
             debug!(
                 "received stop signal from connection {}. aborting..",
                 self.connection_id
             );
+            metrics.remote_connection_closed(&self.listener_port);
+            self.state
+                .get_connection_manager()
+                .remove_connection(&self.connection_id);
             let frame = TcpFrame::SocketDisconnected(SocketDisconnected::new(&self.connection_id));
             let _ = self.client_sender.send(frame).await;
+
+            let event = AuditEvent::new(AuditEventType::RemoteConnectionClosed)
+                .with_account(self.state.get_auth_manager().account_id().as_ref())
+                .with_port(&self.listener_port)
+                .with_remote_addr(&connection_addr)
+                .with_bytes(&stats.bytes_in(), &stats.bytes_out());
+
+            record_audit_event(self.state.get_audit_manager().as_ref(), event);
         });
 
         Ok(())
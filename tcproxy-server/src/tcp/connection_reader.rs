@@ -1,3 +1,4 @@
+use std::sync::Arc;
 use tcproxy_core::framing::DataPacket;
 use tcproxy_core::tcp::StreamReader;
 use tokio::sync::mpsc::Sender;
@@ -6,14 +7,24 @@ use tracing::{error, trace};
 use tcproxy_core::Result;
 use tcproxy_core::TcpFrame;
 
+use crate::tcp::{ConnectionStats, RateLimiter};
+
 pub struct RemoteConnectionReader {
     connection_id: u32,
     client_sender: Sender<TcpFrame>,
     reader: Box<dyn StreamReader>,
+    stats: Arc<ConnectionStats>,
+    rate_limiter: Arc<RateLimiter>,
 }
 
 impl RemoteConnectionReader {
-    pub fn new<T>(connection_id: &u32, sender: &Sender<TcpFrame>, reader: T) -> Self
+    pub fn new<T>(
+        connection_id: &u32,
+        sender: &Sender<TcpFrame>,
+        reader: T,
+        stats: &Arc<ConnectionStats>,
+        rate_limiter: &Arc<RateLimiter>,
+    ) -> Self
     where
         T: StreamReader + 'static,
     {
@@ -21,15 +32,18 @@ impl RemoteConnectionReader {
             connection_id: *connection_id,
             client_sender: sender.clone(),
             reader: Box::new(reader),
+            stats: stats.clone(),
+            rate_limiter: rate_limiter.clone(),
         }
     }
 
     pub async fn start(&mut self) -> Result<()> {
         while let Some(buffer) = self.reader.read().await? {
+            self.rate_limiter.acquire(buffer.len() as u64).await;
             let frame = TcpFrame::DataPacket(DataPacket::new(&self.connection_id, &buffer));
 
             match self.client_sender.send(frame).await {
-                Ok(_) => {}
+                Ok(_) => self.stats.add_bytes_in(buffer.len() as u64),
                 Err(err) => {
                     error!("failed to send frame to client. {}", err);
                     return Err(err.into());
@@ -60,10 +74,13 @@ mod tests {
         let connection_id = random::<u32>();
         let (sender, mut receiver) = mpsc::channel::<TcpFrame>(1);
         let mut reader = MockStreamReader::new();
+        let stats = Arc::new(ConnectionStats::new());
+        let rate_limiter = Arc::new(RateLimiter::new(None));
 
         reader.expect_read().returning(|| Ok(None));
 
-        let mut connection_reader = RemoteConnectionReader::new(&connection_id, &sender, reader);
+        let mut connection_reader =
+            RemoteConnectionReader::new(&connection_id, &sender, reader, &stats, &rate_limiter);
 
         // Act
         let result = connection_reader.start().await;
@@ -86,6 +103,8 @@ mod tests {
         let expected_buff_size = 1024 * 6;
         let random_buffer = generate_random_buffer(expected_buff_size);
         let (sender, mut receiver) = mpsc::channel::<TcpFrame>(3);
+        let stats = Arc::new(ConnectionStats::new());
+        let rate_limiter = Arc::new(RateLimiter::new(None));
 
         let mut reader = MockStreamReader::new();
         let mut sequence = Sequence::new();
@@ -109,7 +128,8 @@ mod tests {
             .returning(|| Ok(None))
             .in_sequence(&mut sequence);
 
-        let mut connection_reader = RemoteConnectionReader::new(&connection_id, &sender, reader);
+        let mut connection_reader =
+            RemoteConnectionReader::new(&connection_id, &sender, reader, &stats, &rate_limiter);
 
         // At this point stream is already closed, but underlying buffer still there for reading.
         let _ = connection_reader.start().await;
@@ -131,5 +151,6 @@ mod tests {
         assert!(!final_buff.is_empty());
         assert_eq!(final_buff.len(), random_buffer.len());
         assert_eq!(&final_buff[..], &random_buffer[..]);
+        assert_eq!(stats.bytes_in(), random_buffer.len() as u64);
     }
 }
@@ -0,0 +1,67 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::metrics::ServerMetrics;
+
+/// Keeps track of how many bytes went through a remote connection.
+/// `bytes_in` are bytes received from the remote peer, `bytes_out` the ones written to it.
+/// the same stats can add up the connections of a whole session, through `with_parent`.
+#[derive(Default)]
+pub struct ConnectionStats {
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    metrics: Option<Arc<ServerMetrics>>,
+    parent: Option<Arc<ConnectionStats>>,
+}
+
+impl ConnectionStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// also reports the transferred bytes to the server wide metrics.
+    pub fn with_metrics(metrics: &Arc<ServerMetrics>) -> Self {
+        Self {
+            metrics: Some(metrics.clone()),
+            ..Self::default()
+        }
+    }
+
+    /// also adds the transferred bytes to `parent`.
+    pub fn with_parent(parent: &Arc<ConnectionStats>) -> Self {
+        Self {
+            parent: Some(parent.clone()),
+            ..Self::default()
+        }
+    }
+
+    pub fn add_bytes_in(&self, bytes: u64) {
+        self.bytes_in.fetch_add(bytes, Ordering::Relaxed);
+        if let Some(metrics) = &self.metrics {
+            metrics.add_bytes_in(bytes);
+        }
+
+        if let Some(parent) = &self.parent {
+            parent.add_bytes_in(bytes);
+        }
+    }
+
+    pub fn add_bytes_out(&self, bytes: u64) {
+        self.bytes_out.fetch_add(bytes, Ordering::Relaxed);
+        if let Some(metrics) = &self.metrics {
+            metrics.add_bytes_out(bytes);
+        }
+
+        if let Some(parent) = &self.parent {
+            parent.add_bytes_out(bytes);
+        }
+    }
+
+    pub fn bytes_in(&self) -> u64 {
+        self.bytes_in.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes_out(&self) -> u64 {
+        self.bytes_out.load(Ordering::Relaxed)
+    }
+}
@@ -1,19 +1,30 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
 use tokio::io::{AsyncWrite, AsyncWriteExt};
 use tokio::sync::mpsc::Receiver;
 use tracing::{error, trace};
 
 use tcproxy_core::Result;
 
+use crate::tcp::{ConnectionStats, RateLimiter};
+
 pub struct RemoteConnectionWriter<'a> {
     connection_addr: SocketAddr,
     receiver: Receiver<Vec<u8>>,
     writer: Box<dyn AsyncWrite + Unpin + Send + 'a>,
+    stats: Arc<ConnectionStats>,
+    rate_limiter: Arc<RateLimiter>,
 }
 
 /// Writes buffers into remote connection.
 impl<'a> RemoteConnectionWriter<'a> {
-    pub fn new<T>(receiver: Receiver<Vec<u8>>, connection_addr: SocketAddr, writer: T) -> Self
+    pub fn new<T>(
+        receiver: Receiver<Vec<u8>>,
+        connection_addr: SocketAddr,
+        writer: T,
+        stats: &Arc<ConnectionStats>,
+        rate_limiter: &Arc<RateLimiter>,
+    ) -> Self
     where
         T: AsyncWrite + Unpin + Send + 'a,
     {
@@ -21,13 +32,17 @@ impl<'a> RemoteConnectionWriter<'a> {
             receiver,
             connection_addr,
             writer: Box::new(writer),
+            stats: stats.clone(),
+            rate_limiter: rate_limiter.clone(),
         }
     }
 
     pub async fn start(&mut self) -> Result<()> {
         while let Some(buffer) = self.receiver.recv().await {
+            self.rate_limiter.acquire(buffer.len() as u64).await;
             match self.writer.write(&buffer).await {
                 Ok(written) => {
+                    self.stats.add_bytes_out(written as u64);
                     trace!("written {} bytes to {}", written, self.connection_addr)
                 }
                 Err(err) => {
@@ -47,7 +62,6 @@ impl<'a> RemoteConnectionWriter<'a> {
 #[cfg(test)]
 mod tests {
     use std::{
-        io::ErrorKind,
         net::Ipv4Addr,
         pin::Pin,
         task::{Context, Poll},
@@ -81,7 +95,10 @@ mod tests {
         let (sender, receiver) = mpsc::channel::<Vec<u8>>(1);
 
         let addr = SocketAddr::new(IpAddr::from([127, 0, 0, 1]), 0);
-        let mut connection_writer = RemoteConnectionWriter::new(receiver, addr, Box::new(cursor));
+        let stats = Arc::new(ConnectionStats::new());
+        let rate_limiter = Arc::new(RateLimiter::new(None));
+        let mut connection_writer =
+            RemoteConnectionWriter::new(receiver, addr, Box::new(cursor), &stats, &rate_limiter);
 
         let _ = sender.send(random_buffer[..].to_vec()).await;
         drop(sender);
@@ -89,6 +106,7 @@ mod tests {
         let result = connection_writer.start().await;
 
         assert!(result.is_ok());
+        assert_eq!(stats.bytes_out(), random_buffer.len() as u64);
     }
 
     #[tokio::test]
@@ -103,10 +121,17 @@ mod tests {
 
         mocked_stream
             .expect_poll_write()
-            .returning(|_, _| Poll::Ready(Err(std::io::Error::new(ErrorKind::Other, ""))));
+            .returning(|_, _| Poll::Ready(Err(std::io::Error::other(""))));
 
-        let mut connection_writer =
-            RemoteConnectionWriter::new(receiver, addr, Box::new(mocked_stream));
+        let stats = Arc::new(ConnectionStats::new());
+        let rate_limiter = Arc::new(RateLimiter::new(None));
+        let mut connection_writer = RemoteConnectionWriter::new(
+            receiver,
+            addr,
+            Box::new(mocked_stream),
+            &stats,
+            &rate_limiter,
+        );
 
         // Act
 
@@ -1,7 +1,13 @@
+mod bandwidth_limits;
 mod connection;
 mod connection_reader;
+mod connection_stats;
 mod connection_writer;
+mod rate_limiter;
 
+pub use bandwidth_limits::*;
 pub use connection::*;
 pub use connection_reader::*;
+pub use connection_stats::*;
 pub use connection_writer::*;
+pub use rate_limiter::*;
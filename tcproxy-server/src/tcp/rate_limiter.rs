@@ -0,0 +1,193 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Token bucket shared by every remote connection of a tunnel, in a single direction.
+/// the bucket holds up to one second worth of bytes, and callers going over it
+/// wait until the debt is paid back. bytes are also taken from the parent limiter, if any.
+#[derive(Debug)]
+pub struct RateLimiter {
+    bucket: Mutex<TokenBucket>,
+    total_bytes: AtomicU64,
+    parent: Option<Arc<RateLimiter>>,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    bytes_per_second: Option<u64>,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn refill(&mut self, now: Instant) {
+        let bytes_per_second = match self.bytes_per_second {
+            Some(value) => value as f64,
+            None => return,
+        };
+
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * bytes_per_second).min(bytes_per_second);
+        self.last_refill = now;
+    }
+}
+
+impl RateLimiter {
+    pub fn new(bytes_per_second: Option<u64>) -> Self {
+        Self {
+            bucket: Mutex::new(TokenBucket {
+                bytes_per_second,
+                tokens: bytes_per_second.unwrap_or(0) as f64,
+                last_refill: Instant::now(),
+            }),
+            total_bytes: AtomicU64::new(0),
+            parent: None,
+        }
+    }
+
+    /// limiter that also waits for `parent`, so several of them can share its rate.
+    pub fn with_parent(bytes_per_second: Option<u64>, parent: &Arc<RateLimiter>) -> Self {
+        Self {
+            parent: Some(parent.clone()),
+            ..Self::new(bytes_per_second)
+        }
+    }
+
+    /// changes the rate, `None` disables the limit.
+    pub fn set_rate(&self, bytes_per_second: Option<u64>) {
+        let mut bucket = self.bucket.lock().unwrap();
+        bucket.refill(Instant::now());
+        bucket.bytes_per_second = bytes_per_second;
+
+        if let Some(value) = bytes_per_second {
+            bucket.tokens = bucket.tokens.min(value as f64);
+        }
+    }
+
+    pub fn rate(&self) -> Option<u64> {
+        self.bucket.lock().unwrap().bytes_per_second
+    }
+
+    /// total of bytes that went through this limiter.
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes.load(Ordering::Relaxed)
+    }
+
+    /// takes `bytes` from the bucket and the ones of its parents,
+    /// waiting if there is not enough tokens available.
+    pub async fn acquire(&self, bytes: u64) {
+        let mut limiter = Some(self);
+        while let Some(current) = limiter {
+            current.total_bytes.fetch_add(bytes, Ordering::Relaxed);
+
+            let wait_time = current.reserve(bytes, Instant::now());
+            if !wait_time.is_zero() {
+                tokio::time::sleep(wait_time).await;
+            }
+
+            limiter = current.parent.as_deref();
+        }
+    }
+
+    fn reserve(&self, bytes: u64, now: Instant) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap();
+        let bytes_per_second = match bucket.bytes_per_second {
+            Some(value) => value as f64,
+            None => return Duration::ZERO,
+        };
+
+        bucket.refill(now);
+        bucket.tokens -= bytes as f64;
+
+        if bucket.tokens >= 0.0 {
+            return Duration::ZERO;
+        }
+
+        Duration::from_secs_f64(-bucket.tokens / bytes_per_second)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::time::Instant;
+
+    use super::RateLimiter;
+
+    #[test]
+    pub fn should_not_wait_when_unlimited() {
+        // Arrange
+        let limiter = RateLimiter::new(None);
+
+        // Act
+        let wait_time = limiter.reserve(1024 * 1024, Instant::now());
+
+        // Assert
+        assert_eq!(wait_time, Duration::ZERO);
+    }
+
+    #[test]
+    pub fn should_wait_when_bucket_is_empty() {
+        // Arrange
+        let limiter = RateLimiter::new(Some(1000));
+        let now = Instant::now();
+
+        // Act
+        let first_wait = limiter.reserve(1000, now);
+        let second_wait = limiter.reserve(500, now);
+
+        // Assert
+        assert_eq!(first_wait, Duration::ZERO);
+        assert_eq!(second_wait, Duration::from_millis(500));
+    }
+
+    #[test]
+    pub fn should_refill_over_time() {
+        // Arrange
+        let limiter = RateLimiter::new(Some(1000));
+        let now = Instant::now();
+        let _ = limiter.reserve(1000, now);
+
+        // Act
+        let wait_time = limiter.reserve(500, now + Duration::from_millis(500));
+
+        // Assert
+        assert_eq!(wait_time, Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn should_count_total_bytes() {
+        // Arrange
+        let limiter = RateLimiter::new(Some(1000));
+
+        // Act
+        limiter.acquire(1000).await;
+        limiter.set_rate(None);
+        limiter.acquire(500).await;
+
+        // Assert
+        assert_eq!(limiter.total_bytes(), 1500);
+        assert_eq!(limiter.rate(), None);
+    }
+
+    #[tokio::test]
+    async fn should_take_bytes_from_parent() {
+        // Arrange
+        let parent = Arc::new(RateLimiter::new(None));
+        let first = RateLimiter::with_parent(Some(1000), &parent);
+        let second = RateLimiter::with_parent(None, &parent);
+
+        // Act
+        first.acquire(100).await;
+        second.acquire(50).await;
+
+        // Assert
+        assert_eq!(first.total_bytes(), 100);
+        assert_eq!(second.total_bytes(), 50);
+        assert_eq!(parent.total_bytes(), 150);
+    }
+}
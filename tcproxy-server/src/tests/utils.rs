@@ -22,3 +22,49 @@ macro_rules! extract_enum_value {
         }
     };
 }
+
+/// Shares the config the way the server does, so it can be replaced while sessions are open.
+#[cfg(test)]
+pub fn create_feature_manager(
+    config: crate::ServerConfig,
+) -> std::sync::Arc<crate::managers::IFeatureManager> {
+    use crate::managers::DefaultFeatureManager;
+
+    std::sync::Arc::new(Box::new(DefaultFeatureManager::new(config)))
+}
+
+/// Creates the state of a control connection that's not authenticated yet.
+#[cfg(test)]
+pub fn create_client_state() -> std::sync::Arc<crate::ClientState> {
+    create_client_state_with_config(&create_feature_manager(crate::ServerConfig::default()))
+}
+
+/// Same as `create_client_state`, reading its config from `feature_manager`.
+#[cfg(test)]
+pub fn create_client_state_with_config(
+    feature_manager: &std::sync::Arc<crate::managers::IFeatureManager>,
+) -> std::sync::Arc<crate::ClientState> {
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    use crate::managers::{
+        AuthenticationManager, AuthenticationManagerGuard, DefaultAccountManager,
+        DefaultAuditManager, DefaultUsageManager, NetworkPortPool, PortManager,
+    };
+    use crate::metrics::ServerMetrics;
+
+    let port_manager = PortManager::from(NetworkPortPool::new(15000..15010));
+    let metrics = Arc::new(ServerMetrics::new(&port_manager));
+
+    crate::ClientState::new(
+        port_manager,
+        Arc::new(AuthenticationManagerGuard::new(AuthenticationManager::new())),
+        feature_manager,
+        &Arc::new(DefaultAccountManager::new()),
+        &Arc::new(DefaultAuditManager::new()),
+        &Arc::new(DefaultUsageManager::new()),
+        &metrics,
+        &Arc::new(crate::tcp::BandwidthRegistry::new(None)),
+        &std::net::SocketAddr::from_str("127.0.0.1:54321").unwrap(),
+    )
+}
@@ -0,0 +1,210 @@
+use std::path::{Path, PathBuf};
+
+use tcproxy_core::tls::ServerTls;
+use tcproxy_core::Result;
+use tokio_native_tls::native_tls::Identity;
+
+use crate::ServerConfig;
+
+/// loads the tls certificate set in the config, `None` when no certificate is configured.
+/// a PEM chain is used when `certificate_key_path` is set, otherwise `certificate_path` is read as pkcs12.
+pub fn load_tls(config: &ServerConfig) -> Result<Option<ServerTls>> {
+    let certificate_path = match config.get_certificate_path() {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+
+    match config.get_certificate_key_path() {
+        Some(key_path) => {
+            load_pem(certificate_path, key_path, config.get_client_ca_path()).map(Some)
+        }
+        None => {
+            let password = config.get_certificate_pass().to_owned().unwrap_or_default();
+            load_pkcs12(certificate_path, &password).map(Some)
+        }
+    }
+}
+
+fn load_pkcs12(path: &Path, password: &str) -> Result<ServerTls> {
+    let file_contents = std::fs::read(path)
+        .map_err(|err| format!("failed to read certificate {}: {}", path.display(), err))?;
+
+    let identity = Identity::from_pkcs12(&file_contents, password)?;
+    tracing::debug!("successfully loaded pkcs12 certificate");
+
+    Ok(ServerTls::Native(identity))
+}
+
+#[cfg(feature = "rustls")]
+fn load_pem(
+    certificate_path: &Path,
+    key_path: &Path,
+    client_ca_path: &Option<PathBuf>,
+) -> Result<ServerTls> {
+    let cert_chain = std::fs::read(certificate_path).map_err(|err| {
+        format!(
+            "failed to read certificate {}: {}",
+            certificate_path.display(),
+            err
+        )
+    })?;
+
+    let private_key = std::fs::read(key_path)
+        .map_err(|err| format!("failed to read key {}: {}", key_path.display(), err))?;
+
+    let client_ca = read_client_ca(client_ca_path)?;
+    let tls =
+        tcproxy_core::tls::rustls_config_from_pem(&cert_chain, &private_key, client_ca.as_deref())?;
+    tracing::debug!("successfully loaded PEM certificate");
+
+    Ok(tls)
+}
+
+/// reads the CA certificates accepted for client certificate authentication.
+pub fn read_client_ca(client_ca_path: &Option<PathBuf>) -> Result<Option<Vec<u8>>> {
+    match client_ca_path {
+        None => Ok(None),
+        Some(path) => std::fs::read(path)
+            .map(Some)
+            .map_err(|err| format!("failed to read client CA {}: {}", path.display(), err).into()),
+    }
+}
+
+#[cfg(not(feature = "rustls"))]
+fn load_pem(
+    _certificate_path: &Path,
+    _key_path: &Path,
+    _client_ca_path: &Option<PathBuf>,
+) -> Result<ServerTls> {
+    Err("PEM certificates require tcproxy-server to be built with the `rustls` feature".into())
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "rustls")]
+    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    use super::load_tls;
+    use crate::ServerConfig;
+
+    #[test]
+    pub fn should_fail_when_certificate_is_missing() {
+        // Arrange
+        let mut config = ServerConfig::default();
+        let path = std::env::temp_dir().join(format!("{}.pfx", Uuid::new_v4()));
+        config.set_certificate_path(Some(path));
+
+        // Act
+        let result = load_tls(&config);
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "rustls")]
+    #[test]
+    pub fn should_load_pem_certificate() {
+        // Arrange
+        let certificate =
+            rcgen::generate_simple_self_signed(vec![String::from("proxy.server.local")]).unwrap();
+        let cert_path = std::env::temp_dir().join(format!("{}.pem", Uuid::new_v4()));
+        let key_path = std::env::temp_dir().join(format!("{}.key", Uuid::new_v4()));
+        std::fs::write(&cert_path, certificate.serialize_pem().unwrap()).unwrap();
+        std::fs::write(&key_path, certificate.serialize_private_key_pem()).unwrap();
+
+        let mut config = ServerConfig::default();
+        config.set_certificate_path(Some(cert_path.clone()));
+        config.set_certificate_key_path(Some(key_path.clone()));
+
+        // Act
+        let result = load_tls(&config);
+        remove_files(&[cert_path, key_path]);
+
+        // Assert
+        assert!(matches!(
+            result,
+            Ok(Some(tcproxy_core::tls::ServerTls::Rustls(_)))
+        ));
+    }
+
+    #[cfg(feature = "rustls")]
+    #[tokio::test]
+    async fn should_accept_client_certificate_signed_by_client_ca() {
+        use rcgen::{BasicConstraints, Certificate, CertificateParams, DnType, IsCa};
+        use std::net::SocketAddr;
+        use std::str::FromStr;
+        use tcproxy_core::tcp::{SocketListener, TcpListener};
+        use tcproxy_core::tls::ClientTls;
+
+        // Arrange
+        let mut ca_params = CertificateParams::new(vec![]);
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        ca_params
+            .distinguished_name
+            .push(DnType::CommonName, "tcproxy test ca");
+        let ca = Certificate::from_params(ca_params).unwrap();
+
+        let mut server_params = CertificateParams::new(vec![String::from("localhost")]);
+        server_params
+            .distinguished_name
+            .push(DnType::CommonName, "localhost");
+        let server = Certificate::from_params(server_params).unwrap();
+        let mut client_params = CertificateParams::new(vec![]);
+        client_params
+            .distinguished_name
+            .push(DnType::CommonName, "build-agent");
+        let client = Certificate::from_params(client_params).unwrap();
+
+        let files = [
+            ("server.pem", server.serialize_pem_with_signer(&ca).unwrap()),
+            ("server.key", server.serialize_private_key_pem()),
+            ("ca.pem", ca.serialize_pem().unwrap()),
+            ("client.pem", client.serialize_pem_with_signer(&ca).unwrap()),
+            ("client.key", client.serialize_private_key_pem()),
+        ];
+
+        let dir = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, contents) in &files {
+            std::fs::write(dir.join(name), contents).unwrap();
+        }
+
+        let mut config = ServerConfig::default();
+        config.set_certificate_path(Some(dir.join("server.pem")));
+        config.set_certificate_key_path(Some(dir.join("server.key")));
+        config.set_client_ca_path(Some(dir.join("ca.pem")));
+
+        let tls = load_tls(&config).unwrap();
+        let addr = SocketAddr::from_str("127.0.0.1:0").unwrap();
+        let listener = TcpListener::bind(addr, tls).await.unwrap();
+        let addr = listener.listen_ip().unwrap();
+
+        let client_tls = ClientTls::new("localhost")
+            .with_ca_bundle(&dir.join("ca.pem"))
+            .with_client_identity(&dir.join("client.pem"), &dir.join("client.key"));
+
+        // Act
+        let client = tokio::spawn(async move {
+            let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+            client_tls.connect(stream).await.map(|_| ())
+        });
+        let connection =
+            tokio::time::timeout(std::time::Duration::from_secs(5), listener.accept()).await;
+        let client_result = client.await.unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // Assert
+        assert!(client_result.is_ok());
+        let connection = connection.unwrap().unwrap();
+        let certificate = connection.peer_certificate().unwrap();
+        assert_eq!(certificate.subject(), Some("CN=build-agent"));
+    }
+
+    #[cfg(feature = "rustls")]
+    fn remove_files(paths: &[PathBuf]) {
+        for path in paths {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}